@@ -1,6 +1,7 @@
-use crate::authorities::AuthDetails;
+use crate::authorities::{AuthDetails, TimedAuthority};
 use actix_web::dev::ServiceRequest;
 use actix_web::HttpMessage;
+use protect_endpoints_core::authorities::{principal_authorities, validity_windows};
 use std::hash::Hash;
 
 /// Allows you to transfer authorities to [`actix-web-grants`] from your custom middleware.
@@ -25,10 +26,39 @@ use std::hash::Hash;
 /// [`ServiceRequest`]: actix_web::dev::ServiceRequest
 pub trait AttachAuthorities<Type> {
     fn attach(&self, authorities: impl IntoIterator<Item = Type>);
+
+    /// Attaches time-bounded authorities, expired ones are ignored by checks.
+    ///
+    /// By default, only authorities active at the moment are attached (without their windows).
+    fn attach_timed(&self, authorities: impl IntoIterator<Item = TimedAuthority<Type>>)
+    where
+        Type: Clone,
+    {
+        self.attach(
+            authorities
+                .into_iter()
+                .filter(TimedAuthority::is_active)
+                .map(|timed| timed.authority),
+        );
+    }
 }
 
 impl<Type: Eq + Hash + 'static> AttachAuthorities<Type> for ServiceRequest {
     fn attach(&self, authorities: impl IntoIterator<Item = Type>) {
-        self.extensions_mut().insert(AuthDetails::new(authorities));
+        self.extensions_mut().insert(AuthDetails {
+            authorities: principal_authorities(authorities),
+            validity: Default::default(),
+        });
+    }
+
+    fn attach_timed(&self, authorities: impl IntoIterator<Item = TimedAuthority<Type>>)
+    where
+        Type: Clone,
+    {
+        let validity = validity_windows(authorities);
+        self.extensions_mut().insert(AuthDetails::with_validity(
+            principal_authorities(validity.keys().cloned()),
+            validity,
+        ));
     }
 }
@@ -13,10 +13,12 @@
 //! [`AttachAuthorities`]: AttachAuthorities
 //! [`GrantsMiddleware`]: actix_web_grants::GrantsMiddleware;
 
+use crate::error::Unauthorized;
 use actix_web::dev::Payload;
-use actix_web::error::ErrorUnauthorized;
 use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
-use std::collections::HashSet;
+use protect_endpoints_core::authorities::{is_authority_active, validity_windows};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::hash::Hash;
 use std::pin::Pin;
@@ -24,34 +26,92 @@ use std::sync::Arc;
 
 mod attache;
 mod extractors;
+mod principal;
 
 pub use attache::AttachAuthorities;
 pub use extractors::*;
+pub use principal::{AttachPrincipal, Principal};
+pub use protect_endpoints_core::authorities::extractor::{active_authorities, AuthoritiesStore};
+pub use protect_endpoints_core::authorities::AuthoritiesCheck;
+pub use protect_endpoints_core::authorities::{
+    anonymous_authorities, is_anonymous, now, reset_anonymous_authorities, reset_clock,
+    set_anonymous_authorities, set_clock, Clock, SystemClock, TimedAuthority, Validity,
+};
 
 pub struct AuthDetails<T = String>
 where
     T: Eq + Hash,
 {
     pub authorities: Arc<HashSet<T>>,
+    /// Validity windows of time-bounded authorities, the rest are unbounded.
+    validity: Arc<HashMap<T, Validity>>,
 }
 
 impl<T: Eq + Hash> AuthDetails<T> {
     pub fn new(authorities: impl IntoIterator<Item = T>) -> AuthDetails<T> {
         AuthDetails {
             authorities: Arc::new(authorities.into_iter().collect()),
+            validity: Default::default(),
         }
     }
+
+    /// Details with time-bounded authorities, see [`TimedAuthority`].
+    pub fn timed(authorities: impl IntoIterator<Item = TimedAuthority<T>>) -> AuthDetails<T>
+    where
+        T: Clone,
+    {
+        let validity = validity_windows(authorities);
+        Self::with_validity(Arc::new(validity.keys().cloned().collect()), validity)
+    }
+
+    /// Details with validity windows of time-bounded authorities, the rest are unbounded.
+    pub fn with_validity(
+        authorities: Arc<HashSet<T>>,
+        validity: HashMap<T, Validity>,
+    ) -> AuthDetails<T> {
+        AuthDetails {
+            authorities,
+            validity: Arc::new(validity),
+        }
+    }
+
+    /// Validity window of the authority, `None` if it's unbounded (or missing).
+    pub fn validity<Q>(&self, authority: &Q) -> Option<&Validity>
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.validity.get(authority)
+    }
+
+    /// Whether the user has the authority and it isn't expired (or not yet valid).
+    pub fn is_active<Q>(&self, authority: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        is_authority_active(&self.authorities, &self.validity, authority)
+    }
 }
 
-pub trait AuthoritiesCheck<T: Eq + Hash> {
-    fn has_authority(&self, authority: T) -> bool;
-    fn has_authorities(&self, authorities: &[T]) -> bool;
-    fn has_any_authority(&self, authorities: &[T]) -> bool;
+impl<T: Eq + Hash + 'static> AuthDetails<T> {
+    /// Details of the anonymous principal, empty if anonymous authorities aren't configured.
+    pub fn anonymous() -> AuthDetails<T> {
+        AuthDetails {
+            authorities: anonymous_authorities().unwrap_or_default(),
+            validity: Default::default(),
+        }
+    }
+
+    /// Whether the details belong to the anonymous principal (see [`set_anonymous_authorities`]).
+    pub fn is_anonymous(&self) -> bool {
+        is_anonymous(&self.authorities)
+    }
 }
 
 impl<T: Eq + Hash> AuthoritiesCheck<&T> for AuthDetails<T> {
     fn has_authority(&self, authority: &T) -> bool {
-        self.authorities.contains(authority)
+        self.is_active(authority)
     }
 
     fn has_authorities(&self, authorities: &[&T]) -> bool {
@@ -65,7 +125,7 @@ impl<T: Eq + Hash> AuthoritiesCheck<&T> for AuthDetails<T> {
 
 impl AuthoritiesCheck<&str> for AuthDetails {
     fn has_authority(&self, authority: &str) -> bool {
-        self.authorities.contains(authority)
+        self.is_active(authority)
     }
 
     fn has_authorities(&self, authorities: &[&str]) -> bool {
@@ -87,8 +147,9 @@ impl<T: Eq + Hash + 'static> FromRequest for AuthDetails<T> {
         Box::pin(async move {
             req.extensions()
                 .get::<AuthDetails<T>>()
+                .filter(|details| !details.is_anonymous())
                 .cloned()
-                .ok_or_else(|| ErrorUnauthorized("User unauthorized!"))
+                .ok_or_else(|| Unauthorized.into())
         })
     }
 }
@@ -97,6 +158,7 @@ impl<T: Eq + Hash> Clone for AuthDetails<T> {
     fn clone(&self) -> Self {
         Self {
             authorities: self.authorities.clone(),
+            validity: self.validity.clone(),
         }
     }
 }
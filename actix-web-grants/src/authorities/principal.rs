@@ -0,0 +1,67 @@
+use crate::error::Unauthorized;
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use protect_endpoints_core::authorities::Principal as PrincipalCore;
+use std::future::{ready, Ready};
+use std::ops::Deref;
+
+/// Subject identifier and typed claims of the user, see [`AttachPrincipal`].
+///
+/// Requests without the attached principal are rejected as unauthorized.
+pub struct Principal<C = ()>(PrincipalCore<C>);
+
+impl<C: 'static> FromRequest for Principal<C> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<PrincipalCore<C>>()
+                .cloned()
+                .map(Principal)
+                .ok_or_else(|| Unauthorized.into()),
+        )
+    }
+}
+
+impl<C> Deref for Principal<C> {
+    type Target = PrincipalCore<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> Clone for Principal<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Allows you to transfer [`Principal`] to [`actix-web-grants`], e.g. from the authorities extractor.
+///
+/// # Example
+///
+/// ```
+/// use actix_web_grants::authorities::AttachPrincipal;
+/// use actix_web::dev::ServiceRequest;
+///
+/// struct Claims { tenant: String }
+///
+/// fn attach(req: &ServiceRequest, subject: String, tenant: String) {
+///     req.attach_principal(subject, Claims { tenant });
+/// }
+/// ```
+///
+/// [`actix-web-grants`]: crate
+pub trait AttachPrincipal<C> {
+    fn attach_principal(&self, subject: impl Into<String>, claims: C);
+}
+
+impl<C: 'static> AttachPrincipal<C> for ServiceRequest {
+    fn attach_principal(&self, subject: impl Into<String>, claims: C) {
+        self.extensions_mut()
+            .insert(PrincipalCore::new(subject, claims));
+    }
+}
@@ -0,0 +1,98 @@
+//! Deny-by-default mode: requests which aren't routed to handlers annotated with [`protect`](crate::protect)
+//! or [`public`](crate::public) are rejected with `403`, see [`protect_endpoints_core::deny_by_default`].
+//!
+//! The mode is enabled by [`DenyByDefault`] middleware configured with [`Routes`] of the app.
+//! Requests are checked before they're routed, so unannotated handlers never run.
+//!
+//! # Example
+//! ```
+//! use actix_web::{get, App};
+//! use actix_web_grants::deny_by_default::{DenyByDefault, Routes};
+//!
+//! #[get("/")]
+//! #[actix_web_grants::public]
+//! async fn index() -> &'static str {
+//!     "Hi!"
+//! }
+//!
+//! let routes = Routes::new().route("GET", "/", index);
+//!
+//! let app = App::new().wrap(DenyByDefault::new(routes)).service(index);
+//! ```
+
+use crate::error::AccessDenied;
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use std::future::{self, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pub use protect_endpoints_core::deny_by_default::{is_annotated, AnnotatedHandler, Route, Routes};
+
+/// Middleware rejecting requests which aren't routed to annotated handlers.
+#[derive(Debug, Clone)]
+pub struct DenyByDefault {
+    routes: Arc<Routes>,
+}
+
+impl DenyByDefault {
+    pub fn new(routes: Routes) -> Self {
+        Self {
+            routes: Arc::new(routes),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for DenyByDefault
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = DenyByDefaultService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ready(Ok(DenyByDefaultService {
+            service: Rc::new(service),
+            routes: self.routes.clone(),
+        }))
+    }
+}
+
+pub struct DenyByDefaultService<S> {
+    service: Rc<S>,
+    routes: Arc<Routes>,
+}
+
+impl<S, B> Service<ServiceRequest> for DenyByDefaultService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<ServiceResponse<EitherBody<B>>, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let checked = self.routes.check(req.method().as_str(), req.path());
+
+        Box::pin(async move {
+            match checked {
+                Ok(()) => Ok(service.call(req).await?.map_into_left_body()),
+                Err(denied) => Ok(req
+                    .error_response(AccessDenied::from(denied))
+                    .map_into_right_body()),
+            }
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use actix_web::http::header::WWW_AUTHENTICATE;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use protect_endpoints_core::condition::{Condition, Explanation};
+use protect_endpoints_core::error::{unauthorized_challenge, AccessDenied as AccessDeniedCore};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+pub use protect_endpoints_core::error::{
+    set_challenge, set_denial_renderer, Challenge, PROBLEM_JSON,
+};
+
+/// Default error of the [`protect`](crate::protect) macro.
+///
+/// Rendered as `application/problem+json`, see [`protect_endpoints_core::error`]
+/// for details and customization.
+#[derive(Debug, Clone)]
+pub struct AccessDenied(AccessDeniedCore);
+
+impl AccessDenied {
+    pub fn new(required: Condition<String>, explanation: Explanation<String>) -> Self {
+        Self(AccessDeniedCore::new(required, explanation))
+    }
+}
+
+impl ResponseError for AccessDenied {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let (parts, body) = self.0.to_response().into_parts();
+        let status = StatusCode::from_u16(parts.status.as_u16()).unwrap_or(self.status_code());
+
+        let mut builder = HttpResponse::build(status);
+        for (name, value) in &parts.headers {
+            builder.append_header((name.as_str(), value.as_bytes()));
+        }
+        builder.body(body)
+    }
+}
+
+impl From<AccessDeniedCore> for AccessDenied {
+    fn from(value: AccessDeniedCore) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for AccessDenied {
+    type Target = AccessDeniedCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for AccessDenied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for AccessDenied {}
+
+/// [`AuthDetails`](crate::authorities::AuthDetails) are missing in the request,
+/// rendered with `WWW-Authenticate` challenge.
+#[derive(Debug, Clone, Copy)]
+pub struct Unauthorized;
+
+impl ResponseError for Unauthorized {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut builder = HttpResponse::build(self.status_code());
+        if let Some(challenge) = unauthorized_challenge() {
+            builder.insert_header((WWW_AUTHENTICATE, challenge));
+        }
+        builder.body(self.to_string())
+    }
+}
+
+impl Display for Unauthorized {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("User unauthorized!")
+    }
+}
+
+impl std::error::Error for Unauthorized {}
@@ -1,4 +1,5 @@
 use crate::authorities::{AuthDetails, AuthoritiesCheck};
+use crate::condition::Condition;
 use actix_web::guard::{Guard, GuardContext};
 use std::hash::Hash;
 
@@ -48,3 +49,37 @@ impl<Type: Eq + Hash + 'static> Guard for AuthorityGuard<Type> {
             .is_some()
     }
 }
+
+/// Guard to validate authorities against a [`Condition`], e.g. loaded from configuration.
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+/// use actix_web_grants::condition::Condition;
+/// use actix_web_grants::ConditionGuard;
+///
+/// let condition: Condition<String> = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)".parse().unwrap();
+///
+/// App::new().service(
+///     web::resource("/admin")
+///         .to(|| async { HttpResponse::Ok().finish() })
+///         .guard(ConditionGuard::new(condition)),
+/// );
+/// ```
+pub struct ConditionGuard<Type> {
+    condition: Condition<Type>,
+}
+
+impl<Type: Eq + Hash + 'static> ConditionGuard<Type> {
+    pub fn new(condition: Condition<Type>) -> ConditionGuard<Type> {
+        ConditionGuard { condition }
+    }
+}
+
+impl<Type: Eq + Hash + 'static> Guard for ConditionGuard<Type> {
+    fn check(&self, request: &GuardContext) -> bool {
+        request
+            .req_data()
+            .get::<AuthDetails<Type>>()
+            .filter(|details| self.condition.is_satisfied_by(*details))
+            .is_some()
+    }
+}
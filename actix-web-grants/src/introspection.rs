@@ -0,0 +1,25 @@
+//! Admin endpoint serving the registered endpoints and simulating the access of authorities,
+//! see [`protect_endpoints_core::registry::introspection`].
+//!
+//! # Example
+//! ```
+//! use actix_web::{web, App, HttpRequest, HttpResponse};
+//!
+//! #[actix_web_grants::protect("ROLE_SUPPORT")]
+//! async fn introspection(req: HttpRequest) -> HttpResponse {
+//!     actix_web_grants::introspection::handler(req).await
+//! }
+//!
+//! // GET /admin/access?authorities=ROLE_USER,OP_READ
+//! let app = App::new().route("/admin/access", web::get().to(introspection));
+//! ```
+
+use actix_web::{HttpRequest, HttpResponse};
+use protect_endpoints_core::registry::introspection;
+
+/// Responds with the registered endpoints, and their access if `authorities` are given in the query.
+pub async fn handler(req: HttpRequest) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(introspection::CONTENT_TYPE)
+        .body(introspection::respond(Some(req.query_string())))
+}
@@ -5,7 +5,7 @@
 //!
 //! For built-in configure see: [`GrantsMiddleware`].
 //!
-//! To check user access to specific services, you can use: [`proc-macro`] and [`AuthorityGuard`] or manual.
+//! To check user access to specific services, you can use: [`proc-macro`], [`AuthorityGuard`], [`ConditionGuard`] or manual.
 //!
 //! The library can also be integrated with third-party solutions (like [`httpauth`]), see [`authorities`] module.
 //!
@@ -17,13 +17,36 @@
 //! [`authorities`]: authorities
 //! [`proc-macro`]: proc_macro
 //! [`AuthorityGuard`]: AuthorityGuard
+//! [`ConditionGuard`]: ConditionGuard
 #![doc = include_str!("../README.md")]
 
 pub mod authorities;
+#[cfg(feature = "deny-by-default")]
+pub mod deny_by_default;
+pub mod error;
+#[cfg(feature = "registry")]
+pub mod introspection;
+pub mod network;
+pub mod table;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[doc(hidden)]
+pub use protect_endpoints_core::__annotate_handler;
+#[doc(hidden)]
+pub use protect_endpoints_core::__register_endpoint;
+pub use protect_endpoints_core::condition;
+#[cfg(any(
+    feature = "policy-toml",
+    feature = "policy-yaml",
+    feature = "policy-json"
+))]
+pub use protect_endpoints_core::policy;
+#[cfg(feature = "registry")]
+pub use protect_endpoints_core::registry;
 mod guards;
 mod middleware;
 
-pub use guards::AuthorityGuard;
+pub use guards::{AuthorityGuard, ConditionGuard};
 pub use middleware::GrantsMiddleware;
 
 /// Procedural macros for checking user authorities (permissions or roles).
@@ -61,6 +84,12 @@ pub use middleware::GrantsMiddleware;
 ///     )
 /// }
 ///
+/// // User should be ADMIN, or have OP_WRITE permission without being SUSPENDED
+/// #[protect(rule = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)")]
+/// async fn rule_macro_secured() -> HttpResponse {
+///     HttpResponse::Ok().body("some secured info")
+/// }
+///
 /// // Additional security condition to ensure the protection of the endpoint
 /// #[protect("USER", expr = "user_id.into_inner() == user.id")]
 /// #[get("/resource/{user_id}")]
@@ -80,7 +109,10 @@ pub use middleware::GrantsMiddleware;
 /// ```
 #[cfg(feature = "macro-check")]
 pub mod proc_macro {
-    pub use protect_endpoints_proc_macro::protect_actix_web as protect;
+    pub use protect_endpoints_proc_macro::{
+        protect_actix_web as protect, protect_all_actix_web as protect_all,
+        public_actix_web as public, requirement_of,
+    };
 }
 
 /// Just a shortcut for proc-macros
@@ -0,0 +1,129 @@
+//! Network conditions: the client IP address within ranges, see [`protect_endpoints_core::network`].
+//!
+//! Handlers are restricted via `from_network` argument of the [`protect`](crate::protect) macro,
+//! whole scopes or services via [`NetworkMiddleware`].
+
+use crate::error::AccessDenied;
+use actix_web::body::EitherBody;
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::HeaderMap;
+use actix_web::{Error, FromRequest, HttpRequest};
+use protect_endpoints_core::network::ClientIp as ClientIpCore;
+use std::future::{self, Future, Ready};
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+pub use protect_endpoints_core::network::{
+    client_ip, register_network, reset_trusted_proxies, set_trusted_proxies, ForwardedHeader,
+    IpNet, NetworkError, Networks,
+};
+
+/// Address of the client: the peer address or the forwarded one, if the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(ClientIpCore);
+
+impl ClientIp {
+    fn resolve(peer: Option<SocketAddr>, headers: &HeaderMap) -> Self {
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        ClientIp(ClientIpCore::new(peer.map(|peer| peer.ip()), headers))
+    }
+}
+
+impl FromRequest for ClientIp {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        future::ready(Ok(ClientIp::resolve(req.peer_addr(), req.headers())))
+    }
+}
+
+impl Deref for ClientIp {
+    type Target = ClientIpCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Middleware denying requests from clients outside of the networks.
+///
+/// # Example
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+/// use actix_web_grants::network::NetworkMiddleware;
+///
+/// let app = App::new().service(
+///     web::scope("/internal")
+///         .wrap(NetworkMiddleware::new("10.0.0.0/8".parse().unwrap()))
+///         .route("/metrics", web::get().to(HttpResponse::Ok)),
+/// );
+/// ```
+pub struct NetworkMiddleware {
+    networks: Rc<Networks>,
+}
+
+impl NetworkMiddleware {
+    pub fn new(networks: Networks) -> Self {
+        Self {
+            networks: Rc::new(networks),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for NetworkMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = NetworkService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ready(Ok(NetworkService {
+            service: Rc::new(service),
+            networks: self.networks.clone(),
+        }))
+    }
+}
+
+pub struct NetworkService<S> {
+    service: Rc<S>,
+    networks: Rc<Networks>,
+}
+
+impl<S, B> Service<ServiceRequest> for NetworkService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<ServiceResponse<EitherBody<B>>, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let client = ClientIp::resolve(req.peer_addr(), req.headers());
+        let checked = client.check(&self.networks);
+
+        Box::pin(async move {
+            match checked {
+                Ok(()) => Ok(service.call(req).await?.map_into_left_body()),
+                Err(denied) => Ok(req
+                    .error_response(AccessDenied::from(denied))
+                    .map_into_right_body()),
+            }
+        })
+    }
+}
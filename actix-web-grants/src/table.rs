@@ -0,0 +1,120 @@
+//! Central authorization table: `(method, path pattern) -> access`, see [`protect_endpoints_core::table`].
+//!
+//! The table is applied via [`TableMiddleware`], which must be wrapped inside of the
+//! [`GrantsMiddleware`](crate::GrantsMiddleware) (i.e. registered before it) to see the attached authorities.
+
+use crate::authorities::AuthDetails;
+use crate::error::{AccessDenied, Unauthorized};
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage};
+use std::fmt::Display;
+use std::future::{self, Future, Ready};
+use std::hash::Hash;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+pub use protect_endpoints_core::table::{
+    Access, AuthorizationTable, PathPattern, Rejection, Rule, SharedTable, TableError,
+};
+
+/// Middleware checking requests against the [`AuthorizationTable`].
+///
+/// # Example
+/// ```
+/// use actix_web::dev::ServiceRequest;
+/// use actix_web::{web, App, Error, HttpResponse};
+/// use actix_web_grants::table::{Access, AuthorizationTable, TableMiddleware};
+/// use actix_web_grants::GrantsMiddleware;
+/// use std::collections::HashSet;
+///
+/// async fn extract(_req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+///     Ok(HashSet::from(["ROLE_ADMIN".to_string()]))
+/// }
+///
+/// let table = AuthorizationTable::new()
+///     .route("GET", "/", Access::PermitAll)
+///     .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+///     .otherwise(Access::Authenticated);
+///
+/// let app = App::new()
+///     .wrap(TableMiddleware::new(table))
+///     .wrap(GrantsMiddleware::with_extractor(extract))
+///     .route("/", web::get().to(HttpResponse::Ok));
+/// ```
+pub struct TableMiddleware<T = String> {
+    table: SharedTable<T>,
+}
+
+impl<T> TableMiddleware<T> {
+    pub fn new(table: impl Into<SharedTable<T>>) -> Self {
+        Self {
+            table: table.into(),
+        }
+    }
+}
+
+impl<S, B, T> Transform<S, ServiceRequest> for TableMiddleware<T>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    T: Eq + Hash + Clone + Display + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = TableService<S, T>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ready(Ok(TableService {
+            service: Rc::new(service),
+            table: self.table.clone(),
+        }))
+    }
+}
+
+pub struct TableService<S, T = String> {
+    service: Rc<S>,
+    table: SharedTable<T>,
+}
+
+impl<S, B, T> Service<ServiceRequest> for TableService<S, T>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    T: Eq + Hash + Clone + Display + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<ServiceResponse<EitherBody<B>>, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let checked = {
+            let extensions = req.extensions();
+            let details = extensions
+                .get::<AuthDetails<T>>()
+                .filter(|details| !details.is_anonymous());
+            self.table
+                .load()
+                .authorize(req.method().as_str(), req.path(), details)
+        };
+
+        Box::pin(async move {
+            match checked {
+                Ok(()) => Ok(service.call(req).await?.map_into_left_body()),
+                Err(Rejection::Unauthorized) => {
+                    Ok(req.error_response(Unauthorized).map_into_right_body())
+                }
+                Err(Rejection::Forbidden(denied)) => Ok(req
+                    .error_response(AccessDenied::from(denied))
+                    .map_into_right_body()),
+            }
+        })
+    }
+}
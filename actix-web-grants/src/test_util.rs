@@ -0,0 +1,74 @@
+//! Injection of authorities into test requests, see [`protect_endpoints_core::test_util`].
+//!
+//! # Example
+//! ```
+//! use actix_web::http::StatusCode;
+//! use actix_web::{get, test, App, HttpResponse};
+//! use actix_web_grants::test_util::{MockExtractor, WithAuthorities};
+//! use actix_web_grants::GrantsMiddleware;
+//!
+//! #[get("/")]
+//! #[actix_web_grants::protect("ROLE_ADMIN")]
+//! async fn secured() -> HttpResponse {
+//!     HttpResponse::Ok().body("some secured info")
+//! }
+//!
+//! # actix_web::rt::System::new().block_on(async {
+//! let extractor = MockExtractor::new();
+//! let app = test::init_service(
+//!     App::new()
+//!         .wrap(GrantsMiddleware::with_extractor(extractor.clone()))
+//!         .service(secured),
+//! )
+//! .await;
+//!
+//! let req = test::TestRequest::get().with_authorities(["ROLE_ADMIN"]);
+//! let resp = test::call_service(&app, req.to_request()).await;
+//!
+//! assert_eq!(StatusCode::OK, resp.status());
+//! assert_eq!(1, extractor.calls().len());
+//! # });
+//! ```
+
+use crate::authorities::AuthoritiesExtractor;
+use actix_web::dev::ServiceRequest;
+use actix_web::test::TestRequest;
+use actix_web::Error;
+use protect_endpoints_core::test_util::header_value;
+pub use protect_endpoints_core::test_util::{Call, AUTHORITIES_HEADER};
+use std::collections::HashSet;
+use std::future::{ready, Ready};
+
+/// Extractor of authorities given by [`WithAuthorities`], scripted results and errors take precedence.
+pub type MockExtractor = protect_endpoints_core::test_util::MockExtractor<Error>;
+
+impl<'a> AuthoritiesExtractor<'a, &ServiceRequest, String> for MockExtractor {
+    type Future = Ready<Result<HashSet<String>, Error>>;
+
+    fn extract(&self, req: &'a mut ServiceRequest) -> Self::Future {
+        let header = req
+            .headers()
+            .get(AUTHORITIES_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        ready(self.respond(Call::new(req.method().as_str(), req.path(), header)))
+    }
+}
+
+/// Passes authorities of the test request to the [`MockExtractor`].
+pub trait WithAuthorities {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>;
+}
+
+impl WithAuthorities for TestRequest {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.insert_header((AUTHORITIES_HEADER, header_value(authorities)))
+    }
+}
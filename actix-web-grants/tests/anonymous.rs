@@ -0,0 +1,91 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::StatusCode;
+use actix_web::{get, test, App, Error};
+use actix_web_grants::authorities::{set_anonymous_authorities, AuthDetails, AuthoritiesCheck};
+use actix_web_grants::{protect, GrantsMiddleware};
+use std::collections::HashSet;
+
+#[get("/allow_anonymous")]
+#[protect(rule = "ANONYMOUS || ROLE_USER", allow_anonymous)]
+async fn allow_anonymous() -> &'static str {
+    "Hi!"
+}
+
+#[get("/user_only")]
+#[protect("ROLE_USER")]
+async fn user_only() -> &'static str {
+    "Hi!"
+}
+
+#[get("/optional")]
+async fn optional(details: Option<AuthDetails>) -> &'static str {
+    match details {
+        Some(details) if details.has_authority("ROLE_USER") => "user",
+        Some(_) => "authenticated",
+        None => "anonymous",
+    }
+}
+
+#[actix_web::test]
+async fn test_anonymous() {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    assert_eq!(
+        (StatusCode::OK, "Hi!".into()),
+        get_response("/allow_anonymous", None).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "Hi!".into()),
+        get_response("/allow_anonymous", Some("ROLE_USER")).await
+    );
+    let (status, _) = get_response("/allow_anonymous", Some("ROLE_OTHER")).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let (status, _) = get_response("/user_only", None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+
+    assert_eq!(
+        (StatusCode::OK, "anonymous".into()),
+        get_response("/optional", None).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "user".into()),
+        get_response("/optional", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "authenticated".into()),
+        get_response("/optional", Some("ROLE_OTHER")).await
+    );
+}
+
+async fn extract(req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_response(uri: &str, role: Option<&str>) -> (StatusCode, String) {
+    let app = test::init_service(
+        App::new()
+            .wrap(GrantsMiddleware::with_extractor(extract))
+            .service(allow_anonymous)
+            .service(user_only)
+            .service(optional),
+    )
+    .await;
+
+    let mut req = test::TestRequest::default().uri(uri);
+    if let Some(role) = role {
+        req = req.insert_header((AUTHORIZATION, role));
+    }
+    let resp = test::call_service(&app, req.to_request()).await;
+    let status = resp.status();
+    let body = test::read_body(resp).await;
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
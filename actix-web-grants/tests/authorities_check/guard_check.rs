@@ -4,7 +4,8 @@ use actix_web::{test, web, App, HttpResponse};
 
 use crate::common::{self, Role, ROLE_ADMIN, ROLE_MANAGER};
 use actix_web::http::{header::AUTHORIZATION, StatusCode};
-use actix_web_grants::{AuthorityGuard, GrantsMiddleware};
+use actix_web_grants::condition::Condition;
+use actix_web_grants::{AuthorityGuard, ConditionGuard, GrantsMiddleware};
 
 #[actix_rt::test]
 async fn test_guard() {
@@ -24,6 +25,18 @@ async fn test_enum_guard() {
     assert_eq!(StatusCode::NOT_FOUND, test_manager.status());
 }
 
+#[actix_rt::test]
+async fn test_condition_guard() {
+    let test_admin = get_user_response_with_condition("/admin", ROLE_ADMIN).await;
+    let test_manager = get_user_response_with_condition("/admin", ROLE_MANAGER).await;
+    let test_blocked =
+        get_user_response_with_condition("/admin", "ROLE_MANAGER,ROLE_BLOCKED").await;
+
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::OK, test_manager.status());
+    assert_eq!(StatusCode::NOT_FOUND, test_blocked.status());
+}
+
 async fn get_user_response(uri: &str, role: &str) -> ServiceResponse<EitherBody<BoxBody>> {
     let app = test::init_service(
         App::new()
@@ -66,3 +79,29 @@ async fn get_user_response_with_enum(
         .to_request();
     test::call_service(&app, req).await
 }
+
+async fn get_user_response_with_condition(
+    uri: &str,
+    role: &str,
+) -> ServiceResponse<EitherBody<BoxBody>> {
+    let condition: Condition<String> = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)"
+        .parse()
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .wrap(GrantsMiddleware::with_extractor(common::extract))
+            .service(
+                web::resource("/admin")
+                    .to(|| async { HttpResponse::Ok().finish() })
+                    .guard(ConditionGuard::new(condition)),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::default()
+        .insert_header((AUTHORIZATION, role))
+        .uri(uri)
+        .to_request();
+    test::call_service(&app, req).await
+}
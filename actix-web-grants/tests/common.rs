@@ -1,7 +1,8 @@
 use actix_web::dev::{ServiceRequest, ServiceResponse};
 use actix_web::error::ErrorUnauthorized;
-use actix_web::http::header::{HeaderValue, AUTHORIZATION};
+use actix_web::http::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use actix_web::{test, Error};
+use actix_web_grants::authorities::AttachPrincipal;
 use serde::Deserialize;
 use std::collections::HashSet;
 use std::hash::Hash;
@@ -9,6 +10,12 @@ use std::str::FromStr;
 
 pub const ROLE_ADMIN: &str = "ROLE_ADMIN";
 pub const ROLE_MANAGER: &str = "ROLE_MANAGER";
+pub const TENANT_HEADER: &str = "x-tenant";
+pub const SUBJECT: &str = "user-1";
+
+pub struct Claims {
+    pub tenant: String,
+}
 
 #[derive(parse_display::Display, parse_display::FromStr, PartialEq, Eq, Hash)]
 #[display(style = "SNAKE_CASE")]
@@ -27,6 +34,15 @@ pub enum Permission {
 }
 
 pub async fn extract(req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+    let tenant = req
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .map(str::to_string);
+    if let Some(tenant) = tenant {
+        req.attach_principal(SUBJECT, Claims { tenant });
+    }
+
     let auth_header: Option<&str> = req
         .headers()
         .get(AUTHORIZATION)
@@ -59,6 +75,21 @@ pub async fn enum_extract<T: FromStr + Eq + Hash>(
         .ok_or_else(|| ErrorUnauthorized("Authorization header incorrect!"))
 }
 
+/// Default denial without explanation, the correlation id is generated.
+pub async fn test_problem<B: actix_web::body::MessageBody>(resp: ServiceResponse<B>) {
+    assert_eq!(
+        Some("application/problem+json"),
+        resp.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    );
+    let body = test::read_body(resp).await;
+
+    assert!(String::from_utf8(body.to_vec()).unwrap().starts_with(
+        r#"{"type":"about:blank","title":"Forbidden","status":403,"correlation_id":""#
+    ));
+}
+
 pub async fn test_body<B: actix_web::body::MessageBody>(
     resp: ServiceResponse<B>,
     expected_body: &str,
@@ -0,0 +1,110 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::error::ErrorUnauthorized;
+use actix_web::middleware::Condition;
+use actix_web::{test, web, App, Error, HttpResponse};
+use actix_web_grants::{protect, GrantsMiddleware};
+use protect_endpoints_testkit::{
+    header_value, parse_header_value, Adapter, Credentials, Role, AUTHORITIES_HEADER, OWNER,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::future::{ready, Ready};
+use std::hash::Hash;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+struct NamePayload {
+    name: Option<String>,
+}
+
+#[protect("ROLE_ADMIN")]
+async fn single() -> HttpResponse {
+    HttpResponse::Ok().body("Hi!")
+}
+
+#[protect("ROLE_ADMIN", "ROLE_MANAGER")]
+async fn all() -> HttpResponse {
+    HttpResponse::Ok().body("Hi!")
+}
+
+#[protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]
+async fn any() -> HttpResponse {
+    HttpResponse::Ok().body("Hi!")
+}
+
+#[protect("ROLE_ADMIN", expr = "payload.name.as_deref() == Some(OWNER)")]
+async fn expr(payload: web::Query<NamePayload>) -> HttpResponse {
+    HttpResponse::Ok().body("Hi!")
+}
+
+#[protect("Role::Admin", ty = "Role")]
+async fn typed() -> HttpResponse {
+    HttpResponse::Ok().body("Hi!")
+}
+
+fn extractor<T: FromStr + Eq + Hash>(
+    credentials: Credentials,
+) -> impl Fn(&ServiceRequest) -> Ready<Result<HashSet<T>, Error>> {
+    move |req| {
+        if credentials == Credentials::ExtractorError {
+            return ready(Err(ErrorUnauthorized("extractor error")));
+        }
+
+        let authorities = req
+            .headers()
+            .get(AUTHORITIES_HEADER)
+            .and_then(|header| header.to_str().ok())
+            .map(parse_header_value)
+            .unwrap_or_default();
+        ready(Ok(authorities
+            .iter()
+            .filter_map(|authority| authority.parse().ok())
+            .collect()))
+    }
+}
+
+struct ActixWeb;
+
+impl Adapter for ActixWeb {
+    async fn status(&self, path: &str, credentials: &Credentials) -> u16 {
+        let enabled = *credentials != Credentials::Missing;
+        let app = test::init_service(
+            App::new()
+                .service(
+                    web::scope("/typed")
+                        .wrap(Condition::new(
+                            enabled,
+                            GrantsMiddleware::with_extractor(extractor::<Role>(*credentials)),
+                        ))
+                        .route("", web::get().to(typed)),
+                )
+                .service(
+                    web::scope("")
+                        .wrap(Condition::new(
+                            enabled,
+                            GrantsMiddleware::with_extractor(extractor::<String>(*credentials)),
+                        ))
+                        .route("/single", web::get().to(single))
+                        .route("/all", web::get().to(all))
+                        .route("/any", web::get().to(any))
+                        .route("/expr", web::get().to(expr)),
+                ),
+        )
+        .await;
+
+        let mut request = test::TestRequest::get().uri(path);
+        if let Credentials::Authorities(authorities) = credentials {
+            request = request.insert_header((AUTHORITIES_HEADER, header_value(*authorities)));
+        }
+
+        test::call_service(&app, request.to_request())
+            .await
+            .status()
+            .as_u16()
+    }
+}
+
+#[actix_rt::test]
+async fn test_conformance() {
+    protect_endpoints_testkit::run(&ActixWeb).await;
+}
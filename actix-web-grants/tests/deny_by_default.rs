@@ -0,0 +1,99 @@
+#![cfg(feature = "deny-by-default")]
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::StatusCode;
+use actix_web::{get, test, App, Error};
+use actix_web_grants::deny_by_default::{DenyByDefault, Routes};
+use actix_web_grants::GrantsMiddleware;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[get("/")]
+#[actix_web_grants::public]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+#[get("/admin")]
+#[actix_web_grants::protect("ROLE_ADMIN")]
+async fn admin() -> &'static str {
+    "Hello, admin!"
+}
+
+static FORGOTTEN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[get("/forgotten")]
+async fn forgotten() -> &'static str {
+    FORGOTTEN_CALLS.fetch_add(1, Ordering::SeqCst);
+    "Oops!"
+}
+
+#[get("/unlisted")]
+#[actix_web_grants::public]
+async fn unlisted() -> &'static str {
+    "Hi!"
+}
+
+#[actix_web::test]
+async fn test_deny_by_default() {
+    assert_eq!(StatusCode::OK, get_status("/", None).await);
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/unlisted", None).await);
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/missing", None).await);
+}
+
+#[actix_web::test]
+async fn test_forgotten_handler_not_called() {
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(0, FORGOTTEN_CALLS.load(Ordering::SeqCst));
+}
+
+async fn extract(req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+fn routes() -> Routes {
+    Routes::new()
+        .route("GET", "/", index)
+        .route("GET", "/admin", admin)
+        .route("GET", "/forgotten", forgotten)
+}
+
+async fn get_status(uri: &str, role: Option<&str>) -> StatusCode {
+    let app = test::init_service(
+        App::new()
+            .wrap(DenyByDefault::new(routes()))
+            .wrap(GrantsMiddleware::with_extractor(extract))
+            .service(index)
+            .service(admin)
+            .service(forgotten)
+            .service(unlisted),
+    )
+    .await;
+
+    let mut req = test::TestRequest::default().uri(uri);
+    if let Some(role) = role {
+        req = req.insert_header((AUTHORIZATION, role));
+    }
+    test::call_service(&app, req.to_request()).await.status()
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "registry")]
+
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::{get, test, web, App};
+
+#[get("/users")]
+#[actix_web_grants::protect("ROLE_ADMIN")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[get("/article")]
+#[actix_web_grants::protect(any("ROLE_ADMIN", "ROLE_EDITOR"), expr = "true")]
+async fn article() -> &'static str {
+    "Hi!"
+}
+
+async fn get_body(uri: &str) -> String {
+    let app = test::init_service(App::new().service(users).service(article).route(
+        "/admin/access",
+        web::get().to(actix_web_grants::introspection::handler),
+    ))
+    .await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    assert_eq!("application/json", res.headers().get(CONTENT_TYPE).unwrap());
+
+    String::from_utf8(test::read_body(res).await.to_vec()).unwrap()
+}
+
+#[actix_rt::test]
+async fn test_introspection() {
+    let body = get_body("/admin/access").await;
+    assert!(body.starts_with(r#"{"endpoints":[{"handler":"introspection::users","#));
+    assert!(!body.contains("access"));
+
+    let body = get_body("/admin/access?authorities=ROLE_EDITOR").await;
+    assert!(body.starts_with(r#"{"authorities":["ROLE_EDITOR"],"endpoints":["#));
+    assert!(body.contains(r#""condition":"ROLE_ADMIN","dynamic":false,"access":"denied"}"#));
+    assert!(body.contains(r#""dynamic":true,"access":"depends"}"#));
+}
@@ -0,0 +1,92 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::StatusCode;
+use actix_web::{get, test, web, App, Error};
+use actix_web_grants::network::NetworkMiddleware;
+use actix_web_grants::{protect, GrantsMiddleware};
+use std::collections::HashSet;
+
+const INTERNAL: &str = "10.1.2.3:4000";
+const EXTERNAL: &str = "203.0.113.1:4000";
+
+#[get("/from_network")]
+#[protect(any("ROLE_ADMIN", from_network = "10.0.0.0/8, ::1"))]
+async fn from_network() -> &'static str {
+    "Hi!"
+}
+
+#[get("/metrics")]
+async fn metrics() -> &'static str {
+    "up 1"
+}
+
+#[actix_web::test]
+async fn test_from_network() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", "[::1]:4000").await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_ADMIN", EXTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/from_network", "ROLE_USER", EXTERNAL).await
+    );
+}
+
+#[actix_web::test]
+async fn test_network_middleware() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/internal/metrics", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/internal/metrics", "ROLE_ADMIN", EXTERNAL).await
+    );
+    // forwarded headers of untrusted peers are ignored
+    let spoofed = test::TestRequest::default()
+        .uri("/internal/metrics")
+        .insert_header(("X-Forwarded-For", "10.1.2.3"))
+        .peer_addr(EXTERNAL.parse().unwrap());
+    assert_eq!(StatusCode::FORBIDDEN, call(spoofed).await);
+}
+
+async fn extract(req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str, peer: &str) -> StatusCode {
+    let req = test::TestRequest::default()
+        .uri(uri)
+        .insert_header((AUTHORIZATION, role))
+        .peer_addr(peer.parse().unwrap());
+    call(req).await
+}
+
+async fn call(req: test::TestRequest) -> StatusCode {
+    let app = test::init_service(
+        App::new()
+            .wrap(GrantsMiddleware::with_extractor(extract))
+            .service(from_network)
+            .service(
+                web::scope("/internal")
+                    .wrap(NetworkMiddleware::new("10.0.0.0/8".parse().unwrap()))
+                    .service(metrics),
+            ),
+    )
+    .await;
+
+    test::call_service(&app, req.to_request()).await.status()
+}
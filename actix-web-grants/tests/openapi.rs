@@ -0,0 +1,46 @@
+#![cfg(feature = "utoipa")]
+
+use actix_web::{get, App};
+use actix_web_grants::registry::openapi::{ProtectedOperations, EXTENSION};
+use serde_json::Value;
+use utoipa::OpenApi;
+
+/// Lists users.
+#[utoipa::path(get, path = "/users", responses((status = 200, description = "Users")))]
+#[get("/users")]
+#[actix_web_grants::protect("ROLE_ADMIN", expr = "user_id.is_some()")]
+async fn users(user_id: Option<actix_web::web::ReqData<u32>>) -> &'static str {
+    "Hi!"
+}
+
+#[utoipa::path(get, path = "/", responses((status = 200, description = "Index")))]
+#[get("/")]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+const PROTECTED: ProtectedOperations = ProtectedOperations::new();
+
+#[derive(OpenApi)]
+#[openapi(paths(users, index), modifiers(&PROTECTED))]
+struct ApiDoc;
+
+#[test]
+fn test_protected_operations() {
+    let _app = App::new().service(users).service(index);
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+    let operation: &Value = &spec["paths"]["/users"]["get"];
+    assert_eq!("Lists users.", operation["summary"]);
+    assert_eq!(
+        "Requires `ROLE_ADMIN` and a condition on the request.",
+        operation["description"]
+    );
+    assert_eq!("ROLE_ADMIN", operation[EXTENSION]);
+    assert!(operation["responses"]["401"].is_object());
+    assert!(operation["responses"]["403"].is_object());
+    assert!(operation.get("security").is_none());
+
+    let operation = &spec["paths"]["/"]["get"];
+    assert!(operation["responses"].get("403").is_none());
+}
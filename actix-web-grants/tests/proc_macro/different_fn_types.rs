@@ -2,10 +2,16 @@ use crate::common::{self, ROLE_ADMIN, ROLE_MANAGER};
 use actix_web::body::{BoxBody, EitherBody};
 use actix_web::dev::ServiceResponse;
 use actix_web::error::ErrorBadRequest;
-use actix_web::http::{header::AUTHORIZATION, StatusCode};
-use actix_web::{get, http, post, test, web, App, Error, HttpResponse};
-use actix_web_grants::{protect, GrantsMiddleware};
+use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use actix_web::http::StatusCode;
+use actix_web::{get, http, post, test, web, App, Error, HttpRequest, HttpResponse};
+use actix_web_grants::authorities::{
+    reset_clock, set_clock, AuthDetails, AuthoritiesCheck, Principal,
+};
+use actix_web_grants::error::AccessDenied;
+use actix_web_grants::{protect, requirement_of, GrantsMiddleware};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
 #[get("/http_response")]
 #[protect("ROLE_ADMIN")]
@@ -57,6 +63,39 @@ async fn access_response() -> &'static str {
     "Hi!"
 }
 
+async fn access_denied_with_context(
+    details: &AuthDetails,
+    denied: &AccessDenied,
+    req: &HttpRequest,
+) -> HttpResponse {
+    let user = if details.has_authority(ROLE_MANAGER) {
+        "Manager"
+    } else {
+        "User"
+    };
+
+    HttpResponse::Forbidden().body(format!(
+        "{user} can't access {}, missing: {}",
+        req.path(),
+        denied.missing_authorities().join(", ")
+    ))
+}
+
+#[get("/context")]
+#[protect(
+    "ROLE_ADMIN",
+    error = "access_denied_with_context(auth_details, denied, request).await"
+)]
+async fn context_response() -> &'static str {
+    "Hi!"
+}
+
+#[get("/rule")]
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)")]
+async fn rule_response() -> &'static str {
+    "Hi!"
+}
+
 #[actix_rt::test]
 async fn test_http_response() {
     let test_admin = get_user_response("/http_response", ROLE_ADMIN).await;
@@ -73,9 +112,13 @@ async fn test_str() {
 
     assert_eq!(StatusCode::OK, test_admin.status());
     assert_eq!(StatusCode::FORBIDDEN, test_manager.status());
+    assert_eq!(
+        r#"Bearer error="insufficient_scope", scope="ROLE_ADMIN""#,
+        test_manager.headers().get(WWW_AUTHENTICATE).unwrap()
+    );
 
     common::test_body(test_admin, "Hi!").await;
-    common::test_body(test_manager, "").await;
+    common::test_problem(test_manager).await;
 }
 
 #[actix_rt::test]
@@ -96,7 +139,7 @@ async fn test_secure_with_user_id() {
     assert_eq!(StatusCode::FORBIDDEN, test_err.status());
 
     common::test_body(test_ok, "Hi!").await;
-    common::test_body(test_err, "").await;
+    common::test_problem(test_err).await;
 }
 
 #[actix_rt::test]
@@ -111,6 +154,17 @@ async fn test_result() {
     common::test_body(test_err, "Query param not found!").await;
 }
 
+#[actix_rt::test]
+async fn test_rule() {
+    let test_admin = get_user_response("/rule", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/rule", ROLE_MANAGER).await;
+    let test_blocked = get_user_response("/rule", "ROLE_MANAGER,ROLE_BLOCKED").await;
+
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::OK, test_manager.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_blocked.status());
+}
+
 #[actix_rt::test]
 async fn test_access_denied_reason() {
     let test_admin = get_user_response("/access", ROLE_ADMIN).await;
@@ -123,7 +177,93 @@ async fn test_access_denied_reason() {
     common::test_body(test_manager, "This resource allowed only for ADMIN").await;
 }
 
+#[actix_rt::test]
+async fn test_access_denied_context() {
+    let test_admin = get_user_response("/context", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/context", ROLE_MANAGER).await;
+
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_manager.status());
+
+    common::test_body(test_admin, "Hi!").await;
+    common::test_body(
+        test_manager,
+        "Manager can't access /context, missing: ROLE_ADMIN",
+    )
+    .await;
+}
+
+#[get("/principal")]
+#[protect(
+    "ROLE_ADMIN",
+    principal = "common::Claims",
+    expr = "principal.claims().tenant == \"acme\""
+)]
+async fn principal_response(user: Principal<common::Claims>) -> String {
+    user.subject().to_string()
+}
+
+#[get("/owner/{user_id}")]
+#[protect(any("ROLE_ADMIN", owner = "user_id"), principal = "common::Claims")]
+async fn owner_response(user_id: web::Path<String>) -> String {
+    format!("Hi, {user_id}!")
+}
+
+#[actix_web::test]
+async fn test_owner() {
+    let test_owner = get_tenant_response("/owner/user-1", ROLE_MANAGER, Some("acme")).await;
+    let test_other = get_tenant_response("/owner/user-2", ROLE_MANAGER, Some("acme")).await;
+    let test_admin = get_tenant_response("/owner/user-2", ROLE_ADMIN, Some("acme")).await;
+    let test_without_principal = get_tenant_response("/owner/user-1", ROLE_MANAGER, None).await;
+
+    assert_eq!(StatusCode::OK, test_owner.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_other.status());
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::UNAUTHORIZED, test_without_principal.status());
+}
+
+#[get("/during")]
+#[protect("ROLE_ADMIN", during = "Mon-Fri 09:00-18:00 UTC")]
+async fn during_response() -> &'static str {
+    "Hi!"
+}
+
+#[actix_web::test]
+async fn test_during() {
+    // Monday, 2024-01-01 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_103_200));
+    let test_working_hours = get_user_response("/during", ROLE_ADMIN).await;
+    // Saturday, 2024-01-06 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_535_200));
+    let test_weekend = get_user_response("/during", ROLE_ADMIN).await;
+    reset_clock();
+
+    assert_eq!(StatusCode::OK, test_working_hours.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_weekend.status());
+}
+
+#[actix_web::test]
+async fn test_principal() {
+    let test_acme = get_tenant_response("/principal", ROLE_ADMIN, Some("acme")).await;
+    let test_other = get_tenant_response("/principal", ROLE_ADMIN, Some("other")).await;
+    let test_anonymous = get_tenant_response("/principal", ROLE_ADMIN, None).await;
+
+    assert_eq!(StatusCode::OK, test_acme.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_other.status());
+    assert_eq!(StatusCode::UNAUTHORIZED, test_anonymous.status());
+
+    common::test_body(test_acme, common::SUBJECT).await;
+}
+
 async fn get_user_response(uri: &str, role: &str) -> ServiceResponse<EitherBody<BoxBody>> {
+    get_tenant_response(uri, role, None).await
+}
+
+async fn get_tenant_response(
+    uri: &str,
+    role: &str,
+    tenant: Option<&str>,
+) -> ServiceResponse<EitherBody<BoxBody>> {
     let app = test::init_service(
         App::new()
             .wrap(GrantsMiddleware::with_extractor(common::extract))
@@ -131,15 +271,22 @@ async fn get_user_response(uri: &str, role: &str) -> ServiceResponse<EitherBody<
             .service(str_response)
             .service(return_response)
             .service(result_response)
-            .service(access_response),
+            .service(access_response)
+            .service(context_response)
+            .service(rule_response)
+            .service(principal_response)
+            .service(owner_response)
+            .service(during_response),
     )
     .await;
 
-    let req = test::TestRequest::default()
+    let mut req = test::TestRequest::default()
         .insert_header((AUTHORIZATION, role))
-        .uri(uri)
-        .to_request();
-    test::call_service(&app, req).await
+        .uri(uri);
+    if let Some(tenant) = tenant {
+        req = req.insert_header((common::TENANT_HEADER, tenant));
+    }
+    test::call_service(&app, req.to_request()).await
 }
 
 async fn post_user_response<T: Serialize>(
@@ -161,3 +308,37 @@ async fn post_user_response<T: Serialize>(
         .to_request();
     test::call_service(&app, req).await
 }
+
+// route macros of `actix-web` expand the handler within the service, so it's routed explicitly
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)", expr = "true")]
+async fn routed_rule_response() -> &'static str {
+    "Hi!"
+}
+
+#[actix_rt::test]
+async fn test_requirement() {
+    let _ = App::new().route("/rule", web::get().to(routed_rule_response));
+
+    let requirement = requirement_of!(routed_rule_response);
+    assert_eq!(
+        "(ROLE_ADMIN || ROLE_MANAGER && !ROLE_BLOCKED) && expr(true)",
+        requirement.to_string()
+    );
+    // `expr` can't be checked without a request
+    assert!(requirement.is_dynamic());
+    assert!(!requirement.is_satisfied_by(&[ROLE_ADMIN]));
+    assert!(requirement.may_be_satisfied_by(&[ROLE_ADMIN]));
+    assert!(!requirement.may_be_satisfied_by(&[ROLE_MANAGER, "ROLE_BLOCKED"]));
+}
+
+// handlers of route macros are found in the registry by their paths
+#[cfg(feature = "registry")]
+#[actix_rt::test]
+async fn test_route_macro_requirement() {
+    use actix_web_grants::registry;
+
+    let requirement = registry::requirement_of(&http_response).unwrap();
+    assert_eq!("ROLE_ADMIN", requirement.to_string());
+    assert!(requirement.is_satisfied_by(&[ROLE_ADMIN]));
+    assert!(!requirement.is_satisfied_by(&[ROLE_MANAGER]));
+}
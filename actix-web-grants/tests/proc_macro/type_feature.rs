@@ -3,7 +3,7 @@ use crate::common::Permission;
 use crate::common::Role::{self, ADMIN, MANAGER};
 use actix_web::body::{BoxBody, EitherBody};
 use actix_web::dev::ServiceResponse;
-use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
 use actix_web::http::StatusCode;
 use actix_web::{get, test, App, HttpResponse};
 use actix_web_grants::{protect, GrantsMiddleware};
@@ -75,6 +75,7 @@ async fn test_incorrect_http_response() {
     let test = get_user_response("/incorrect_enum_secure", &ADMIN.to_string()).await;
 
     assert_eq!(StatusCode::UNAUTHORIZED, test.status());
+    assert_eq!("Bearer", test.headers().get(WWW_AUTHENTICATE).unwrap());
 }
 
 async fn get_user_response(
@@ -0,0 +1,126 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App, Error};
+#[cfg(feature = "deny-by-default")]
+use actix_web_grants::deny_by_default::{DenyByDefault, Routes};
+use actix_web_grants::GrantsMiddleware;
+use std::collections::HashSet;
+
+#[actix_web_grants::protect_all("ROLE_ADMIN")]
+mod admin {
+    use actix_web::get;
+
+    #[get("/admin")]
+    pub async fn index() -> &'static str {
+        "Hello, admin!"
+    }
+
+    #[get("/admin/users")]
+    #[protect("OP_WRITE")]
+    pub async fn users() -> &'static str {
+        "Users"
+    }
+
+    #[get("/admin/tickets")]
+    #[protect(any("ROLE_ADMIN", "ROLE_SUPPORT"), overrides)]
+    pub async fn tickets() -> &'static str {
+        "Tickets"
+    }
+
+    #[get("/admin/health")]
+    #[public]
+    pub async fn health() -> &'static str {
+        "Ok"
+    }
+
+    #[protect_all(skip)]
+    pub async fn load_title() -> &'static str {
+        "Admin"
+    }
+}
+
+struct Reports;
+
+#[actix_web_grants::protect_all("ROLE_MANAGER")]
+impl Reports {
+    async fn daily() -> &'static str {
+        "Daily"
+    }
+}
+
+#[actix_web::test]
+async fn test_module() {
+    assert_eq!("Admin", admin::load_title().await);
+    assert_eq!(StatusCode::OK, get_status("/admin", "ROLE_ADMIN").await);
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", "ROLE_SUPPORT").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/users", "ROLE_ADMIN,OP_WRITE").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin/users", "ROLE_ADMIN").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/tickets", "ROLE_SUPPORT").await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/health", "ROLE_USER").await
+    );
+}
+
+#[actix_web::test]
+async fn test_impl() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/reports/daily", "ROLE_MANAGER").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/reports/daily", "ROLE_ADMIN").await
+    );
+}
+
+async fn extract(req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str) -> StatusCode {
+    let app = App::new();
+    #[cfg(feature = "deny-by-default")]
+    let app = app.wrap(DenyByDefault::new(
+        Routes::new()
+            .route("GET", "/admin", admin::index)
+            .route("GET", "/admin/users", admin::users)
+            .route("GET", "/admin/tickets", admin::tickets)
+            .route("GET", "/admin/health", admin::health)
+            .route("GET", "/reports/daily", Reports::daily),
+    ));
+    let app = test::init_service(
+        app.wrap(GrantsMiddleware::with_extractor(extract))
+            .service(admin::index)
+            .service(admin::users)
+            .service(admin::tickets)
+            .service(admin::health)
+            .route("/reports/daily", web::get().to(Reports::daily)),
+    )
+    .await;
+
+    let req = test::TestRequest::default()
+        .uri(uri)
+        .insert_header((AUTHORIZATION, role));
+    test::call_service(&app, req.to_request()).await.status()
+}
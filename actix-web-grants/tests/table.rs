@@ -0,0 +1,102 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::{Method, StatusCode};
+use actix_web::{test, web, App, Error};
+use actix_web_grants::authorities::set_anonymous_authorities;
+use actix_web_grants::table::{Access, AuthorizationTable, TableMiddleware};
+use actix_web_grants::GrantsMiddleware;
+use std::collections::HashSet;
+
+async fn hi() -> &'static str {
+    "Hi!"
+}
+
+#[actix_web::test]
+async fn test_table() {
+    assert_eq!(StatusCode::OK, get_status(Method::GET, "/", None).await);
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        get_status(Method::GET, "/profile", None).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/profile", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/admin/users", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/admin/users", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::POST, "/articles/1", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::POST, "/articles/1", Some("ROLE_EDITOR")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/internal/metrics", Some("ROLE_ADMIN")).await
+    );
+}
+
+#[actix_web::test]
+async fn test_normalized_paths() {
+    for uri in ["/%61dmin/users", "//admin/users", "/./admin/users"] {
+        assert_eq!(
+            StatusCode::FORBIDDEN,
+            get_status(Method::GET, uri, Some("ROLE_USER")).await,
+            "{uri}"
+        );
+    }
+}
+
+fn table() -> AuthorizationTable {
+    AuthorizationTable::new()
+        .route("GET", "/", Access::PermitAll)
+        .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+        .route(
+            "POST",
+            "/articles/{id}",
+            "ROLE_EDITOR || ROLE_ADMIN".parse::<Access>().unwrap(),
+        )
+        .route("*", "/internal/**", Access::DenyAll)
+        .otherwise(Access::Authenticated)
+}
+
+async fn extract(req: &ServiceRequest) -> Result<HashSet<String>, Error> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(method: Method, uri: &str, role: Option<&str>) -> StatusCode {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    let app = test::init_service(
+        App::new()
+            .wrap(TableMiddleware::new(table()))
+            .wrap(GrantsMiddleware::with_extractor(extract))
+            .route("/", web::get().to(hi))
+            .route("/profile", web::get().to(hi))
+            .route("/admin/users", web::get().to(hi))
+            .route("/articles/1", web::post().to(hi))
+            .route("/internal/metrics", web::get().to(hi)),
+    )
+    .await;
+
+    let mut req = test::TestRequest::default().method(method).uri(uri);
+    if let Some(role) = role {
+        req = req.insert_header((AUTHORIZATION, role));
+    }
+    test::call_service(&app, req.to_request()).await.status()
+}
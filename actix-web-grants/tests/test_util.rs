@@ -0,0 +1,83 @@
+#![cfg(feature = "test-util")]
+
+use actix_web::error::ErrorUnauthorized;
+use actix_web::http::StatusCode;
+use actix_web::{get, test, App, HttpResponse};
+use actix_web_grants::test_util::{MockExtractor, WithAuthorities};
+use actix_web_grants::{protect, GrantsMiddleware};
+use std::collections::HashSet;
+
+const ROLE_ADMIN: &str = "ROLE_ADMIN";
+
+#[get("/admin")]
+#[protect("ROLE_ADMIN")]
+async fn admin() -> HttpResponse {
+    HttpResponse::Ok().body("Hi!")
+}
+
+async fn get_status(extractor: &MockExtractor, request: test::TestRequest) -> StatusCode {
+    let app = test::init_service(
+        App::new()
+            .wrap(GrantsMiddleware::with_extractor(extractor.clone()))
+            .service(admin),
+    )
+    .await;
+
+    test::call_service(&app, request.to_request())
+        .await
+        .status()
+}
+
+fn admin_request(authorities: &[&str]) -> test::TestRequest {
+    test::TestRequest::get()
+        .uri("/admin")
+        .with_authorities(authorities)
+}
+
+#[actix_rt::test]
+async fn test_with_authorities() {
+    let extractor = MockExtractor::new();
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(&extractor, admin_request(&[])).await
+    );
+    let request = test::TestRequest::get().uri("/admin");
+    assert_eq!(StatusCode::FORBIDDEN, get_status(&extractor, request).await);
+
+    let calls = extractor.calls();
+    assert_eq!(3, calls.len());
+    assert_eq!("GET", calls[0].method());
+    assert_eq!("/admin", calls[0].path());
+    assert_eq!(
+        Some(&HashSet::from([ROLE_ADMIN.to_string()])),
+        calls[0].authorities()
+    );
+    assert_eq!(None, calls[2].authorities());
+}
+
+#[actix_rt::test]
+async fn test_scripted_results() {
+    let extractor = MockExtractor::new();
+    extractor
+        .then_return([ROLE_ADMIN])
+        .then_fail(ErrorUnauthorized("token expired"));
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status(&extractor, admin_request(&[])).await
+    );
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(3, extractor.calls().len());
+}
@@ -1,13 +1,26 @@
 //! A set of traits and structures to check authorities.
 
-use std::collections::HashSet;
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::sync::Arc;
 
+mod anonymous;
 mod attache;
 pub mod extractor;
+mod principal;
+mod validity;
 
+pub use anonymous::{
+    anonymous_authorities, is_anonymous, principal_authorities, reset_anonymous_authorities,
+    set_anonymous_authorities,
+};
 pub use attache::AttachAuthorities;
+pub use principal::{AttachPrincipal, Principal};
+pub use validity::{
+    is_authority_active, now, reset_clock, set_clock, validity_windows, Clock, SystemClock,
+    TimedAuthority, Validity,
+};
 
 /// Trait to check if the user has the required authorities.
 pub trait AuthoritiesCheck<T: Eq + Hash> {
@@ -22,27 +35,84 @@ where
     T: Eq + Hash,
 {
     pub authorities: Arc<HashSet<T>>,
+    /// Validity windows of time-bounded authorities, the rest are unbounded.
+    validity: Arc<HashMap<T, Validity>>,
 }
 
 impl<T: Eq + Hash> AuthDetails<T> {
     pub fn new(authorities: impl IntoIterator<Item = T>) -> AuthDetails<T> {
         AuthDetails {
             authorities: Arc::new(authorities.into_iter().collect()),
+            validity: Default::default(),
         }
     }
+
+    /// Details with time-bounded authorities, see [`TimedAuthority`].
+    pub fn timed(authorities: impl IntoIterator<Item = TimedAuthority<T>>) -> AuthDetails<T>
+    where
+        T: Clone,
+    {
+        let validity = validity_windows(authorities);
+        Self::with_validity(Arc::new(validity.keys().cloned().collect()), validity)
+    }
+
+    /// Details with validity windows of time-bounded authorities, the rest are unbounded.
+    pub fn with_validity(
+        authorities: Arc<HashSet<T>>,
+        validity: HashMap<T, Validity>,
+    ) -> AuthDetails<T> {
+        AuthDetails {
+            authorities,
+            validity: Arc::new(validity),
+        }
+    }
+
+    /// Validity window of the authority, `None` if it's unbounded (or missing).
+    pub fn validity<Q>(&self, authority: &Q) -> Option<&Validity>
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.validity.get(authority)
+    }
+
+    /// Whether the user has the authority and it isn't expired (or not yet valid).
+    pub fn is_active<Q>(&self, authority: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        is_authority_active(&self.authorities, &self.validity, authority)
+    }
+}
+
+impl<T: Eq + Hash + 'static> AuthDetails<T> {
+    /// Details of the anonymous principal, empty if anonymous authorities aren't configured.
+    pub fn anonymous() -> AuthDetails<T> {
+        AuthDetails {
+            authorities: anonymous_authorities().unwrap_or_default(),
+            validity: Default::default(),
+        }
+    }
+
+    /// Whether the details belong to the anonymous principal (see [`set_anonymous_authorities`]).
+    pub fn is_anonymous(&self) -> bool {
+        is_anonymous(&self.authorities)
+    }
 }
 
 impl<T: Eq + Hash> Clone for AuthDetails<T> {
     fn clone(&self) -> Self {
         Self {
             authorities: self.authorities.clone(),
+            validity: self.validity.clone(),
         }
     }
 }
 
 impl<T: Eq + Hash> AuthoritiesCheck<&T> for AuthDetails<T> {
     fn has_authority(&self, authority: &T) -> bool {
-        self.authorities.contains(authority)
+        self.is_active(authority)
     }
 
     fn has_authorities(&self, authorities: &[&T]) -> bool {
@@ -54,9 +124,38 @@ impl<T: Eq + Hash> AuthoritiesCheck<&T> for AuthDetails<T> {
     }
 }
 
+/// Plain lists of authorities, e.g. to check conditions in tests: `condition.is_satisfied_by(&["ADMIN"])`.
+impl<T: Eq + Hash> AuthoritiesCheck<&T> for [T] {
+    fn has_authority(&self, authority: &T) -> bool {
+        self.contains(authority)
+    }
+
+    fn has_authorities(&self, authorities: &[&T]) -> bool {
+        authorities.iter().all(|auth| self.has_authority(auth))
+    }
+
+    fn has_any_authority(&self, authorities: &[&T]) -> bool {
+        authorities.iter().any(|auth| self.has_authority(auth))
+    }
+}
+
+impl<T: Eq + Hash, const N: usize> AuthoritiesCheck<&T> for [T; N] {
+    fn has_authority(&self, authority: &T) -> bool {
+        self.as_slice().has_authority(authority)
+    }
+
+    fn has_authorities(&self, authorities: &[&T]) -> bool {
+        self.as_slice().has_authorities(authorities)
+    }
+
+    fn has_any_authority(&self, authorities: &[&T]) -> bool {
+        self.as_slice().has_any_authority(authorities)
+    }
+}
+
 impl AuthoritiesCheck<&str> for AuthDetails {
     fn has_authority(&self, authority: &str) -> bool {
-        self.authorities.contains(authority)
+        self.is_active(authority)
     }
 
     fn has_authorities(&self, authorities: &[&str]) -> bool {
@@ -0,0 +1,107 @@
+//! Anonymous principal: authorities of users for whom the extractor yields nothing.
+//!
+//! Anonymous authorities are configured per type of authorities and disabled by default.
+//! Once configured, an empty set of authorities is attached as the anonymous principal
+//! (shared [`Arc`], so it's recognized by [`is_anonymous`]).
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+type Authorities = Box<dyn Any + Send + Sync>;
+
+static ANONYMOUS: RwLock<Vec<Authorities>> = RwLock::new(Vec::new());
+
+/// Globally sets authorities of the anonymous principal for the type `T`, e.g. `["ANONYMOUS"]`.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::{set_anonymous_authorities, AttachAuthorities, AuthDetails};
+///
+/// set_anonymous_authorities(["ANONYMOUS".to_string()]);
+///
+/// let mut request = http::Request::new(());
+/// request.attach(Vec::<String>::new());
+///
+/// let details = request.extensions().get::<AuthDetails>().unwrap();
+/// assert!(details.is_anonymous());
+/// assert!(details.authorities.contains("ANONYMOUS"));
+/// ```
+pub fn set_anonymous_authorities<T>(authorities: impl IntoIterator<Item = T>)
+where
+    T: Eq + Hash + Send + Sync + 'static,
+{
+    let authorities: Arc<HashSet<T>> = Arc::new(authorities.into_iter().collect());
+    let mut anonymous = ANONYMOUS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    anonymous.retain(|stored| !stored.is::<Arc<HashSet<T>>>());
+    anonymous.push(Box::new(authorities));
+}
+
+/// Disables the anonymous principal for the type `T`.
+pub fn reset_anonymous_authorities<T: 'static>() {
+    ANONYMOUS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .retain(|stored| !stored.is::<Arc<HashSet<T>>>());
+}
+
+/// Authorities of the anonymous principal for the type `T`, if configured.
+pub fn anonymous_authorities<T: 'static>() -> Option<Arc<HashSet<T>>> {
+    ANONYMOUS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .find_map(|stored| stored.downcast_ref::<Arc<HashSet<T>>>())
+        .cloned()
+}
+
+/// Whether the authorities belong to the anonymous principal.
+pub fn is_anonymous<T: 'static>(authorities: &Arc<HashSet<T>>) -> bool {
+    anonymous_authorities::<T>().is_some_and(|anonymous| Arc::ptr_eq(&anonymous, authorities))
+}
+
+/// Authorities to attach to the request: the anonymous principal if the extractor yields nothing.
+pub fn principal_authorities<T>(authorities: impl IntoIterator<Item = T>) -> Arc<HashSet<T>>
+where
+    T: Eq + Hash + 'static,
+{
+    let authorities: HashSet<T> = authorities.into_iter().collect();
+    match anonymous_authorities::<T>() {
+        Some(anonymous) if authorities.is_empty() => anonymous,
+        _ => Arc::new(authorities),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Eq, PartialEq, Hash)]
+    enum Role {
+        Anonymous,
+        User,
+    }
+
+    #[test]
+    fn test_anonymous_authorities() {
+        assert!(anonymous_authorities::<Role>().is_none());
+        assert!(!is_anonymous(&principal_authorities::<Role>([])));
+
+        set_anonymous_authorities([Role::Anonymous]);
+
+        let anonymous = principal_authorities::<Role>([]);
+        assert!(is_anonymous(&anonymous));
+        assert!(anonymous.contains(&Role::Anonymous));
+
+        let user = principal_authorities([Role::User]);
+        assert!(!is_anonymous(&user));
+        assert!(!is_anonymous(&Arc::new(HashSet::from([Role::Anonymous]))));
+
+        reset_anonymous_authorities::<Role>();
+        assert!(!is_anonymous(&anonymous));
+    }
+}
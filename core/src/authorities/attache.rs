@@ -1,3 +1,5 @@
+use super::{validity_windows, TimedAuthority};
+
 /// Allows you to transfer authorities to a web framework.
 ///
 /// # Example
@@ -15,6 +17,21 @@
 ///
 pub trait AttachAuthorities<Type> {
     fn attach(&mut self, authorities: impl IntoIterator<Item = Type>);
+
+    /// Attaches time-bounded authorities, expired ones are ignored by checks.
+    ///
+    /// By default, only authorities active at the moment are attached (without their windows).
+    fn attach_timed(&mut self, authorities: impl IntoIterator<Item = TimedAuthority<Type>>)
+    where
+        Type: Clone,
+    {
+        self.attach(
+            authorities
+                .into_iter()
+                .filter(TimedAuthority::is_active)
+                .map(|timed| timed.authority),
+        );
+    }
 }
 
 impl<Type, Body> AttachAuthorities<Type> for http::Request<Body>
@@ -22,7 +39,21 @@ where
     Type: Eq + std::hash::Hash + Send + Sync + 'static,
 {
     fn attach(&mut self, authorities: impl IntoIterator<Item = Type>) {
+        self.extensions_mut().insert(super::AuthDetails {
+            authorities: super::principal_authorities(authorities),
+            validity: Default::default(),
+        });
+    }
+
+    fn attach_timed(&mut self, authorities: impl IntoIterator<Item = TimedAuthority<Type>>)
+    where
+        Type: Clone,
+    {
+        let validity = validity_windows(authorities);
         self.extensions_mut()
-            .insert(super::AuthDetails::new(authorities));
+            .insert(super::AuthDetails::with_validity(
+                super::principal_authorities(validity.keys().cloned()),
+                validity,
+            ));
     }
 }
@@ -1,3 +1,4 @@
+use super::TimedAuthority;
 use std::collections::HashSet;
 use std::future::Future;
 use std::hash::Hash;
@@ -25,10 +26,59 @@ where
     }
 }
 
+/// Store of time-bounded authorities of users (e.g. temporary on-call grants in a database).
+/// By default, is implemented for functions with signature `fn(&str) -> Result<Vec<TimedAuthority<Type>>, Error>`,
+/// where the argument is the subject of the user.
+pub trait AuthoritiesStore<'a, Type, Error> {
+    type Future: Future<Output = Result<Vec<TimedAuthority<Type>>, Error>> + Send;
+
+    fn load(&self, subject: &'a str) -> Self::Future;
+}
+
+impl<'a, F, O, Type, Error> AuthoritiesStore<'a, Type, Error> for F
+where
+    F: Fn(&'a str) -> O,
+    O: Future<Output = Result<Vec<TimedAuthority<Type>>, Error>> + Send,
+{
+    type Future = O;
+
+    fn load(&self, subject: &'a str) -> Self::Future {
+        (self)(subject)
+    }
+}
+
+/// Helper for extractors: loads authorities of the subject from the store, skipping expired (or not yet valid) ones.
+///
+/// # Example
+/// ```rust,ignore
+/// async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+///     let subject = ...;
+///     active_authorities(&store, subject).await.map_err(|_| StatusCode::UNAUTHORIZED.into_response())
+/// }
+/// ```
+pub async fn active_authorities<'a, S, Type, Error>(
+    store: &S,
+    subject: &'a str,
+) -> Result<HashSet<Type>, Error>
+where
+    S: AuthoritiesStore<'a, Type, Error>,
+    Type: Eq + Hash,
+{
+    Ok(store
+        .load(subject)
+        .await?
+        .into_iter()
+        .filter(TimedAuthority::is_active)
+        .map(|timed| timed.authority)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::AuthoritiesExtractor;
+    use super::{active_authorities, AuthoritiesExtractor};
+    use crate::authorities::TimedAuthority;
     use std::collections::HashSet;
+    use std::time::{Duration, SystemTime};
 
     struct FakeRequest;
 
@@ -45,4 +95,24 @@ mod tests {
             .iter()
             .for_each(|perm| assert_eq!("TEST_PERMISSION", perm.as_str()));
     }
+
+    async fn store(subject: &str) -> Result<Vec<TimedAuthority<String>>, ()> {
+        Ok(vec![
+            TimedAuthority::new(format!("ROLE_{subject}")),
+            TimedAuthority::new("ROLE_ON_CALL".to_string())
+                .not_before(SystemTime::now() - Duration::from_secs(60))
+                .expires_at(SystemTime::now() + Duration::from_secs(3600)),
+            TimedAuthority::new("ROLE_EXPIRED".to_string()).expires_at(SystemTime::UNIX_EPOCH),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_active_authorities() {
+        let authorities = active_authorities(&store, "USER").await.unwrap();
+
+        assert_eq!(
+            HashSet::from(["ROLE_USER".to_string(), "ROLE_ON_CALL".to_string()]),
+            authorities
+        );
+    }
 }
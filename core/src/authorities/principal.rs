@@ -0,0 +1,81 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+/// Identity of the user alongside [`AuthDetails`](super::AuthDetails):
+/// subject identifier and typed claims produced by the extractor (e.g. tenant of the token).
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::Principal;
+///
+/// struct Claims {
+///     tenant: String,
+/// }
+///
+/// let principal = Principal::new("42", Claims { tenant: "acme".to_string() });
+/// assert_eq!("42", principal.subject());
+/// assert_eq!("acme", principal.claims().tenant);
+/// ```
+pub struct Principal<C = ()> {
+    subject: Arc<str>,
+    claims: Arc<C>,
+}
+
+impl<C> Principal<C> {
+    pub fn new(subject: impl Into<String>, claims: C) -> Principal<C> {
+        Principal {
+            subject: subject.into().into(),
+            claims: Arc::new(claims),
+        }
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    pub fn claims(&self) -> &C {
+        &self.claims
+    }
+}
+
+impl<C> Clone for Principal<C> {
+    fn clone(&self) -> Self {
+        Self {
+            subject: self.subject.clone(),
+            claims: self.claims.clone(),
+        }
+    }
+}
+
+impl<C: Debug> Debug for Principal<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Principal")
+            .field("subject", &self.subject)
+            .field("claims", &self.claims)
+            .finish()
+    }
+}
+
+/// Allows you to transfer the [`Principal`] to a web framework, e.g. from the authorities extractor.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// fn handle(req: &mut Request) {
+///     let claims: Claims = ...;
+///     req.attach_principal(claims.sub.clone(), claims);
+/// }
+/// ```
+pub trait AttachPrincipal<C> {
+    fn attach_principal(&mut self, subject: impl Into<String>, claims: C);
+}
+
+impl<C, Body> AttachPrincipal<C> for http::Request<Body>
+where
+    C: Send + Sync + 'static,
+{
+    fn attach_principal(&mut self, subject: impl Into<String>, claims: C) {
+        self.extensions_mut()
+            .insert(Principal::new(subject, claims));
+    }
+}
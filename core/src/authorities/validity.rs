@@ -0,0 +1,243 @@
+//! Time-bounded authorities, e.g. temporary on-call privileges.
+//!
+//! Authorities of [`AuthDetails`](super::AuthDetails) may have validity windows (not-before / expires-at),
+//! expired ones (or not yet valid) are ignored by [`AuthoritiesCheck`](super::AuthoritiesCheck).
+//! Windows are checked against the global [`Clock`], which can be replaced in tests via [`set_clock`].
+
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Source of the current time for validity checks.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/// The default [`Clock`] using [`SystemTime::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> SystemTime + Send + Sync,
+{
+    fn now(&self) -> SystemTime {
+        (self)()
+    }
+}
+
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// Globally replaces the clock used for validity checks, e.g. by a fixed time in tests.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::{now, reset_clock, set_clock};
+/// use std::time::{Duration, SystemTime};
+///
+/// let fixed = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// set_clock(move || fixed);
+/// assert_eq!(fixed, now());
+///
+/// reset_clock();
+/// assert_ne!(fixed, now());
+/// ```
+pub fn set_clock(clock: impl Clock + 'static) {
+    *CLOCK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(clock));
+}
+
+/// Restores the [`SystemClock`].
+pub fn reset_clock() {
+    *CLOCK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Current time of the configured [`Clock`].
+pub fn now() -> SystemTime {
+    let clock = CLOCK
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+
+    match clock {
+        Some(clock) => clock.now(),
+        None => SystemClock.now(),
+    }
+}
+
+/// Validity window of an authority, unbounded by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Validity {
+    pub not_before: Option<SystemTime>,
+    pub expires_at: Option<SystemTime>,
+}
+
+impl Validity {
+    pub fn not_before(mut self, time: SystemTime) -> Self {
+        self.not_before = Some(time);
+        self
+    }
+
+    pub fn expires_at(mut self, time: SystemTime) -> Self {
+        self.expires_at = Some(time);
+        self
+    }
+
+    /// Whether the window has no bounds, so it's active at any time.
+    pub fn is_unbounded(&self) -> bool {
+        self.not_before.is_none() && self.expires_at.is_none()
+    }
+
+    /// Whether the window contains the time (`not_before` is inclusive, `expires_at` is exclusive).
+    pub fn is_active_at(&self, time: SystemTime) -> bool {
+        self.not_before
+            .map_or(true, |not_before| not_before <= time)
+            && self.expires_at.map_or(true, |expires_at| time < expires_at)
+    }
+
+    /// Whether the window is active according to the configured [`Clock`].
+    pub fn is_active(&self) -> bool {
+        self.is_unbounded() || self.is_active_at(now())
+    }
+}
+
+/// Validity windows of time-bounded authorities, keyed by authority.
+pub fn validity_windows<T: Eq + Hash>(
+    authorities: impl IntoIterator<Item = TimedAuthority<T>>,
+) -> HashMap<T, Validity> {
+    authorities
+        .into_iter()
+        .map(|timed| (timed.authority, timed.validity))
+        .collect()
+}
+
+/// Whether the authority is among `authorities` and its window in `validity` is active,
+/// authorities without a window are unbounded.
+pub fn is_authority_active<T, Q>(
+    authorities: &HashSet<T>,
+    validity: &HashMap<T, Validity>,
+    authority: &Q,
+) -> bool
+where
+    T: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+{
+    authorities.contains(authority) && validity.get(authority).map_or(true, Validity::is_active)
+}
+
+/// Authority with a validity window.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::{AuthDetails, AuthoritiesCheck, TimedAuthority};
+/// use std::time::{Duration, SystemTime};
+///
+/// let details = AuthDetails::timed([
+///     TimedAuthority::new("ROLE_USER".to_string()),
+///     TimedAuthority::new("ROLE_ON_CALL".to_string()).valid_for(Duration::from_secs(8 * 3600)),
+///     TimedAuthority::new("ROLE_ADMIN".to_string()).expires_at(SystemTime::UNIX_EPOCH),
+/// ]);
+///
+/// assert!(details.has_authorities(&["ROLE_USER", "ROLE_ON_CALL"]));
+/// assert!(!details.has_authority("ROLE_ADMIN"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimedAuthority<T> {
+    pub authority: T,
+    pub validity: Validity,
+}
+
+impl<T> TimedAuthority<T> {
+    pub fn new(authority: T) -> TimedAuthority<T> {
+        TimedAuthority {
+            authority,
+            validity: Validity::default(),
+        }
+    }
+
+    pub fn not_before(mut self, time: SystemTime) -> Self {
+        self.validity = self.validity.not_before(time);
+        self
+    }
+
+    pub fn expires_at(mut self, time: SystemTime) -> Self {
+        self.validity = self.validity.expires_at(time);
+        self
+    }
+
+    /// Expires after the duration from now (according to the configured [`Clock`]).
+    pub fn valid_for(self, duration: Duration) -> Self {
+        self.expires_at(now() + duration)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.validity.is_active()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validity_window() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let end = start + Duration::from_secs(10);
+        let validity = Validity::default().not_before(start).expires_at(end);
+
+        assert!(!validity.is_active_at(start - Duration::from_secs(1)));
+        assert!(validity.is_active_at(start));
+        assert!(validity.is_active_at(end - Duration::from_secs(1)));
+        assert!(!validity.is_active_at(end));
+
+        assert!(Validity::default().is_unbounded());
+        assert!(Validity::default().is_active_at(SystemTime::UNIX_EPOCH));
+    }
+
+    #[test]
+    fn test_is_authority_active() {
+        let expired = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        let validity = validity_windows([
+            TimedAuthority::new("USER"),
+            TimedAuthority::new("ADMIN").expires_at(expired),
+        ]);
+        let authorities: HashSet<_> = validity.keys().copied().chain(["STAFF"]).collect();
+
+        assert!(is_authority_active(&authorities, &validity, "USER"));
+        assert!(is_authority_active(&authorities, &validity, "STAFF"));
+        assert!(!is_authority_active(&authorities, &validity, "ADMIN"));
+        assert!(!is_authority_active(&authorities, &validity, "OTHER"));
+    }
+
+    struct Attached(Vec<&'static str>);
+
+    impl crate::authorities::AttachAuthorities<&'static str> for Attached {
+        fn attach(&mut self, authorities: impl IntoIterator<Item = &'static str>) {
+            self.0.extend(authorities);
+        }
+    }
+
+    #[test]
+    fn test_default_attach_timed() {
+        use crate::authorities::AttachAuthorities;
+
+        let mut attached = Attached(Vec::new());
+        attached.attach_timed([
+            TimedAuthority::new("USER"),
+            TimedAuthority::new("ADMIN").expires_at(SystemTime::UNIX_EPOCH),
+        ]);
+
+        assert_eq!(vec!["USER"], attached.0);
+    }
+}
@@ -0,0 +1,452 @@
+//! Runtime representation of authorities requirements.
+//!
+//! A [`Condition`] can be built in code, parsed from a rule (see [`Condition::parse`]),
+//! loaded from configuration (with `serde` feature) and evaluated against [`AuthDetails`]
+//! or any other implementation of [`AuthoritiesCheck`].
+//!
+//! To find out why the access was denied, use [`Condition::explain`].
+//! Explanations can also be included in denial responses of the framework crates, see [`set_explain_mode`].
+//!
+//! [`AuthDetails`]: crate::authorities::AuthDetails
+//! [`AuthoritiesCheck`]: crate::authorities::AuthoritiesCheck
+
+use crate::authorities::AuthoritiesCheck;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::str::FromStr;
+
+mod explain;
+mod parser;
+mod requirement;
+pub mod schedule;
+
+pub use explain::{explain_mode, set_explain_mode, ExplainMode, Explanation, ExplanationNode};
+pub use parser::{ParseError, Spanned};
+pub use requirement::{Part, Requirement};
+pub use schedule::{register_schedule, register_time_zone, Schedule, ScheduleError, TimeZone};
+
+/// Tree of requirements over authorities of type `T`.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::AuthDetails;
+/// use protect_endpoints_core::condition::Condition;
+///
+/// let condition = Condition::any([
+///     Condition::authority("ROLE_ADMIN".to_string()),
+///     Condition::all([
+///         Condition::authority("OP_WRITE".to_string()),
+///         Condition::not(Condition::authority("SUSPENDED".to_string())),
+///     ]),
+/// ]);
+///
+/// assert_eq!("ROLE_ADMIN || OP_WRITE && !SUSPENDED", condition.to_string());
+/// assert_eq!(condition, "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)".parse().unwrap());
+///
+/// let details = AuthDetails::new(["OP_WRITE".to_string()]);
+/// assert!(condition.is_satisfied_by(&details));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Condition<T> {
+    /// All nested conditions must be satisfied.
+    All(Vec<Condition<T>>),
+    /// At least one of nested conditions must be satisfied.
+    Any(Vec<Condition<T>>),
+    /// Nested condition must not be satisfied.
+    Not(Box<Condition<T>>),
+    /// At least `count` of nested conditions must be satisfied.
+    AtLeast { count: usize, of: Vec<Condition<T>> },
+    /// The current time must be within the schedule, e.g. business hours.
+    During(Schedule),
+    /// The authority must be present.
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Authority(T),
+}
+
+impl<T> Condition<T> {
+    pub fn authority(authority: T) -> Self {
+        Condition::Authority(authority)
+    }
+
+    pub fn all(conditions: impl IntoIterator<Item = Condition<T>>) -> Self {
+        Condition::All(conditions.into_iter().collect())
+    }
+
+    pub fn any(conditions: impl IntoIterator<Item = Condition<T>>) -> Self {
+        Condition::Any(conditions.into_iter().collect())
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(condition: Condition<T>) -> Self {
+        Condition::Not(Box::new(condition))
+    }
+
+    pub fn at_least(count: usize, conditions: impl IntoIterator<Item = Condition<T>>) -> Self {
+        Condition::AtLeast {
+            count,
+            of: conditions.into_iter().collect(),
+        }
+    }
+
+    pub fn during(schedule: Schedule) -> Self {
+        Condition::During(schedule)
+    }
+
+    /// Checks the condition against authorities of the user.
+    pub fn is_satisfied_by<D>(&self, details: &D) -> bool
+    where
+        T: Eq + Hash,
+        D: for<'a> AuthoritiesCheck<&'a T> + ?Sized,
+    {
+        self.evaluate(&mut |authority| details.has_authority(authority))
+    }
+
+    /// Evaluates the condition using the given predicate to check a single authority.
+    ///
+    /// It's useful when the authority type of the condition differs from the user's one,
+    /// e.g. `Condition<&str>` and `AuthDetails<String>`.
+    pub fn evaluate(&self, has_authority: &mut impl FnMut(&T) -> bool) -> bool {
+        match self {
+            Condition::All(nested) => nested.iter().all(|cond| cond.evaluate(has_authority)),
+            Condition::Any(nested) => nested.iter().any(|cond| cond.evaluate(has_authority)),
+            Condition::Not(nested) => !nested.evaluate(has_authority),
+            Condition::AtLeast { count, of } => {
+                of.iter()
+                    .filter(|cond| cond.evaluate(has_authority))
+                    .take(*count)
+                    .count()
+                    == *count
+            }
+            Condition::During(schedule) => schedule.is_active(),
+            Condition::Authority(authority) => has_authority(authority),
+        }
+    }
+
+    /// Returns all the authorities mentioned in the condition (including negated ones).
+    pub fn authorities(&self) -> Vec<&T> {
+        let mut authorities = Vec::new();
+        self.collect_authorities(&mut authorities);
+        authorities
+    }
+
+    fn collect_authorities<'a>(&'a self, authorities: &mut Vec<&'a T>) {
+        match self {
+            Condition::All(nested)
+            | Condition::Any(nested)
+            | Condition::AtLeast { of: nested, .. } => {
+                nested
+                    .iter()
+                    .for_each(|cond| cond.collect_authorities(authorities));
+            }
+            Condition::Not(nested) => nested.collect_authorities(authorities),
+            Condition::During(_) => {}
+            Condition::Authority(authority) => authorities.push(authority),
+        }
+    }
+
+    /// Converts authorities of the condition, keeping its structure.
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Condition<U> {
+        self.try_map(|authority| Ok::<_, std::convert::Infallible>(f(authority)))
+            .unwrap_or_else(|never| match never {})
+    }
+
+    /// Fallible version of [`Condition::map`], e.g. to parse authorities into a custom type.
+    pub fn try_map<U, E>(self, mut f: impl FnMut(T) -> Result<U, E>) -> Result<Condition<U>, E> {
+        self.try_map_inner(&mut f)
+    }
+
+    fn try_map_inner<U, E>(self, f: &mut impl FnMut(T) -> Result<U, E>) -> Result<Condition<U>, E> {
+        let map_all = |nested: Vec<Condition<T>>, f: &mut _| {
+            nested
+                .into_iter()
+                .map(|cond| cond.try_map_inner(f))
+                .collect::<Result<Vec<_>, E>>()
+        };
+
+        Ok(match self {
+            Condition::All(nested) => Condition::All(map_all(nested, f)?),
+            Condition::Any(nested) => Condition::Any(map_all(nested, f)?),
+            Condition::Not(nested) => Condition::Not(Box::new(nested.try_map_inner(f)?)),
+            Condition::AtLeast { count, of } => Condition::AtLeast {
+                count,
+                of: map_all(of, f)?,
+            },
+            Condition::During(schedule) => Condition::During(schedule),
+            Condition::Authority(authority) => Condition::Authority(f(authority)?),
+        })
+    }
+}
+
+impl Condition<String> {
+    /// Parses a rule like `ROLE_ADMIN || (OP_WRITE && !SUSPENDED)`.
+    ///
+    /// Supported syntax (from the lowest precedence to the highest): `||`, `&&`, `!`,
+    /// parentheses, `all(condition, ...)` / `any(condition, ...)` (`all()` is always satisfied, `any()` never),
+    /// `at_least(count, condition, ...)` and `during('Mon-Fri 09:00-18:00 UTC')` (see [`Schedule`]).
+    /// An authority is either a path-like identifier (`ROLE_ADMIN`, `Role::Admin`)
+    /// or a single-quoted string for values with other characters (`'read users'`),
+    /// where `'` and `\` are escaped with a backslash (`'O\'Reilly'`).
+    pub fn parse(rule: &str) -> Result<Self, ParseError> {
+        parser::parse(rule).map(|cond| cond.map(|authority| authority.value))
+    }
+}
+
+impl Condition<Spanned<String>> {
+    /// The same as [`Condition::parse`], but keeps the position of each authority in the rule.
+    /// Useful to report errors pointing to the original source.
+    pub fn parse_spanned(rule: &str) -> Result<Self, ParseError> {
+        parser::parse(rule)
+    }
+}
+
+impl FromStr for Condition<String> {
+    type Err = ParseError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        Condition::parse(rule)
+    }
+}
+
+/// Renders the condition as a rule which can be parsed back by [`Condition::parse`].
+///
+/// `all` and `any` of a single condition are rendered as the condition itself, so the parsed rule is equivalent,
+/// but not always equal to the original. Empty authorities can't be rendered as a valid rule.
+impl<T: Display> Display for Condition<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_precedence(f, Precedence::Or, &write_authority)
+    }
+}
+
+fn write_authority(f: &mut Formatter<'_>, authority: &impl Display) -> fmt::Result {
+    let authority = authority.to_string();
+    if parser::is_plain_authority(&authority) {
+        f.write_str(&authority)
+    } else {
+        parser::write_quoted(f, &authority)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    Or,
+    And,
+    Unary,
+}
+
+impl<T> Condition<T> {
+    /// `write_leaf` renders a single authority, so [`Requirement`] can render its dynamic parts as is.
+    fn fmt_with_precedence(
+        &self,
+        f: &mut Formatter<'_>,
+        outer: Precedence,
+        write_leaf: &impl Fn(&mut Formatter<'_>, &T) -> fmt::Result,
+    ) -> fmt::Result {
+        let (nested, separator, precedence) = match self {
+            Condition::Authority(authority) => return write_leaf(f, authority),
+            Condition::Not(nested) => {
+                f.write_str("!")?;
+                return nested.fmt_with_precedence(f, Precedence::Unary, write_leaf);
+            }
+            Condition::During(schedule) => return write!(f, "during('{schedule}')"),
+            Condition::AtLeast { count, of } => {
+                write!(f, "at_least({count}")?;
+                for cond in of {
+                    f.write_str(", ")?;
+                    cond.fmt_with_precedence(f, Precedence::Or, write_leaf)?;
+                }
+                return f.write_str(")");
+            }
+            Condition::All(nested) if nested.is_empty() => return f.write_str("all()"),
+            Condition::Any(nested) if nested.is_empty() => return f.write_str("any()"),
+            Condition::All(nested) | Condition::Any(nested) if nested.len() == 1 => {
+                return nested[0].fmt_with_precedence(f, outer, write_leaf);
+            }
+            Condition::All(nested) => (nested, " && ", Precedence::And),
+            Condition::Any(nested) => (nested, " || ", Precedence::Or),
+        };
+        // Nested conditions of the same kind are parenthesized to keep the structure on parsing
+        let nested_precedence = match precedence {
+            Precedence::Or => Precedence::And,
+            Precedence::And | Precedence::Unary => Precedence::Unary,
+        };
+
+        let parenthesized = outer > precedence;
+        if parenthesized {
+            f.write_str("(")?;
+        }
+        for (idx, cond) in nested.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(separator)?;
+            }
+            cond.fmt_with_precedence(f, nested_precedence, write_leaf)?;
+        }
+        if parenthesized {
+            f.write_str(")")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Condition;
+    use crate::authorities::AuthDetails;
+
+    fn rule(rule: &str) -> Condition<String> {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn test_evaluation() {
+        let details = AuthDetails::new(["ADMIN".to_string(), "WRITE".to_string()]);
+
+        assert!(rule("ADMIN && WRITE").is_satisfied_by(&details));
+        assert!(rule("READ || WRITE").is_satisfied_by(&details));
+        assert!(!rule("ADMIN && !WRITE").is_satisfied_by(&details));
+        assert!(rule("at_least(2, ADMIN, READ, WRITE)").is_satisfied_by(&details));
+        assert!(!rule("at_least(2, ADMIN, READ, DELETE)").is_satisfied_by(&details));
+    }
+
+    #[test]
+    fn test_evaluation_by_list() {
+        let condition =
+            Condition::all([Condition::authority("ADMIN"), Condition::authority("WRITE")]);
+
+        assert!(condition.is_satisfied_by(&["ADMIN", "WRITE", "READ"]));
+        assert!(!condition.is_satisfied_by(&["ADMIN"]));
+        assert!(!condition.is_satisfied_by(&[] as &[&str]));
+        assert!(rule("ADMIN || !WRITE").is_satisfied_by(&vec!["ADMIN".to_string()][..]));
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for source in [
+            "A",
+            "A || B && C",
+            "(A || B) && C",
+            "!(A && B) || !C",
+            "A && (B || !(C || D))",
+            "at_least(2, A, B || C, !D)",
+            "'read users' && Role::Admin",
+            "DEPLOY && during('Mon-Fri 09:00-18:00 UTC')",
+            "ADMIN || !during('business_hours')",
+            "all()",
+            "any() || !all()",
+            "at_least(1)",
+            "'O\\'Reilly' && 'C:\\\\temp' && 'all'",
+        ] {
+            assert_eq!(source, rule(source).to_string());
+        }
+    }
+
+    /// `all` and `any` of a single condition are rendered as the condition itself.
+    fn flatten(condition: Condition<String>) -> Condition<String> {
+        let flatten_all =
+            |nested: Vec<Condition<String>>| nested.into_iter().map(flatten).collect();
+        match condition {
+            Condition::All(nested) | Condition::Any(nested) if nested.len() == 1 => {
+                flatten(nested.into_iter().next().unwrap())
+            }
+            Condition::All(nested) => Condition::All(flatten_all(nested)),
+            Condition::Any(nested) => Condition::Any(flatten_all(nested)),
+            Condition::Not(nested) => Condition::not(flatten(*nested)),
+            Condition::AtLeast { count, of } => Condition::at_least(count, flatten_all(of)),
+            other => other,
+        }
+    }
+
+    /// Pseudo-random conditions (xorshift with a fixed seed), since there is no property testing dependency.
+    struct Generator(u64);
+
+    impl Generator {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+
+        fn condition(&mut self, depth: usize) -> Condition<String> {
+            const AUTHORITIES: [&str; 9] = [
+                "A",
+                "ROLE_ADMIN",
+                "Role::Admin",
+                "read users",
+                "O'Reilly",
+                "C:\\temp\\",
+                "all",
+                "at_least",
+                "'",
+            ];
+
+            match if depth == 0 { 0 } else { self.next(7) } {
+                0 | 1 => {
+                    Condition::authority(AUTHORITIES[self.next(AUTHORITIES.len())].to_string())
+                }
+                2 => Condition::not(self.condition(depth - 1)),
+                3 => Condition::All(self.conditions(depth - 1)),
+                4 => Condition::Any(self.conditions(depth - 1)),
+                5 => Condition::at_least(self.next(3), self.conditions(depth - 1)),
+                _ => Condition::during("Mon-Fri 09:00-18:00 UTC".parse().unwrap()),
+            }
+        }
+
+        fn conditions(&mut self, depth: usize) -> Vec<Condition<String>> {
+            (0..self.next(4)).map(|_| self.condition(depth)).collect()
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrip_generated() {
+        let mut generator = Generator(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..2000 {
+            let condition = generator.condition(4);
+            let source = condition.to_string();
+            let parsed = Condition::parse(&source)
+                .unwrap_or_else(|err| panic!("`{source}` can't be parsed back: {err}"));
+
+            assert_eq!(source, parsed.to_string());
+            assert_eq!(flatten(condition), flatten(parsed), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_typed_condition() {
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        enum Role {
+            Admin,
+            Manager,
+        }
+
+        let condition = rule("Admin || !Manager").try_map(|name| match name.as_str() {
+            "Admin" => Ok(Role::Admin),
+            "Manager" => Ok(Role::Manager),
+            _ => Err(name),
+        });
+
+        let condition = condition.unwrap();
+        assert!(condition.is_satisfied_by(&AuthDetails::new([Role::Admin])));
+        assert!(!condition.is_satisfied_by(&AuthDetails::new([Role::Manager])));
+        assert_eq!(vec![&Role::Admin, &Role::Manager], condition.authorities());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let condition = rule("A || !B && at_least(1, C) && during('Sat-Sun 00:00-24:00 UTC')");
+        let json = serde_json::to_string(&condition).unwrap();
+
+        assert_eq!(
+            r#"{"any":["A",{"all":[{"not":"B"},{"at_least":{"count":1,"of":["C"]}},{"during":"Sat-Sun 00:00-24:00 UTC"}]}]}"#,
+            json
+        );
+        assert_eq!(condition, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -0,0 +1,349 @@
+//! Explanation of the condition evaluation: which branches passed or failed
+//! and which authorities were missing.
+
+use super::Condition;
+use crate::authorities::AuthoritiesCheck;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Result of the condition evaluation with results of all nested conditions.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::AuthDetails;
+/// use protect_endpoints_core::condition::Condition;
+///
+/// let condition: Condition<String> = "ADMIN || (WRITE && !SUSPENDED)".parse().unwrap();
+/// let explanation = condition.explain(&AuthDetails::new(["READ".to_string()]));
+///
+/// assert!(!explanation.passed);
+/// let missing: Vec<&str> = explanation
+///     .missing_authorities()
+///     .into_iter()
+///     .map(|authority| authority.as_str())
+///     .collect();
+/// assert_eq!(vec!["ADMIN", "WRITE"], missing);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Explanation<T = String> {
+    pub passed: bool,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub node: ExplanationNode<T>,
+}
+
+/// Kind of the explained condition, mirrors [`Condition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ExplanationNode<T> {
+    All(Vec<Explanation<T>>),
+    Any(Vec<Explanation<T>>),
+    Not(Box<Explanation<T>>),
+    AtLeast {
+        count: usize,
+        of: Vec<Explanation<T>>,
+    },
+    Authority(T),
+    /// Schedule of the condition, see [`Schedule`](super::Schedule).
+    During(String),
+    /// Custom check which isn't based on authorities, e.g. `expr` of the macro.
+    Expr(String),
+}
+
+impl<T> Explanation<T> {
+    pub fn authority(authority: T, passed: bool) -> Self {
+        Self {
+            passed,
+            node: ExplanationNode::Authority(authority),
+        }
+    }
+
+    pub fn expr(source: impl Into<String>, passed: bool) -> Self {
+        Self {
+            passed,
+            node: ExplanationNode::Expr(source.into()),
+        }
+    }
+
+    pub fn during(schedule: impl Into<String>, passed: bool) -> Self {
+        Self {
+            passed,
+            node: ExplanationNode::During(schedule.into()),
+        }
+    }
+
+    pub fn all(nested: Vec<Explanation<T>>) -> Self {
+        Self {
+            passed: nested.iter().all(|explanation| explanation.passed),
+            node: ExplanationNode::All(nested),
+        }
+    }
+
+    pub fn any(nested: Vec<Explanation<T>>) -> Self {
+        Self {
+            passed: nested.iter().any(|explanation| explanation.passed),
+            node: ExplanationNode::Any(nested),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(nested: Explanation<T>) -> Self {
+        Self {
+            passed: !nested.passed,
+            node: ExplanationNode::Not(Box::new(nested)),
+        }
+    }
+
+    pub fn at_least(count: usize, nested: Vec<Explanation<T>>) -> Self {
+        Self {
+            passed: nested
+                .iter()
+                .filter(|explanation| explanation.passed)
+                .count()
+                >= count,
+            node: ExplanationNode::AtLeast { count, of: nested },
+        }
+    }
+
+    /// Authorities which the user lacks in the failed branches.
+    /// Negated authorities are not included, since their presence is the reason of failure.
+    pub fn missing_authorities(&self) -> Vec<&T> {
+        let mut missing = Vec::new();
+        self.collect_missing(&mut missing);
+        missing
+    }
+
+    fn collect_missing<'a>(&'a self, missing: &mut Vec<&'a T>) {
+        if self.passed {
+            return;
+        }
+
+        match &self.node {
+            ExplanationNode::All(nested)
+            | ExplanationNode::Any(nested)
+            | ExplanationNode::AtLeast { of: nested, .. } => nested
+                .iter()
+                .for_each(|explanation| explanation.collect_missing(missing)),
+            ExplanationNode::Authority(authority) => missing.push(authority),
+            ExplanationNode::Not(_) | ExplanationNode::During(_) | ExplanationNode::Expr(_) => {}
+        }
+    }
+
+    /// Converts authorities of the explanation, keeping its structure.
+    pub fn map<U>(self, f: &mut impl FnMut(T) -> U) -> Explanation<U> {
+        let map_all = |nested: Vec<Explanation<T>>, f: &mut _| {
+            nested
+                .into_iter()
+                .map(|explanation| explanation.map(f))
+                .collect()
+        };
+
+        let node = match self.node {
+            ExplanationNode::All(nested) => ExplanationNode::All(map_all(nested, f)),
+            ExplanationNode::Any(nested) => ExplanationNode::Any(map_all(nested, f)),
+            ExplanationNode::Not(nested) => ExplanationNode::Not(Box::new(nested.map(f))),
+            ExplanationNode::AtLeast { count, of } => ExplanationNode::AtLeast {
+                count,
+                of: map_all(of, f),
+            },
+            ExplanationNode::Authority(authority) => ExplanationNode::Authority(f(authority)),
+            ExplanationNode::During(schedule) => ExplanationNode::During(schedule),
+            ExplanationNode::Expr(source) => ExplanationNode::Expr(source),
+        };
+
+        Explanation {
+            passed: self.passed,
+            node,
+        }
+    }
+
+    /// Keeps the structure and results only, hiding authorities, schedules and expressions.
+    pub fn redacted(self) -> Explanation<&'static str> {
+        let mut redacted = self.map(&mut |_| REDACTED);
+        redacted.redact_exprs();
+        redacted
+    }
+}
+
+const REDACTED: &str = "***";
+
+impl Explanation<&'static str> {
+    fn redact_exprs(&mut self) {
+        match &mut self.node {
+            ExplanationNode::All(nested)
+            | ExplanationNode::Any(nested)
+            | ExplanationNode::AtLeast { of: nested, .. } => {
+                nested.iter_mut().for_each(Explanation::redact_exprs)
+            }
+            ExplanationNode::Not(nested) => nested.redact_exprs(),
+            ExplanationNode::During(source) | ExplanationNode::Expr(source) => {
+                *source = REDACTED.to_string()
+            }
+            ExplanationNode::Authority(_) => {}
+        }
+    }
+}
+
+/// Renders the explanation as an indented tree, one condition per line.
+impl<T: Display> Display for Explanation<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_indent(f, 0)
+    }
+}
+
+impl<T: Display> Explanation<T> {
+    fn fmt_with_indent(&self, f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+        if indent > 0 {
+            writeln!(f)?;
+        }
+        let result = if self.passed { "passed" } else { "failed" };
+        write!(f, "{:indent$}[{result}] ", "", indent = indent * 2)?;
+
+        let nested = match &self.node {
+            ExplanationNode::Authority(authority) => return write!(f, "{authority}"),
+            ExplanationNode::During(schedule) => return write!(f, "during: {schedule}"),
+            ExplanationNode::Expr(source) => return write!(f, "expr: {source}"),
+            ExplanationNode::Not(nested) => {
+                f.write_str("not")?;
+                return nested.fmt_with_indent(f, indent + 1);
+            }
+            ExplanationNode::All(nested) => {
+                f.write_str("all")?;
+                nested
+            }
+            ExplanationNode::Any(nested) => {
+                f.write_str("any")?;
+                nested
+            }
+            ExplanationNode::AtLeast { count, of } => {
+                write!(f, "at_least {count}")?;
+                of
+            }
+        };
+
+        nested
+            .iter()
+            .try_for_each(|explanation| explanation.fmt_with_indent(f, indent + 1))
+    }
+}
+
+impl<T> Condition<T> {
+    /// Checks the condition against authorities of the user, explaining the result.
+    pub fn explain<D>(&self, details: &D) -> Explanation<&T>
+    where
+        T: Eq + Hash,
+        D: for<'a> AuthoritiesCheck<&'a T>,
+    {
+        self.explain_with(&mut |authority| details.has_authority(authority))
+    }
+
+    /// Explaining version of [`Condition::evaluate`].
+    pub fn explain_with(&self, has_authority: &mut impl FnMut(&T) -> bool) -> Explanation<&T> {
+        fn explain_all<'a, T>(
+            nested: &'a [Condition<T>],
+            has_authority: &mut impl FnMut(&T) -> bool,
+        ) -> Vec<Explanation<&'a T>> {
+            nested
+                .iter()
+                .map(|cond| cond.explain_with(has_authority))
+                .collect()
+        }
+
+        match self {
+            Condition::All(nested) => Explanation::all(explain_all(nested, has_authority)),
+            Condition::Any(nested) => Explanation::any(explain_all(nested, has_authority)),
+            Condition::Not(nested) => Explanation::not(nested.explain_with(has_authority)),
+            Condition::AtLeast { count, of } => {
+                Explanation::at_least(*count, explain_all(of, has_authority))
+            }
+            Condition::During(schedule) => {
+                Explanation::during(schedule.to_string(), schedule.is_active())
+            }
+            Condition::Authority(authority) => {
+                Explanation::authority(authority, has_authority(authority))
+            }
+        }
+    }
+}
+
+/// Whether explanations of denied access are included in responses,
+/// see [`AccessDenied`](crate::error::AccessDenied).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainMode {
+    /// Denial responses don't contain any details (default).
+    #[default]
+    Disabled,
+    /// Only the structure of the failed condition, authorities and expressions are hidden.
+    Redacted,
+    /// The whole explanation. Intended for debugging, since it exposes the access rules.
+    Full,
+}
+
+impl ExplainMode {
+    pub fn is_enabled(self) -> bool {
+        self != ExplainMode::Disabled
+    }
+}
+
+static EXPLAIN_MODE: AtomicU8 = AtomicU8::new(ExplainMode::Disabled as u8);
+
+/// Globally enables explanations of denied access, see [`ExplainMode`].
+pub fn set_explain_mode(mode: ExplainMode) {
+    EXPLAIN_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn explain_mode() -> ExplainMode {
+    match EXPLAIN_MODE.load(Ordering::Relaxed) {
+        mode if mode == ExplainMode::Full as u8 => ExplainMode::Full,
+        mode if mode == ExplainMode::Redacted as u8 => ExplainMode::Redacted,
+        _ => ExplainMode::Disabled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorities::AuthDetails;
+
+    fn explain(rule: &str, authorities: &[&str]) -> Explanation<String> {
+        let condition: Condition<String> = rule.parse().unwrap();
+        let details = AuthDetails::new(authorities.iter().map(|auth| auth.to_string()));
+
+        condition.explain(&details).map(&mut String::clone)
+    }
+
+    #[test]
+    fn test_explanation() {
+        let explanation = explain("ADMIN || WRITE && !SUSPENDED", &["WRITE", "SUSPENDED"]);
+
+        assert!(!explanation.passed);
+        assert_eq!(vec!["ADMIN"], explanation.missing_authorities());
+        assert_eq!(
+            "[failed] any\n  [failed] ADMIN\n  [failed] all\n    [passed] WRITE\n    [failed] not\n      [passed] SUSPENDED",
+            explanation.to_string()
+        );
+
+        let explanation = explain("at_least(2, A, B, C)", &["B"]);
+        assert!(!explanation.passed);
+        assert_eq!(vec!["A", "C"], explanation.missing_authorities());
+        assert!(explain("at_least(2, A, B, C)", &["A", "C"]).passed);
+    }
+
+    #[test]
+    fn test_redacted() {
+        let explanation = Explanation::all(vec![
+            Explanation::authority("ADMIN".to_string(), true),
+            Explanation::expr("user.id == id", false),
+        ]);
+
+        assert_eq!(
+            "[failed] all\n  [passed] ***\n  [failed] expr: ***",
+            explanation.redacted().to_string()
+        );
+    }
+}
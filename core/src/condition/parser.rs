@@ -0,0 +1,495 @@
+//! Parser of the rule syntax.
+//!
+//! Grammar (from the lowest precedence to the highest):
+//! ```text
+//! or       := and ( "||" and )*
+//! and      := unary ( "&&" unary )*
+//! unary    := "!" unary | "(" or ")" | list | at_least | during | authority
+//! list     := ( "all" | "any" ) "(" ( or ( "," or )* )? ")"
+//! at_least := "at_least" "(" count ( "," or )* ")"
+//! during   := "during" "(" schedule ")"
+//! ```
+//!
+//! Quoted authorities escape `'` and `\` with a backslash, e.g. `'O\'Reilly'`.
+
+use super::{Condition, Schedule};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+/// A value with its byte range in the parsed rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Range<usize>,
+}
+
+/// Error of the rule parsing, points to the byte range of the rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    span: Range<usize>,
+    message: String,
+}
+
+impl ParseError {
+    fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Byte range of the rule where the error occurred.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Description of the error without position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Authority(String),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+    Comma,
+}
+
+const ALL: &str = "all";
+const ANY: &str = "any";
+const AT_LEAST: &str = "at_least";
+const DURING: &str = "during";
+
+pub(super) fn parse(source: &str) -> Result<Condition<Spanned<String>>, ParseError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        end: source.len(),
+    };
+
+    let cond = parser.parse_or()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(cond),
+        Some((Token::Close, span)) => Err(ParseError::new(span.clone(), "unexpected `)`")),
+        Some((_, span)) => Err(ParseError::new(
+            span.clone(),
+            "expected `&&` or `||` between operands",
+        )),
+    }
+}
+
+/// Whether the authority can be written without quotes.
+pub(super) fn is_plain_authority(authority: &str) -> bool {
+    let mut chars = authority.chars();
+    chars.next().is_some_and(is_ident_char)
+        && chars.all(|c| is_ident_char(c) || c == ':')
+        && ![ALL, ANY, AT_LEAST, DURING].contains(&authority)
+}
+
+/// Writes the authority as a quoted string, escaping `'` and `\`.
+pub(super) fn write_quoted(f: &mut Formatter<'_>, authority: &str) -> fmt::Result {
+    f.write_str("'")?;
+    for c in authority.chars() {
+        if c == '\'' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    f.write_str("'")
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Range<usize>)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        let (token, len) = match ch {
+            c if c.is_whitespace() => continue,
+            '!' => (Token::Not, 1),
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            ',' => (Token::Comma, 1),
+            '&' | '|' => {
+                if chars.next_if(|(_, next)| *next == ch).is_none() {
+                    return Err(ParseError::new(
+                        start..start + 1,
+                        format!("expected `{ch}{ch}`, found single `{ch}`"),
+                    ));
+                }
+                if ch == '&' {
+                    (Token::And, 2)
+                } else {
+                    (Token::Or, 2)
+                }
+            }
+            '\'' => {
+                let mut value = String::new();
+                let mut end = None;
+                while let Some((idx, c)) = chars.next() {
+                    match c {
+                        '\'' => {
+                            end = Some(idx + 1);
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some((_, escaped @ ('\'' | '\\'))) => value.push(escaped),
+                            Some((next, other)) => {
+                                return Err(ParseError::new(
+                                    idx..next + other.len_utf8(),
+                                    format!("unknown escape `\\{other}`, expected `\\'` or `\\\\`"),
+                                ))
+                            }
+                            None => break,
+                        },
+                        c => value.push(c),
+                    }
+                }
+                let Some(end) = end else {
+                    return Err(ParseError::new(
+                        start..source.len(),
+                        "unterminated quoted authority",
+                    ));
+                };
+                if value.is_empty() {
+                    return Err(ParseError::new(start..start + 2, "empty quoted authority"));
+                }
+                tokens.push((Token::Authority(value), start..end));
+                continue;
+            }
+            c if is_ident_char(c) => {
+                let mut end = start + c.len_utf8();
+                while let Some((idx, c)) = chars.next_if(|(_, c)| is_ident_char(*c) || *c == ':') {
+                    end = idx + c.len_utf8();
+                }
+                tokens.push((Token::Authority(source[start..end].to_string()), start..end));
+                continue;
+            }
+            other => {
+                return Err(ParseError::new(
+                    start..start + other.len_utf8(),
+                    format!("unexpected character `{other}`"),
+                ))
+            }
+        };
+
+        tokens.push((token, start..start + len));
+    }
+
+    Ok(tokens)
+}
+
+type ParseResult = Result<Condition<Spanned<String>>, ParseError>;
+
+struct Parser<'a> {
+    tokens: &'a [(Token, Range<usize>)],
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_or(&mut self) -> ParseResult {
+        let mut nested = vec![self.parse_and()?];
+        while self.eat(&Token::Or) {
+            nested.push(self.parse_and()?);
+        }
+
+        Ok(if nested.len() == 1 {
+            nested.pop().unwrap()
+        } else {
+            Condition::Any(nested)
+        })
+    }
+
+    fn parse_and(&mut self) -> ParseResult {
+        let mut nested = vec![self.parse_unary()?];
+        while self.eat(&Token::And) {
+            nested.push(self.parse_unary()?);
+        }
+
+        Ok(if nested.len() == 1 {
+            nested.pop().unwrap()
+        } else {
+            Condition::All(nested)
+        })
+    }
+
+    fn parse_unary(&mut self) -> ParseResult {
+        let (token, span) = self.next("an authority, `!` or `(`")?;
+
+        match token {
+            Token::Not => Ok(Condition::Not(Box::new(self.parse_unary()?))),
+            Token::Open => {
+                let inner = self.parse_or()?;
+                if self.eat(&Token::Close) {
+                    Ok(inner)
+                } else {
+                    Err(ParseError::new(span.clone(), "unclosed `(`"))
+                }
+            }
+            Token::Authority(value) if value == ALL && self.eat(&Token::Open) => {
+                self.parse_list(span).map(Condition::All)
+            }
+            Token::Authority(value) if value == ANY && self.eat(&Token::Open) => {
+                self.parse_list(span).map(Condition::Any)
+            }
+            Token::Authority(value) if value == AT_LEAST && self.eat(&Token::Open) => {
+                self.parse_at_least(span)
+            }
+            Token::Authority(value) if value == DURING && self.eat(&Token::Open) => {
+                self.parse_during(span)
+            }
+            Token::Authority(value) => Ok(Condition::Authority(Spanned {
+                value: value.clone(),
+                span: span.clone(),
+            })),
+            Token::And | Token::Or | Token::Close | Token::Comma => Err(ParseError::new(
+                span.clone(),
+                "expected an authority, `!` or `(`",
+            )),
+        }
+    }
+
+    fn parse_at_least(&mut self, open_span: &Range<usize>) -> ParseResult {
+        let count = match self.next("the count of `at_least`")? {
+            (Token::Authority(value), span) => value.parse::<usize>().map_err(|_| {
+                ParseError::new(span.clone(), "the count of `at_least` must be a number")
+            })?,
+            (_, span) => {
+                return Err(ParseError::new(
+                    span.clone(),
+                    "expected the count of `at_least`",
+                ))
+            }
+        };
+
+        let mut of = Vec::new();
+        while self.eat(&Token::Comma) {
+            of.push(self.parse_or()?);
+        }
+
+        if !self.eat(&Token::Close) {
+            return Err(ParseError::new(open_span.clone(), "unclosed `at_least(`"));
+        }
+
+        Ok(Condition::AtLeast { count, of })
+    }
+
+    /// Comma-separated conditions of `all(..)` or `any(..)`, possibly empty.
+    fn parse_list(
+        &mut self,
+        open_span: &Range<usize>,
+    ) -> Result<Vec<Condition<Spanned<String>>>, ParseError> {
+        let mut nested = Vec::new();
+        if self.eat(&Token::Close) {
+            return Ok(nested);
+        }
+
+        loop {
+            nested.push(self.parse_or()?);
+            if self.eat(&Token::Close) {
+                return Ok(nested);
+            }
+            if !self.eat(&Token::Comma) {
+                return Err(ParseError::new(
+                    open_span.clone(),
+                    "unclosed list of conditions",
+                ));
+            }
+        }
+    }
+
+    fn parse_during(&mut self, open_span: &Range<usize>) -> ParseResult {
+        let schedule = match self.next("the schedule of `during`")? {
+            (Token::Authority(value), span) => Schedule::parse(value)
+                .map_err(|err| ParseError::new(span.clone(), format!("invalid schedule: {err}")))?,
+            (_, span) => {
+                return Err(ParseError::new(
+                    span.clone(),
+                    "expected the schedule of `during`",
+                ))
+            }
+        };
+
+        if !self.eat(&Token::Close) {
+            return Err(ParseError::new(open_span.clone(), "unclosed `during(`"));
+        }
+
+        Ok(Condition::During(schedule))
+    }
+
+    fn next(&mut self, expected: &str) -> Result<&'a (Token, Range<usize>), ParseError> {
+        let tokens = self.tokens;
+        let token = tokens.get(self.pos).ok_or_else(|| {
+            ParseError::new(
+                self.end.saturating_sub(1)..self.end,
+                format!("unexpected end of rule, expected {expected}"),
+            )
+        })?;
+        self.pos += 1;
+
+        Ok(token)
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        match self.tokens.get(self.pos) {
+            Some((token, _)) if token == expected => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(rule: &str) -> Result<Condition<String>, ParseError> {
+        Condition::parse(rule)
+    }
+
+    fn authority(value: &str) -> Condition<String> {
+        Condition::Authority(value.to_string())
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            Condition::any([
+                authority("ROLE_ADMIN"),
+                Condition::all([
+                    authority("OP_WRITE"),
+                    Condition::not(authority("SUSPENDED"))
+                ]),
+            ]),
+            parse_str("ROLE_ADMIN || OP_WRITE && !SUSPENDED").unwrap()
+        );
+        assert_eq!(
+            Condition::all([
+                Condition::any([authority("A"), authority("B")]),
+                authority("C")
+            ]),
+            parse_str("(A || B) && C").unwrap()
+        );
+        assert_eq!(
+            Condition::at_least(2, [authority("A"), authority("B"), authority("C")]),
+            parse_str("at_least(2, A, B, C)").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_authority_forms() {
+        assert_eq!(
+            Condition::all([authority("Role::Admin"), authority("read users")]),
+            parse_str("Role::Admin && 'read users'").unwrap()
+        );
+        assert_eq!(authority("at_least"), parse_str("'at_least'").unwrap());
+        assert_eq!(authority("during"), parse_str("during").unwrap());
+        assert_eq!(
+            Condition::all([authority("all"), authority("O'Reilly"), authority("C:\\")]),
+            parse_str(r"all && 'O\'Reilly' && 'C:\\'").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(Condition::All(vec![]), parse_str("all()").unwrap());
+        assert_eq!(Condition::Any(vec![]), parse_str("any( )").unwrap());
+        assert_eq!(
+            Condition::any([
+                authority("A"),
+                Condition::all([authority("B"), Condition::any([authority("C")])])
+            ]),
+            parse_str("any(A, all(B, any(C)))").unwrap()
+        );
+        assert_eq!(
+            Condition::at_least(1, []),
+            parse_str("at_least(1)").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let cond = Condition::parse_spanned("A && !'B C'").unwrap();
+
+        assert_eq!(
+            vec![0..1, 6..11],
+            cond.authorities()
+                .into_iter()
+                .map(|authority| authority.span.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |rule| {
+            let err = parse_str(rule).unwrap_err();
+            (err.message().to_string(), err.span())
+        };
+
+        assert_eq!(("unclosed `(`".into(), 0..1), err("(A || B"));
+        assert_eq!(("unexpected `)`".into(), 1..2), err("A)"));
+        assert_eq!(
+            ("expected `&&`, found single `&`".into(), 2..3),
+            err("A & B")
+        );
+        assert_eq!(
+            ("expected `&&` or `||` between operands".into(), 2..3),
+            err("A B")
+        );
+        assert_eq!(
+            (
+                "unexpected end of rule, expected an authority, `!` or `(`".into(),
+                3..4
+            ),
+            err("A &&")
+        );
+        assert_eq!(("unexpected character `=`".into(), 2..3), err("A == B"));
+        assert_eq!(
+            (
+                "unknown escape `\\n`, expected `\\'` or `\\\\`".into(),
+                2..4
+            ),
+            err(r"'a\n'")
+        );
+        assert_eq!(
+            ("unclosed list of conditions".into(), 0..3),
+            err("all(A B)")
+        );
+        assert_eq!(
+            ("the count of `at_least` must be a number".into(), 9..10),
+            err("at_least(X, A)")
+        );
+        assert_eq!(
+            (
+                "invalid schedule: invalid time `9:00`, expected `HH:MM`".into(),
+                7..23
+            ),
+            err("during('Mon 9:00-18:00')")
+        );
+    }
+}
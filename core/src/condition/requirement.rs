@@ -0,0 +1,196 @@
+//! Requirements of handlers reflected by the `protect` macros, e.g. to unit-test them without requests.
+
+use super::{write_authority, Condition, Precedence};
+use crate::authorities::AuthoritiesCheck;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+
+/// Leaf of a [`Requirement`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Part<T> {
+    Authority(T),
+    /// Condition depending on the request (`expr`, `owner` or `from_network` of the macro) in the source form,
+    /// e.g. `owner(user_id)`.
+    Dynamic(String),
+}
+
+/// Condition of a handler, where parts depending on the request are kept as opaque [`Part::Dynamic`] nodes.
+///
+/// Such parts can't be evaluated without a request, so the requirement is satisfied by authorities
+/// only if it's satisfied whatever the result of the dynamic parts is.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::condition::{Condition, Part, Requirement};
+///
+/// let requirement = Requirement::new(Condition::all([
+///     Condition::authority(Part::Authority("ROLE_USER")),
+///     Condition::authority(Part::Dynamic("owner(user_id)".to_string())),
+/// ]));
+///
+/// assert_eq!("ROLE_USER && owner(user_id)", requirement.to_string());
+/// assert!(requirement.is_dynamic());
+/// assert!(!requirement.is_satisfied_by(&["ROLE_USER"]));
+/// assert!(requirement.may_be_satisfied_by(&["ROLE_USER"]));
+/// assert!(!requirement.may_be_satisfied_by(&[] as &[&str]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Requirement<T> {
+    condition: Condition<Part<T>>,
+}
+
+impl<T> Requirement<T> {
+    pub fn new(condition: Condition<Part<T>>) -> Self {
+        Self { condition }
+    }
+
+    pub fn condition(&self) -> &Condition<Part<T>> {
+        &self.condition
+    }
+
+    /// Whether the requirement has parts depending on the request.
+    pub fn is_dynamic(&self) -> bool {
+        self.condition
+            .authorities()
+            .into_iter()
+            .any(|part| matches!(part, Part::Dynamic(_)))
+    }
+
+    /// Satisfied by the authorities, whatever the result of the dynamic parts is.
+    pub fn is_satisfied_by<D>(&self, details: &D) -> bool
+    where
+        T: Eq + Hash,
+        D: for<'a> AuthoritiesCheck<&'a T> + ?Sized,
+    {
+        self.evaluate(&mut |authority| details.has_authority(authority)) == Some(true)
+    }
+
+    /// Not denied by the authorities, i.e. it's satisfied for some results of the dynamic parts.
+    pub fn may_be_satisfied_by<D>(&self, details: &D) -> bool
+    where
+        T: Eq + Hash,
+        D: for<'a> AuthoritiesCheck<&'a T> + ?Sized,
+    {
+        self.evaluate(&mut |authority| details.has_authority(authority)) != Some(false)
+    }
+
+    /// Evaluates the requirement using the given predicate to check a single authority,
+    /// `None` if the result depends on the dynamic parts.
+    pub fn evaluate(&self, has_authority: &mut impl FnMut(&T) -> bool) -> Option<bool> {
+        evaluate(&self.condition, has_authority)
+    }
+}
+
+fn evaluate<T>(
+    condition: &Condition<Part<T>>,
+    has_authority: &mut impl FnMut(&T) -> bool,
+) -> Option<bool> {
+    match condition {
+        Condition::All(nested) => at_least(nested.len(), nested, has_authority),
+        Condition::Any(nested) => at_least(1, nested, has_authority),
+        Condition::Not(nested) => evaluate(nested, has_authority).map(|passed| !passed),
+        Condition::AtLeast { count, of } => at_least(*count, of, has_authority),
+        Condition::During(schedule) => Some(schedule.is_active()),
+        Condition::Authority(Part::Authority(authority)) => Some(has_authority(authority)),
+        Condition::Authority(Part::Dynamic(_)) => None,
+    }
+}
+
+/// Unknown if the count of satisfied nested conditions depends on the dynamic parts.
+fn at_least<T>(
+    count: usize,
+    nested: &[Condition<Part<T>>],
+    has_authority: &mut impl FnMut(&T) -> bool,
+) -> Option<bool> {
+    let (mut passed, mut unknown) = (0, 0);
+    for cond in nested {
+        match evaluate(cond, has_authority) {
+            Some(true) => passed += 1,
+            Some(false) => {}
+            None => unknown += 1,
+        }
+    }
+
+    if passed >= count {
+        Some(true)
+    } else if passed + unknown < count {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Renders the requirement as a rule, dynamic parts are rendered in the source form.
+impl<T: Display> Display for Requirement<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.condition
+            .fmt_with_precedence(f, Precedence::Or, &|f, part| match part {
+                Part::Authority(authority) => write_authority(f, authority),
+                Part::Dynamic(source) => f.write_str(source),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Part, Requirement};
+    use crate::condition::Condition;
+
+    fn authority(authority: &'static str) -> Condition<Part<&'static str>> {
+        Condition::authority(Part::Authority(authority))
+    }
+
+    fn dynamic() -> Condition<Part<&'static str>> {
+        Condition::authority(Part::Dynamic("expr(true)".to_string()))
+    }
+
+    #[test]
+    fn test_evaluation() {
+        let any = Requirement::new(Condition::any([authority("ADMIN"), dynamic()]));
+        assert_eq!(Some(true), any.evaluate(&mut |auth| *auth == "ADMIN"));
+        assert_eq!(None, any.evaluate(&mut |_| false));
+
+        let not = Requirement::new(Condition::not(Condition::all([
+            authority("BLOCKED"),
+            dynamic(),
+        ])));
+        assert_eq!(Some(true), not.evaluate(&mut |_| false));
+        assert_eq!(None, not.evaluate(&mut |_| true));
+
+        let at_least = Requirement::new(Condition::at_least(
+            2,
+            [authority("A"), dynamic(), authority("B")],
+        ));
+        assert!(at_least.is_satisfied_by(&["A", "B"]));
+        assert!(!at_least.is_satisfied_by(&["A"]));
+        assert!(at_least.may_be_satisfied_by(&["A"]));
+        assert!(!at_least.may_be_satisfied_by(&[] as &[&str]));
+
+        let empty = Requirement::<&str>::new(Condition::any([]));
+        assert_eq!(Some(false), empty.evaluate(&mut |_| true));
+    }
+
+    #[test]
+    fn test_expr_only() {
+        let requirement = Requirement::new(dynamic());
+
+        assert!(requirement.is_dynamic());
+        assert!(!requirement.is_satisfied_by(&[] as &[&str]));
+        assert!(requirement.may_be_satisfied_by(&[] as &[&str]));
+        assert_eq!("expr(true)", requirement.to_string());
+    }
+
+    #[test]
+    fn test_display() {
+        let requirement = Requirement::new(Condition::any([
+            authority("read users"),
+            Condition::all([authority("ADMIN"), dynamic()]),
+        ]));
+
+        assert!(!Requirement::new(authority("ADMIN")).is_dynamic());
+        assert_eq!(
+            "'read users' || ADMIN && expr(true)",
+            requirement.to_string()
+        );
+    }
+}
@@ -0,0 +1,544 @@
+//! Time windows of conditions, e.g. business hours for production deploys: `Mon-Fri 09:00-18:00 Europe/Berlin`.
+//!
+//! Syntax of a window: `[days] HH:MM-HH:MM [zone]`.
+//! - days: comma-separated days or ranges (`Mon-Fri`, `Sat,Sun`, `Fri-Mon`), every day if omitted;
+//! - time range: the end is exclusive, `24:00` is allowed as the end; if the end is before the start,
+//!   the window lasts overnight (`Mon-Fri 22:00-06:00` includes Saturday morning);
+//! - zone: `UTC` (default), a fixed offset (`UTC+02:00`, `-05:30`) or a name registered via [`register_time_zone`]
+//!   (IANA names like `Europe/Berlin` are resolved without registration with the `chrono-tz` feature).
+//!   Checking a window in a zone which can't be resolved panics, since such a window could never be active.
+//!
+//! A schedule can also be referenced by name (`business_hours`) and registered at runtime via [`register_schedule`].
+//! Schedules are checked against the clock of [`authorities::now`](crate::authorities::now),
+//! which can be replaced in tests.
+
+use crate::authorities::now;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const ALL_DAYS: u8 = 0b111_1111;
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Time window (or a named one) for `during` conditions, see the [module](self) docs for the syntax.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::condition::Schedule;
+/// use std::time::{Duration, SystemTime};
+///
+/// let schedule: Schedule = "Mon-Fri 09:00-18:00 UTC+01:00".parse().unwrap();
+///
+/// // Monday, 2024-01-01 10:00 (UTC+01:00)
+/// let monday = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_099_600);
+/// assert!(schedule.is_active_at(monday));
+/// assert!(!schedule.is_active_at(monday + Duration::from_secs(5 * 24 * 3600)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Schedule(Repr);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    Window(Window),
+    Named(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Window {
+    /// Bit per day, starting from Monday.
+    days: u8,
+    start: u16,
+    end: u16,
+    zone: Zone,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Zone {
+    /// Seconds east of UTC.
+    Offset(i32),
+    Named(String),
+}
+
+/// Error of the schedule parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleError {
+    message: String,
+}
+
+impl ScheduleError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// Time zone for schedules: the offset from UTC at the given time (it may change, e.g. for daylight saving time).
+///
+/// Only `UTC` and fixed offsets are built-in, other zones are registered via [`register_time_zone`]
+/// or resolved by the tz database with the `chrono-tz` feature (e.g. `Europe/Berlin`).
+pub trait TimeZone: Send + Sync {
+    /// Offset in seconds east of UTC.
+    fn utc_offset(&self, time: SystemTime) -> i32;
+}
+
+impl<F> TimeZone for F
+where
+    F: Fn(SystemTime) -> i32 + Send + Sync,
+{
+    fn utc_offset(&self, time: SystemTime) -> i32 {
+        (self)(time)
+    }
+}
+
+static TIME_ZONES: RwLock<BTreeMap<String, Arc<dyn TimeZone>>> = RwLock::new(BTreeMap::new());
+static SCHEDULES: RwLock<BTreeMap<String, Schedule>> = RwLock::new(BTreeMap::new());
+
+/// Globally registers the time zone to use its name in schedules, registered zones take precedence over the tz database.
+///
+/// Zones have to be registered before schedules using them are checked, otherwise checks panic.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::condition::{register_time_zone, Schedule};
+/// use std::time::{Duration, SystemTime};
+///
+/// // a fixed offset for brevity, it may depend on the time
+/// register_time_zone("Asia/Tokyo", |_| 9 * 3600);
+///
+/// let schedule: Schedule = "Mon-Fri 09:00-18:00 Asia/Tokyo".parse().unwrap();
+/// // Monday, 2024-01-01 00:00 UTC (09:00 in Tokyo)
+/// let monday = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+/// assert!(schedule.is_active_at(monday));
+/// ```
+pub fn register_time_zone(name: impl Into<String>, zone: impl TimeZone + 'static) {
+    TIME_ZONES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(name.into(), Arc::new(zone));
+}
+
+/// Globally registers (or replaces) the named schedule, e.g. `business_hours`.
+/// Named schedules which aren't registered are never active.
+pub fn register_schedule(name: impl Into<String>, window: &str) -> Result<(), ScheduleError> {
+    let name = name.into();
+    if !is_name(&name) {
+        return Err(ScheduleError::new(format!(
+            "invalid schedule name `{name}`, expected an identifier"
+        )));
+    }
+    let window = Window::parse(window)?;
+
+    SCHEDULES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(name, Schedule(Repr::Window(window)));
+    Ok(())
+}
+
+impl Schedule {
+    /// Parses a window (`Mon-Fri 09:00-18:00 UTC`) or a name of the registered schedule (`business_hours`).
+    pub fn parse(source: &str) -> Result<Self, ScheduleError> {
+        let source = source.trim();
+        if is_name(source) {
+            return Ok(Schedule(Repr::Named(source.to_string())));
+        }
+
+        Window::parse(source).map(|window| Schedule(Repr::Window(window)))
+    }
+
+    /// Whether the schedule is active according to the configured clock.
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(now())
+    }
+
+    pub fn is_active_at(&self, time: SystemTime) -> bool {
+        match &self.0 {
+            Repr::Window(window) => window.is_active_at(time),
+            Repr::Named(name) => {
+                let schedules = SCHEDULES
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                match schedules.get(name) {
+                    Some(Schedule(Repr::Window(window))) => window.is_active_at(time),
+                    _ => {
+                        log::warn!("schedule `{name}` isn't registered, so it's never active");
+                        false
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Schedule::parse(source)
+    }
+}
+
+/// Renders the schedule in the canonical form, which can be parsed back.
+impl Display for Schedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Window(window) => window.fmt(f),
+            Repr::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Schedule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Schedule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Schedule::parse(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Window {
+    fn parse(source: &str) -> Result<Self, ScheduleError> {
+        let parts: Vec<&str> = source.split_whitespace().collect();
+        let (days, times, zone) = match parts[..] {
+            [times] => (None, times, None),
+            [first, second] if first.contains(':') => (None, first, Some(second)),
+            [days, times] => (Some(days), times, None),
+            [days, times, zone] => (Some(days), times, Some(zone)),
+            _ => {
+                return Err(ScheduleError::new(format!(
+                    "invalid schedule `{source}`, expected `[days] HH:MM-HH:MM [zone]`"
+                )))
+            }
+        };
+
+        let days = days.map(parse_days).transpose()?.unwrap_or(ALL_DAYS);
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| ScheduleError::new(format!("invalid time range `{times}`")))?;
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+        if start == MINUTES_PER_DAY {
+            return Err(ScheduleError::new(
+                "`24:00` can only be the end of the range",
+            ));
+        }
+        if start == end {
+            return Err(ScheduleError::new(format!("empty time range `{times}`")));
+        }
+        let zone = zone.map(parse_zone).transpose()?.unwrap_or(Zone::Offset(0));
+
+        Ok(Window {
+            days,
+            start,
+            end,
+            zone,
+        })
+    }
+
+    fn is_active_at(&self, time: SystemTime) -> bool {
+        let secs = unix_secs(time) + i64::from(self.zone.utc_offset(time));
+
+        let day = secs.div_euclid(24 * 3600);
+        let minute = (secs.rem_euclid(24 * 3600) / 60) as u16;
+        // 1970-01-01 is Thursday
+        let weekday = (day + 3).rem_euclid(7) as u8;
+        let previous = (weekday + 6) % 7;
+        let has_day = |weekday: u8| self.days & (1 << weekday) != 0;
+
+        if self.start < self.end {
+            has_day(weekday) && self.start <= minute && minute < self.end
+        } else {
+            (has_day(weekday) && self.start <= minute) || (has_day(previous) && minute < self.end)
+        }
+    }
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.days != ALL_DAYS {
+            let mut day = 0;
+            let mut first = true;
+            while day < 7 {
+                if self.days & (1 << day) == 0 {
+                    day += 1;
+                    continue;
+                }
+                let start = day;
+                while day + 1 < 7 && self.days & (1 << (day + 1)) != 0 {
+                    day += 1;
+                }
+
+                if !first {
+                    f.write_str(",")?;
+                }
+                first = false;
+                f.write_str(DAYS[start])?;
+                if day > start {
+                    write!(f, "-{}", DAYS[day])?;
+                }
+                day += 1;
+            }
+            f.write_str(" ")?;
+        }
+
+        let time = |minutes: u16| format!("{:02}:{:02}", minutes / 60, minutes % 60);
+        write!(f, "{}-{} {}", time(self.start), time(self.end), self.zone)
+    }
+}
+
+impl Zone {
+    /// # Panics
+    /// If the named zone isn't registered (and isn't known to the tz database with the `chrono-tz` feature).
+    fn utc_offset(&self, time: SystemTime) -> i32 {
+        let name = match self {
+            Zone::Offset(offset) => return *offset,
+            Zone::Named(name) => name,
+        };
+
+        let registered = TIME_ZONES
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(name)
+            .cloned();
+        if let Some(zone) = registered {
+            return zone.utc_offset(time);
+        }
+
+        #[cfg(feature = "chrono-tz")]
+        if let Ok(zone) = name.parse::<chrono_tz::Tz>() {
+            use chrono::{Offset, TimeZone};
+
+            let utc = chrono::DateTime::from_timestamp(unix_secs(time), 0).unwrap_or_default();
+            return zone
+                .offset_from_utc_datetime(&utc.naive_utc())
+                .fix()
+                .local_minus_utc();
+        }
+
+        let hint = if cfg!(feature = "chrono-tz") {
+            "it isn't known to the tz database, register it via `register_time_zone`"
+        } else {
+            "register it via `register_time_zone` or enable the `chrono-tz` feature for IANA names"
+        };
+        panic!("time zone `{name}` of the schedule can't be resolved: {hint}")
+    }
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Zone::Offset(0) => f.write_str("UTC"),
+            Zone::Offset(offset) => {
+                let sign = if *offset < 0 { '-' } else { '+' };
+                let minutes = offset.unsigned_abs() / 60;
+                write!(f, "UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+            Zone::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    }
+}
+
+fn is_name(source: &str) -> bool {
+    let mut chars = source.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn parse_days(source: &str) -> Result<u8, ScheduleError> {
+    let day = |name: &str| {
+        DAYS.iter()
+            .position(|day| day.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                ScheduleError::new(format!(
+                    "unknown day `{name}`, expected one of: Mon, Tue, Wed, Thu, Fri, Sat, Sun"
+                ))
+            })
+    };
+
+    source.split(',').try_fold(0, |days, item| {
+        let (start, end) = match item.split_once('-') {
+            Some((start, end)) => (day(start)?, day(end)?),
+            None => (day(item)?, day(item)?),
+        };
+
+        let mut days = days;
+        let mut current = start;
+        loop {
+            days |= 1 << current;
+            if current == end {
+                break Ok(days);
+            }
+            current = (current + 1) % 7;
+        }
+    })
+}
+
+fn parse_time(source: &str) -> Result<u16, ScheduleError> {
+    let invalid = || ScheduleError::new(format!("invalid time `{source}`, expected `HH:MM`"));
+
+    let (hours, minutes) = source.split_once(':').ok_or_else(invalid)?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return Err(invalid());
+    }
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+
+    match (hours, minutes) {
+        (24, 0) => Ok(MINUTES_PER_DAY),
+        (0..=23, 0..=59) => Ok(hours * 60 + minutes),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_zone(source: &str) -> Result<Zone, ScheduleError> {
+    let offset = source
+        .strip_prefix("UTC")
+        .or_else(|| source.strip_prefix("utc"))
+        .unwrap_or(source);
+    if offset.is_empty() {
+        return Ok(Zone::Offset(0));
+    }
+
+    let sign = match offset.as_bytes()[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ if offset.len() == source.len() => return Ok(Zone::Named(source.to_string())),
+        _ => return Err(ScheduleError::new(format!("invalid time zone `{source}`"))),
+    };
+    let minutes = parse_time(&offset[1..])
+        .ok()
+        .filter(|minutes| *minutes < MINUTES_PER_DAY)
+        .ok_or_else(|| ScheduleError::new(format!("invalid UTC offset `{source}`")))?;
+
+    Ok(Zone::Offset(sign * i32::from(minutes) * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Monday, 2024-01-01 00:00 UTC
+    const MONDAY: u64 = 1_704_067_200;
+
+    fn at(day: u64, hours: u64, minutes: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH
+            + Duration::from_secs(MONDAY + day * 24 * 3600 + hours * 3600 + minutes * 60)
+    }
+
+    fn schedule(source: &str) -> Schedule {
+        source.parse().unwrap()
+    }
+
+    #[test]
+    fn test_business_hours() {
+        let schedule = schedule("Mon-Fri 09:00-18:00");
+
+        assert!(!schedule.is_active_at(at(0, 8, 59)));
+        assert!(schedule.is_active_at(at(0, 9, 0)));
+        assert!(schedule.is_active_at(at(4, 17, 59)));
+        assert!(!schedule.is_active_at(at(4, 18, 0)));
+        assert!(!schedule.is_active_at(at(5, 12, 0)));
+    }
+
+    #[test]
+    fn test_overnight_and_offset() {
+        let schedule = schedule("Fri 22:00-06:00 UTC+02:00");
+
+        // Friday 22:00 and Saturday 05:59 in UTC+02:00
+        assert!(schedule.is_active_at(at(4, 20, 0)));
+        assert!(schedule.is_active_at(at(5, 3, 59)));
+        assert!(!schedule.is_active_at(at(5, 4, 0)));
+        // Thursday night isn't included
+        assert!(!schedule.is_active_at(at(3, 21, 0)));
+    }
+
+    #[test]
+    fn test_named() {
+        register_time_zone("Test/Plus_One", |_| 3600);
+        register_schedule("weekend", "Sat,Sun 00:00-24:00 Test/Plus_One").unwrap();
+
+        let weekend = schedule("weekend");
+        assert!(weekend.is_active_at(at(5, 12, 0)));
+        // Sunday 23:30 UTC is Monday in UTC+01:00
+        assert!(!weekend.is_active_at(at(6, 23, 30)));
+        assert!(!schedule("unknown").is_active_at(at(5, 12, 0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "time zone `Unknown/Zone` of the schedule can't be resolved")]
+    fn test_unresolved_zone() {
+        schedule("Sat 00:00-24:00 Unknown/Zone").is_active_at(at(5, 12, 0));
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn test_iana_zone() {
+        let schedule = schedule("Mon-Fri 09:00-18:00 Europe/Berlin");
+
+        // 09:00 is 08:00 UTC in winter (CET)
+        assert!(!schedule.is_active_at(at(0, 7, 59)));
+        assert!(schedule.is_active_at(at(0, 8, 0)));
+        // and 07:00 UTC in summer (CEST), Monday 2024-07-01
+        assert!(schedule.is_active_at(at(182, 7, 0)));
+        assert!(!schedule.is_active_at(at(182, 16, 0)));
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for (source, canonical) in [
+            ("Mon-Fri 09:00-18:00 UTC", "Mon-Fri 09:00-18:00 UTC"),
+            ("mon,tue,wed,fri 09:00-18:00", "Mon-Wed,Fri 09:00-18:00 UTC"),
+            (
+                "Fri-Mon 22:00-06:00 +05:30",
+                "Mon,Fri-Sun 22:00-06:00 UTC+05:30",
+            ),
+            ("00:00-24:00 Europe/Berlin", "00:00-24:00 Europe/Berlin"),
+            ("business_hours", "business_hours"),
+        ] {
+            assert_eq!(canonical, schedule(source).to_string());
+            assert_eq!(schedule(source), schedule(canonical));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        for source in [
+            "",
+            "Mon-Fri",
+            "Mon-Fri 9:00-18:00",
+            "Mon-Fri 09:00-09:00",
+            "Mon-Fri 24:00-06:00",
+            "Mon-Xyz 09:00-18:00",
+            "Mon 09:00-18:00 UTC+25:00",
+            "Mon 09:00-18:00 UTC extra",
+        ] {
+            assert!(Schedule::parse(source).is_err(), "{source}");
+        }
+    }
+}
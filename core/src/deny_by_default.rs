@@ -0,0 +1,220 @@
+//! Deny-by-default mode: requests are rejected unless they're routed to a handler annotated
+//! with `#[protect(..)]` or `#[public]`.
+//!
+//! Both attributes register the handler in a static list collected at link time (see [`is_annotated`]).
+//! Routers of the frameworks don't expose handlers of matched routes to middleware, so the middleware
+//! is configured with [`Routes`] mirroring the routes of the app: `(method, path pattern) -> handler`.
+//! Requests are checked before they're passed on: a request matching no route or a route of
+//! an unannotated handler is rejected with `403`, so a handler someone forgot to annotate never runs.
+//!
+//! Path patterns have the syntax of the authorization [`table`](crate::table) and paths are matched the same way.
+//! If a request matches several routes (e.g. `/users/me` and `/users/{id}`), handlers of all of them must be annotated.
+
+use crate::condition::{Condition, Explanation};
+use crate::error::AccessDenied;
+use crate::table::{parse_methods, PathPattern, TableError};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use inventory;
+}
+
+/// Static record of a handler annotated with `#[protect(..)]` or `#[public]`, submitted by the attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnnotatedHandler {
+    handler: &'static str,
+}
+
+impl AnnotatedHandler {
+    pub const fn new(handler: &'static str) -> Self {
+        Self { handler }
+    }
+
+    /// Path of the handler, e.g. `my_app::api::get_users`.
+    pub fn handler(&self) -> &'static str {
+        self.handler
+    }
+}
+
+::inventory::collect!(AnnotatedHandler);
+
+/// Whether the handler (by its path, e.g. `my_app::api::get_users`) is annotated.
+pub fn is_annotated(handler: &str) -> bool {
+    ::inventory::iter::<AnnotatedHandler>
+        .into_iter()
+        .any(|annotated| annotated.handler == handler)
+}
+
+/// Path of the handler by its value: a function or a type generated by the routing macros of the framework.
+pub fn handler_path<H>(handler: &H) -> &'static str {
+    crate::handler_path(handler)
+}
+
+/// Route of the app handled by the handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// Upper-case methods, empty for any method.
+    methods: Vec<String>,
+    pattern: PathPattern,
+    handler: &'static str,
+    annotated: bool,
+}
+
+impl Route {
+    pub fn new<H>(methods: &str, pattern: &str, handler: H) -> Result<Self, TableError> {
+        let handler = handler_path(&handler);
+        Ok(Route {
+            methods: parse_methods(methods)?,
+            pattern: pattern.parse()?,
+            handler,
+            annotated: is_annotated(handler),
+        })
+    }
+
+    /// Methods of the route, empty for any method.
+    pub fn methods(&self) -> &[String] {
+        &self.methods
+    }
+
+    pub fn pattern(&self) -> &PathPattern {
+        &self.pattern
+    }
+
+    /// Path of the handler, see [`handler_path`].
+    pub fn handler(&self) -> &'static str {
+        self.handler
+    }
+
+    pub fn is_annotated(&self) -> bool {
+        self.annotated
+    }
+
+    pub fn matches(&self, method: &str, path: &str) -> bool {
+        (self.methods.is_empty() || self.methods.iter().any(|allowed| allowed == method))
+            && self.pattern.matches(path)
+    }
+}
+
+/// Routes of the app checked by the middleware, see the [module](self) docs.
+///
+/// Handlers are passed by value: functions or types generated by the routing macros of the framework
+/// (e.g. `#[handler]` of `poem` and `salvo`, `#[get(..)]` of `actix-web`), methods as `Api::method`.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::deny_by_default::Routes;
+///
+/// async fn forgotten() -> &'static str {
+///     "Oops!"
+/// }
+///
+/// let routes = Routes::new().route("GET", "/forgotten", forgotten);
+///
+/// assert!(routes.check("GET", "/forgotten").is_err());
+/// assert!(routes.check("GET", "/missing").is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Routes {
+    routes: Vec<Route>,
+}
+
+impl Routes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the route for comma-separated methods (or `*`) and the path pattern.
+    ///
+    /// # Panics
+    /// If methods or the pattern are invalid, see [`Routes::try_route`].
+    pub fn route<H>(self, methods: &str, pattern: &str, handler: H) -> Self {
+        self.try_route(methods, pattern, handler)
+            .unwrap_or_else(|err| panic!("invalid route `{methods} {pattern}`: {err}"))
+    }
+
+    /// Appends the route, failing on invalid methods or the pattern.
+    pub fn try_route<H>(
+        mut self,
+        methods: &str,
+        pattern: &str,
+        handler: H,
+    ) -> Result<Self, TableError> {
+        self.routes.push(Route::new(methods, pattern, handler)?);
+        Ok(self)
+    }
+
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// Checks the request before it's routed: it must match a route and handlers of all matching routes must be annotated.
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self, method: &str, path: &str) -> Result<(), AccessDenied> {
+        let mut matched = self
+            .routes
+            .iter()
+            .filter(|route| route.matches(method, path))
+            .peekable();
+
+        if matched.peek().is_none() {
+            log::error!("`{method} {path}` matches no route of the deny-by-default mode");
+        } else if let Some(route) = matched.find(|route| !route.annotated) {
+            log::error!(
+                "Handler `{}` of `{method} {path}` is neither protected nor public, annotate it with `#[protect(..)]` or `#[public]`",
+                route.handler
+            );
+        } else {
+            return Ok(());
+        }
+
+        let denied = AccessDenied::new(
+            Condition::All(Vec::new()),
+            Explanation::expr("#[protect] || #[public]", false),
+        );
+        denied.log();
+        Err(denied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotated() {}
+
+    fn forgotten() {}
+
+    ::inventory::submit! { AnnotatedHandler::new(concat!(module_path!(), "::annotated")) }
+
+    #[test]
+    fn test_handler_path() {
+        assert_eq!(
+            concat!(module_path!(), "::annotated"),
+            handler_path(&annotated)
+        );
+        assert!(is_annotated(handler_path(&annotated)));
+        assert!(!is_annotated(handler_path(&forgotten)));
+    }
+
+    #[test]
+    fn test_routes_check() {
+        let routes = Routes::new()
+            .route("GET", "/", annotated)
+            .route("GET,POST", "/users/{id}", annotated)
+            .route("GET", "/users/me", forgotten);
+
+        assert!(routes.check("GET", "/").is_ok());
+        assert!(routes.check("POST", "/users/1").is_ok());
+        assert!(routes.check("GET", "/%75sers/1").is_ok());
+        // the route of the forgotten handler also matches
+        assert!(routes.check("GET", "/users/me").is_err());
+        assert!(routes.check("DELETE", "/users/1").is_err());
+        assert!(routes.check("GET", "/missing").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid route")]
+    fn test_invalid_route() {
+        Routes::new().route("GET", "users", annotated);
+    }
+}
@@ -0,0 +1,250 @@
+//! Error of denied access and its rendering into responses.
+//!
+//! By default, it's rendered as `application/problem+json` ([RFC 7807]) with `403` status.
+//! Details of the failed condition are included only if enabled via [`set_explain_mode`].
+//! The rendering can be customized globally via [`set_denial_renderer`].
+//!
+//! Both `401` and `403` responses carry `WWW-Authenticate` header ([RFC 6750]), see [`set_challenge`].
+//!
+//! [RFC 7807]: https://www.rfc-editor.org/rfc/rfc7807
+//! [RFC 6750]: https://www.rfc-editor.org/rfc/rfc6750#section-3
+//! [`set_explain_mode`]: crate::condition::set_explain_mode
+
+use crate::condition::{explain_mode, Condition, ExplainMode, Explanation};
+use http::header::{CONTENT_TYPE, WWW_AUTHENTICATE};
+use http::{HeaderValue, Response, StatusCode};
+use std::fmt::{self, Display, Formatter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod challenge;
+
+pub use challenge::{challenge, set_challenge, unauthorized_challenge, Challenge};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// The user doesn't satisfy the required condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDenied {
+    required: Condition<String>,
+    explanation: Explanation<String>,
+    missing_authorities: Vec<String>,
+    correlation_id: String,
+}
+
+impl AccessDenied {
+    /// Creates the error with a unique correlation id to find it in logs, see [`AccessDenied::log`].
+    pub fn new(required: Condition<String>, explanation: Explanation<String>) -> Self {
+        let missing_authorities = explanation
+            .missing_authorities()
+            .into_iter()
+            .cloned()
+            .collect();
+
+        Self {
+            required,
+            explanation,
+            missing_authorities,
+            correlation_id: next_correlation_id(),
+        }
+    }
+
+    /// Replaces the generated correlation id, e.g. with the id of the request.
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = correlation_id.into();
+        self
+    }
+
+    /// Authorities part of the failed condition (custom expressions are omitted).
+    pub fn required(&self) -> &Condition<String> {
+        &self.required
+    }
+
+    pub fn explanation(&self) -> &Explanation<String> {
+        &self.explanation
+    }
+
+    pub fn missing_authorities(&self) -> &[String] {
+        &self.missing_authorities
+    }
+
+    pub fn correlation_id(&self) -> &str {
+        &self.correlation_id
+    }
+
+    /// Logs the denial with the full explanation at `debug` level.
+    pub fn log(&self) {
+        log::debug!(
+            "access denied (correlation id: {}):\n{}",
+            self.correlation_id,
+            self.explanation
+        );
+    }
+
+    /// Value of `WWW-Authenticate` header with `insufficient_scope` error and missing authorities,
+    /// if the challenge is enabled (see [`set_challenge`]).
+    pub fn www_authenticate(&self) -> Option<String> {
+        challenge().map(|challenge| challenge.insufficient_scope(&self.missing_authorities))
+    }
+
+    /// Renders the response with the renderer set by [`set_denial_renderer`]
+    /// or [`AccessDenied::to_problem_json`] by default.
+    pub fn to_response(&self) -> Response<String> {
+        let renderer = RENDERER
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+
+        match renderer {
+            Some(render) => render(self),
+            None => self.to_problem_json(),
+        }
+    }
+
+    /// Renders `application/problem+json` response with `WWW-Authenticate` header,
+    /// details depend on the [`explain_mode`].
+    pub fn to_problem_json(&self) -> Response<String> {
+        let status = StatusCode::FORBIDDEN;
+        let mut body = format!(
+            r#"{{"type":"about:blank","title":"Forbidden","status":{},"correlation_id":{}"#,
+            status.as_u16(),
+            json_string(&self.correlation_id)
+        );
+
+        match explain_mode() {
+            ExplainMode::Disabled => {}
+            ExplainMode::Redacted => {
+                let detail = self.explanation.clone().redacted().to_string();
+                write!(body, r#","detail":{}"#, json_string(&detail)).unwrap();
+            }
+            ExplainMode::Full => {
+                let missing: Vec<_> = self
+                    .missing_authorities
+                    .iter()
+                    .map(|authority| json_string(authority))
+                    .collect();
+
+                write!(
+                    body,
+                    r#","detail":{},"required":{},"missing_authorities":[{}]"#,
+                    json_string(&self.explanation.to_string()),
+                    json_string(&self.required.to_string()),
+                    missing.join(",")
+                )
+                .unwrap();
+            }
+        }
+        body.push('}');
+
+        let mut response = Response::new(body);
+        *response.status_mut() = status;
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        if let Some(challenge) = self
+            .www_authenticate()
+            .and_then(|challenge| HeaderValue::try_from(challenge).ok())
+        {
+            headers.insert(WWW_AUTHENTICATE, challenge);
+        }
+        response
+    }
+}
+
+impl Display for AccessDenied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Access denied, required: {}", self.required)
+    }
+}
+
+impl std::error::Error for AccessDenied {}
+
+type Renderer = dyn Fn(&AccessDenied) -> Response<String> + Send + Sync;
+
+static RENDERER: RwLock<Option<Arc<Renderer>>> = RwLock::new(None);
+
+/// Globally replaces the default rendering of [`AccessDenied`] used by the framework crates.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::error::set_denial_renderer;
+///
+/// set_denial_renderer(|denied| {
+///     http::Response::builder()
+///         .status(http::StatusCode::NOT_FOUND)
+///         .header("x-correlation-id", denied.correlation_id())
+///         .body(String::new())
+///         .unwrap()
+/// });
+/// ```
+pub fn set_denial_renderer(
+    renderer: impl Fn(&AccessDenied) -> Response<String> + Send + Sync + 'static,
+) {
+    *RENDERER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(renderer));
+}
+
+/// Unique within the process and unlikely to repeat across restarts.
+fn next_correlation_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    format!("{millis:x}-{count:x}")
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorities::AuthDetails;
+
+    #[test]
+    fn test_access_denied() {
+        let condition: Condition<String> = "ADMIN || WRITE && !SUSPENDED".parse().unwrap();
+        let details = AuthDetails::new(["SUSPENDED".to_string()]);
+        let explanation = condition.explain(&details).map(&mut String::clone);
+
+        let denied = AccessDenied::new(condition, explanation).with_correlation_id("42");
+        assert_eq!(["ADMIN", "WRITE"], denied.missing_authorities());
+
+        let response = denied.to_problem_json();
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert_eq!(PROBLEM_JSON, response.headers()[CONTENT_TYPE]);
+        assert_eq!(
+            r#"Bearer error="insufficient_scope", scope="ADMIN WRITE""#,
+            response.headers()[WWW_AUTHENTICATE]
+        );
+        assert_eq!(
+            r#"{"type":"about:blank","title":"Forbidden","status":403,"correlation_id":"42"}"#,
+            response.body()
+        );
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(r#""a \"b\"\n\\""#, json_string("a \"b\"\n\\"));
+    }
+}
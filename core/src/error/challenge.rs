@@ -0,0 +1,146 @@
+//! `WWW-Authenticate` challenges of [RFC 6750].
+//!
+//! [RFC 6750]: https://www.rfc-editor.org/rfc/rfc6750#section-3
+
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::sync::RwLock;
+
+/// Scheme and realm of `WWW-Authenticate` header, `Bearer` without realm by default.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::error::Challenge;
+///
+/// let challenge = Challenge::bearer().with_realm("api");
+/// assert_eq!(r#"Bearer realm="api""#, challenge.unauthorized());
+/// assert_eq!(
+///     r#"Bearer realm="api", error="insufficient_scope", scope="ADMIN WRITE""#,
+///     challenge.insufficient_scope(&["ADMIN", "WRITE"])
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    scheme: Cow<'static, str>,
+    realm: Option<Cow<'static, str>>,
+}
+
+impl Challenge {
+    pub const fn bearer() -> Self {
+        Self {
+            scheme: Cow::Borrowed("Bearer"),
+            realm: None,
+        }
+    }
+
+    pub fn new(scheme: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            realm: None,
+        }
+    }
+
+    pub fn with_realm(mut self, realm: impl Into<Cow<'static, str>>) -> Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    pub fn realm(&self) -> Option<&str> {
+        self.realm.as_deref()
+    }
+
+    /// Challenge of `401 Unauthorized` response, when the request lacks authentication.
+    /// Error code isn't included in this case, as recommended by the RFC.
+    pub fn unauthorized(&self) -> String {
+        self.render(&[])
+    }
+
+    /// Challenge of `403 Forbidden` response with `insufficient_scope` error.
+    /// The scope is omitted if it's empty (e.g. only custom expressions failed).
+    pub fn insufficient_scope<S: AsRef<str>>(&self, scope: &[S]) -> String {
+        let scope = scope
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut params = vec![("error", "insufficient_scope")];
+        if !scope.is_empty() {
+            params.push(("scope", &scope));
+        }
+        self.render(&params)
+    }
+
+    fn render(&self, params: &[(&str, &str)]) -> String {
+        let mut challenge = self.scheme.to_string();
+        let realm = self.realm.as_deref().map(|realm| ("realm", realm));
+
+        for (idx, (name, value)) in realm.iter().chain(params).enumerate() {
+            let separator = if idx == 0 { " " } else { ", " };
+            write!(challenge, "{separator}{name}={}", quoted_string(value)).unwrap();
+        }
+        challenge
+    }
+}
+
+impl Default for Challenge {
+    fn default() -> Self {
+        Self::bearer()
+    }
+}
+
+static CHALLENGE: RwLock<Option<Challenge>> = RwLock::new(Some(Challenge::bearer()));
+
+/// Globally sets the challenge of `401` and `403` responses, `None` disables `WWW-Authenticate` header.
+pub fn set_challenge(challenge: Option<Challenge>) {
+    *CHALLENGE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = challenge;
+}
+
+pub fn challenge() -> Option<Challenge> {
+    CHALLENGE
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Value of `WWW-Authenticate` header for `401 Unauthorized` responses, if enabled.
+pub fn unauthorized_challenge() -> Option<String> {
+    challenge().map(|challenge| challenge.unauthorized())
+}
+
+fn quoted_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for ch in value.chars() {
+        if matches!(ch, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge() {
+        let challenge = Challenge::bearer();
+        assert_eq!("Bearer", challenge.unauthorized());
+        assert_eq!(
+            r#"Bearer error="insufficient_scope""#,
+            challenge.insufficient_scope::<&str>(&[])
+        );
+
+        let challenge = Challenge::new("DPoP").with_realm(r#"my "api""#);
+        assert_eq!(r#"DPoP realm="my \"api\"""#, challenge.unauthorized());
+    }
+}
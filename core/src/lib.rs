@@ -1,5 +1,76 @@
 pub mod authorities;
+pub mod condition;
+#[cfg(feature = "deny-by-default")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deny-by-default")))]
+pub mod deny_by_default;
+pub mod error;
+pub mod network;
+#[cfg(any(
+    feature = "policy-toml",
+    feature = "policy-yaml",
+    feature = "policy-json"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "policy-toml",
+        feature = "policy-yaml",
+        feature = "policy-json"
+    )))
+)]
+pub mod policy;
+#[cfg(feature = "registry")]
+#[cfg_attr(docsrs, doc(cfg(feature = "registry")))]
+pub mod registry;
+pub mod table;
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod test_util;
 
 #[cfg(feature = "tower")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
 pub mod tower;
+
+/// Path of the handler by its value, the same as the one registered by the macros (`module_path!()` and the name).
+#[cfg(any(feature = "registry", feature = "deny-by-default"))]
+pub(crate) fn handler_path<H>(_handler: &H) -> &'static str {
+    let path = std::any::type_name::<H>();
+    // generic parameters aren't known to the attributes
+    path.split_once('<').map_or(path, |(path, _)| path)
+}
+
+/// Submits the [`registry::Endpoint`] record, used by the code generated by the `protect` macros.
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_endpoint {
+    ($endpoint:expr) => {
+        $crate::registry::__private::inventory::submit! { $endpoint }
+    };
+}
+
+/// The registry is disabled, so records are dropped.
+#[cfg(not(feature = "registry"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_endpoint {
+    ($endpoint:expr) => {};
+}
+
+/// Submits the [`deny_by_default::AnnotatedHandler`] record, used by the code generated by the `protect` and `public` macros.
+#[cfg(feature = "deny-by-default")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __annotate_handler {
+    ($handler:expr) => {
+        $crate::deny_by_default::__private::inventory::submit! { $handler }
+    };
+}
+
+/// The deny-by-default mode is disabled, so records are dropped.
+#[cfg(not(feature = "deny-by-default"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __annotate_handler {
+    ($handler:expr) => {};
+}
@@ -0,0 +1,579 @@
+//! Network conditions: the client IP address within ranges, e.g. internal endpoints available from VPN only.
+//!
+//! Networks are comma-separated CIDR ranges or addresses (`10.0.0.0/8, 192.168.0.0/16, ::1`)
+//! or a name of the set registered at runtime via [`register_network`] (`vpn`).
+//!
+//! The client address is the peer address of the connection. The forwarded header set by trusted proxies
+//! (either `Forwarded` or `X-Forwarded-For`, see [`set_trusted_proxies`]) is taken into account only if the peer
+//! is a trusted proxy, otherwise it could be spoofed by the client. The other header is never read, since the proxy
+//! passes it from the client as is. Hops are checked from the closest one: the first untrusted hop is the client.
+
+use crate::condition::{Condition, Explanation};
+use crate::error::AccessDenied;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Range of addresses, e.g. `10.0.0.0/8`.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::network::IpNet;
+///
+/// let net: IpNet = "10.0.0.0/8".parse().unwrap();
+/// assert!(net.contains("10.1.2.3".parse().unwrap()));
+/// assert!(!net.contains("192.168.0.1".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    /// Parses a CIDR range (`10.0.0.0/8`) or a single address (`::1`).
+    pub fn parse(source: &str) -> Result<Self, NetworkError> {
+        let source = source.trim();
+        let (addr, prefix) = match source.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (source, None),
+        };
+
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| NetworkError::new(format!("invalid address `{addr}`")))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse()
+                .ok()
+                .filter(|prefix| *prefix <= max)
+                .ok_or_else(|| {
+                    NetworkError::new(format!("invalid prefix length `{prefix}` of `{source}`"))
+                })?,
+            None => max,
+        };
+
+        Ok(IpNet { addr, prefix })
+    }
+
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Whether the address is within the range, IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) are matched as IPv4.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = NetworkError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        IpNet::parse(source)
+    }
+}
+
+impl Display for IpNet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Ranges (or a named set of them) for `from_network` conditions, see the [module](self) docs for the syntax.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::network::{register_network, Networks};
+///
+/// register_network("vpn", "10.8.0.0/16, fd00::/8").unwrap();
+///
+/// let vpn: Networks = "vpn".parse().unwrap();
+/// assert!(vpn.contains("10.8.1.1".parse().unwrap()));
+/// assert!(!vpn.contains("10.9.1.1".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Networks(Repr);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    List(Vec<IpNet>),
+    Named(String),
+}
+
+/// Error of the network parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkError {
+    message: String,
+}
+
+impl NetworkError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+static NETWORKS: RwLock<BTreeMap<String, Vec<IpNet>>> = RwLock::new(BTreeMap::new());
+static TRUSTED_PROXIES: RwLock<Option<(Networks, ForwardedHeader)>> = RwLock::new(None);
+
+/// Header which trusted proxies set (or append to) with the address of their peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ForwardedHeader {
+    /// The standard `Forwarded` header (`for=192.0.2.60;proto=https`).
+    Forwarded,
+    /// The de-facto standard `X-Forwarded-For` header (`192.0.2.60, 198.51.100.17`).
+    XForwardedFor,
+}
+
+impl ForwardedHeader {
+    /// Name of the header.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForwardedHeader::Forwarded => "Forwarded",
+            ForwardedHeader::XForwardedFor => "X-Forwarded-For",
+        }
+    }
+
+    /// Addresses of the hops in the value of the header, `None` for obfuscated or malformed ones.
+    fn hops(&self, value: &str) -> Vec<Option<IpAddr>> {
+        match self {
+            ForwardedHeader::Forwarded => value
+                .split(',')
+                .filter_map(|element| {
+                    element.split(';').find_map(|pair| {
+                        let (key, node) = pair.split_once('=')?;
+                        key.trim().eq_ignore_ascii_case("for").then_some(node)
+                    })
+                })
+                .map(parse_node)
+                .collect(),
+            ForwardedHeader::XForwardedFor => value.split(',').map(parse_node).collect(),
+        }
+    }
+}
+
+/// Globally registers the set of ranges to use its name in `from_network` conditions.
+pub fn register_network(name: impl Into<String>, ranges: &str) -> Result<(), NetworkError> {
+    let name = name.into();
+    if !is_name(&name) {
+        return Err(NetworkError::new(format!(
+            "invalid network name `{name}`, expected an identifier"
+        )));
+    }
+    let ranges = parse_ranges(ranges)?;
+
+    NETWORKS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(name, ranges);
+    Ok(())
+}
+
+/// Globally sets proxies (e.g. the load balancer) and the header they set, which is trusted.
+/// No proxies are trusted by default, so the client address is always the peer address.
+///
+/// Only the configured header is read: the proxy has to set it (or append to it), so the client
+/// can't forge the hops added by the proxies.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::network::{client_ip, set_trusted_proxies, ForwardedHeader};
+///
+/// set_trusted_proxies("172.16.0.0/12", ForwardedHeader::XForwardedFor).unwrap();
+///
+/// let peer = "172.16.0.2".parse().ok();
+/// let headers = [
+///     ("X-Forwarded-For", "10.8.1.1, 172.16.0.3"),
+///     ("Forwarded", "for=10.1.1.1"),
+/// ];
+/// assert_eq!("10.8.1.1".parse().ok(), client_ip(peer, headers));
+/// ```
+pub fn set_trusted_proxies(networks: &str, header: ForwardedHeader) -> Result<(), NetworkError> {
+    let networks = Networks::parse(networks)?;
+    *TRUSTED_PROXIES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((networks, header));
+    Ok(())
+}
+
+/// Stops trusting forwarded headers.
+pub fn reset_trusted_proxies() {
+    *TRUSTED_PROXIES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+impl Networks {
+    /// Parses comma-separated ranges (`10.0.0.0/8, ::1`) or a name of the registered set (`vpn`).
+    pub fn parse(source: &str) -> Result<Self, NetworkError> {
+        let source = source.trim();
+        if is_name(source) {
+            return Ok(Networks(Repr::Named(source.to_string())));
+        }
+
+        parse_ranges(source).map(|ranges| Networks(Repr::List(ranges)))
+    }
+
+    /// Whether the address is within any of the ranges.
+    /// Names which aren't registered contain no addresses.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match &self.0 {
+            Repr::List(ranges) => ranges.iter().any(|net| net.contains(ip)),
+            Repr::Named(name) => {
+                let networks = NETWORKS
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                match networks.get(name) {
+                    Some(ranges) => ranges.iter().any(|net| net.contains(ip)),
+                    None => {
+                        log::warn!(
+                            "network `{name}` isn't registered, so it contains no addresses"
+                        );
+                        false
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Networks {
+    type Err = NetworkError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Networks::parse(source)
+    }
+}
+
+/// Renders the networks in the canonical form, which can be parsed back.
+impl Display for Networks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::List(ranges) => {
+                for (idx, net) in ranges.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    net.fmt(f)?;
+                }
+                Ok(())
+            }
+            Repr::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Networks {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Networks {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Networks::parse(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Address of the client, see the [module](self) docs for how it's resolved.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::network::{ClientIp, Networks};
+///
+/// let client = ClientIp::new("10.8.1.1".parse().ok(), []);
+/// assert!(client.is_from(&"10.0.0.0/8".parse::<Networks>().unwrap()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientIp(Option<IpAddr>);
+
+impl ClientIp {
+    /// Resolves the client address by the peer address and headers of the request (names are case-insensitive).
+    pub fn new<'a>(
+        peer: Option<IpAddr>,
+        headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        ClientIp(client_ip(peer, headers))
+    }
+
+    /// Address of the client, unknown if the peer address isn't available or a trusted proxy hides it.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.0
+    }
+
+    /// Whether the client is within the networks, unknown clients are never.
+    pub fn is_from(&self, networks: &Networks) -> bool {
+        self.0.is_some_and(|ip| networks.contains(ip))
+    }
+
+    /// Denies clients outside of the networks (the denial is logged), e.g. in route-level layers.
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self, networks: &Networks) -> Result<(), AccessDenied> {
+        if self.is_from(networks) {
+            return Ok(());
+        }
+
+        let denied = AccessDenied::new(
+            Condition::All(Vec::new()),
+            Explanation::expr(format!("from_network = {networks}"), false),
+        );
+        denied.log();
+        Err(denied)
+    }
+}
+
+/// Address of the client by the peer address and headers of the request (names are case-insensitive).
+pub fn client_ip<'a>(
+    peer: Option<IpAddr>,
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Option<IpAddr> {
+    let trusted = TRUSTED_PROXIES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+
+    resolve(
+        peer?.to_canonical(),
+        headers,
+        trusted.as_ref().map(|(proxies, header)| (proxies, *header)),
+    )
+}
+
+fn resolve<'a>(
+    peer: IpAddr,
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    trusted: Option<(&Networks, ForwardedHeader)>,
+) -> Option<IpAddr> {
+    let Some((proxies, header)) = trusted.filter(|(proxies, _)| proxies.contains(peer)) else {
+        return Some(peer);
+    };
+
+    let hops: Vec<_> = headers
+        .into_iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case(header.name()))
+        .flat_map(|(_, value)| header.hops(value))
+        .collect();
+
+    for hop in hops.iter().rev() {
+        match hop {
+            Some(ip) if proxies.contains(*ip) => continue,
+            // obfuscated or malformed hops hide the client
+            hop => return *hop,
+        }
+    }
+
+    // all hops are trusted, so the farthest one is the client
+    hops.first().copied().unwrap_or(Some(peer))
+}
+
+/// Parses a node of forwarded headers: `192.0.2.60`, `192.0.2.60:8080`, `"[2001:db8::17]:4711"`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        let (addr, _port) = rest.split_once(']')?;
+        return addr
+            .parse::<Ipv6Addr>()
+            .ok()
+            .map(|ip| IpAddr::V6(ip).to_canonical());
+    }
+
+    match node.parse::<IpAddr>() {
+        Ok(ip) => Some(ip.to_canonical()),
+        Err(_) => {
+            let (addr, _port) = node.split_once(':')?;
+            addr.parse::<Ipv4Addr>().ok().map(IpAddr::V4)
+        }
+    }
+}
+
+fn parse_ranges(source: &str) -> Result<Vec<IpNet>, NetworkError> {
+    if source.trim().is_empty() {
+        return Err(NetworkError::new("expected at least one network"));
+    }
+
+    source.split(',').map(IpNet::parse).collect()
+}
+
+fn is_name(source: &str) -> bool {
+    let mut chars = source.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(source: &str) -> IpAddr {
+        source.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ranges() {
+        let networks = Networks::parse("10.0.0.0/8, 192.168.1.1, 2001:db8::/32").unwrap();
+
+        assert!(networks.contains(ip("10.255.0.1")));
+        assert!(networks.contains(ip("::ffff:10.0.0.1")));
+        assert!(networks.contains(ip("192.168.1.1")));
+        assert!(!networks.contains(ip("192.168.1.2")));
+        assert!(networks.contains(ip("2001:db8:1::1")));
+        assert!(!networks.contains(ip("2001:db9::1")));
+        assert!(Networks::parse("0.0.0.0/0")
+            .unwrap()
+            .contains(ip("8.8.8.8")));
+
+        assert_eq!(
+            "10.0.0.0/8, 192.168.1.1/32, 2001:db8::/32",
+            networks.to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_networks() {
+        assert!(Networks::parse("").is_err());
+        assert!(Networks::parse("10.0.0.0/33").is_err());
+        assert!(Networks::parse("10.0.0/8").is_err());
+        assert!(Networks::parse("10.0.0.0/8,").is_err());
+        assert!(register_network("not a name", "10.0.0.0/8").is_err());
+        assert!(register_network("vpn", "office").is_err());
+    }
+
+    #[test]
+    fn test_named_networks() {
+        register_network("office", "192.168.0.0/16").unwrap();
+
+        assert!(Networks::parse("office")
+            .unwrap()
+            .contains(ip("192.168.10.1")));
+        assert!(!Networks::parse("unknown")
+            .unwrap()
+            .contains(ip("192.168.10.1")));
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let proxies = Networks::parse("172.16.0.0/12").unwrap();
+        let resolve = |peer, headers: &[(&'static str, &'static str)]| {
+            resolve(
+                ip(peer),
+                headers.iter().copied(),
+                Some((&proxies, ForwardedHeader::XForwardedFor)),
+            )
+        };
+        let resolve_forwarded = |peer, headers: &[(&'static str, &'static str)]| {
+            super::resolve(
+                ip(peer),
+                headers.iter().copied(),
+                Some((&proxies, ForwardedHeader::Forwarded)),
+            )
+        };
+
+        let spoofed = [("X-Forwarded-For", "10.0.0.1")];
+        assert_eq!(Some(ip("203.0.113.1")), resolve("203.0.113.1", &spoofed));
+        assert_eq!(Some(ip("10.0.0.1")), resolve("172.16.0.1", &spoofed));
+
+        let chain = [
+            ("x-forwarded-for", "10.0.0.1, 203.0.113.1"),
+            ("x-forwarded-for", "172.16.0.2"),
+        ];
+        assert_eq!(Some(ip("203.0.113.1")), resolve("172.16.0.1", &chain));
+
+        let forwarded = [
+            ("X-Forwarded-For", "10.0.0.1"),
+            (
+                "Forwarded",
+                "for=\"[2001:db8::17]:4711\";proto=https, for=172.16.0.2",
+            ),
+        ];
+        assert_eq!(
+            Some(ip("2001:db8::17")),
+            resolve_forwarded("172.16.0.1", &forwarded)
+        );
+        assert_eq!(Some(ip("10.0.0.1")), resolve("172.16.0.1", &forwarded));
+
+        assert_eq!(
+            None,
+            resolve_forwarded("172.16.0.1", &[("Forwarded", "for=unknown")])
+        );
+        assert_eq!(
+            Some(ip("172.16.0.2")),
+            resolve("172.16.0.1", &[("X-Forwarded-For", "172.16.0.2")])
+        );
+        assert_eq!(Some(ip("172.16.0.1")), resolve("172.16.0.1", &[]));
+        assert_eq!(None, client_ip(None, []));
+    }
+
+    #[test]
+    fn test_forged_header() {
+        let proxies = Networks::parse("172.16.0.0/12").unwrap();
+        // the proxy appends `X-Forwarded-For`, but passes `Forwarded` of the client as is
+        let headers = [
+            ("Forwarded", "for=10.1.1.1"),
+            ("X-Forwarded-For", "203.0.113.7"),
+        ];
+
+        assert_eq!(
+            Some(ip("203.0.113.7")),
+            resolve(
+                ip("172.16.0.2"),
+                headers,
+                Some((&proxies, ForwardedHeader::XForwardedFor))
+            )
+        );
+        assert_eq!(
+            Some(ip("172.16.0.2")),
+            resolve(
+                ip("172.16.0.2"),
+                [("Forwarded", "for=10.1.1.1")],
+                Some((&proxies, ForwardedHeader::XForwardedFor))
+            )
+        );
+    }
+
+    #[test]
+    fn test_forwarded_nodes() {
+        assert_eq!(Some(ip("192.0.2.60")), parse_node(" 192.0.2.60"));
+        assert_eq!(Some(ip("192.0.2.60")), parse_node("192.0.2.60:8080"));
+        assert_eq!(
+            Some(ip("2001:db8::17")),
+            parse_node("\"[2001:db8::17]:4711\"")
+        );
+        assert_eq!(Some(ip("2001:db8::17")), parse_node("2001:db8::17"));
+        assert_eq!(None, parse_node("unknown"));
+        assert_eq!(None, parse_node("_hidden"));
+    }
+}
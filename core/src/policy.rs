@@ -0,0 +1,555 @@
+//! Authorization policy files: rules of the [`AuthorizationTable`] and role mappings kept outside of the code,
+//! so permissions can be changed without a redeploy.
+//!
+//! Formats are enabled by features: `policy-toml`, `policy-yaml` and `policy-json`,
+//! the format of a file is detected by its extension.
+//!
+//! ```toml
+//! # access of requests matching no rule, `deny_all` by default
+//! default = "authenticated"
+//!
+//! # role -> authorities it implies (transitively)
+//! [roles]
+//! ROLE_ADMIN = ["ROLE_EDITOR"]
+//! ROLE_EDITOR = ["ROLE_USER"]
+//!
+//! # evaluated in order, the first matching rule decides
+//! [[rules]]
+//! methods = "GET,HEAD"  # `*` by default
+//! path = "/health"
+//! access = "permit_all"  # `permit_all`, `deny_all`, `authenticated` or a rule, e.g. `ROLE_ADMIN || OP_WRITE`
+//!
+//! [[rules]]
+//! path = "/admin/**"
+//! access = "ROLE_ADMIN"
+//! ```
+//!
+//! Policies are validated on load: errors point to the failed entry (e.g. `rules[1].access`).
+//! A [`PolicyFile`] feeds the [`SharedTable`] of the table middleware and can be reloaded explicitly
+//! or watched for changes; failed reloads keep the last good policy.
+
+use crate::condition::Condition;
+use crate::table::{Access, AuthorizationTable, SharedTable};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// Format of the policy source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    #[cfg(feature = "policy-toml")]
+    Toml,
+    #[cfg(feature = "policy-yaml")]
+    Yaml,
+    #[cfg(feature = "policy-json")]
+    Json,
+}
+
+impl Format {
+    /// Detects the format by the extension, `None` if it's unknown or the format isn't enabled.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            #[cfg(feature = "policy-toml")]
+            "toml" => Some(Format::Toml),
+            #[cfg(feature = "policy-yaml")]
+            "yaml" | "yml" => Some(Format::Yaml),
+            #[cfg(feature = "policy-json")]
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    fn deserialize(self, source: &str) -> Result<RawPolicy, String> {
+        match self {
+            #[cfg(feature = "policy-toml")]
+            Format::Toml => toml::from_str(source).map_err(|err| err.to_string()),
+            #[cfg(feature = "policy-yaml")]
+            Format::Yaml => serde_yaml::from_str(source).map_err(|err| err.to_string()),
+            #[cfg(feature = "policy-json")]
+            Format::Json => serde_json::from_str(source).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPolicy {
+    #[serde(default)]
+    default: Option<String>,
+    #[serde(default)]
+    roles: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    rules: Vec<RawRule>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    #[serde(default = "any_method")]
+    methods: String,
+    path: String,
+    access: String,
+}
+
+fn any_method() -> String {
+    "*".to_string()
+}
+
+/// Error of the policy loading: unreadable file, invalid syntax or entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyError {
+    path: Option<PathBuf>,
+    message: String,
+}
+
+impl PolicyError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    /// Path of the policy file, if loaded from a file.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Description of the error without the path.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "invalid policy `{}`: {}", path.display(), self.message),
+            None => write!(f, "invalid policy: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+/// Parses and validates the policy.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "policy-json")]
+/// # {
+/// use protect_endpoints_core::authorities::AuthDetails;
+/// use protect_endpoints_core::policy::{self, Format};
+///
+/// let table = policy::parse(
+///     r#"{
+///         "roles": { "ROLE_ADMIN": ["ROLE_EDITOR"] },
+///         "rules": [{ "methods": "POST", "path": "/articles/**", "access": "ROLE_EDITOR" }]
+///     }"#,
+///     Format::Json,
+/// )
+/// .unwrap();
+///
+/// let admin = AuthDetails::new(["ROLE_ADMIN".to_string()]);
+/// assert!(table.authorize("POST", "/articles/1", Some(&admin)).is_ok());
+/// assert!(table.authorize("GET", "/articles/1", Some(&admin)).is_err());
+/// # }
+/// ```
+pub fn parse(source: &str, format: Format) -> Result<AuthorizationTable, PolicyError> {
+    let raw = format.deserialize(source).map_err(PolicyError::new)?;
+    compile(raw).map_err(PolicyError::new)
+}
+
+fn compile(raw: RawPolicy) -> Result<AuthorizationTable, String> {
+    let implied_by = implying_roles(&raw.roles)?;
+    let access = |source: &str, entry: &str| {
+        source
+            .parse::<Access>()
+            .map(|access| expand_access(access, &implied_by))
+            .map_err(|err| format!("{entry}: {err} in `{source}`"))
+    };
+
+    let mut table = AuthorizationTable::new();
+    for (idx, rule) in raw.rules.iter().enumerate() {
+        let entry = format!("rules[{idx}]");
+        let rule_access = access(&rule.access, &format!("{entry}.access"))?;
+        table = table
+            .try_route(&rule.methods, &rule.path, rule_access)
+            .map_err(|err| format!("{entry}: {err}"))?;
+    }
+
+    match raw.default {
+        Some(default) => Ok(table.otherwise(access(&default, "default")?)),
+        None => Ok(table),
+    }
+}
+
+/// Roles implying each authority, transitively.
+fn implying_roles(
+    roles: &BTreeMap<String, Vec<String>>,
+) -> Result<HashMap<String, BTreeSet<String>>, String> {
+    let mut implied_by: HashMap<String, BTreeSet<String>> = HashMap::new();
+
+    for (role, implied) in roles {
+        let mut pending: Vec<&String> = implied.iter().collect();
+        let mut seen = HashSet::new();
+        while let Some(authority) = pending.pop() {
+            if authority == role {
+                return Err(format!(
+                    "roles.{role}: cyclic mapping, `{role}` implies itself"
+                ));
+            }
+            if seen.insert(authority) {
+                implied_by
+                    .entry(authority.clone())
+                    .or_default()
+                    .insert(role.clone());
+                pending.extend(roles.get(authority).into_iter().flatten());
+            }
+        }
+    }
+
+    Ok(implied_by)
+}
+
+fn expand_access(access: Access, implied_by: &HashMap<String, BTreeSet<String>>) -> Access {
+    match access {
+        Access::Condition(condition) => Access::Condition(expand(condition, implied_by)),
+        access => access,
+    }
+}
+
+/// Replaces authorities with alternatives of roles implying them.
+fn expand(
+    condition: Condition<String>,
+    implied_by: &HashMap<String, BTreeSet<String>>,
+) -> Condition<String> {
+    let expand_all = |conditions: Vec<Condition<String>>| {
+        conditions
+            .into_iter()
+            .map(|condition| expand(condition, implied_by))
+            .collect()
+    };
+
+    match condition {
+        Condition::All(conditions) => Condition::All(expand_all(conditions)),
+        Condition::Any(conditions) => Condition::Any(expand_all(conditions)),
+        Condition::Not(condition) => Condition::not(expand(*condition, implied_by)),
+        Condition::AtLeast { count, of } => Condition::AtLeast {
+            count,
+            of: expand_all(of),
+        },
+        Condition::During(schedule) => Condition::During(schedule),
+        Condition::Authority(authority) => match implied_by.get(&authority) {
+            Some(roles) => Condition::any(
+                std::iter::once(authority)
+                    .chain(roles.iter().cloned())
+                    .map(Condition::authority),
+            ),
+            None => Condition::Authority(authority),
+        },
+    }
+}
+
+/// Policy loaded from a file, the handle for reloading it.
+///
+/// # Example
+/// ```rust,ignore
+/// let policy = PolicyFile::load("policy.toml")?;
+/// // checks the file every 5 seconds while the watcher is alive
+/// let _watcher = policy.watch(Duration::from_secs(5));
+///
+/// let app = Router::new()
+///     .route("/articles/{id}", put(update_article))
+///     .layer(TableLayer::new(policy.table()))
+///     .layer(GrantsLayer::with_extractor(extract));
+/// ```
+#[derive(Clone)]
+pub struct PolicyFile {
+    path: PathBuf,
+    format: Format,
+    table: SharedTable,
+    /// Modification time of the last loaded (even if invalid) version.
+    modified: Arc<Mutex<Option<SystemTime>>>,
+}
+
+impl PolicyFile {
+    /// Loads the policy, failing if it's invalid.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        let path = path.as_ref();
+        let format = Format::from_path(path).ok_or_else(|| {
+            PolicyError::new("unknown format, expected enabled `.toml`, `.yaml` or `.json`")
+                .with_path(path)
+        })?;
+        let (table, modified) = read(path, format)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            table: SharedTable::new(table),
+            modified: Arc::new(Mutex::new(modified)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Table of the last good policy for the table middleware.
+    pub fn table(&self) -> SharedTable {
+        self.table.clone()
+    }
+
+    /// Loads the policy again, keeping the last good one if it fails.
+    pub fn reload(&self) -> Result<(), PolicyError> {
+        *self
+            .modified
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = modified(&self.path);
+
+        match read(&self.path, self.format) {
+            Ok((table, _)) => {
+                self.table.replace(table);
+                log::info!("Reloaded policy `{}`", self.path.display());
+                Ok(())
+            }
+            Err(err) => {
+                log::error!("{err}, keeping the last good policy");
+                Err(err)
+            }
+        }
+    }
+
+    /// Checks the file for changes with the interval and reloads it, until the watcher is dropped.
+    pub fn watch(&self, interval: Duration) -> PolicyWatcher {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let policy = self.clone();
+
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let last = *policy
+                    .modified
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                // the file might be missing for a moment while it's replaced
+                if modified(&policy.path).is_some_and(|modified| Some(modified) != last) {
+                    let _ = policy.reload();
+                }
+            }
+        });
+
+        PolicyWatcher {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Watcher of the [`PolicyFile`], stops watching on drop.
+pub struct PolicyWatcher {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for PolicyWatcher {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+fn read(
+    path: &Path,
+    format: Format,
+) -> Result<(AuthorizationTable, Option<SystemTime>), PolicyError> {
+    let modified = modified(path);
+    let source = std::fs::read_to_string(path)
+        .map_err(|err| PolicyError::new(err.to_string()).with_path(path))?;
+    let table = parse(&source, format).map_err(|err| err.with_path(path))?;
+
+    Ok((table, modified))
+}
+
+#[cfg(all(test, feature = "policy-toml"))]
+mod tests {
+    use super::*;
+    use crate::authorities::AuthDetails;
+    use crate::table::Rejection;
+
+    const POLICY: &str = r#"
+        default = "authenticated"
+
+        [roles]
+        ROLE_ADMIN = ["ROLE_EDITOR"]
+        ROLE_EDITOR = ["ROLE_USER"]
+
+        [[rules]]
+        methods = "GET"
+        path = "/health"
+        access = "permit_all"
+
+        [[rules]]
+        path = "/articles/**"
+        access = "ROLE_USER && !SUSPENDED"
+    "#;
+
+    fn details(authorities: &[&str]) -> AuthDetails {
+        AuthDetails::new(authorities.iter().map(|authority| authority.to_string()))
+    }
+
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "protect-endpoints-{}-{name}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_toml_policy() {
+        let table = parse(POLICY, Format::Toml).unwrap();
+
+        assert!(table
+            .authorize("GET", "/health", None::<&AuthDetails>)
+            .is_ok());
+        assert!(table
+            .authorize("GET", "/articles/1", Some(&details(&["ROLE_ADMIN"])))
+            .is_ok());
+        assert!(table
+            .authorize(
+                "GET",
+                "/articles/1",
+                Some(&details(&["ROLE_EDITOR", "SUSPENDED"]))
+            )
+            .is_err());
+        assert!(matches!(
+            table.authorize("GET", "/profile", None::<&AuthDetails>),
+            Err(Rejection::Unauthorized)
+        ));
+        assert!(table
+            .authorize("GET", "/profile", Some(&details(&["ROLE_OTHER"])))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let error = |source: &str| parse(source, Format::Toml).unwrap_err().to_string();
+
+        assert!(error(
+            r#"
+            [[rules]]
+            path = "/"
+            access = "permit_all"
+
+            [[rules]]
+            path = "/admin"
+            access = "ROLE_ADMIN ||"
+            "#
+        )
+        .starts_with("invalid policy: rules[1].access: "));
+        assert!(error(
+            r#"
+            [[rules]]
+            path = "admin"
+            access = "ROLE_ADMIN"
+            "#
+        )
+        .starts_with("invalid policy: rules[0]: path pattern `admin`"));
+        assert!(error(r#"default = "ROLE_ADMIN &&""#).starts_with("invalid policy: default: "));
+        assert_eq!(
+            "invalid policy: roles.A: cyclic mapping, `A` implies itself",
+            error("roles = { A = [\"B\"], B = [\"A\"] }")
+        );
+        assert!(error(
+            r#"
+            [[rules]]
+            path = "/"
+            acess = "permit_all"
+            "#
+        )
+        .contains("unknown field `acess`"));
+    }
+
+    #[test]
+    fn test_reload_keeps_last_good() {
+        let path = temp_file("reload", POLICY);
+        let policy = PolicyFile::load(&path).unwrap();
+        let table = policy.table();
+        let user = details(&["ROLE_OTHER"]);
+
+        std::fs::write(&path, "default = \"deny_all\"\n[[rules]]\npath = \"/").unwrap();
+        let error = policy.reload().unwrap_err();
+        assert_eq!(Some(path.as_path()), error.path());
+        assert!(table
+            .load()
+            .authorize("GET", "/profile", Some(&user))
+            .is_ok());
+
+        std::fs::write(&path, "default = \"deny_all\"").unwrap();
+        policy.reload().unwrap();
+        assert!(table
+            .load()
+            .authorize("GET", "/profile", Some(&user))
+            .is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_watch() {
+        let path = temp_file("watch", POLICY);
+        let policy = PolicyFile::load(&path).unwrap();
+        let watcher = policy.watch(Duration::from_millis(10));
+        let user = details(&["ROLE_OTHER"]);
+
+        std::fs::write(&path, "default = \"deny_all\"").unwrap();
+        let reloaded = (0..200).any(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            policy
+                .table()
+                .load()
+                .authorize("GET", "/profile", Some(&user))
+                .is_err()
+        });
+        assert!(reloaded);
+
+        drop(watcher);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "policy-yaml")]
+    #[test]
+    fn test_yaml_policy() {
+        let table = parse(
+            "roles:\n  ROLE_ADMIN: [ROLE_USER]\nrules:\n  - path: /articles/**\n    access: ROLE_USER\n",
+            Format::Yaml,
+        )
+        .unwrap();
+
+        assert!(table
+            .authorize("DELETE", "/articles/1", Some(&details(&["ROLE_ADMIN"])))
+            .is_ok());
+        assert!(table
+            .authorize("DELETE", "/profile", Some(&details(&["ROLE_ADMIN"])))
+            .is_err());
+    }
+}
@@ -0,0 +1,696 @@
+//! Registry of handlers protected by the `protect` macros, collected at link time.
+//!
+//! Every expansion of `#[protect(..)]` (and of methods within `#[open_api]` for `poem`) submits
+//! a static [`Endpoint`] record, so the whole application can be audited without running requests,
+//! e.g. to answer "which endpoints need `OP_X`?" via [`PermissionMatrix`].
+//!
+//! Note that paths of routes are not known to the macros, so endpoints are identified by their handlers.
+//!
+//! # Example
+//! ```
+//! use protect_endpoints_core::registry::{self, PermissionMatrix};
+//!
+//! for endpoint in registry::endpoints() {
+//!     println!("{} requires `{}`", endpoint.handler(), endpoint.condition());
+//! }
+//!
+//! let matrix = PermissionMatrix::collect();
+//! let report = matrix.to_markdown();
+//! ```
+//!
+//! The registry can also be served by the admin endpoints of the integrations, see [`introspection`].
+//! With the `utoipa` or `aide` feature, protected operations of OpenAPI specs are documented by `openapi::ProtectedOperations`.
+
+use crate::condition::{Condition, Requirement};
+use crate::error::json_string;
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter, Write};
+use std::hash::{Hash, Hasher};
+
+pub mod introspection;
+#[cfg(any(feature = "utoipa", feature = "aide"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "utoipa", feature = "aide"))))]
+pub mod openapi;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use inventory;
+}
+
+/// Location of the handler in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    file: &'static str,
+    line: u32,
+    column: u32,
+}
+
+impl Location {
+    pub const fn new(file: &'static str, line: u32, column: u32) -> Self {
+        Self { file, line, column }
+    }
+
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Static record of a protected handler.
+#[derive(Debug, Clone, Copy)]
+pub struct Endpoint {
+    handler: &'static str,
+    location: Location,
+    framework: &'static str,
+    authority_type: &'static str,
+    condition: &'static str,
+    dynamic: bool,
+    reflection: Option<fn() -> Requirement<&'static str>>,
+}
+
+impl Endpoint {
+    /// Creates the record, `condition` is a rule (see [`Condition::parse`]).
+    pub const fn new(
+        handler: &'static str,
+        location: Location,
+        framework: &'static str,
+        authority_type: &'static str,
+        condition: &'static str,
+        dynamic: bool,
+    ) -> Self {
+        Self {
+            handler,
+            location,
+            framework,
+            authority_type,
+            condition,
+            dynamic,
+            reflection: None,
+        }
+    }
+
+    /// Sets the function returning the whole condition of the handler, see [`Endpoint::reflection`].
+    pub const fn with_reflection(mut self, reflection: fn() -> Requirement<&'static str>) -> Self {
+        self.reflection = Some(reflection);
+        self
+    }
+
+    /// Path of the handler, e.g. `my_app::api::get_users`.
+    pub fn handler(&self) -> &'static str {
+        self.handler
+    }
+
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    /// Framework integration which generated the record, e.g. `axum`.
+    pub fn framework(&self) -> &'static str {
+        self.framework
+    }
+
+    /// Type of authorities in the source form, `String` by default.
+    pub fn authority_type(&self) -> &'static str {
+        self.authority_type
+    }
+
+    /// Rule with the authorities part of the condition (including stacked `protect` attributes),
+    /// typed authorities are kept in the source form, e.g. `Role::Admin`. Empty if there is no such part.
+    pub fn condition(&self) -> &'static str {
+        self.condition
+    }
+
+    /// Whether the condition also depends on the request (`expr`, `owner` or `from_network`),
+    /// which is omitted from [`Endpoint::condition`].
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
+    /// The whole condition of the handler (including its dynamic parts), see [`requirement_of`].
+    pub fn reflection(&self) -> Option<Requirement<&'static str>> {
+        self.reflection.map(|reflection| reflection())
+    }
+
+    /// Parsed [`Endpoint::condition`], empty conditions (e.g. with `expr` only) are always satisfied.
+    pub fn requirement(&self) -> Condition<String> {
+        if self.condition.is_empty() {
+            return Condition::All(Vec::new());
+        }
+
+        self.condition
+            .parse()
+            .expect("condition of the endpoint is generated by the macro")
+    }
+
+    /// Simulates the access of the user with the authorities (typed ones in the source form, e.g. `Role::Admin`).
+    /// Schedules are evaluated at the current time.
+    pub fn access<S: AsRef<str>>(&self, authorities: &[S]) -> Access {
+        let granted = self.requirement().evaluate(&mut |authority| {
+            authorities
+                .iter()
+                .any(|granted| granted.as_ref() == authority)
+        });
+
+        match (granted, self.dynamic) {
+            (false, _) => Access::Denied,
+            (true, true) => Access::Depends,
+            (true, false) => Access::Allowed,
+        }
+    }
+}
+
+// the reflection is generated by the macro along with the other fields, while addresses of functions aren't meaningful
+impl Endpoint {
+    fn key(
+        &self,
+    ) -> (
+        &'static str,
+        Location,
+        &'static str,
+        &'static str,
+        &'static str,
+        bool,
+    ) {
+        (
+            self.handler,
+            self.location,
+            self.framework,
+            self.authority_type,
+            self.condition,
+            self.dynamic,
+        )
+    }
+}
+
+impl PartialEq for Endpoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Endpoint {}
+
+impl Hash for Endpoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Simulated access to an endpoint, see [`Endpoint::access`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    Allowed,
+    Denied,
+    /// The authorities are sufficient, but the condition also depends on the request.
+    Depends,
+}
+
+impl Access {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Access::Allowed => "allowed",
+            Access::Denied => "denied",
+            Access::Depends => "depends",
+        }
+    }
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+::inventory::collect!(Endpoint);
+
+/// All the registered endpoints, ordered by their location.
+pub fn endpoints() -> Vec<&'static Endpoint> {
+    let mut endpoints: Vec<_> = ::inventory::iter::<Endpoint>.into_iter().collect();
+    endpoints.sort_by_key(|endpoint| (endpoint.location, endpoint.handler));
+    endpoints
+}
+
+/// Requirement of the handler by its value (see [`Endpoint::reflection`]), e.g. to unit-test it without requests.
+///
+/// Unlike `requirement_of!` macro of the framework crates, it also supports handlers of route macros
+/// (e.g. `#[get("/")]` of `actix-web`) and methods (as `Api::method`), since endpoints are identified by their paths.
+/// `None` if the handler isn't protected.
+pub fn requirement_of<H>(handler: &H) -> Option<Requirement<&'static str>> {
+    let handler = crate::handler_path(handler);
+    ::inventory::iter::<Endpoint>
+        .into_iter()
+        .find(|endpoint| endpoint.handler == handler)
+        .and_then(Endpoint::reflection)
+}
+
+/// Role of an authority in the condition of an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// The condition can't be satisfied without the authority.
+    Required,
+    /// The authority satisfies the condition along with (or instead of) other ones.
+    Alternative,
+    /// The authority must be absent.
+    Excluded,
+}
+
+impl Permission {
+    /// Permission of the authority in the condition, `None` if it isn't mentioned.
+    ///
+    /// Schedules are considered active, since the matrix doesn't depend on the time.
+    pub fn of<T: PartialEq>(condition: &Condition<T>, authority: &T) -> Option<Self> {
+        let (positive, negative) = polarities(condition);
+        if positive.contains(&authority) {
+            let granted = |candidate: &T| candidate != authority && positive.contains(&candidate);
+            if satisfied(condition, &granted) {
+                Some(Permission::Alternative)
+            } else {
+                Some(Permission::Required)
+            }
+        } else if negative.contains(&authority) {
+            Some(Permission::Excluded)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Required => "required",
+            Permission::Alternative => "alternative",
+            Permission::Excluded => "excluded",
+        }
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Authorities mentioned in the condition without negation and under it.
+fn polarities<T: PartialEq>(condition: &Condition<T>) -> (Vec<&T>, Vec<&T>) {
+    fn visit<'a, T: PartialEq>(
+        condition: &'a Condition<T>,
+        negated: bool,
+        polarities: &mut (Vec<&'a T>, Vec<&'a T>),
+    ) {
+        match condition {
+            Condition::Authority(authority) if negated => polarities.1.push(authority),
+            Condition::Authority(authority) => polarities.0.push(authority),
+            Condition::Not(nested) => visit(nested, !negated, polarities),
+            Condition::All(nested)
+            | Condition::Any(nested)
+            | Condition::AtLeast { of: nested, .. } => nested
+                .iter()
+                .for_each(|cond| visit(cond, negated, polarities)),
+            Condition::During(_) => {}
+        }
+    }
+
+    let mut polarities = (Vec::new(), Vec::new());
+    visit(condition, false, &mut polarities);
+    polarities
+}
+
+fn satisfied<T>(condition: &Condition<T>, granted: &impl Fn(&T) -> bool) -> bool {
+    match condition {
+        Condition::All(nested) => nested.iter().all(|cond| satisfied(cond, granted)),
+        Condition::Any(nested) => nested.iter().any(|cond| satisfied(cond, granted)),
+        Condition::Not(nested) => !satisfied(nested, granted),
+        Condition::AtLeast { count, of } => {
+            of.iter().filter(|cond| satisfied(cond, granted)).count() >= *count
+        }
+        Condition::During(_) => true,
+        Condition::Authority(authority) => granted(authority),
+    }
+}
+
+/// Endpoints by authorities: which of them are required, alternative or excluded for each endpoint.
+#[derive(Debug, Clone)]
+pub struct PermissionMatrix {
+    authorities: Vec<String>,
+    rows: Vec<MatrixRow>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatrixRow {
+    endpoint: &'static Endpoint,
+    permissions: Vec<Option<Permission>>,
+}
+
+impl MatrixRow {
+    pub fn endpoint(&self) -> &'static Endpoint {
+        self.endpoint
+    }
+
+    /// Permissions in the order of [`PermissionMatrix::authorities`].
+    pub fn permissions(&self) -> &[Option<Permission>] {
+        &self.permissions
+    }
+}
+
+impl PermissionMatrix {
+    /// Builds the matrix of all the registered endpoints.
+    pub fn collect() -> Self {
+        Self::new(endpoints())
+    }
+
+    pub fn new(endpoints: impl IntoIterator<Item = &'static Endpoint>) -> Self {
+        let endpoints: Vec<_> = endpoints
+            .into_iter()
+            .map(|endpoint| (endpoint, endpoint.requirement()))
+            .collect();
+        let authorities: Vec<String> = endpoints
+            .iter()
+            .flat_map(|(_, requirement)| requirement.authorities())
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let rows = endpoints
+            .iter()
+            .map(|(endpoint, requirement)| MatrixRow {
+                endpoint,
+                permissions: authorities
+                    .iter()
+                    .map(|authority| Permission::of(requirement, authority))
+                    .collect(),
+            })
+            .collect();
+
+        Self { authorities, rows }
+    }
+
+    /// All the authorities mentioned by the endpoints, sorted.
+    pub fn authorities(&self) -> &[String] {
+        &self.authorities
+    }
+
+    pub fn rows(&self) -> &[MatrixRow] {
+        &self.rows
+    }
+
+    /// Endpoints mentioning the authority along with its permission.
+    pub fn endpoints_with(&self, authority: &str) -> Vec<(&'static Endpoint, Permission)> {
+        let Some(idx) = self.authorities.iter().position(|a| a == authority) else {
+            return Vec::new();
+        };
+
+        self.rows
+            .iter()
+            .filter_map(|row| row.permissions[idx].map(|permission| (row.endpoint, permission)))
+            .collect()
+    }
+
+    /// Renders the matrix as a JSON object with `authorities` and `endpoints` (with `permissions` by authority).
+    pub fn to_json(&self) -> String {
+        let authorities: Vec<_> = self.authorities.iter().map(|a| json_string(a)).collect();
+        let mut json = format!(
+            r#"{{"authorities":[{}],"endpoints":["#,
+            authorities.join(",")
+        );
+
+        for (idx, row) in self.rows.iter().enumerate() {
+            let endpoint = row.endpoint;
+            if idx > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                r#"{{{},"permissions":{{"#,
+                endpoint_json_fields(endpoint)
+            )
+            .unwrap();
+
+            let permissions: Vec<_> = self.permissions(row).collect();
+            for (idx, (authority, permission)) in permissions.into_iter().enumerate() {
+                if idx > 0 {
+                    json.push(',');
+                }
+                write!(json, r#"{}:"{}""#, json_string(authority), permission).unwrap();
+            }
+            json.push_str("}}");
+        }
+
+        json.push_str("]}");
+        json
+    }
+
+    /// Renders the matrix as CSV with a column per authority.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["handler", "location", "condition", "dynamic"];
+        header.extend(self.authorities.iter().map(String::as_str));
+        let mut csv = csv_line(header);
+
+        for row in &self.rows {
+            let endpoint = row.endpoint;
+            let location = endpoint.location.to_string();
+            let mut line = vec![
+                endpoint.handler,
+                &location,
+                endpoint.condition,
+                if endpoint.dynamic { "true" } else { "false" },
+            ];
+            line.extend(
+                row.permissions
+                    .iter()
+                    .map(|permission| permission.map_or("", |p| p.as_str())),
+            );
+            csv.push_str(&csv_line(line));
+        }
+
+        csv
+    }
+
+    /// Renders the matrix as a Markdown table with a column per authority.
+    pub fn to_markdown(&self) -> String {
+        let mut header = vec!["Handler", "Location", "Condition", "Dynamic"];
+        header.extend(self.authorities.iter().map(String::as_str));
+        let mut markdown = markdown_line(header.iter().copied());
+        markdown.push_str(&markdown_line(header.iter().map(|_| "---")));
+
+        for row in &self.rows {
+            let endpoint = row.endpoint;
+            let handler = format!("`{}`", endpoint.handler);
+            let location = endpoint.location.to_string();
+            let condition = if endpoint.condition.is_empty() {
+                String::new()
+            } else {
+                format!("`{}`", endpoint.condition)
+            };
+            let mut line = vec![
+                handler.as_str(),
+                &location,
+                &condition,
+                if endpoint.dynamic { "yes" } else { "" },
+            ];
+            line.extend(
+                row.permissions
+                    .iter()
+                    .map(|permission| permission.map_or("", |p| p.as_str())),
+            );
+            markdown.push_str(&markdown_line(line.into_iter()));
+        }
+
+        markdown
+    }
+
+    fn permissions<'a>(
+        &'a self,
+        row: &'a MatrixRow,
+    ) -> impl Iterator<Item = (&'a String, Permission)> + 'a {
+        self.authorities
+            .iter()
+            .zip(&row.permissions)
+            .filter_map(|(authority, permission)| permission.map(|p| (authority, p)))
+    }
+}
+
+/// Fields of the endpoint for JSON objects (without braces).
+fn endpoint_json_fields(endpoint: &Endpoint) -> String {
+    format!(
+        r#""handler":{},"file":{},"line":{},"column":{},"framework":{},"authority_type":{},"condition":{},"dynamic":{}"#,
+        json_string(endpoint.handler),
+        json_string(endpoint.location.file),
+        endpoint.location.line,
+        endpoint.location.column,
+        json_string(endpoint.framework),
+        json_string(endpoint.authority_type),
+        json_string(endpoint.condition),
+        endpoint.dynamic,
+    )
+}
+
+fn csv_line(values: Vec<&str>) -> String {
+    let values: Vec<_> = values
+        .into_iter()
+        .map(|value| {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", values.join(","))
+}
+
+fn markdown_line<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    let values: Vec<_> = values.map(|value| value.replace('|', "\\|")).collect();
+    format!("| {} |\n", values.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::Part;
+
+    static USERS: Endpoint = Endpoint::new(
+        "app::users",
+        Location::new("src/main.rs", 10, 1),
+        "axum",
+        "String",
+        "ROLE_ADMIN || OP_READ_USERS && !SUSPENDED",
+        false,
+    );
+    static ARTICLE: Endpoint = Endpoint::new(
+        "app::article",
+        Location::new("src/main.rs", 20, 1),
+        "axum",
+        "String",
+        "ROLE_EDITOR && OP_WRITE",
+        true,
+    );
+
+    fn rule(rule: &str) -> Condition<String> {
+        rule.parse().unwrap()
+    }
+
+    fn reflected() {}
+
+    fn unprotected() {}
+
+    ::inventory::submit! {
+        Endpoint::new(
+            concat!(module_path!(), "::reflected"),
+            Location::new("src/main.rs", 30, 1),
+            "axum",
+            "String",
+            "ROLE_ADMIN",
+            true,
+        )
+        .with_reflection(|| {
+            Requirement::new(Condition::all([
+                Condition::authority(Part::Authority("ROLE_ADMIN")),
+                Condition::authority(Part::Dynamic("owner(user_id)".to_string())),
+            ]))
+        })
+    }
+
+    #[test]
+    fn test_permissions() {
+        let of = |cond: &str, authority: &str| Permission::of(&rule(cond), &authority.to_string());
+
+        assert_eq!(Some(Permission::Required), of("A && B", "A"));
+        assert_eq!(Some(Permission::Alternative), of("A || B", "A"));
+        assert_eq!(Some(Permission::Required), of("A && (B || C)", "A"));
+        assert_eq!(Some(Permission::Alternative), of("A && (B || C)", "B"));
+        assert_eq!(Some(Permission::Excluded), of("A && !B", "B"));
+        assert_eq!(
+            Some(Permission::Alternative),
+            of("at_least(2, A, B, C)", "A")
+        );
+        assert_eq!(Some(Permission::Required), of("at_least(3, A, B, C)", "A"));
+        assert_eq!(None, of("A && B", "C"));
+    }
+
+    #[test]
+    fn test_access() {
+        assert_eq!(Access::Allowed, USERS.access(&["ROLE_ADMIN"]));
+        assert_eq!(Access::Allowed, USERS.access(&["OP_READ_USERS"]));
+        assert_eq!(
+            Access::Denied,
+            USERS.access(&["OP_READ_USERS", "SUSPENDED"])
+        );
+        assert_eq!(Access::Denied, USERS.access::<&str>(&[]));
+        assert_eq!(
+            Access::Depends,
+            ARTICLE.access(&["ROLE_EDITOR", "OP_WRITE"])
+        );
+        assert_eq!(Access::Denied, ARTICLE.access(&["ROLE_EDITOR"]));
+    }
+
+    #[test]
+    fn test_requirement_of() {
+        let requirement = requirement_of(&reflected).unwrap();
+
+        assert_eq!("ROLE_ADMIN && owner(user_id)", requirement.to_string());
+        assert!(!requirement.is_satisfied_by(&["ROLE_ADMIN"]));
+        assert!(requirement.may_be_satisfied_by(&["ROLE_ADMIN"]));
+        assert_eq!(None, requirement_of(&unprotected));
+        assert_eq!(None, USERS.reflection());
+    }
+
+    #[test]
+    fn test_matrix() {
+        let matrix = PermissionMatrix::new([&USERS, &ARTICLE]);
+
+        assert_eq!(
+            [
+                "OP_READ_USERS",
+                "OP_WRITE",
+                "ROLE_ADMIN",
+                "ROLE_EDITOR",
+                "SUSPENDED"
+            ],
+            matrix.authorities()
+        );
+        assert_eq!(
+            vec![(&ARTICLE, Permission::Required)],
+            matrix.endpoints_with("OP_WRITE")
+        );
+        assert!(matrix.endpoints_with("UNKNOWN").is_empty());
+
+        assert_eq!(
+            concat!(
+                r#"{"authorities":["OP_READ_USERS","OP_WRITE","ROLE_ADMIN","ROLE_EDITOR","SUSPENDED"],"endpoints":["#,
+                r#"{"handler":"app::users","file":"src/main.rs","line":10,"column":1,"framework":"axum","authority_type":"String","#,
+                r#""condition":"ROLE_ADMIN || OP_READ_USERS && !SUSPENDED","dynamic":false,"#,
+                r#""permissions":{"OP_READ_USERS":"alternative","ROLE_ADMIN":"alternative","SUSPENDED":"excluded"}},"#,
+                r#"{"handler":"app::article","file":"src/main.rs","line":20,"column":1,"framework":"axum","authority_type":"String","#,
+                r#""condition":"ROLE_EDITOR && OP_WRITE","dynamic":true,"#,
+                r#""permissions":{"OP_WRITE":"required","ROLE_EDITOR":"required"}}]}"#,
+            ),
+            matrix.to_json()
+        );
+        assert_eq!(
+            "handler,location,condition,dynamic,OP_READ_USERS,OP_WRITE,ROLE_ADMIN,ROLE_EDITOR,SUSPENDED\n\
+             app::users,src/main.rs:10:1,ROLE_ADMIN || OP_READ_USERS && !SUSPENDED,false,alternative,,alternative,,excluded\n\
+             app::article,src/main.rs:20:1,ROLE_EDITOR && OP_WRITE,true,,required,,required,\n",
+            matrix.to_csv()
+        );
+        assert_eq!(
+            "| Handler | Location | Condition | Dynamic | OP_READ_USERS | OP_WRITE | ROLE_ADMIN | ROLE_EDITOR | SUSPENDED |\n\
+             | --- | --- | --- | --- | --- | --- | --- | --- | --- |\n\
+             | `app::users` | src/main.rs:10:1 | `ROLE_ADMIN \\|\\| OP_READ_USERS && !SUSPENDED` |  | alternative |  | alternative |  | excluded |\n\
+             | `app::article` | src/main.rs:20:1 | `ROLE_EDITOR && OP_WRITE` | yes |  | required |  | required |  |\n",
+            matrix.to_markdown()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Introspection of the registry for admin endpoints, mounted via `introspection` modules of the integrations.
+//!
+//! The endpoint serves the registered endpoints with their requirements as JSON and simulates the access
+//! of a set of authorities, so access problems can be diagnosed without reading the code:
+//! - `GET /admin/access` lists the endpoints;
+//! - `GET /admin/access?authorities=ROLE_USER,OP_READ` also adds `access` of each endpoint
+//!   (`allowed`, `denied` or `depends`, see [`Access`]), `authorities` can be repeated as well.
+//!
+//! Note that it discloses the whole authorization model of the application,
+//! so the endpoint should be protected itself (or be reachable internally only).
+//!
+//! # Example
+//! ```
+//! use protect_endpoints_core::registry::introspection;
+//!
+//! let body = introspection::respond(Some("authorities=ROLE_ADMIN"));
+//! assert!(body.starts_with(r#"{"authorities":["ROLE_ADMIN"],"endpoints":["#));
+//! ```
+
+use super::{endpoint_json_fields, endpoints, Access, Endpoint};
+use crate::error::json_string;
+use std::fmt::Write;
+
+/// Content type of the response.
+pub const CONTENT_TYPE: &str = "application/json";
+
+/// Parsed query of the introspection endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    authorities: Option<Vec<String>>,
+}
+
+impl Query {
+    /// Parses the raw (percent-encoded) query string, unknown parameters are ignored.
+    pub fn parse(query: &str) -> Self {
+        let mut authorities: Option<Vec<String>> = None;
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if decode(name) != "authorities" {
+                continue;
+            }
+
+            authorities.get_or_insert_with(Vec::new).extend(
+                decode(value)
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|authority| !authority.is_empty())
+                    .map(str::to_string),
+            );
+        }
+
+        Self { authorities }
+    }
+
+    /// Authorities to simulate the access with, `None` if the access isn't requested.
+    pub fn authorities(&self) -> Option<&[String]> {
+        self.authorities.as_deref()
+    }
+}
+
+/// Body of the response for the raw query string, with all the registered endpoints.
+pub fn respond(query: Option<&str>) -> String {
+    render(&endpoints(), &Query::parse(query.unwrap_or_default()))
+}
+
+/// Renders the endpoints as a JSON object, with `authorities` and `access` of each endpoint if they're requested.
+pub fn render(endpoints: &[&Endpoint], query: &Query) -> String {
+    let mut json = String::from("{");
+    if let Some(authorities) = query.authorities() {
+        let authorities: Vec<_> = authorities.iter().map(|a| json_string(a)).collect();
+        write!(json, r#""authorities":[{}],"#, authorities.join(",")).unwrap();
+    }
+    json.push_str(r#""endpoints":["#);
+
+    for (idx, endpoint) in endpoints.iter().enumerate() {
+        if idx > 0 {
+            json.push(',');
+        }
+        json.push('{');
+        json.push_str(&endpoint_json_fields(endpoint));
+        if let Some(authorities) = query.authorities() {
+            let access: Access = endpoint.access(authorities);
+            write!(json, r#","access":"{access}""#).unwrap();
+        }
+        json.push('}');
+    }
+
+    json.push_str("]}");
+    json
+}
+
+/// Decodes the component of `application/x-www-form-urlencoded` query, invalid escapes are kept as is.
+fn decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = || std::str::from_utf8(bytes.get(idx + 1..idx + 3)?).ok();
+        match bytes[idx] {
+            b'+' => decoded.push(b' '),
+            b'%' => match hex().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    idx += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Location;
+
+    static USERS: Endpoint = Endpoint::new(
+        "app::users",
+        Location::new("src/main.rs", 10, 1),
+        "axum",
+        "String",
+        "ROLE_ADMIN || OP_READ_USERS",
+        false,
+    );
+    static ARTICLE: Endpoint = Endpoint::new(
+        "app::article",
+        Location::new("src/main.rs", 20, 1),
+        "axum",
+        "Role",
+        "Role::Editor",
+        true,
+    );
+
+    #[test]
+    fn test_query() {
+        assert_eq!(None, Query::parse("").authorities());
+        assert_eq!(None, Query::parse("page=2").authorities());
+        assert_eq!(Some(&[][..]), Query::parse("authorities=").authorities());
+        assert_eq!(
+            Some(
+                &[
+                    "ROLE_ADMIN".to_string(),
+                    "Role::Editor".to_string(),
+                    "read users".to_string()
+                ][..]
+            ),
+            Query::parse("authorities=ROLE_ADMIN%2CRole%3A%3AEditor&page=2&authorities=read+users")
+                .authorities()
+        );
+        assert_eq!("100%", decode("100%"));
+        assert_eq!("%zz", decode("%zz"));
+    }
+
+    #[test]
+    fn test_render() {
+        let fields = |endpoint| endpoint_json_fields(endpoint);
+
+        assert_eq!(
+            format!(
+                r#"{{"endpoints":[{{{}}},{{{}}}]}}"#,
+                fields(&USERS),
+                fields(&ARTICLE)
+            ),
+            render(&[&USERS, &ARTICLE], &Query::default())
+        );
+        assert_eq!(
+            format!(
+                r#"{{"authorities":["OP_READ_USERS","Role::Editor"],"endpoints":[{{{},"access":"allowed"}},{{{},"access":"depends"}}]}}"#,
+                fields(&USERS),
+                fields(&ARTICLE)
+            ),
+            render(
+                &[&USERS, &ARTICLE],
+                &Query::parse("authorities=OP_READ_USERS,Role::Editor")
+            )
+        );
+        assert!(
+            render(&[&USERS], &Query::parse("authorities=")).ends_with(r#""access":"denied"}]}"#)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Documentation of protected operations in OpenAPI specs of `utoipa` (with the `utoipa` feature)
+//! and `aide` (with the `aide` feature), so the docs can't drift from the enforcement.
+//!
+//! [`ProtectedOperations`] matches operations of the spec with the registered endpoints by `operation_id`
+//! (the name of the handler by default in `#[utoipa::path]`, set by `TransformOperation::id` in `aide`) and, for each of them:
+//! - adds the requirement to the description, e.g. ``Requires `ROLE_ADMIN`.``;
+//! - declares `401` and `403` responses, unless they're already declared;
+//! - adds the `x-required-authorities` extension with the rule of the requirement;
+//! - requires the configured security scheme, with the required authorities as scopes if enabled.
+//!
+//! An `operation_id` matches the handler by its name or by a longer path suffix (e.g. `users::get`),
+//! operations matching several handlers are left untouched.
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "utoipa")]
+//! # {
+//! use protect_endpoints_core::registry::openapi::ProtectedOperations;
+//! use utoipa::OpenApi;
+//!
+//! const PROTECTED: ProtectedOperations = ProtectedOperations::new().security("oauth2").scopes();
+//!
+//! #[derive(OpenApi)]
+//! #[openapi(modifiers(&PROTECTED))]
+//! struct ApiDoc;
+//!
+//! let spec = ApiDoc::openapi();
+//! # }
+//! ```
+//!
+//! Specs of `aide` are documented after they're finished, see [`ProtectedOperations::modify_aide`].
+
+use super::Endpoint;
+use crate::condition::Condition;
+
+#[cfg(feature = "aide")]
+mod aide;
+#[cfg(feature = "utoipa")]
+mod utoipa;
+
+/// Name of the extension with the rule of the requirement.
+pub const EXTENSION: &str = "x-required-authorities";
+
+const UNAUTHORIZED: &str = "Unauthorized: the authorities of the request can't be extracted";
+const FORBIDDEN: &str = "Forbidden: the request doesn't satisfy the required authorities";
+
+/// Documentation of operations of the registered endpoints, see the [module](self) docs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProtectedOperations {
+    security: Option<&'static str>,
+    scopes: bool,
+}
+
+impl ProtectedOperations {
+    pub const fn new() -> Self {
+        Self {
+            security: None,
+            scopes: false,
+        }
+    }
+
+    /// Requires the security scheme (by its name in the components) for protected operations.
+    pub const fn security(mut self, scheme: &'static str) -> Self {
+        self.security = Some(scheme);
+        self
+    }
+
+    /// Declares required authorities as scopes of the security scheme (typed ones in the source form,
+    /// e.g. `Scope::Read`). Only conditions requiring all of their authorities can be expressed by scopes,
+    /// the scheme is required without scopes for others (e.g. `any`).
+    pub const fn scopes(mut self) -> Self {
+        self.scopes = true;
+        self
+    }
+
+    /// Additions to the operation of the endpoint, independent of the spec model.
+    fn documentation(&self, endpoint: &Endpoint) -> Documentation {
+        let condition = endpoint.condition();
+        let requirement = match (condition.is_empty(), endpoint.is_dynamic()) {
+            (false, false) => format!("Requires `{condition}`."),
+            (false, true) => format!("Requires `{condition}` and a condition on the request."),
+            (true, _) => "Requires a condition on the request.".to_string(),
+        };
+        let security = self.security.map(|scheme| {
+            let scopes = if self.scopes {
+                scopes(&endpoint.requirement())
+            } else {
+                Vec::new()
+            };
+            (scheme, scopes)
+        });
+
+        Documentation {
+            requirement,
+            extension: (!condition.is_empty()).then_some(condition),
+            security,
+        }
+    }
+}
+
+struct Documentation {
+    /// Sentence with the requirement appended to the description.
+    requirement: String,
+    /// Value of the [`EXTENSION`], if the requirement has authorities.
+    extension: Option<&'static str>,
+    /// Required security scheme with its scopes.
+    security: Option<(&'static str, Vec<String>)>,
+}
+
+impl Documentation {
+    fn description(&self, description: Option<String>) -> String {
+        match description {
+            Some(description) if !description.is_empty() => {
+                format!("{description}\n\n{}", self.requirement)
+            }
+            _ => self.requirement.clone(),
+        }
+    }
+}
+
+/// The only endpoint handling the operation.
+fn handler_of<'a>(operation_id: Option<&str>, endpoints: &[&'a Endpoint]) -> Option<&'a Endpoint> {
+    let id = operation_id?;
+    let mut matched = endpoints.iter().filter(|e| handled_by(id, e.handler()));
+    match (matched.next(), matched.next()) {
+        (Some(endpoint), None) => Some(endpoint),
+        _ => None,
+    }
+}
+
+/// Whether the `operation_id` is the name of the handler or a suffix of its path.
+fn handled_by(operation_id: &str, handler: &str) -> bool {
+    handler == operation_id
+        || handler
+            .strip_suffix(operation_id)
+            .is_some_and(|module| module.ends_with("::"))
+}
+
+/// Authorities required by the condition, empty if it doesn't require all of them.
+fn scopes(requirement: &Condition<String>) -> Vec<String> {
+    match requirement {
+        Condition::Authority(authority) => vec![authority.clone()],
+        Condition::All(nested) => nested
+            .iter()
+            .map(|cond| match cond {
+                Condition::Authority(authority) => Some(authority.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::handled_by;
+
+    #[test]
+    fn test_handled_by() {
+        assert!(handled_by("list", "app::users::list"));
+        assert!(handled_by("users::list", "app::users::list"));
+        assert!(handled_by("app::users::list", "app::users::list"));
+        assert!(!handled_by("st", "app::users::list"));
+        assert!(!handled_by("ers::list", "app::users::list"));
+    }
+}
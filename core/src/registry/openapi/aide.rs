@@ -0,0 +1,164 @@
+//! Documentation of `aide` specs.
+
+use super::{handler_of, ProtectedOperations, EXTENSION, FORBIDDEN, UNAUTHORIZED};
+use crate::registry::{endpoints, Endpoint};
+use ::aide::openapi::{OpenApi, Operation, ReferenceOr, Response, SecurityRequirement, StatusCode};
+use ::aide::transform::TransformOpenApi;
+
+impl ProtectedOperations {
+    /// Documents operations of the given endpoints in the `aide` spec, see the [module](super) docs.
+    pub fn document_aide(&self, openapi: &mut OpenApi, endpoints: &[&Endpoint]) {
+        let Some(paths) = openapi.paths.as_mut() else {
+            return;
+        };
+
+        for item in paths.paths.values_mut() {
+            let ReferenceOr::Item(item) = item else {
+                continue;
+            };
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.options,
+                &mut item.head,
+                &mut item.patch,
+                &mut item.trace,
+            ];
+            for operation in operations.into_iter().flatten() {
+                if let Some(endpoint) = handler_of(operation.operation_id.as_deref(), endpoints) {
+                    self.document_aide_operation(operation, endpoint);
+                }
+            }
+        }
+    }
+
+    /// Documents operations of the registered endpoints, a transform of the `aide` spec
+    /// for `ApiRouter::finish_api_with`, which is applied once the routes are merged into the spec:
+    /// `router.finish_api_with(&mut api, |api| PROTECTED.transform_aide(api))`.
+    pub fn transform_aide<'t>(&self, mut api: TransformOpenApi<'t>) -> TransformOpenApi<'t> {
+        self.document_aide(api.inner_mut(), &endpoints());
+        api
+    }
+
+    fn document_aide_operation(&self, operation: &mut Operation, endpoint: &Endpoint) {
+        let documentation = self.documentation(endpoint);
+        operation.description = Some(documentation.description(operation.description.take()));
+
+        let responses = &mut operation
+            .responses
+            .get_or_insert_with(Default::default)
+            .responses;
+        for (status, description) in [(401, UNAUTHORIZED), (403, FORBIDDEN)] {
+            responses
+                .entry(StatusCode::Code(status))
+                .or_insert_with(|| {
+                    ReferenceOr::Item(Response {
+                        description: description.to_string(),
+                        ..Response::default()
+                    })
+                });
+        }
+
+        if let Some(condition) = documentation.extension {
+            operation
+                .extensions
+                .insert(EXTENSION.to_string(), condition.into());
+        }
+
+        if let Some((scheme, scopes)) = documentation.security {
+            let requirement: SecurityRequirement =
+                [(scheme.to_string(), scopes)].into_iter().collect();
+            operation.security.push(requirement);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Location;
+    use ::aide::openapi::{PathItem, Paths};
+
+    static USERS: Endpoint = Endpoint::new(
+        "app::users::list",
+        Location::new("src/users.rs", 10, 1),
+        "axum",
+        "String",
+        "ROLE_ADMIN && OP_READ_USERS",
+        false,
+    );
+    static ARTICLES: Endpoint = Endpoint::new(
+        "app::articles::list",
+        Location::new("src/articles.rs", 20, 1),
+        "axum",
+        "String",
+        "ROLE_ADMIN || ROLE_EDITOR",
+        true,
+    );
+
+    fn spec(operations: Vec<(&str, &str)>) -> OpenApi {
+        let paths = operations
+            .into_iter()
+            .map(|(path, id)| {
+                let operation = Operation {
+                    operation_id: Some(id.to_string()),
+                    description: Some("Lists them.".to_string()),
+                    ..Operation::default()
+                };
+                let item = PathItem {
+                    get: Some(operation),
+                    ..PathItem::default()
+                };
+                (path.to_string(), ReferenceOr::Item(item))
+            })
+            .collect();
+
+        OpenApi {
+            paths: Some(Paths {
+                paths,
+                ..Paths::default()
+            }),
+            ..OpenApi::default()
+        }
+    }
+
+    fn documented(spec: &OpenApi, path: &str) -> serde_json::Value {
+        serde_json::to_value(&spec.paths.as_ref().unwrap().paths[path]).unwrap()["get"].clone()
+    }
+
+    #[test]
+    fn test_document() {
+        let mut spec = spec(vec![
+            ("/users", "users::list"),
+            ("/articles", "articles::list"),
+            ("/public", "public"),
+        ]);
+        ProtectedOperations::new()
+            .security("oauth2")
+            .scopes()
+            .document_aide(&mut spec, &[&USERS, &ARTICLES]);
+
+        let users = documented(&spec, "/users");
+        assert_eq!(
+            "Lists them.\n\nRequires `ROLE_ADMIN && OP_READ_USERS`.",
+            users["description"]
+        );
+        assert_eq!("ROLE_ADMIN && OP_READ_USERS", users[EXTENSION]);
+        assert!(users["responses"]["401"].is_object());
+        assert!(users["responses"]["403"].is_object());
+        assert_eq!(
+            serde_json::json!([{ "oauth2": ["ROLE_ADMIN", "OP_READ_USERS"] }]),
+            users["security"]
+        );
+
+        let articles = documented(&spec, "/articles");
+        assert_eq!(serde_json::json!([{ "oauth2": [] }]), articles["security"]);
+
+        let public = documented(&spec, "/public");
+        assert_eq!("Lists them.", public["description"]);
+        assert!(public.get("responses").is_none());
+        assert!(public.get(EXTENSION).is_none());
+    }
+}
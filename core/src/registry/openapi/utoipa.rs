@@ -0,0 +1,168 @@
+//! Documentation of `utoipa` specs.
+
+use super::{handler_of, ProtectedOperations, FORBIDDEN, UNAUTHORIZED};
+use crate::registry::{endpoints, Endpoint};
+use ::utoipa::openapi::extensions::Extensions;
+use ::utoipa::openapi::path::Operation;
+use ::utoipa::openapi::security::SecurityRequirement;
+use ::utoipa::openapi::{OpenApi, RefOr, Response};
+use ::utoipa::Modify;
+
+impl ProtectedOperations {
+    /// Documents operations of the given endpoints, see the [module](super) docs.
+    pub fn document(&self, openapi: &mut OpenApi, endpoints: &[&Endpoint]) {
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.options,
+                &mut item.head,
+                &mut item.patch,
+                &mut item.trace,
+            ];
+            for operation in operations.into_iter().flatten() {
+                if let Some(endpoint) = handler_of(operation.operation_id.as_deref(), endpoints) {
+                    self.document_operation(operation, endpoint);
+                }
+            }
+        }
+    }
+
+    fn document_operation(&self, operation: &mut Operation, endpoint: &Endpoint) {
+        let documentation = self.documentation(endpoint);
+        operation.description = Some(documentation.description(operation.description.take()));
+
+        let responses = &mut operation.responses.responses;
+        responses
+            .entry("401".to_string())
+            .or_insert_with(|| RefOr::T(Response::new(UNAUTHORIZED)));
+        responses
+            .entry("403".to_string())
+            .or_insert_with(|| RefOr::T(Response::new(FORBIDDEN)));
+
+        if let Some(condition) = documentation.extension {
+            operation
+                .extensions
+                .get_or_insert_with(Extensions::default)
+                .insert(super::EXTENSION.to_string(), condition.into());
+        }
+
+        if let Some((scheme, scopes)) = documentation.security {
+            operation
+                .security
+                .get_or_insert_with(Vec::new)
+                .push(SecurityRequirement::new(scheme, scopes));
+        }
+    }
+}
+
+impl Modify for ProtectedOperations {
+    fn modify(&self, openapi: &mut OpenApi) {
+        self.document(openapi, &endpoints());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::openapi::EXTENSION;
+    use crate::registry::Location;
+    use ::utoipa::openapi::path::{HttpMethod, PathItem};
+    use ::utoipa::openapi::{OpenApiBuilder, PathsBuilder};
+
+    static USERS: Endpoint = Endpoint::new(
+        "app::users::list",
+        Location::new("src/users.rs", 10, 1),
+        "axum",
+        "String",
+        "ROLE_ADMIN && OP_READ_USERS",
+        false,
+    );
+    static ARTICLES: Endpoint = Endpoint::new(
+        "app::articles::list",
+        Location::new("src/articles.rs", 20, 1),
+        "axum",
+        "String",
+        "ROLE_ADMIN || ROLE_EDITOR",
+        true,
+    );
+
+    fn operation(id: &str) -> Operation {
+        let mut operation = Operation::new();
+        operation.operation_id = Some(id.to_string());
+        operation
+    }
+
+    fn spec(operations: Vec<(&str, Operation)>) -> OpenApi {
+        let mut paths = PathsBuilder::new();
+        for (path, operation) in operations {
+            paths = paths.path(path, PathItem::new(HttpMethod::Get, operation));
+        }
+        OpenApiBuilder::new().paths(paths).build()
+    }
+
+    fn documented(spec: &OpenApi, path: &str) -> serde_json::Value {
+        serde_json::to_value(&spec.paths.paths[path].get).unwrap()
+    }
+
+    #[test]
+    fn test_document() {
+        let mut spec = spec(vec![
+            ("/users", operation("users::list")),
+            ("/articles", operation("articles::list")),
+            ("/public", operation("public")),
+        ]);
+        ProtectedOperations::new().document(&mut spec, &[&USERS, &ARTICLES]);
+
+        let users = documented(&spec, "/users");
+        assert_eq!(
+            "Requires `ROLE_ADMIN && OP_READ_USERS`.",
+            users["description"]
+        );
+        assert_eq!("ROLE_ADMIN && OP_READ_USERS", users[EXTENSION]);
+        assert!(users["responses"]["401"].is_object());
+        assert!(users["responses"]["403"].is_object());
+        assert!(users.get("security").is_none());
+
+        let articles = documented(&spec, "/articles");
+        assert_eq!(
+            "Requires `ROLE_ADMIN || ROLE_EDITOR` and a condition on the request.",
+            articles["description"]
+        );
+
+        let public = documented(&spec, "/public");
+        assert!(public.get("description").is_none());
+        assert!(public["responses"].get("403").is_none());
+    }
+
+    #[test]
+    fn test_ambiguous_operation() {
+        let mut spec = spec(vec![("/list", operation("list"))]);
+        ProtectedOperations::new().document(&mut spec, &[&USERS, &ARTICLES]);
+
+        assert!(documented(&spec, "/list").get(EXTENSION).is_none());
+    }
+
+    #[test]
+    fn test_security() {
+        let mut spec = spec(vec![
+            ("/users", operation("users::list")),
+            ("/articles", operation("articles::list")),
+        ]);
+        ProtectedOperations::new()
+            .security("oauth2")
+            .scopes()
+            .document(&mut spec, &[&USERS, &ARTICLES]);
+
+        assert_eq!(
+            serde_json::json!([{ "oauth2": ["ROLE_ADMIN", "OP_READ_USERS"] }]),
+            documented(&spec, "/users")["security"]
+        );
+        assert_eq!(
+            serde_json::json!([{ "oauth2": [] }]),
+            documented(&spec, "/articles")["security"]
+        );
+    }
+}
@@ -0,0 +1,581 @@
+//! Central authorization table: `(method, path pattern) -> access`, one reviewable place
+//! for the authorization of the whole app instead of attributes scattered across handlers.
+//!
+//! Rules are evaluated in order, the first matching one decides; requests matching no rule
+//! get the default access ([`Access::DenyAll`] unless replaced via [`AuthorizationTable::otherwise`]).
+//!
+//! Path patterns consist of segments: literals, `*` or `{name}` (exactly one segment) and `**` (any number of segments),
+//! e.g. `/admin/**`, `/users/{id}/orders`. Trailing slashes are ignored.
+//!
+//! Paths are matched the way routers see them: segments are percent-decoded (`/%61dmin` is `/admin`),
+//! empty and `.` segments are skipped and `..` removes the previous segment, so `//admin` and `/./admin`
+//! can't bypass rules for `/admin`.
+//! Methods are comma-separated (`GET,HEAD`), `*` matches any method.
+//!
+//! Requests without attached authorities or with the anonymous principal
+//! (see [`set_anonymous_authorities`](crate::authorities::set_anonymous_authorities))
+//! pass only [`Access::PermitAll`] rules, otherwise they are rejected as unauthorized.
+//!
+//! The table is applied by middleware of the framework crates, e.g. `tower::table::TableLayer` (with `tower` feature).
+
+use crate::authorities::AuthoritiesCheck;
+use crate::condition::{Condition, Explanation, ParseError};
+use crate::error::{unauthorized_challenge, AccessDenied};
+use http::header::WWW_AUTHENTICATE;
+use http::{HeaderValue, Response, StatusCode};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+const ANY_METHOD: &str = "*";
+
+/// Access granted by a rule of the [`AuthorizationTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access<T = String> {
+    /// Everyone, including users without authorities.
+    PermitAll,
+    /// Nobody.
+    DenyAll,
+    /// Users with any authorities.
+    Authenticated,
+    /// Users satisfying the condition.
+    Condition(Condition<T>),
+}
+
+impl<T> From<Condition<T>> for Access<T> {
+    fn from(condition: Condition<T>) -> Self {
+        Access::Condition(condition)
+    }
+}
+
+/// Parses `permit_all`, `deny_all`, `authenticated` or a rule (see [`Condition::parse`]).
+impl FromStr for Access {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Ok(match source.trim() {
+            "permit_all" => Access::PermitAll,
+            "deny_all" => Access::DenyAll,
+            "authenticated" => Access::Authenticated,
+            rule => Access::Condition(rule.parse()?),
+        })
+    }
+}
+
+impl<T: Display> Display for Access<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::PermitAll => f.write_str("permit_all"),
+            Access::DenyAll => f.write_str("deny_all"),
+            Access::Authenticated => f.write_str("authenticated"),
+            Access::Condition(condition) => condition.fmt(f),
+        }
+    }
+}
+
+/// Error of the rule definition: invalid methods or path pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableError {
+    message: String,
+}
+
+impl TableError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for TableError {}
+
+/// Path pattern of a rule, see the [module](self) docs for the syntax.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::table::PathPattern;
+///
+/// let pattern: PathPattern = "/users/{id}/**".parse().unwrap();
+/// assert!(pattern.matches("/users/1"));
+/// assert!(pattern.matches("/users/1/orders/2/"));
+/// assert!(!pattern.matches("/users"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathPattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    Literal(String),
+    /// `*` or `{name}`
+    Single,
+    /// `**`
+    Rest,
+}
+
+impl PathPattern {
+    pub fn parse(source: &str) -> Result<Self, TableError> {
+        let source = source.trim();
+        if !source.starts_with('/') {
+            return Err(TableError::new(format!(
+                "path pattern `{source}` must start with `/`"
+            )));
+        }
+
+        let segments = source
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment {
+                "**" => Ok(Segment::Rest),
+                "*" => Ok(Segment::Single),
+                param if param.starts_with('{') && param.ends_with('}') && param.len() > 2 => {
+                    Ok(Segment::Single)
+                }
+                literal if literal.contains(['*', '{', '}']) => Err(TableError::new(format!(
+                    "invalid segment `{literal}` of `{source}`, wildcards and parameters must be whole segments"
+                ))),
+                literal => Ok(Segment::Literal(literal.to_string())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(PathPattern {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// Whether the path (as is in the request, possibly percent-encoded) matches the pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = normalize_path(path);
+        let path: Vec<&str> = path.iter().map(|segment| segment.as_ref()).collect();
+        matches_segments(&self.segments, &path)
+    }
+}
+
+/// Decoded segments of the path without empty and dot segments.
+fn normalize_path(path: &str) -> Vec<Cow<'_, str>> {
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match percent_decode(segment) {
+            segment if segment.is_empty() || segment == "." => {}
+            segment if segment == ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments
+}
+
+/// Decodes `%XX` sequences, malformed ones are kept as is.
+fn percent_decode(segment: &str) -> Cow<'_, str> {
+    if !segment.contains('%') {
+        return Cow::Borrowed(segment);
+    }
+
+    let hex = |byte: u8| char::from(byte).to_digit(16).map(|digit| digit as u8);
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = match bytes.get(idx..idx + 3) {
+            Some([b'%', high, low]) => hex(*high).zip(hex(*low)),
+            _ => None,
+        };
+        match escaped {
+            Some((high, low)) => {
+                decoded.push(high << 4 | low);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+fn matches_segments(pattern: &[Segment], path: &[&str]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, _) => path.is_empty(),
+        (Some((Segment::Rest, rest)), _) => {
+            (0..=path.len()).any(|skipped| matches_segments(rest, &path[skipped..]))
+        }
+        (Some(_), None) => false,
+        (Some((Segment::Single, rest)), Some((_, path))) => matches_segments(rest, path),
+        (Some((Segment::Literal(literal), rest)), Some((segment, path))) => {
+            literal == segment && matches_segments(rest, path)
+        }
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = TableError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        PathPattern::parse(source)
+    }
+}
+
+impl Display for PathPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Rule of the [`AuthorizationTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule<T = String> {
+    /// Upper-case methods, empty for any method.
+    methods: Vec<String>,
+    pattern: PathPattern,
+    access: Access<T>,
+}
+
+impl<T> Rule<T> {
+    pub fn new(
+        methods: &str,
+        pattern: &str,
+        access: impl Into<Access<T>>,
+    ) -> Result<Self, TableError> {
+        Ok(Rule {
+            methods: parse_methods(methods)?,
+            pattern: pattern.parse()?,
+            access: access.into(),
+        })
+    }
+
+    /// Methods of the rule, empty for any method.
+    pub fn methods(&self) -> &[String] {
+        &self.methods
+    }
+
+    pub fn pattern(&self) -> &PathPattern {
+        &self.pattern
+    }
+
+    pub fn access(&self) -> &Access<T> {
+        &self.access
+    }
+
+    pub fn matches(&self, method: &str, path: &str) -> bool {
+        (self.methods.is_empty() || self.methods.iter().any(|allowed| allowed == method))
+            && self.pattern.matches(path)
+    }
+}
+
+pub(crate) fn parse_methods(methods: &str) -> Result<Vec<String>, TableError> {
+    if methods.trim() == ANY_METHOD {
+        return Ok(Vec::new());
+    }
+
+    methods
+        .split(',')
+        .map(|method| {
+            let method = method.trim();
+            if !method.is_empty() && method.chars().all(|c| c.is_ascii_alphabetic()) {
+                Ok(method.to_ascii_uppercase())
+            } else {
+                Err(TableError::new(format!(
+                    "invalid method `{method}`, expected e.g. `GET,HEAD` or `*`"
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Ordered rules with the default access, see the [module](self) docs.
+///
+/// # Example
+/// ```
+/// use protect_endpoints_core::authorities::AuthDetails;
+/// use protect_endpoints_core::table::{Access, AuthorizationTable};
+///
+/// let table = AuthorizationTable::new()
+///     .route("GET", "/health", Access::PermitAll)
+///     .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+///     .route("POST,PUT", "/articles/{id}", "ROLE_EDITOR || ROLE_ADMIN".parse::<Access>().unwrap())
+///     .otherwise(Access::Authenticated);
+///
+/// let editor = AuthDetails::new(["ROLE_EDITOR".to_string()]);
+/// assert!(table.authorize("PUT", "/articles/1", Some(&editor)).is_ok());
+/// assert!(table.authorize("GET", "/admin/users", Some(&editor)).is_err());
+/// assert!(table.authorize("GET", "/health", None::<&AuthDetails>).is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationTable<T = String> {
+    rules: Vec<Rule<T>>,
+    default: Access<T>,
+}
+
+impl<T> Default for AuthorizationTable<T> {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: Access::DenyAll,
+        }
+    }
+}
+
+impl<T> AuthorizationTable<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the rule for comma-separated methods (or `*`) and the path pattern.
+    ///
+    /// # Panics
+    /// If methods or the pattern are invalid, see [`AuthorizationTable::try_route`].
+    pub fn route(self, methods: &str, pattern: &str, access: impl Into<Access<T>>) -> Self {
+        self.try_route(methods, pattern, access)
+            .unwrap_or_else(|err| panic!("invalid rule `{methods} {pattern}`: {err}"))
+    }
+
+    /// Appends the rule, failing on invalid methods or the pattern.
+    pub fn try_route(
+        mut self,
+        methods: &str,
+        pattern: &str,
+        access: impl Into<Access<T>>,
+    ) -> Result<Self, TableError> {
+        self.rules.push(Rule::new(methods, pattern, access)?);
+        Ok(self)
+    }
+
+    /// Replaces the access for requests matching no rule.
+    pub fn otherwise(mut self, access: impl Into<Access<T>>) -> Self {
+        self.default = access.into();
+        self
+    }
+
+    pub fn rules(&self) -> &[Rule<T>] {
+        &self.rules
+    }
+
+    pub fn default_access(&self) -> &Access<T> {
+        &self.default
+    }
+
+    /// Access of the first rule matching the request or the default one.
+    pub fn access_for(&self, method: &str, path: &str) -> &Access<T> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(method, path))
+            .map_or(&self.default, Rule::access)
+    }
+
+    /// Checks the request against the table, `details` are `None` for users without authorities.
+    #[allow(clippy::result_large_err)]
+    pub fn authorize<D>(
+        &self,
+        method: &str,
+        path: &str,
+        details: Option<&D>,
+    ) -> Result<(), Rejection>
+    where
+        T: Eq + Hash + Clone + Display,
+        D: for<'a> AuthoritiesCheck<&'a T>,
+    {
+        let access = self.access_for(method, path);
+        let details = match (access, details) {
+            (Access::PermitAll, _) => return Ok(()),
+            (_, None) => return Err(Rejection::Unauthorized),
+            (_, Some(details)) => details,
+        };
+
+        let denied = match access {
+            Access::Authenticated => return Ok(()),
+            Access::Condition(condition) => {
+                let explanation = condition.explain(details);
+                if explanation.passed {
+                    return Ok(());
+                }
+                AccessDenied::new(
+                    condition.clone().map(|authority| authority.to_string()),
+                    explanation.map(&mut |authority| authority.to_string()),
+                )
+            }
+            Access::PermitAll | Access::DenyAll => AccessDenied::new(
+                Condition::All(Vec::new()),
+                Explanation::expr(Access::<T>::DenyAll.to_string(), false),
+            ),
+        };
+
+        denied.log();
+        Err(Rejection::Forbidden(denied))
+    }
+}
+
+/// Table shared by middleware and replaceable at runtime, e.g. by a reloaded policy file (`policy-*` features).
+///
+/// Requests in flight keep the table they started with.
+pub struct SharedTable<T = String> {
+    current: Arc<RwLock<Arc<AuthorizationTable<T>>>>,
+}
+
+impl<T> SharedTable<T> {
+    pub fn new(table: AuthorizationTable<T>) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(table))),
+        }
+    }
+
+    /// The current table.
+    pub fn load(&self) -> Arc<AuthorizationTable<T>> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces the table for subsequent requests.
+    pub fn replace(&self, table: AuthorizationTable<T>) {
+        *self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(table);
+    }
+}
+
+impl<T> Clone for SharedTable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<T> From<AuthorizationTable<T>> for SharedTable<T> {
+    fn from(table: AuthorizationTable<T>) -> Self {
+        SharedTable::new(table)
+    }
+}
+
+/// Rejection of the request by the [`AuthorizationTable`].
+#[derive(Debug, Clone)]
+pub enum Rejection {
+    /// The user has no authorities, but the rule requires them.
+    Unauthorized,
+    /// The user doesn't satisfy the rule.
+    Forbidden(AccessDenied),
+}
+
+impl Rejection {
+    /// Renders `401` with `WWW-Authenticate` challenge or the denial (see [`AccessDenied::to_response`]).
+    pub fn to_response(&self) -> Response<String> {
+        match self {
+            Rejection::Unauthorized => {
+                let mut response = Response::new(String::new());
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                if let Some(challenge) = unauthorized_challenge()
+                    .and_then(|challenge| HeaderValue::try_from(challenge).ok())
+                {
+                    response.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+                }
+                response
+            }
+            Rejection::Forbidden(denied) => denied.to_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorities::AuthDetails;
+
+    #[test]
+    fn test_path_patterns() {
+        let pattern = |source: &str| PathPattern::parse(source).unwrap();
+
+        assert!(pattern("/").matches("/"));
+        assert!(pattern("/health").matches("/health/"));
+        assert!(!pattern("/health").matches("/health/live"));
+        assert!(pattern("/users/*").matches("/users/1"));
+        assert!(!pattern("/users/{id}").matches("/users"));
+        assert!(pattern("/**").matches("/"));
+        assert!(pattern("/**/edit").matches("/articles/1/edit"));
+        assert!(!pattern("/**/edit").matches("/articles/1"));
+        assert!(pattern("/admin/**").matches("/admin"));
+
+        assert!(pattern("/admin/users").matches("/%61dmin/users"));
+        assert!(!pattern("/admin/users").matches("/admin%2Fusers"));
+        assert!(pattern("/admin/users").matches("//admin//users"));
+        assert!(pattern("/admin/users").matches("/./admin/./users/."));
+        assert!(pattern("/admin/users").matches("/public/../admin/%2e/users"));
+        assert!(pattern("/admin/**").matches("/../admin"));
+        assert!(pattern("/100%/%zz").matches("/100%/%zz"));
+        assert!(pattern("/café").matches("/caf%C3%A9"));
+
+        assert!(PathPattern::parse("admin").is_err());
+        assert!(PathPattern::parse("/admin*").is_err());
+        assert!(PathPattern::parse("/users/{id").is_err());
+    }
+
+    #[test]
+    fn test_rule_order() {
+        let table = AuthorizationTable::new()
+            .route("GET", "/articles/**", Access::PermitAll)
+            .route(
+                "*",
+                "/articles/**",
+                Access::Condition(Condition::authority("EDITOR".to_string())),
+            )
+            .otherwise(Access::Authenticated);
+        let user = AuthDetails::new(["USER".to_string()]);
+
+        assert!(table
+            .authorize("GET", "/articles/1", None::<&AuthDetails>)
+            .is_ok());
+        assert!(matches!(
+            table.authorize("POST", "/articles", None::<&AuthDetails>),
+            Err(Rejection::Unauthorized)
+        ));
+        assert!(matches!(
+            table.authorize("POST", "/articles", Some(&user)),
+            Err(Rejection::Forbidden(_))
+        ));
+        assert!(table.authorize("POST", "/profile", Some(&user)).is_ok());
+        assert!(matches!(
+            AuthorizationTable::<String>::new().authorize("GET", "/", Some(&user)),
+            Err(Rejection::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let table = AuthorizationTable::<String>::new();
+
+        assert!(table
+            .clone()
+            .try_route("GET POST", "/", Access::PermitAll)
+            .is_err());
+        assert!(table.clone().try_route("", "/", Access::PermitAll).is_err());
+        assert!(table.try_route("get,head", "/", Access::PermitAll).is_ok());
+    }
+
+    #[test]
+    fn test_access_parsing() {
+        for source in [
+            "permit_all",
+            "deny_all",
+            "authenticated",
+            "ADMIN || EDITOR && !SUSPENDED",
+        ] {
+            assert_eq!(source, source.parse::<Access>().unwrap().to_string());
+        }
+        assert!("ADMIN ||".parse::<Access>().is_err());
+    }
+}
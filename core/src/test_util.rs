@@ -0,0 +1,243 @@
+//! Utilities for tests of protected endpoints, without wiring a fake extractor into every test.
+//!
+//! Authorities of a test request are passed via the [`AUTHORITIES_HEADER`]
+//! (see `with_authorities` helpers in `test_util` modules of the integrations)
+//! and extracted by [`MockExtractor`], which also records the calls and returns scripted results or errors.
+//!
+//! Never enable the `test-util` feature outside of `dev-dependencies`:
+//! anyone can send the header.
+//!
+//! # Example
+//! ```
+//! use protect_endpoints_core::authorities::extractor::AuthoritiesExtractor;
+//! use protect_endpoints_core::test_util::{header_value, MockExtractor, AUTHORITIES_HEADER};
+//! use std::collections::HashSet;
+//!
+//! # tokio_test();
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn tokio_test() {
+//! let extractor = MockExtractor::<&str>::new();
+//! extractor.then_fail("token expired");
+//!
+//! let mut req = http::Request::get("/users")
+//!     .header(AUTHORITIES_HEADER, header_value(["ROLE_ADMIN"]))
+//!     .body(())
+//!     .unwrap();
+//!
+//! assert_eq!(Err("token expired"), extractor.extract(&mut req).await);
+//! assert_eq!(
+//!     Ok(HashSet::from(["ROLE_ADMIN".to_string()])),
+//!     extractor.extract(&mut req).await
+//! );
+//! assert_eq!(2, extractor.calls().len());
+//! # }
+//! ```
+
+use crate::authorities::extractor::AuthoritiesExtractor;
+use std::collections::{HashSet, VecDeque};
+use std::future::{ready, Ready};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Header with comma-separated authorities of a test request.
+pub const AUTHORITIES_HEADER: &str = "x-test-authorities";
+
+/// Value of the [`AUTHORITIES_HEADER`] for the authorities.
+pub fn header_value<I, S>(authorities: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    authorities
+        .into_iter()
+        .map(|authority| authority.as_ref().to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Authorities of the [`AUTHORITIES_HEADER`] value, empty items are skipped.
+pub fn parse_header_value(value: &str) -> HashSet<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|authority| !authority.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Request passed to the [`MockExtractor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    method: String,
+    path: String,
+    authorities: Option<HashSet<String>>,
+}
+
+impl Call {
+    /// Call for the request with an optional value of the [`AUTHORITIES_HEADER`].
+    pub fn new(method: impl Into<String>, path: impl Into<String>, header: Option<&str>) -> Self {
+        Self {
+            method: method.into(),
+            path: path.into(),
+            authorities: header.map(parse_header_value),
+        }
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Authorities of the [`AUTHORITIES_HEADER`], `None` if the request doesn't have it.
+    pub fn authorities(&self) -> Option<&HashSet<String>> {
+        self.authorities.as_ref()
+    }
+}
+
+/// Extractor recording the calls, clones share the script and the calls
+/// (so the extractor can be inspected after it's moved into a middleware).
+///
+/// Each call takes the next scripted result, if any.
+/// Otherwise, the authorities of the [`AUTHORITIES_HEADER`] are returned (no authorities without the header).
+///
+/// The integrations implement their extractor traits for it (see `MockExtractor` of their `test_util` modules).
+pub struct MockExtractor<Error> {
+    state: Arc<Mutex<State<Error>>>,
+}
+
+struct State<Error> {
+    script: VecDeque<Result<HashSet<String>, Error>>,
+    calls: Vec<Call>,
+}
+
+impl<Error> MockExtractor<Error> {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                script: VecDeque::new(),
+                calls: Vec::new(),
+            })),
+        }
+    }
+
+    /// Scripts the authorities of the next unscripted call.
+    pub fn then_return<I, S>(&self, authorities: I) -> &Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let authorities = authorities.into_iter().map(Into::into).collect();
+        self.state().script.push_back(Ok(authorities));
+        self
+    }
+
+    /// Scripts the error of the next unscripted call.
+    pub fn then_fail(&self, error: Error) -> &Self {
+        self.state().script.push_back(Err(error));
+        self
+    }
+
+    /// Recorded calls in the order of extraction.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    /// Records the call and returns the result for it.
+    pub fn respond(&self, call: Call) -> Result<HashSet<String>, Error> {
+        let mut state = self.state();
+        let result = state
+            .script
+            .pop_front()
+            .unwrap_or_else(|| Ok(call.authorities().cloned().unwrap_or_default()));
+        state.calls.push(call);
+        result
+    }
+
+    // a failed assertion shouldn't break other tests sharing the extractor
+    fn state(&self) -> MutexGuard<'_, State<Error>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Error> Default for MockExtractor<Error> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Error> Clone for MockExtractor<Error> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<'a, Body, Error> AuthoritiesExtractor<'a, http::Request<Body>, String, Error>
+    for MockExtractor<Error>
+where
+    Error: Send,
+{
+    type Future = Ready<Result<HashSet<String>, Error>>;
+
+    fn extract(&self, req: &'a mut http::Request<Body>) -> Self::Future {
+        let header = req
+            .headers()
+            .get(AUTHORITIES_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        ready(self.respond(Call::new(req.method().as_str(), req.uri().path(), header)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_value() {
+        assert_eq!(
+            "ROLE_ADMIN,OP_READ",
+            header_value(["ROLE_ADMIN", "OP_READ"])
+        );
+        assert_eq!(
+            HashSet::from(["ROLE_ADMIN".to_string(), "OP_READ".to_string()]),
+            parse_header_value("ROLE_ADMIN, OP_READ,,")
+        );
+        assert!(parse_header_value("").is_empty());
+    }
+
+    #[test]
+    fn test_mock_extractor() {
+        let extractor = MockExtractor::new();
+        extractor.then_return(["ROLE_ADMIN"]).then_fail(401);
+
+        let shared = extractor.clone();
+        let call = |path, header| shared.respond(Call::new("GET", path, header));
+
+        assert_eq!(
+            Ok(HashSet::from(["ROLE_ADMIN".to_string()])),
+            call("/a", Some("OP_READ"))
+        );
+        assert_eq!(Err(401), call("/b", Some("OP_READ")));
+        assert_eq!(
+            Ok(HashSet::from(["OP_READ".to_string()])),
+            call("/c", Some("OP_READ"))
+        );
+        assert_eq!(Ok(HashSet::new()), call("/d", None));
+
+        let calls = extractor.calls();
+        assert_eq!(
+            vec!["/a", "/b", "/c", "/d"],
+            calls.iter().map(Call::path).collect::<Vec<_>>()
+        );
+        assert_eq!("GET", calls[0].method());
+        assert_eq!(
+            Some(&HashSet::from(["OP_READ".to_string()])),
+            calls[0].authorities()
+        );
+        assert_eq!(None, calls[3].authorities());
+    }
+}
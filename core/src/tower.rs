@@ -1 +1,5 @@
+#[cfg(feature = "deny-by-default")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deny-by-default")))]
+pub mod deny_by_default;
 pub mod middleware;
+pub mod table;
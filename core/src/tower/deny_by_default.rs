@@ -0,0 +1,63 @@
+use crate::deny_by_default::Routes;
+use futures_util::future::{self, Either, Ready};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Tower compatible middleware rejecting requests which aren't routed to handlers annotated
+/// with `#[protect(..)]` or `#[public]`, see [`deny_by_default`](crate::deny_by_default).
+///
+/// Requests are checked against the [`Routes`] before they're passed to the inner service.
+#[derive(Clone)]
+pub struct DenyByDefaultLayer {
+    routes: Arc<Routes>,
+}
+
+impl DenyByDefaultLayer {
+    pub fn new(routes: Routes) -> Self {
+        Self {
+            routes: Arc::new(routes),
+        }
+    }
+}
+
+impl<S> Layer<S> for DenyByDefaultLayer {
+    type Service = DenyByDefaultService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DenyByDefaultService {
+            inner,
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DenyByDefaultService<S> {
+    inner: S,
+    routes: Arc<Routes>,
+}
+
+impl<S, ReqBody, RespBody> Service<http::Request<ReqBody>> for DenyByDefaultService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<RespBody>>,
+    RespBody: From<String>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        match self.routes.check(req.method().as_str(), req.uri().path()) {
+            Ok(()) => Either::Left(self.inner.call(req)),
+            Err(denied) => {
+                let response = denied.to_response().map(RespBody::from);
+                Either::Right(future::ready(Ok(response)))
+            }
+        }
+    }
+}
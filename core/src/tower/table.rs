@@ -0,0 +1,90 @@
+use crate::authorities::AuthDetails;
+use crate::table::SharedTable;
+use futures_util::future::{self, Either, Ready};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Tower compatible middleware checking requests against the [`AuthorizationTable`](crate::table::AuthorizationTable).
+///
+/// Authorities are taken from the request extensions, so the layer must be applied inside
+/// of the [`GrantsLayer`](super::middleware::GrantsLayer) (i.e. added before it).
+pub struct TableLayer<T = String> {
+    table: SharedTable<T>,
+}
+
+impl<T> TableLayer<T> {
+    pub fn new(table: impl Into<SharedTable<T>>) -> Self {
+        Self {
+            table: table.into(),
+        }
+    }
+}
+
+impl<T> Clone for TableLayer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<S, T> Layer<S> for TableLayer<T> {
+    type Service = TableService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TableService {
+            inner,
+            table: self.table.clone(),
+        }
+    }
+}
+
+pub struct TableService<S, T = String> {
+    inner: S,
+    table: SharedTable<T>,
+}
+
+impl<S: Clone, T> Clone for TableService<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<S, T, ReqBody, RespBody> Service<http::Request<ReqBody>> for TableService<S, T>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<RespBody>>,
+    T: Eq + Hash + Clone + Display + Send + Sync + 'static,
+    RespBody: From<String>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let details = req
+            .extensions()
+            .get::<AuthDetails<T>>()
+            .filter(|details| !details.is_anonymous());
+
+        match self
+            .table
+            .load()
+            .authorize(req.method().as_str(), req.uri().path(), details)
+        {
+            Ok(()) => Either::Left(self.inner.call(req)),
+            Err(rejection) => {
+                let response = rejection.to_response().map(RespBody::from);
+                Either::Right(future::ready(Ok(response)))
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::error::AccessError;
+use poem::{FromRequest, Request, RequestBody};
+use protect_endpoints_core::authorities::Principal as PrincipalCore;
+use std::ops::Deref;
+
+/// Subject identifier and typed claims of the user, see [`AttachPrincipal`].
+///
+/// Requests without the attached principal are rejected as unauthorized.
+pub struct Principal<C = ()>(PrincipalCore<C>);
+
+impl<'a, C: Send + Sync + 'static> FromRequest<'a> for Principal<C> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+        req.extensions()
+            .get::<PrincipalCore<C>>()
+            .cloned()
+            .map(Principal)
+            .ok_or(AccessError::UnauthorizedRequest)
+            .map_err(Into::into)
+    }
+}
+
+impl<C> Deref for Principal<C> {
+    type Target = PrincipalCore<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> Clone for Principal<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Allows you to transfer [`Principal`] to [`poem-grants`], e.g. from the authorities extractor.
+///
+/// # Example
+///
+/// ```
+/// use poem_grants::authorities::AttachPrincipal;
+///
+/// struct Claims { tenant: String }
+///
+/// fn attach(req: &mut poem::Request, subject: String, tenant: String) {
+///     req.attach_principal(subject, Claims { tenant });
+/// }
+/// ```
+///
+/// [`poem-grants`]: crate
+pub trait AttachPrincipal<C> {
+    fn attach_principal(&mut self, subject: impl Into<String>, claims: C);
+}
+
+impl<C: Send + Sync + 'static> AttachPrincipal<C> for Request {
+    fn attach_principal(&mut self, subject: impl Into<String>, claims: C) {
+        self.extensions_mut()
+            .insert(PrincipalCore::new(subject, claims));
+    }
+}
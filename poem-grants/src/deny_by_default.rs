@@ -0,0 +1,72 @@
+//! Deny-by-default mode: requests which aren't routed to handlers annotated with [`protect`](crate::protect)
+//! or [`public`](crate::public) are rejected with `403`, see [`protect_endpoints_core::deny_by_default`].
+//!
+//! The mode is enabled by [`DenyByDefault`] middleware configured with [`Routes`] of the app,
+//! methods of `open_api` are passed as `Api::method`.
+//! Requests are checked before they're routed, so unannotated handlers never run.
+//!
+//! # Example
+//! ```
+//! use poem::{get, handler, EndpointExt, Route};
+//! use poem_grants::deny_by_default::{DenyByDefault, Routes};
+//!
+//! #[poem_grants::public]
+//! #[handler]
+//! async fn index() -> &'static str {
+//!     "Hi!"
+//! }
+//!
+//! let routes = Routes::new().route("GET", "/", index);
+//!
+//! let app = Route::new()
+//!     .at("/", get(index))
+//!     .with(DenyByDefault::new(routes));
+//! ```
+
+use crate::error::AccessDenied;
+use poem::{Endpoint, IntoResponse, Middleware, Request, Response};
+use std::sync::Arc;
+
+pub use protect_endpoints_core::deny_by_default::{is_annotated, AnnotatedHandler, Route, Routes};
+
+/// Middleware rejecting requests which aren't routed to annotated handlers.
+#[derive(Debug, Clone)]
+pub struct DenyByDefault {
+    routes: Arc<Routes>,
+}
+
+impl DenyByDefault {
+    pub fn new(routes: Routes) -> Self {
+        Self {
+            routes: Arc::new(routes),
+        }
+    }
+}
+
+pub struct DenyByDefaultEndpoint<End> {
+    inner: End,
+    routes: Arc<Routes>,
+}
+
+impl<End: Endpoint> Middleware<End> for DenyByDefault {
+    type Output = DenyByDefaultEndpoint<End>;
+
+    fn transform(&self, ep: End) -> Self::Output {
+        DenyByDefaultEndpoint {
+            inner: ep,
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+impl<End: Endpoint> Endpoint for DenyByDefaultEndpoint<End> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        self.routes
+            .check(req.method().as_str(), req.uri().path())
+            .map_err(AccessDenied::from)?;
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
@@ -1,4 +1,14 @@
-use poem::error::{Forbidden, Unauthorized};
+use poem::error::ResponseError;
+use poem::http::header::WWW_AUTHENTICATE;
+use poem::http::StatusCode;
+use poem::Response;
+use protect_endpoints_core::condition::{Condition, Explanation};
+use protect_endpoints_core::error::{challenge, AccessDenied as AccessDeniedCore};
+use std::ops::Deref;
+
+pub use protect_endpoints_core::error::{
+    set_challenge, set_denial_renderer, Challenge, PROBLEM_JSON,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum AccessError {
@@ -8,11 +18,70 @@ pub enum AccessError {
     ForbiddenRequest,
 }
 
-impl From<AccessError> for poem::Error {
-    fn from(value: AccessError) -> Self {
-        match value {
-            e @ AccessError::UnauthorizedRequest => Unauthorized(e),
-            e @ AccessError::ForbiddenRequest => Forbidden(e),
+impl ResponseError for AccessError {
+    fn status(&self) -> StatusCode {
+        match self {
+            AccessError::UnauthorizedRequest => StatusCode::UNAUTHORIZED,
+            AccessError::ForbiddenRequest => StatusCode::FORBIDDEN,
+        }
+    }
+
+    /// Responses carry `WWW-Authenticate` challenge ([RFC 6750]), see [`set_challenge`].
+    ///
+    /// [RFC 6750]: https://www.rfc-editor.org/rfc/rfc6750#section-3
+    fn as_response(&self) -> Response {
+        let challenge = challenge().map(|challenge| match self {
+            AccessError::UnauthorizedRequest => challenge.unauthorized(),
+            AccessError::ForbiddenRequest => challenge.insufficient_scope::<&str>(&[]),
+        });
+
+        let mut response = Response::builder()
+            .status(self.status())
+            .body(self.to_string());
+        if let Some(challenge) = challenge.and_then(|c| c.try_into().ok()) {
+            response.headers_mut().insert(WWW_AUTHENTICATE, challenge);
         }
+        response
+    }
+}
+
+/// Default error of the [`protect`](crate::protect) macro.
+///
+/// Rendered as `application/problem+json`, see [`protect_endpoints_core::error`]
+/// for details and customization.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct AccessDenied(AccessDeniedCore);
+
+impl AccessDenied {
+    pub fn new(required: Condition<String>, explanation: Explanation<String>) -> Self {
+        Self(AccessDeniedCore::new(required, explanation))
+    }
+}
+
+impl ResponseError for AccessDenied {
+    fn status(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+
+    fn as_response(&self) -> Response {
+        let (parts, body) = self.0.to_response().into_parts();
+        let mut response = Response::builder().status(parts.status).body(body);
+        response.headers_mut().extend(parts.headers);
+        response
+    }
+}
+
+impl From<AccessDeniedCore> for AccessDenied {
+    fn from(value: AccessDeniedCore) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for AccessDenied {
+    type Target = AccessDeniedCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
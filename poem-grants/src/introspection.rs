@@ -0,0 +1,24 @@
+//! Admin endpoint serving the registered endpoints and simulating the access of authorities,
+//! see [`protect_endpoints_core::registry::introspection`].
+//!
+//! The endpoint isn't protected by itself, e.g. use the authorization [`table`](crate::table) for it.
+//!
+//! # Example
+//! ```
+//! use poem::{get, Route};
+//!
+//! // GET /admin/access?authorities=ROLE_USER,OP_READ
+//! let app = Route::new().at("/admin/access", get(poem_grants::introspection::handler));
+//! ```
+
+use poem::http::Uri;
+use poem::Response;
+use protect_endpoints_core::registry::introspection;
+
+/// Responds with the registered endpoints, and their access if `authorities` are given in the query.
+#[poem::handler]
+pub fn handler(uri: &Uri) -> Response {
+    Response::builder()
+        .content_type(introspection::CONTENT_TYPE)
+        .body(introspection::respond(uri.query()))
+}
@@ -18,8 +18,31 @@
 #![doc = include_str!("../README.md")]
 
 pub mod authorities;
+#[cfg(feature = "deny-by-default")]
+pub mod deny_by_default;
+#[doc(hidden)]
+pub use protect_endpoints_core::__annotate_handler;
+#[doc(hidden)]
+pub use protect_endpoints_core::__register_endpoint;
+pub use protect_endpoints_core::condition;
+#[cfg(any(
+    feature = "policy-toml",
+    feature = "policy-yaml",
+    feature = "policy-json"
+))]
+pub use protect_endpoints_core::policy;
+#[cfg(feature = "registry")]
+pub use protect_endpoints_core::registry;
 pub mod error;
+#[cfg(feature = "registry")]
+pub mod introspection;
 mod middleware;
+pub mod network;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod table;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 pub use middleware::GrantsMiddleware;
 
@@ -43,6 +66,13 @@ pub use middleware::GrantsMiddleware;
 ///     Response::builder().status(StatusCode::OK).body("some secured info")
 /// }
 ///
+/// // User should be ADMIN, or have OP_WRITE permission without being SUSPENDED
+/// #[poem_grants::protect(rule = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)")]
+/// #[poem::handler]
+/// async fn rule_macro_secured() -> Response {
+///     Response::builder().status(StatusCode::OK).body("some secured info")
+/// }
+///
 /// // Additional security condition to ensure the protection of the endpoint
 /// #[poem_grants::protect("USER", expr = "*user_id == user.id")]
 /// #[poem::handler]
@@ -63,7 +93,10 @@ pub use middleware::GrantsMiddleware;
 /// ```
 #[cfg(feature = "macro-check")]
 pub mod proc_macro {
-    pub use protect_endpoints_proc_macro::{open_api, protect_poem as protect};
+    pub use protect_endpoints_proc_macro::{
+        open_api, protect_all_poem as protect_all, protect_poem as protect, public_poem as public,
+        requirement_of,
+    };
 }
 
 /// Just a shortcut for proc-macros
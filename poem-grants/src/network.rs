@@ -0,0 +1,101 @@
+//! Network conditions: the client IP address within ranges, see [`protect_endpoints_core::network`].
+//!
+//! Handlers are restricted via `from_network` argument of the [`protect`](crate::protect) macro,
+//! whole routes via [`NetworkMiddleware`].
+
+use crate::error::AccessDenied;
+use poem::{Endpoint, FromRequest, Middleware, Request, RequestBody};
+use protect_endpoints_core::network::ClientIp as ClientIpCore;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub use protect_endpoints_core::network::{
+    client_ip, register_network, reset_trusted_proxies, set_trusted_proxies, ForwardedHeader,
+    IpNet, NetworkError, Networks,
+};
+
+/// Address of the client: the peer address or the forwarded one, if the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(ClientIpCore);
+
+impl ClientIp {
+    fn resolve(req: &Request) -> Self {
+        let peer = req.remote_addr().as_socket_addr().map(|peer| peer.ip());
+        let headers = req
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        ClientIp(ClientIpCore::new(peer, headers))
+    }
+}
+
+impl<'a> FromRequest<'a> for ClientIp {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+        Ok(ClientIp::resolve(req))
+    }
+}
+
+impl Deref for ClientIp {
+    type Target = ClientIpCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Middleware denying requests from clients outside of the networks.
+///
+/// # Example
+/// ```
+/// use poem::{get, handler, EndpointExt, Route};
+/// use poem_grants::network::NetworkMiddleware;
+///
+/// #[handler]
+/// async fn metrics() -> &'static str {
+///     "up 1"
+/// }
+///
+/// let app = Route::new().at(
+///     "/internal/metrics",
+///     get(metrics).with(NetworkMiddleware::new("10.0.0.0/8".parse().unwrap())),
+/// );
+/// ```
+pub struct NetworkMiddleware {
+    networks: Arc<Networks>,
+}
+
+impl NetworkMiddleware {
+    pub fn new(networks: Networks) -> Self {
+        Self {
+            networks: Arc::new(networks),
+        }
+    }
+}
+
+pub struct NetworkEndpoint<End> {
+    inner: End,
+    networks: Arc<Networks>,
+}
+
+impl<End: Endpoint> Middleware<End> for NetworkMiddleware {
+    type Output = NetworkEndpoint<End>;
+
+    fn transform(&self, ep: End) -> Self::Output {
+        NetworkEndpoint {
+            inner: ep,
+            networks: self.networks.clone(),
+        }
+    }
+}
+
+impl<End: Endpoint> Endpoint for NetworkEndpoint<End> {
+    type Output = End::Output;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        ClientIp::resolve(&req)
+            .check(&self.networks)
+            .map_err(AccessDenied::from)?;
+
+        self.inner.call(req).await
+    }
+}
@@ -0,0 +1,50 @@
+//! Responses of operations protected within [`open_api`](crate::open_api) for `poem-openapi`.
+//!
+//! With the `openapi` feature, the macro adds the requirement of a protected method to the description
+//! of its operation and declares `401` and `403` responses via [`AccessError`].
+
+use poem_openapi::registry::{MetaResponse, MetaResponses, Registry};
+use poem_openapi::ApiResponse;
+
+/// An error of a protected operation, documented as `401 Unauthorized` and `403 Forbidden` responses.
+///
+/// Any [`poem::Error`] of the operation is converted into it, the original status is kept.
+#[derive(Debug)]
+pub struct AccessError(poem::Error);
+
+impl From<poem::Error> for AccessError {
+    fn from(err: poem::Error) -> Self {
+        Self(err)
+    }
+}
+
+impl From<AccessError> for poem::Error {
+    fn from(err: AccessError) -> Self {
+        err.0
+    }
+}
+
+impl ApiResponse for AccessError {
+    fn meta() -> MetaResponses {
+        MetaResponses {
+            responses: vec![
+                MetaResponse {
+                    description: "Unauthorized: the authorities of the request can't be extracted",
+                    status: Some(401),
+                    status_range: None,
+                    content: vec![],
+                    headers: vec![],
+                },
+                MetaResponse {
+                    description: "Forbidden: the request doesn't satisfy the required authorities",
+                    status: Some(403),
+                    status_range: None,
+                    content: vec![],
+                    headers: vec![],
+                },
+            ],
+        }
+    }
+
+    fn register(_registry: &mut Registry) {}
+}
@@ -0,0 +1,101 @@
+//! Central authorization table: `(method, path pattern) -> access`, see [`protect_endpoints_core::table`].
+//!
+//! The table is applied via [`TableMiddleware`], which must be applied inside of the
+//! [`GrantsMiddleware`](crate::GrantsMiddleware) to see the attached authorities.
+
+use crate::authorities::AuthDetails;
+use crate::error::{AccessDenied, AccessError};
+use poem::{Endpoint, Middleware, Request};
+use std::fmt::Display;
+use std::hash::Hash;
+
+pub use protect_endpoints_core::table::{
+    Access, AuthorizationTable, PathPattern, Rejection, Rule, SharedTable, TableError,
+};
+
+/// Middleware checking requests against the [`AuthorizationTable`].
+///
+/// # Example
+/// ```
+/// use poem::{get, handler, EndpointExt, Request, Route};
+/// use poem_grants::table::{Access, AuthorizationTable, TableMiddleware};
+/// use poem_grants::GrantsMiddleware;
+/// use std::collections::HashSet;
+///
+/// #[handler]
+/// async fn index() -> &'static str {
+///     "Hi!"
+/// }
+///
+/// async fn extract(_req: &Request) -> poem::Result<HashSet<String>> {
+///     Ok(HashSet::from(["ROLE_ADMIN".to_string()]))
+/// }
+///
+/// let table = AuthorizationTable::new()
+///     .route("GET", "/", Access::PermitAll)
+///     .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+///     .otherwise(Access::Authenticated);
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .with(TableMiddleware::new(table))
+///     .with(GrantsMiddleware::with_extractor(extract));
+/// ```
+pub struct TableMiddleware<T = String> {
+    table: SharedTable<T>,
+}
+
+impl<T> TableMiddleware<T> {
+    pub fn new(table: impl Into<SharedTable<T>>) -> Self {
+        Self {
+            table: table.into(),
+        }
+    }
+}
+
+pub struct TableEndpoint<End, T = String> {
+    inner: End,
+    table: SharedTable<T>,
+}
+
+impl<End, T> Middleware<End> for TableMiddleware<T>
+where
+    End: Endpoint,
+    T: Eq + Hash + Clone + Display + Send + Sync + 'static,
+{
+    type Output = TableEndpoint<End, T>;
+
+    fn transform(&self, ep: End) -> Self::Output {
+        TableEndpoint {
+            inner: ep,
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<End, T> Endpoint for TableEndpoint<End, T>
+where
+    End: Endpoint,
+    T: Eq + Hash + Clone + Display + Send + Sync + 'static,
+{
+    type Output = End::Output;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        let details = req
+            .extensions()
+            .get::<AuthDetails<T>>()
+            .filter(|details| !details.is_anonymous());
+
+        match self
+            .table
+            .load()
+            .authorize(req.method().as_str(), req.uri().path(), details)
+        {
+            Ok(()) => {}
+            Err(Rejection::Unauthorized) => return Err(AccessError::UnauthorizedRequest.into()),
+            Err(Rejection::Forbidden(denied)) => return Err(AccessDenied::from(denied).into()),
+        }
+
+        self.inner.call(req).await
+    }
+}
@@ -0,0 +1,73 @@
+//! Injection of authorities into test requests, see [`protect_endpoints_core::test_util`].
+//!
+//! # Example
+//! ```
+//! use poem::http::StatusCode;
+//! use poem::test::TestClient;
+//! use poem::{get, EndpointExt, Route};
+//! use poem_grants::test_util::{MockExtractor, WithAuthorities};
+//! use poem_grants::GrantsMiddleware;
+//!
+//! #[poem_grants::protect("ROLE_ADMIN")]
+//! #[poem::handler]
+//! async fn secured() -> &'static str {
+//!     "some secured info"
+//! }
+//!
+//! # tokio_test();
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn tokio_test() {
+//! let extractor = MockExtractor::new();
+//! let app = Route::new()
+//!     .at("/", get(secured))
+//!     .with(GrantsMiddleware::with_extractor(extractor.clone()));
+//! let client = TestClient::new(app);
+//!
+//! let resp = client.get("/").with_authorities(["ROLE_ADMIN"]).send().await;
+//!
+//! resp.assert_status(StatusCode::OK);
+//! assert_eq!(1, extractor.calls().len());
+//! # }
+//! ```
+
+use crate::authorities::AuthoritiesExtractor;
+use poem::test::TestRequestBuilder;
+use poem::Request;
+use protect_endpoints_core::test_util::header_value;
+pub use protect_endpoints_core::test_util::{Call, AUTHORITIES_HEADER};
+use std::collections::HashSet;
+use std::future::{ready, Ready};
+
+/// Extractor of authorities given by [`WithAuthorities`], scripted results and errors take precedence.
+pub type MockExtractor = protect_endpoints_core::test_util::MockExtractor<poem::Error>;
+
+impl<'a> AuthoritiesExtractor<'a, &Request, String> for MockExtractor {
+    type Future = Ready<poem::Result<HashSet<String>>>;
+
+    fn extract(&self, req: &'a mut Request) -> Self::Future {
+        let header = req
+            .headers()
+            .get(AUTHORITIES_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        ready(self.respond(Call::new(req.method().as_str(), req.uri().path(), header)))
+    }
+}
+
+/// Passes authorities of the test request to the [`MockExtractor`].
+pub trait WithAuthorities {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>;
+}
+
+impl<E> WithAuthorities for TestRequestBuilder<'_, E> {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.header(AUTHORITIES_HEADER, header_value(authorities))
+    }
+}
@@ -0,0 +1,86 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use poem::http::header::AUTHORIZATION;
+use poem::http::StatusCode;
+use poem::test::{TestClient, TestResponse};
+use poem::{EndpointExt, Request, Route};
+use poem_grants::authorities::{set_anonymous_authorities, AuthDetails, AuthoritiesCheck};
+use poem_grants::{protect, GrantsMiddleware};
+use std::collections::HashSet;
+
+#[protect(rule = "ANONYMOUS || ROLE_USER", allow_anonymous)]
+#[poem::handler]
+async fn allow_anonymous() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_USER")]
+#[poem::handler]
+async fn user_only() -> &'static str {
+    "Hi!"
+}
+
+#[poem::handler]
+async fn optional(details: Option<AuthDetails>) -> &'static str {
+    match details {
+        Some(details) if details.has_authority("ROLE_USER") => "user",
+        Some(_) => "authenticated",
+        None => "anonymous",
+    }
+}
+
+#[tokio::test]
+async fn test_anonymous() {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    let resp = get_response("/allow_anonymous", None).await;
+    resp.assert_status_is_ok();
+    resp.assert_text("Hi!").await;
+    get_response("/allow_anonymous", Some("ROLE_USER"))
+        .await
+        .assert_status_is_ok();
+    get_response("/allow_anonymous", Some("ROLE_OTHER"))
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    get_response("/user_only", None)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    get_response("/optional", None)
+        .await
+        .assert_text("anonymous")
+        .await;
+    get_response("/optional", Some("ROLE_USER"))
+        .await
+        .assert_text("user")
+        .await;
+    get_response("/optional", Some("ROLE_OTHER"))
+        .await
+        .assert_text("authenticated")
+        .await;
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_response(uri: &str, role: Option<&str>) -> TestResponse {
+    let app = Route::new()
+        .at("/allow_anonymous", allow_anonymous)
+        .at("/user_only", user_only)
+        .at("/optional", optional)
+        .with(GrantsMiddleware::with_extractor(extract));
+    let client = TestClient::new(app);
+
+    let mut request = client.get(uri);
+    if let Some(role) = role {
+        request = request.header(AUTHORIZATION, role);
+    }
+    request.send().await
+}
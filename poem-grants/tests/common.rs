@@ -1,12 +1,19 @@
 use poem::http::header::{HeaderValue, AUTHORIZATION};
 use poem::test::TestResponse;
 use poem::Request;
+use poem_grants::authorities::AttachPrincipal;
 use serde::Deserialize;
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 
 pub const ROLE_ADMIN: &str = "ROLE_ADMIN";
 pub const ROLE_MANAGER: &str = "ROLE_MANAGER";
+pub const TENANT_HEADER: &str = "x-tenant";
+pub const SUBJECT: &str = "user-1";
+
+pub struct Claims {
+    pub tenant: String,
+}
 
 #[derive(Eq, PartialEq, Hash)]
 #[allow(clippy::upper_case_acronyms)]
@@ -16,6 +23,15 @@ pub enum Role {
 }
 
 pub async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    let tenant = req
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .map(str::to_string);
+    if let Some(tenant) = tenant {
+        req.attach_principal(SUBJECT, Claims { tenant });
+    }
+
     let auth_header: Option<&str> = req
         .headers()
         .get(AUTHORIZATION)
@@ -41,6 +57,16 @@ pub async fn enum_extract(req: &mut Request) -> poem::Result<HashSet<Role>> {
         .unwrap())
 }
 
+/// Default denial without explanation, the correlation id is generated.
+pub async fn test_problem(resp: TestResponse) {
+    resp.assert_content_type("application/problem+json");
+    let body = resp.0.into_body().into_string().await.unwrap();
+
+    assert!(body.starts_with(
+        r#"{"type":"about:blank","title":"Forbidden","status":403,"correlation_id":""#
+    ));
+}
+
 pub async fn test_body(resp: TestResponse, expected_body: &str) {
     let body = resp.0.into_body().into_string().await.unwrap();
 
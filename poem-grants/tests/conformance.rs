@@ -0,0 +1,103 @@
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::web::Query;
+use poem::{get, EndpointExt, Request, Route};
+use poem_grants::{protect, GrantsMiddleware};
+use protect_endpoints_testkit::{
+    header_value, parse_header_value, Adapter, Credentials, Role, AUTHORITIES_HEADER, OWNER,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::future::{ready, Ready};
+use std::hash::Hash;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+struct NamePayload {
+    name: Option<String>,
+}
+
+#[protect("ROLE_ADMIN")]
+#[poem::handler]
+async fn single() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_ADMIN", "ROLE_MANAGER")]
+#[poem::handler]
+async fn all() -> &'static str {
+    "Hi!"
+}
+
+#[protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]
+#[poem::handler]
+async fn any() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_ADMIN", expr = "payload.name.as_deref() == Some(OWNER)")]
+#[poem::handler]
+async fn expr(payload: Query<NamePayload>) -> &'static str {
+    "Hi!"
+}
+
+#[protect("Role::Admin", ty = "Role")]
+#[poem::handler]
+async fn typed() -> &'static str {
+    "Hi!"
+}
+
+fn extractor<T: FromStr + Eq + Hash>(
+    credentials: Credentials,
+) -> impl Fn(&Request) -> Ready<poem::Result<HashSet<T>>> {
+    move |req| {
+        if credentials == Credentials::ExtractorError {
+            return ready(Err(poem::Error::from_status(StatusCode::UNAUTHORIZED)));
+        }
+
+        let authorities = req
+            .headers()
+            .get(AUTHORITIES_HEADER)
+            .and_then(|header| header.to_str().ok())
+            .map(parse_header_value)
+            .unwrap_or_default();
+        ready(Ok(authorities
+            .iter()
+            .filter_map(|authority| authority.parse().ok())
+            .collect()))
+    }
+}
+
+struct Poem;
+
+impl Adapter for Poem {
+    async fn status(&self, path: &str, credentials: &Credentials) -> u16 {
+        let enabled = *credentials != Credentials::Missing;
+        let grants = || GrantsMiddleware::with_extractor(extractor::<String>(*credentials));
+        let app = Route::new()
+            .at("/single", get(single).with_if(enabled, grants()))
+            .at("/all", get(all).with_if(enabled, grants()))
+            .at("/any", get(any).with_if(enabled, grants()))
+            .at("/expr", get(expr).with_if(enabled, grants()))
+            .at(
+                "/typed",
+                get(typed).with_if(
+                    enabled,
+                    GrantsMiddleware::with_extractor(extractor::<Role>(*credentials)),
+                ),
+            );
+
+        let client = TestClient::new(app);
+        let mut request = client.get(path);
+        if let Credentials::Authorities(authorities) = credentials {
+            request = request.header(AUTHORITIES_HEADER, header_value(*authorities));
+        }
+
+        request.send().await.0.status().as_u16()
+    }
+}
+
+#[tokio::test]
+async fn test_conformance() {
+    protect_endpoints_testkit::run(&Poem).await;
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "deny-by-default")]
+
+use poem::http::header::AUTHORIZATION;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::{get, EndpointExt, Request, Route};
+use poem_grants::deny_by_default::{DenyByDefault, Routes};
+use poem_grants::GrantsMiddleware;
+use poem_openapi::payload::PlainText;
+use poem_openapi::{OpenApi, OpenApiService};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[poem_grants::public]
+#[poem::handler]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+#[poem_grants::protect("ROLE_ADMIN")]
+#[poem::handler]
+async fn admin() -> &'static str {
+    "Hello, admin!"
+}
+
+static FORGOTTEN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[poem::handler]
+async fn forgotten() -> &'static str {
+    FORGOTTEN_CALLS.fetch_add(1, Ordering::SeqCst);
+    "Oops!"
+}
+
+#[poem_grants::public]
+#[poem::handler]
+async fn unlisted() -> &'static str {
+    "Hi!"
+}
+
+struct Api;
+
+#[poem_grants::open_api]
+#[OpenApi]
+impl Api {
+    #[public]
+    #[oai(path = "/", method = "get")]
+    async fn index(&self) -> PlainText<&'static str> {
+        PlainText("Hi!")
+    }
+
+    #[protect("ROLE_ADMIN")]
+    #[oai(path = "/admin", method = "get")]
+    async fn admin(&self) -> PlainText<&'static str> {
+        PlainText("Hello, admin!")
+    }
+
+    #[oai(path = "/forgotten", method = "get")]
+    async fn forgotten(&self) -> PlainText<&'static str> {
+        FORGOTTEN_CALLS.fetch_add(1, Ordering::SeqCst);
+        PlainText("Oops!")
+    }
+}
+
+#[tokio::test]
+async fn test_deny_by_default() {
+    assert_eq!(StatusCode::OK, get_status("/", None).await);
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/unlisted", None).await);
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/missing", None).await);
+}
+
+#[tokio::test]
+async fn test_forgotten_handler_not_called() {
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    let api = Route::new().nest("/", OpenApiService::new(Api, "Api", "1.0"));
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status_of(&api, api_routes(), "/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(0, FORGOTTEN_CALLS.load(Ordering::SeqCst));
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_open_api_deny_by_default() {
+    let api = Route::new().nest("/", OpenApiService::new(Api, "Api", "1.0"));
+    assert_eq!(
+        StatusCode::OK,
+        get_status_of(&api, api_routes(), "/", None).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status_of(&api, api_routes(), "/admin", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status_of(&api, api_routes(), "/admin", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status_of(&api, api_routes(), "/forgotten", Some("ROLE_ADMIN")).await
+    );
+}
+
+async fn get_status(uri: &str, role: Option<&str>) -> StatusCode {
+    let app = Route::new()
+        .at("/", get(index))
+        .at("/admin", get(admin))
+        .at("/forgotten", get(forgotten))
+        .at("/unlisted", get(unlisted));
+    let routes = Routes::new()
+        .route("GET", "/", index)
+        .route("GET", "/admin", admin)
+        .route("GET", "/forgotten", forgotten);
+
+    get_status_of(&app, routes, uri, role).await
+}
+
+fn api_routes() -> Routes {
+    Routes::new()
+        .route("GET", "/", Api::index)
+        .route("GET", "/admin", Api::admin)
+        .route("GET", "/forgotten", Api::forgotten)
+}
+
+async fn get_status_of(app: &Route, routes: Routes, uri: &str, role: Option<&str>) -> StatusCode {
+    let app = app
+        .with(DenyByDefault::new(routes))
+        .with(GrantsMiddleware::with_extractor(extract));
+    let client = TestClient::new(app);
+
+    let mut request = client.get(uri);
+    if let Some(role) = role {
+        request = request.header(AUTHORIZATION, role);
+    }
+    request.send().await.0.status()
+}
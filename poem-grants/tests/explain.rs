@@ -0,0 +1,61 @@
+//! Explanations are enabled globally, so they are tested in a separate binary.
+
+use poem::http::header::AUTHORIZATION;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::{EndpointExt, Request, Route};
+use poem_grants::condition::{set_explain_mode, ExplainMode};
+use poem_grants::{protect, GrantsMiddleware};
+use std::collections::HashSet;
+
+#[protect("ROLE_ADMIN", expr = "*user_id == 1")]
+#[poem::handler]
+async fn secure_user_id(user_id: poem::web::Path<i32>) -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_explain_modes() {
+    let app = Route::new()
+        .at("/secure/:user_id", secure_user_id)
+        .with(GrantsMiddleware::with_extractor(extract));
+    let client = TestClient::new(app);
+
+    set_explain_mode(ExplainMode::Full);
+    let resp = client
+        .get("/secure/2")
+        .header(AUTHORIZATION, "ROLE_ADMIN")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    resp.assert_content_type("application/problem+json");
+    let json = resp.json().await;
+    let problem = json.value().object();
+    problem.get("status").assert_i64(403);
+    problem
+        .get("detail")
+        .assert_string("[failed] all\n  [passed] ROLE_ADMIN\n  [failed] expr: *user_id == 1");
+    problem.get("required").assert_string("ROLE_ADMIN");
+    problem.get("missing_authorities").assert_string_array(&[]);
+
+    set_explain_mode(ExplainMode::Disabled);
+    let resp = client
+        .get("/secure/2")
+        .header(AUTHORIZATION, "ROLE_ADMIN")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    let json = resp.json().await;
+    let problem = json.value().object();
+    problem.get("title").assert_string("Forbidden");
+    assert!(problem.get_opt("detail").is_none());
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
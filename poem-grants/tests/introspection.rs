@@ -0,0 +1,31 @@
+#![cfg(feature = "registry")]
+
+use poem::test::TestClient;
+use poem::{get, Route};
+
+#[poem_grants::protect("ROLE_ADMIN")]
+#[poem::handler]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_introspection() {
+    let app = Route::new()
+        .at("/users", get(users))
+        .at("/admin/access", get(poem_grants::introspection::handler));
+    let client = TestClient::new(app);
+
+    let resp = client
+        .get("/admin/access")
+        .query("authorities", &"ROLE_ADMIN,OP_READ")
+        .send()
+        .await;
+    resp.assert_content_type("application/json");
+
+    let body = resp.0.into_body().into_string().await.unwrap();
+    assert!(body.starts_with(
+        r#"{"authorities":["ROLE_ADMIN","OP_READ"],"endpoints":[{"handler":"introspection::users","#
+    ));
+    assert!(body.ends_with(r#""condition":"ROLE_ADMIN","dynamic":false,"access":"allowed"}]}"#));
+}
@@ -0,0 +1,92 @@
+use poem::http::header::AUTHORIZATION;
+use poem::http::uri::Scheme;
+use poem::http::StatusCode;
+use poem::web::{LocalAddr, RemoteAddr};
+use poem::{get, Body, Endpoint, EndpointExt, Request, RequestParts, Route};
+use poem_grants::network::NetworkMiddleware;
+use poem_grants::{protect, GrantsMiddleware};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+const INTERNAL: &str = "10.1.2.3:4000";
+const EXTERNAL: &str = "203.0.113.1:4000";
+
+#[protect(any("ROLE_ADMIN", from_network = "10.0.0.0/8, ::1"))]
+#[poem::handler]
+async fn from_network() -> &'static str {
+    "Hi!"
+}
+
+#[poem::handler]
+async fn metrics() -> &'static str {
+    "up 1"
+}
+
+#[tokio::test]
+async fn test_from_network() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", "[::1]:4000").await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_ADMIN", EXTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/from_network", "ROLE_USER", EXTERNAL).await
+    );
+}
+
+#[tokio::test]
+async fn test_network_middleware() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/internal/metrics", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/internal/metrics", "ROLE_ADMIN", EXTERNAL).await
+    );
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str, peer: &str) -> StatusCode {
+    let app = Route::new()
+        .at("/from_network", get(from_network))
+        .at(
+            "/internal/metrics",
+            get(metrics).with(NetworkMiddleware::new("10.0.0.0/8".parse().unwrap())),
+        )
+        .with(GrantsMiddleware::with_extractor(extract));
+
+    let (parts, body) = poem::http::Request::builder()
+        .uri(uri)
+        .header(AUTHORIZATION, role)
+        .body(Body::empty())
+        .unwrap()
+        .into_parts();
+    let peer: SocketAddr = peer.parse().unwrap();
+    let parts = RequestParts::from((
+        parts,
+        LocalAddr::default(),
+        RemoteAddr(peer.into()),
+        Scheme::HTTP,
+    ));
+
+    app.get_response(Request::from_parts(parts, body))
+        .await
+        .status()
+}
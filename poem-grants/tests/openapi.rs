@@ -0,0 +1,179 @@
+#![cfg(feature = "openapi")]
+
+use poem::http::header::AUTHORIZATION;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::{EndpointExt, Request, Route};
+use poem_grants::GrantsMiddleware;
+use poem_openapi::payload::PlainText;
+use poem_openapi::{OAuthScopes, OpenApi, OpenApiService, SecurityScheme};
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(SecurityScheme)]
+#[oai(ty = "api_key", key_name = "X-API-Key", key_in = "header")]
+#[allow(dead_code)]
+struct ApiKey(poem_openapi::auth::ApiKey);
+
+#[derive(OAuthScopes, Debug, PartialEq, Eq, Hash)]
+enum Scope {
+    #[oai(rename = "read")]
+    Read,
+    #[oai(rename = "write")]
+    Write,
+}
+
+#[derive(SecurityScheme)]
+#[oai(
+    ty = "oauth2",
+    flows(implicit(authorization_url = "https://example.com/authorize", scopes = "Scope"))
+)]
+#[allow(dead_code)]
+struct OAuth(poem_openapi::auth::Bearer);
+
+struct Api;
+
+#[poem_grants::open_api]
+#[OpenApi]
+impl Api {
+    /// Admin info
+    #[protect("ROLE_ADMIN")]
+    #[oai(path = "/admin", method = "get")]
+    async fn admin(&self) -> PlainText<&'static str> {
+        PlainText("Hello, admin!")
+    }
+
+    #[protect(any("ROLE_ADMIN", "ROLE_MANAGER"), expr = "*id > 0")]
+    #[oai(path = "/staff", method = "get")]
+    async fn staff(&self, id: poem_openapi::param::Query<i32>) -> PlainText<&'static str> {
+        PlainText("Hello, staff!")
+    }
+
+    #[oai(path = "/", method = "get")]
+    async fn index(&self) -> PlainText<&'static str> {
+        PlainText("Hi!")
+    }
+}
+
+struct KeyApi;
+
+#[poem_grants::open_api(security = "ApiKey")]
+#[OpenApi]
+impl KeyApi {
+    #[protect("ROLE_ADMIN")]
+    #[oai(path = "/admin", method = "get")]
+    async fn admin(&self) -> PlainText<&'static str> {
+        PlainText("Hello, admin!")
+    }
+}
+
+struct ScopedApi;
+
+#[poem_grants::open_api(security = "OAuth", scopes)]
+#[OpenApi]
+impl ScopedApi {
+    #[protect("Scope::Read", "Scope::Write", ty = "Scope")]
+    #[oai(path = "/edit", method = "post")]
+    async fn edit(&self) -> PlainText<&'static str> {
+        PlainText("Edited!")
+    }
+}
+
+fn spec<T: OpenApi>(api: T) -> Value {
+    serde_json::from_str(&OpenApiService::new(api, "Api", "1.0").spec()).unwrap()
+}
+
+#[test]
+fn test_description_and_responses() {
+    let spec = spec(Api);
+
+    let admin = &spec["paths"]["/admin"]["get"];
+    assert_eq!("Admin info", admin["summary"]);
+    assert_eq!("Requires `ROLE_ADMIN`.", admin["description"]);
+    let responses = admin["responses"].as_object().unwrap();
+    assert!(responses.contains_key("200"));
+    assert!(responses.contains_key("401"));
+    assert!(responses.contains_key("403"));
+
+    let staff = &spec["paths"]["/staff"]["get"];
+    assert_eq!(
+        "Requires `ROLE_ADMIN || ROLE_MANAGER` and a condition on the request.",
+        staff["summary"]
+    );
+
+    let index = &spec["paths"]["/"]["get"];
+    assert!(!index["responses"].as_object().unwrap().contains_key("403"));
+}
+
+#[test]
+fn test_security_scheme() {
+    let spec = spec(KeyApi);
+
+    assert_eq!(
+        serde_json::json!([{ "ApiKey": [] }]),
+        spec["paths"]["/admin"]["get"]["security"]
+    );
+    assert!(spec["components"]["securitySchemes"]["ApiKey"].is_object());
+}
+
+#[test]
+fn test_scopes() {
+    let spec = spec(ScopedApi);
+
+    assert_eq!(
+        serde_json::json!([{ "OAuth": ["read", "write"] }]),
+        spec["paths"]["/edit"]["post"]["security"]
+    );
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_access_is_unchanged() {
+    let app = Route::new()
+        .nest("/", OpenApiService::new(Api, "Api", "1.0"))
+        .nest("/key", OpenApiService::new(KeyApi, "Api", "1.0"))
+        .with(GrantsMiddleware::with_extractor(extract));
+    let client = TestClient::new(app);
+
+    let status = |uri: &'static str, role: &'static str, key: bool| {
+        let mut request = client.get(uri).header(AUTHORIZATION, role);
+        if key {
+            request = request.header("X-API-Key", "key");
+        }
+        async move { request.send().await.0.status() }
+    };
+
+    assert_eq!(StatusCode::OK, status("/admin", "ROLE_ADMIN", false).await);
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        status("/admin", "ROLE_USER", false).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        status("/staff?id=1", "ROLE_MANAGER", false).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        status("/staff?id=0", "ROLE_MANAGER", false).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        status("/key/admin", "ROLE_ADMIN", true).await
+    );
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        status("/key/admin", "ROLE_ADMIN", false).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        status("/key/admin", "ROLE_USER", true).await
+    );
+}
@@ -4,9 +4,12 @@ use poem::http::header::AUTHORIZATION;
 use poem::http::StatusCode;
 use poem::test::{TestClient, TestResponse};
 use poem::web::{Json, Path, Query};
-use poem::{EndpointExt, Response, Route};
+use poem::{EndpointExt, Request, Response, Route};
+use poem_grants::authorities::{reset_clock, set_clock, AuthDetails, AuthoritiesCheck, Principal};
+use poem_grants::error::AccessDenied;
 use poem_grants::{protect, GrantsMiddleware};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
 #[protect("ROLE_ADMIN")]
 #[poem::handler]
@@ -52,6 +55,40 @@ fn sync_handler() -> Response {
     Response::builder().status(StatusCode::OK).finish()
 }
 
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)")]
+#[poem::handler]
+async fn rule_response() -> &'static str {
+    "Hi!"
+}
+
+async fn access_denied_with_context(
+    details: &AuthDetails,
+    denied: &AccessDenied,
+    req: &Request,
+) -> poem::Error {
+    let user = if details.has_authority(ROLE_MANAGER) {
+        "Manager"
+    } else {
+        "User"
+    };
+
+    let message = format!(
+        "{user} can't access {}, missing: {}",
+        req.uri().path(),
+        denied.missing_authorities().join(", ")
+    );
+    poem::Error::from_string(message, StatusCode::FORBIDDEN)
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    error = "access_denied_with_context(auth_details, denied, request).await"
+)]
+#[poem::handler]
+async fn context_response() -> &'static str {
+    "Hi!"
+}
+
 #[tokio::test]
 async fn test_http_response() {
     let test_admin = get_user_response("/http_response", ROLE_ADMIN).await;
@@ -70,7 +107,7 @@ async fn test_str() {
     test_manager.assert_status(StatusCode::FORBIDDEN);
 
     common::test_body(test_admin, "Hi!").await;
-    common::test_body(test_manager, "Forbidden request").await;
+    common::test_problem(test_manager).await;
 }
 
 #[tokio::test]
@@ -91,7 +128,7 @@ async fn test_secure_with_user_id() {
     test_err.assert_status(StatusCode::FORBIDDEN);
 
     common::test_body(test_ok, "Hi!").await;
-    common::test_body(test_err, "Forbidden request").await;
+    common::test_problem(test_err).await;
 }
 
 #[tokio::test]
@@ -106,7 +143,7 @@ async fn test_result() {
 
     common::test_body(test_ok, "Welcome Test!").await;
     common::test_body(test_err, "method not allowed").await;
-    common::test_body(test_forbidden, "Forbidden request").await;
+    common::test_problem(test_forbidden).await;
 }
 
 #[tokio::test]
@@ -118,17 +155,119 @@ async fn test_sync_handler() {
     test_manager.assert_status(StatusCode::FORBIDDEN);
 }
 
+#[tokio::test]
+async fn test_rule() {
+    let test_admin = get_user_response("/rule", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/rule", ROLE_MANAGER).await;
+    let test_blocked = get_user_response("/rule", "ROLE_MANAGER,ROLE_BLOCKED").await;
+
+    test_admin.assert_status_is_ok();
+    test_manager.assert_status_is_ok();
+    test_blocked.assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_access_denied_context() {
+    let test_admin = get_user_response("/context", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/context", ROLE_MANAGER).await;
+
+    test_admin.assert_status_is_ok();
+    test_manager.assert_status(StatusCode::FORBIDDEN);
+
+    common::test_body(test_admin, "Hi!").await;
+    common::test_body(
+        test_manager,
+        "Manager can't access /context, missing: ROLE_ADMIN",
+    )
+    .await;
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    principal = "common::Claims",
+    expr = "principal.claims().tenant == \"acme\""
+)]
+#[poem::handler]
+async fn principal_response(user: Principal<common::Claims>) -> String {
+    user.subject().to_string()
+}
+
+#[protect("ROLE_ADMIN", during = "Mon-Fri 09:00-18:00 UTC")]
+#[poem::handler]
+async fn during_response() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_during() {
+    // Monday, 2024-01-01 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_103_200));
+    let test_working_hours = get_user_response("/during", ROLE_ADMIN).await;
+    // Saturday, 2024-01-06 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_535_200));
+    let test_weekend = get_user_response("/during", ROLE_ADMIN).await;
+    reset_clock();
+
+    test_working_hours.assert_status_is_ok();
+    test_weekend.assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_principal() {
+    let test_acme = get_tenant_response("/principal", ROLE_ADMIN, Some("acme")).await;
+    let test_other = get_tenant_response("/principal", ROLE_ADMIN, Some("other")).await;
+    let test_anonymous = get_tenant_response("/principal", ROLE_ADMIN, None).await;
+
+    test_acme.assert_status_is_ok();
+    test_other.assert_status(StatusCode::FORBIDDEN);
+    test_anonymous.assert_status(StatusCode::UNAUTHORIZED);
+
+    common::test_body(test_acme, common::SUBJECT).await;
+}
+
+#[protect(any("ROLE_ADMIN", owner = "user_id"), principal = "common::Claims")]
+#[poem::handler]
+async fn owner_response(Path(user_id): Path<String>) -> String {
+    format!("Hi, {user_id}!")
+}
+
+#[tokio::test]
+async fn test_owner() {
+    let test_owner = get_tenant_response("/owner/user-1", ROLE_MANAGER, Some("acme")).await;
+    let test_other = get_tenant_response("/owner/user-2", ROLE_MANAGER, Some("acme")).await;
+    let test_admin = get_tenant_response("/owner/user-2", ROLE_ADMIN, Some("acme")).await;
+    let test_without_principal = get_tenant_response("/owner/user-1", ROLE_MANAGER, None).await;
+
+    test_owner.assert_status_is_ok();
+    test_other.assert_status(StatusCode::FORBIDDEN);
+    test_admin.assert_status_is_ok();
+    test_without_principal.assert_status(StatusCode::UNAUTHORIZED);
+}
+
 async fn get_user_response(uri: &str, role: &str) -> TestResponse {
+    get_tenant_response(uri, role, None).await
+}
+
+async fn get_tenant_response(uri: &str, role: &str, tenant: Option<&str>) -> TestResponse {
     let app = Route::new()
         .at("/http_response", http_response)
         .at("/str", str_response)
         .at("/return", return_response)
         .at("/result", result_response)
         .at("/sync_handler", sync_handler)
+        .at("/context", context_response)
+        .at("/rule", rule_response)
+        .at("/principal", principal_response)
+        .at("/owner/:user_id", owner_response)
+        .at("/during", during_response)
         .with(GrantsMiddleware::with_extractor(common::extract));
     let cli = TestClient::new(app);
 
-    cli.get(uri).header(AUTHORIZATION, role).send().await
+    let mut request = cli.get(uri).header(AUTHORIZATION, role);
+    if let Some(tenant) = tenant {
+        request = request.header(common::TENANT_HEADER, tenant);
+    }
+    request.send().await
 }
 
 async fn post_user_response<T: Serialize>(uri: &str, role: &str, data: &T) -> TestResponse {
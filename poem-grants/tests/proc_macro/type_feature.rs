@@ -1,6 +1,6 @@
 use crate::common;
 use crate::common::Role::{self, ADMIN, MANAGER};
-use poem::http::header::AUTHORIZATION;
+use poem::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
 use poem::http::StatusCode;
 use poem::test::{TestClient, TestResponse};
 use poem::{EndpointExt, Response, Route};
@@ -49,6 +49,7 @@ async fn test_http_response_for_full_path_enum() {
 async fn test_incorrect_http_response() {
     let test = get_user_response("/incorrect_enum_secure", &ADMIN.to_string()).await;
     test.assert_status(StatusCode::UNAUTHORIZED);
+    test.assert_header(WWW_AUTHENTICATE, "Bearer");
 }
 
 async fn get_user_response(uri: &str, role: &str) -> TestResponse {
@@ -0,0 +1,136 @@
+use poem::http::header::AUTHORIZATION;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::{get, EndpointExt, Request, Route};
+#[cfg(feature = "deny-by-default")]
+use poem_grants::deny_by_default::{DenyByDefault, Routes};
+use poem_grants::GrantsMiddleware;
+use poem_openapi::payload::PlainText;
+use poem_openapi::{OpenApi, OpenApiService};
+use std::collections::HashSet;
+
+#[poem_grants::protect_all("ROLE_ADMIN")]
+mod admin {
+    #[poem::handler]
+    pub async fn index() -> &'static str {
+        "Hello, admin!"
+    }
+
+    #[protect("OP_WRITE")]
+    #[poem::handler]
+    pub async fn users() -> &'static str {
+        "Users"
+    }
+
+    #[protect(any("ROLE_ADMIN", "ROLE_SUPPORT"), overrides)]
+    #[poem::handler]
+    pub async fn tickets() -> &'static str {
+        "Tickets"
+    }
+
+    #[public]
+    #[poem::handler]
+    pub async fn health() -> &'static str {
+        "Ok"
+    }
+
+    pub async fn load_title() -> &'static str {
+        "Admin"
+    }
+}
+
+struct Api;
+
+#[poem_grants::protect_all("ROLE_MANAGER")]
+#[OpenApi]
+impl Api {
+    #[oai(path = "/daily", method = "get")]
+    async fn daily(&self) -> PlainText<&'static str> {
+        PlainText("Daily")
+    }
+
+    #[public]
+    #[oai(path = "/health", method = "get")]
+    async fn health(&self) -> PlainText<&'static str> {
+        PlainText("Ok")
+    }
+}
+
+#[tokio::test]
+async fn test_module() {
+    assert_eq!("Admin", admin::load_title().await);
+    assert_eq!(StatusCode::OK, get_status("/admin", "ROLE_ADMIN").await);
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", "ROLE_SUPPORT").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/users", "ROLE_ADMIN,OP_WRITE").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin/users", "ROLE_ADMIN").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/tickets", "ROLE_SUPPORT").await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/health", "ROLE_USER").await
+    );
+}
+
+#[tokio::test]
+async fn test_open_api() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/api/daily", "ROLE_MANAGER").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/api/daily", "ROLE_ADMIN").await
+    );
+    assert_eq!(StatusCode::OK, get_status("/api/health", "ROLE_USER").await);
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str) -> StatusCode {
+    let app = Route::new()
+        .at("/admin", get(admin::index))
+        .at("/admin/users", get(admin::users))
+        .at("/admin/tickets", get(admin::tickets))
+        .at("/admin/health", get(admin::health))
+        .nest("/api", OpenApiService::new(Api, "Api", "1.0"));
+    #[cfg(feature = "deny-by-default")]
+    let app = app.with(DenyByDefault::new(
+        Routes::new()
+            .route("GET", "/admin", admin::index)
+            .route("GET", "/admin/users", admin::users)
+            .route("GET", "/admin/tickets", admin::tickets)
+            .route("GET", "/admin/health", admin::health)
+            .route("GET", "/api/daily", Api::daily)
+            .route("GET", "/api/health", Api::health),
+    ));
+    let app = app.with(GrantsMiddleware::with_extractor(extract));
+    let client = TestClient::new(app);
+
+    client
+        .get(uri)
+        .header(AUTHORIZATION, role)
+        .send()
+        .await
+        .0
+        .status()
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "registry")]
+
+use poem_grants::registry;
+use poem_openapi::payload::PlainText;
+use poem_openapi::{OpenApi, OpenApiService};
+
+struct Api;
+
+#[poem_grants::open_api]
+#[OpenApi]
+impl Api {
+    #[protect("ROLE_ADMIN")]
+    #[oai(path = "/admin", method = "get")]
+    async fn admin(&self) -> PlainText<&'static str> {
+        PlainText("Hello, admin!")
+    }
+}
+
+#[poem_grants::protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]
+#[poem::handler]
+async fn manager() -> &'static str {
+    "Hello, manager!"
+}
+
+#[test]
+fn test_registry() {
+    let _ = OpenApiService::new(Api, "Api", "1.0");
+    let _ = poem::get(manager);
+
+    let handlers: Vec<_> = registry::endpoints()
+        .into_iter()
+        .map(|endpoint| {
+            (
+                endpoint.handler(),
+                endpoint.framework(),
+                endpoint.condition(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        vec![
+            ("registry::Api::admin", "poem", "ROLE_ADMIN"),
+            ("registry::manager", "poem", "ROLE_ADMIN || ROLE_MANAGER"),
+        ],
+        handlers
+    );
+}
+
+#[test]
+fn test_requirement_of() {
+    let requirement = registry::requirement_of(&Api::admin).unwrap();
+    assert_eq!("ROLE_ADMIN", requirement.to_string());
+    assert!(requirement.is_satisfied_by(&["ROLE_ADMIN"]));
+
+    let requirement = registry::requirement_of(&manager).unwrap();
+    assert!(requirement.is_satisfied_by(&["ROLE_MANAGER"]));
+    assert!(!requirement.is_satisfied_by(&["ROLE_USER"]));
+}
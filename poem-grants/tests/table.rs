@@ -0,0 +1,101 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use poem::http::header::AUTHORIZATION;
+use poem::http::{Method, StatusCode};
+use poem::test::TestClient;
+use poem::{get, post, EndpointExt, Request, Route};
+use poem_grants::authorities::set_anonymous_authorities;
+use poem_grants::table::{Access, AuthorizationTable, TableMiddleware};
+use poem_grants::GrantsMiddleware;
+use std::collections::HashSet;
+
+#[poem::handler]
+async fn hi() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_table() {
+    assert_eq!(StatusCode::OK, get_status(Method::GET, "/", None).await);
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        get_status(Method::GET, "/profile", None).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/profile", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/admin/users", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/admin/users", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::POST, "/articles/1", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::POST, "/articles/1", Some("ROLE_EDITOR")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/internal/metrics", Some("ROLE_ADMIN")).await
+    );
+}
+
+#[tokio::test]
+async fn test_normalized_paths() {
+    for uri in ["/%61dmin/users", "//admin/users", "/./admin/users"] {
+        assert_eq!(
+            StatusCode::FORBIDDEN,
+            get_status(Method::GET, uri, Some("ROLE_USER")).await,
+            "{uri}"
+        );
+    }
+}
+
+fn table() -> AuthorizationTable {
+    AuthorizationTable::new()
+        .route("GET", "/", Access::PermitAll)
+        .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+        .route(
+            "POST",
+            "/articles/{id}",
+            "ROLE_EDITOR || ROLE_ADMIN".parse::<Access>().unwrap(),
+        )
+        .route("*", "/internal/**", Access::DenyAll)
+        .otherwise(Access::Authenticated)
+}
+
+async fn extract(req: &mut Request) -> poem::Result<HashSet<String>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(method: Method, uri: &str, role: Option<&str>) -> StatusCode {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    let app = Route::new()
+        .at("/", get(hi))
+        .at("/profile", get(hi))
+        .at("/admin/users", get(hi))
+        .at("/articles/1", post(hi))
+        .at("/internal/metrics", get(hi))
+        .with(TableMiddleware::new(table()))
+        .with(GrantsMiddleware::with_extractor(extract));
+    let client = TestClient::new(app);
+
+    let mut request = client.request(method, uri);
+    if let Some(role) = role {
+        request = request.header(AUTHORIZATION, role);
+    }
+    request.send().await.0.status()
+}
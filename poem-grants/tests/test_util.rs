@@ -0,0 +1,67 @@
+#![cfg(feature = "test-util")]
+
+use poem::http::StatusCode;
+use poem::test::{TestClient, TestRequestBuilder};
+use poem::{get, Endpoint, EndpointExt, Route};
+use poem_grants::test_util::{MockExtractor, WithAuthorities};
+use poem_grants::GrantsMiddleware;
+use std::collections::HashSet;
+
+const ROLE_ADMIN: &str = "ROLE_ADMIN";
+
+#[poem_grants::protect("ROLE_ADMIN")]
+#[poem::handler]
+async fn admin() -> &'static str {
+    "Hi!"
+}
+
+fn app(extractor: &MockExtractor) -> impl Endpoint {
+    Route::new()
+        .at("/admin", get(admin))
+        .with(GrantsMiddleware::with_extractor(extractor.clone()))
+}
+
+async fn get_status<E: Endpoint>(request: TestRequestBuilder<'_, E>) -> StatusCode {
+    request.send().await.0.status()
+}
+
+#[tokio::test]
+async fn test_with_authorities() {
+    let extractor = MockExtractor::new();
+    let client = TestClient::new(app(&extractor));
+
+    let request = client.get("/admin").with_authorities([ROLE_ADMIN]);
+    assert_eq!(StatusCode::OK, get_status(request).await);
+    let request = client.get("/admin").with_authorities(["ROLE_USER"]);
+    assert_eq!(StatusCode::FORBIDDEN, get_status(request).await);
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(client.get("/admin")).await
+    );
+
+    let calls = extractor.calls();
+    assert_eq!(3, calls.len());
+    assert_eq!("GET", calls[0].method());
+    assert_eq!("/admin", calls[0].path());
+    assert_eq!(
+        Some(&HashSet::from([ROLE_ADMIN.to_string()])),
+        calls[0].authorities()
+    );
+    assert_eq!(None, calls[2].authorities());
+}
+
+#[tokio::test]
+async fn test_scripted_results() {
+    let extractor = MockExtractor::new();
+    extractor
+        .then_return([ROLE_ADMIN])
+        .then_fail(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    let client = TestClient::new(app(&extractor));
+
+    assert_eq!(StatusCode::OK, get_status(client.get("/admin")).await);
+    let request = client.get("/admin").with_authorities([ROLE_ADMIN]);
+    assert_eq!(StatusCode::UNAUTHORIZED, get_status(request).await);
+    let request = client.get("/admin").with_authorities([ROLE_ADMIN]);
+    assert_eq!(StatusCode::OK, get_status(request).await);
+    assert_eq!(3, extractor.calls().len());
+}
@@ -0,0 +1,264 @@
+//! Report of the found handlers: an aligned table, Markdown or JSON.
+
+use std::fmt;
+
+/// Handler found in the sources.
+#[derive(Debug)]
+pub(crate) struct Route {
+    /// Name of the handler within the file, e.g. `api::Api::users`.
+    pub(crate) handler: String,
+    pub(crate) file: String,
+    pub(crate) line: usize,
+    pub(crate) framework: Option<&'static str>,
+    /// Empty if the route is defined outside the handler (e.g. `axum` or `salvo` routers).
+    pub(crate) methods: Vec<String>,
+    pub(crate) path: Option<String>,
+    pub(crate) protection: Protection,
+}
+
+#[derive(Debug)]
+pub(crate) enum Protection {
+    /// `condition` is empty if the handler checks only conditions depending on the request.
+    Protected {
+        condition: String,
+        authority_type: String,
+        dynamic: bool,
+    },
+    Public,
+    /// Neither `protect` nor `public` attribute.
+    Unprotected,
+    /// Arguments of `protect` attribute can't be parsed, so the handler doesn't compile.
+    Invalid(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Format {
+    Text,
+    Markdown,
+    Json,
+}
+
+impl Route {
+    fn location(&self) -> String {
+        format!("{}:{}", self.file, self.line)
+    }
+
+    fn methods(&self) -> String {
+        match self.methods.as_slice() {
+            [] => "*".to_string(),
+            methods => methods.join(","),
+        }
+    }
+
+    fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or("-")
+    }
+
+    /// Whether the route should be reported as a failure.
+    pub(crate) fn is_unprotected(&self) -> bool {
+        matches!(
+            self.protection,
+            Protection::Unprotected | Protection::Invalid(_)
+        )
+    }
+}
+
+impl fmt::Display for Protection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protection::Protected {
+                condition,
+                dynamic: true,
+                ..
+            } if condition.is_empty() => f.write_str("dynamic"),
+            Protection::Protected {
+                condition, dynamic, ..
+            } => {
+                f.write_str(condition)?;
+                if *dynamic {
+                    f.write_str(" (+ dynamic)")?;
+                }
+                Ok(())
+            }
+            Protection::Public => f.write_str("public"),
+            Protection::Unprotected => f.write_str("UNPROTECTED"),
+            Protection::Invalid(err) => write!(f, "invalid: {err}"),
+        }
+    }
+}
+
+pub(crate) fn render(routes: &[Route], format: Format) -> String {
+    match format {
+        Format::Text => to_text(routes),
+        Format::Markdown => to_markdown(routes),
+        Format::Json => to_json(routes),
+    }
+}
+
+const HEADER: [&str; 5] = ["location", "handler", "methods", "path", "condition"];
+
+fn columns(route: &Route) -> [String; 5] {
+    [
+        route.location(),
+        route.handler.clone(),
+        route.methods(),
+        route.path().to_string(),
+        route.protection.to_string(),
+    ]
+}
+
+fn to_text(routes: &[Route]) -> String {
+    let rows: Vec<[String; 5]> = std::iter::once(HEADER.map(str::to_uppercase))
+        .chain(routes.iter().map(columns))
+        .collect();
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for row in &rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(column, width)| format!("{column:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    let unprotected = routes.iter().filter(|route| route.is_unprotected()).count();
+    out.push_str(&format!(
+        "\n{} handlers, {unprotected} unprotected\n",
+        routes.len()
+    ));
+
+    out
+}
+
+fn to_markdown(routes: &[Route]) -> String {
+    let row = |columns: &[String]| {
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|column| column.replace('|', "\\|"))
+            .collect();
+        format!("| {} |\n", columns.join(" | "))
+    };
+
+    let mut out = row(&HEADER.map(str::to_string));
+    out.push_str(&format!("|{}\n", " --- |".repeat(HEADER.len())));
+    for route in routes {
+        let mut columns = columns(route);
+        columns[0] = format!("`{}`", columns[0]);
+        columns[1] = format!("`{}`", columns[1]);
+        out.push_str(&row(&columns));
+    }
+
+    out
+}
+
+fn to_json(routes: &[Route]) -> String {
+    let routes: Vec<_> = routes
+        .iter()
+        .map(|route| {
+            let methods: Vec<_> = route.methods.iter().map(|m| json_string(m)).collect();
+            let protection = match &route.protection {
+                Protection::Protected {
+                    condition,
+                    authority_type,
+                    dynamic,
+                } => format!(
+                    r#""protection":"protected","condition":{},"authority_type":{},"dynamic":{dynamic}"#,
+                    json_string(condition),
+                    json_string(authority_type),
+                ),
+                Protection::Public => r#""protection":"public""#.to_string(),
+                Protection::Unprotected => r#""protection":"unprotected""#.to_string(),
+                Protection::Invalid(err) => {
+                    format!(r#""protection":"invalid","error":{}"#, json_string(err))
+                }
+            };
+
+            format!(
+                r#"{{"handler":{},"file":{},"line":{},"framework":{},"methods":[{}],"path":{},{protection}}}"#,
+                json_string(&route.handler),
+                json_string(&route.file),
+                route.line,
+                route.framework.map_or("null".to_string(), json_string),
+                methods.join(","),
+                route.path.as_deref().map_or("null".to_string(), json_string),
+            )
+        })
+        .collect();
+
+    format!("[{}]\n", routes.join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if ch.is_control() => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes() -> Vec<Route> {
+        vec![
+            Route {
+                handler: "users".to_string(),
+                file: "src/main.rs".to_string(),
+                line: 6,
+                framework: Some("axum"),
+                methods: vec![],
+                path: None,
+                protection: Protection::Protected {
+                    condition: "ADMIN || EDITOR".to_string(),
+                    authority_type: "String".to_string(),
+                    dynamic: false,
+                },
+            },
+            Route {
+                handler: "forgotten".to_string(),
+                file: "src/main.rs".to_string(),
+                line: 12,
+                framework: None,
+                methods: vec!["GET".to_string()],
+                path: Some("/forgotten".to_string()),
+                protection: Protection::Unprotected,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            "LOCATION        HANDLER    METHODS  PATH        CONDITION\n\
+             src/main.rs:6   users      *        -           ADMIN || EDITOR\n\
+             src/main.rs:12  forgotten  GET      /forgotten  UNPROTECTED\n\
+             \n\
+             2 handlers, 1 unprotected\n",
+            render(&routes(), Format::Text)
+        );
+        assert!(render(&routes(), Format::Markdown)
+            .contains("| `src/main.rs:6` | `users` | * | - | ADMIN \\|\\| EDITOR |\n"));
+        assert!(render(&routes(), Format::Json).starts_with(
+            r#"[{"handler":"users","file":"src/main.rs","line":6,"framework":"axum","methods":[],"path":null,"protection":"protected","condition":"ADMIN || EDITOR","authority_type":"String","dynamic":false},"#
+        ));
+    }
+}
@@ -0,0 +1,467 @@
+//! Discovery of handlers in the source files: functions (and methods of `#[OpenApi]` implementations)
+//! annotated with route attributes (`#[get("/")]`, `#[rocket::post("/")]`, `#[oai(path = "/")]`),
+//! `#[handler]`, `#[protect]` or `#[public]`.
+
+use crate::cli::report::{Protection, Route};
+use crate::expand::{FnType, Framework, ProtectAll, ProtectEndpoint, ProtectionArgs};
+use darling::ast::NestedMeta;
+use darling::FromMeta;
+use syn::{Attribute, ImplItem, Item, Meta};
+
+const METHODS: &[&str] = &[
+    "get", "post", "put", "delete", "patch", "head", "options", "trace", "connect",
+];
+
+/// Frameworks by the crates of the paths (or the names of `protect_*` macros).
+const FRAMEWORKS: &[(&str, Framework)] = &[
+    ("actix_web_grants", Framework::ActixWeb),
+    ("protect_actix_web", Framework::ActixWeb),
+    ("actix_web", Framework::ActixWeb),
+    ("protect_axum", Framework::Axum),
+    ("axum", Framework::Axum),
+    ("poem_grants", Framework::Poem),
+    ("protect_poem", Framework::Poem),
+    ("poem_openapi", Framework::Poem),
+    ("poem", Framework::Poem),
+    ("rocket_grants", Framework::Rocket),
+    ("protect_rocket", Framework::Rocket),
+    ("rocket", Framework::Rocket),
+    ("protect_salvo", Framework::Salvo),
+    ("salvo", Framework::Salvo),
+];
+
+/// Scans the source of the file, `file` is used for locations only.
+pub(crate) fn scan(file: &str, source: &str) -> syn::Result<Vec<Route>> {
+    let syntax = syn::parse_file(source)?;
+    let mut scanner = Scanner {
+        file,
+        framework: file_framework(&syntax.items),
+        routes: Vec::new(),
+    };
+    scanner.items(&syntax.items, &[], None);
+
+    Ok(scanner.routes)
+}
+
+struct Scanner<'a> {
+    file: &'a str,
+    /// Framework of the imports, used for attributes without a crate path (e.g. `#[protect]`).
+    framework: Option<Framework>,
+    routes: Vec<Route>,
+}
+
+impl Scanner<'_> {
+    /// `default` is the protection of the enclosing block (`protect_all`), not inherited by nested modules.
+    fn items(&mut self, items: &[Item], scope: &[String], default: Option<&ProtectAll>) {
+        for item in items {
+            match item {
+                Item::Fn(func) => self.handler(scope, FnType::Fn(func.clone()), default),
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        let mut scope = scope.to_vec();
+                        scope.push(module.ident.to_string());
+                        let default = module.attrs.iter().find_map(ProtectAll::from_attr);
+                        self.items(items, &scope, default.as_ref());
+                    }
+                }
+                Item::Impl(item_impl) => {
+                    let mut scope = scope.to_vec();
+                    scope.push(
+                        quote::ToTokens::to_token_stream(&item_impl.self_ty)
+                            .to_string()
+                            .replace(' ', ""),
+                    );
+                    let default = item_impl.attrs.iter().find_map(ProtectAll::from_attr);
+                    for item in &item_impl.items {
+                        if let ImplItem::Fn(method) = item {
+                            self.handler(&scope, FnType::Method(method.clone()), default.as_ref());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handler(&mut self, scope: &[String], mut func: FnType, default: Option<&ProtectAll>) {
+        let route = func.attrs().iter().find_map(route);
+        let framework = func
+            .attrs()
+            .iter()
+            .find_map(attr_framework)
+            .or(route.as_ref().and_then(|route| route.framework))
+            .or(self.framework);
+        let invalid = default.and_then(|default| {
+            // plain `async` functions are handlers unless the framework is known to require attributes
+            let framework = framework.unwrap_or(Framework::Axum);
+            default.annotate(framework, &mut func).err()
+        });
+        let attrs = func.attrs();
+        let is_handler = attrs.iter().any(|attr| last_segment(attr) == "handler");
+        let is_protected = attrs.iter().any(is_protect_attr);
+        let is_public = attrs.iter().any(|attr| last_segment(attr) == "public");
+        if route.is_none() && !is_handler && !is_protected && !is_public && invalid.is_none() {
+            return;
+        }
+
+        let (methods, path) = route
+            .map(|route| (route.methods, route.path))
+            .unwrap_or_default();
+        let ident = &func.sig().ident;
+        let protection = if let Some(err) = invalid {
+            Protection::Invalid(err.to_string())
+        } else if is_protected {
+            // the framework affects the generated code only, not the requirement
+            protection(framework.unwrap_or(Framework::Axum), func.clone())
+                .unwrap_or_else(|err| Protection::Invalid(err.to_string()))
+        } else if is_public {
+            Protection::Public
+        } else {
+            Protection::Unprotected
+        };
+
+        self.routes.push(Route {
+            handler: scope
+                .iter()
+                .map(String::as_str)
+                .chain([ident.to_string().as_str()])
+                .collect::<Vec<_>>()
+                .join("::"),
+            file: self.file.to_string(),
+            line: ident.span().start().line,
+            framework: framework.map(|framework| framework.name()),
+            methods,
+            path,
+            protection,
+        });
+    }
+}
+
+/// Expands `protect` attributes one by one starting from the outermost, as the compiler does,
+/// so stacked attributes are combined by the macro itself.
+/// Methods of `#[OpenApi]` implementations are protected by the last attribute.
+fn protection(framework: Framework, mut func: FnType) -> darling::Result<Protection> {
+    loop {
+        let attrs = match &mut func {
+            FnType::Fn(func) => &mut func.attrs,
+            FnType::Method(method) => &mut method.attrs,
+        };
+        let Some(idx) = attrs.iter().position(is_protect_attr) else {
+            unreachable!("the handler is checked for `protect` attributes");
+        };
+        let attr = attrs.remove(idx);
+
+        let args = ProtectionArgs::from_meta(&attr.meta)?;
+        let endpoint = ProtectEndpoint::new(framework, args, func);
+        let summary = endpoint.summary();
+        func = endpoint.into_func();
+
+        if !func.attrs().iter().any(is_protect_attr) {
+            return Ok(Protection::Protected {
+                condition: summary.condition,
+                authority_type: summary.authority_type,
+                dynamic: summary.dynamic,
+            });
+        }
+    }
+}
+
+/// Route defined by the attribute of the handler.
+struct RouteAttr {
+    methods: Vec<String>,
+    path: Option<String>,
+    framework: Option<Framework>,
+}
+
+/// Supports method attributes (`#[get("/")]`), `#[route("/", method = "GET")]` of `actix-web`,
+/// `#[route(GET, uri = "/")]` of `rocket` and `#[oai(path = "/", method = "get")]` of `poem-openapi`.
+fn route(attr: &Attribute) -> Option<RouteAttr> {
+    let name = last_segment(attr);
+    let Meta::List(list) = &attr.meta else {
+        return None;
+    };
+    let args = NestedMeta::parse_meta_list(list.tokens.clone()).ok()?;
+
+    let mut methods = Vec::new();
+    let mut path = None;
+    if METHODS.contains(&name.as_str()) {
+        methods.push(name.to_uppercase());
+        path = Some(first_lit(&args)?);
+    } else if name == "route" || name == "oai" {
+        for arg in &args {
+            match arg {
+                NestedMeta::Meta(Meta::NameValue(meta)) => {
+                    let value = String::from_expr(&meta.value).ok();
+                    if meta.path.is_ident("method") {
+                        methods.extend(value.map(|method| method.to_uppercase()));
+                    } else if meta.path.is_ident("path") || meta.path.is_ident("uri") {
+                        path = value;
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(method)) if name == "route" => {
+                    methods.extend(method.get_ident().map(ToString::to_string));
+                }
+                _ => {}
+            }
+        }
+        if name == "route" {
+            path = path.or_else(|| first_lit(&args));
+        }
+        path.as_ref()?;
+    } else {
+        return None;
+    }
+
+    Some(RouteAttr {
+        methods,
+        path,
+        framework: match name.as_str() {
+            "oai" => Some(Framework::Poem),
+            _ => attr_framework(attr),
+        },
+    })
+}
+
+fn first_lit(args: &[NestedMeta]) -> Option<String> {
+    match args.first()? {
+        NestedMeta::Lit(syn::Lit::Str(lit)) => Some(lit.value()),
+        _ => None,
+    }
+}
+
+/// `protect` attributes are matched by the last segment (as stacked attributes are found by the macro)
+/// or by the names of `protect_*` macros.
+fn is_protect_attr(attr: &Attribute) -> bool {
+    last_segment(attr) == "protect"
+        || FRAMEWORKS
+            .iter()
+            .any(|(name, _)| name.starts_with("protect_") && attr.path().is_ident(name))
+}
+
+fn last_segment(attr: &Attribute) -> String {
+    attr.path()
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+fn attr_framework(attr: &Attribute) -> Option<Framework> {
+    let path = attr.path();
+    let first = path.segments.first()?.ident.to_string();
+    // a single segment is the name of the attribute (unless it's a `protect_*` macro)
+    if path.segments.len() == 1 && !first.starts_with("protect_") {
+        return None;
+    }
+
+    framework(&first)
+}
+
+fn framework(krate: &str) -> Option<Framework> {
+    FRAMEWORKS
+        .iter()
+        .find(|(name, _)| *name == krate)
+        .map(|(_, framework)| *framework)
+}
+
+/// Framework of the first import of an integration crate, falling back to the first import of a framework.
+fn file_framework(items: &[Item]) -> Option<Framework> {
+    let roots: Vec<_> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Use(item_use) => use_root(&item_use.tree),
+            _ => None,
+        })
+        .collect();
+
+    roots
+        .iter()
+        .filter(|root| root.ends_with("_grants") || root.starts_with("protect_"))
+        .chain(&roots)
+        .find_map(|root| framework(root))
+}
+
+fn use_root(tree: &syn::UseTree) -> Option<String> {
+    match tree {
+        syn::UseTree::Path(path) => Some(path.ident.to_string()),
+        syn::UseTree::Name(name) => Some(name.ident.to_string()),
+        syn::UseTree::Rename(rename) => Some(rename.ident.to_string()),
+        syn::UseTree::Glob(_) | syn::UseTree::Group(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+use actix_web_grants::protect;
+
+#[get("/users")]
+#[protect("ROLE_ADMIN", "OP_READ_USERS")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[actix_web::post("/articles/{id}")]
+#[protect(expr = "id > 0")]
+#[protect(any("ROLE_ADMIN", "ROLE_EDITOR"))]
+async fn article(id: Path<u32>) -> &'static str {
+    "Hi!"
+}
+
+#[route("/health", method = "GET", method = "HEAD")]
+#[actix_web_grants::public]
+async fn health() -> &'static str {
+    "Ok"
+}
+
+#[get("/forgotten")]
+async fn forgotten() -> &'static str {
+    "Oops!"
+}
+
+#[get("/invalid")]
+#[protect(rule = "A ||")]
+async fn invalid() -> &'static str {
+    "Oops!"
+}
+
+fn helper() {}
+
+mod api {
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/admin", method = "get")]
+        #[protect("Role::Admin", ty = "Role")]
+        async fn admin(&self) -> PlainText<String> {
+            PlainText("Hi!".to_string())
+        }
+
+        fn helper(&self) {}
+    }
+}
+
+#[rocket_grants::protect(rule = "ADMIN || during('mon-fri 09:00-18:00')")]
+#[rocket::route(DELETE, uri = "/cache")]
+async fn clear_cache() {}
+"#;
+
+    #[test]
+    fn test_scan() {
+        let routes = scan("src/main.rs", SOURCE).unwrap();
+        let summary: Vec<_> = routes
+            .iter()
+            .map(|route| {
+                (
+                    route.handler.as_str(),
+                    route.methods.join("|"),
+                    route.path.clone().unwrap_or_default(),
+                    route.framework.unwrap_or_default(),
+                    route.protection.to_string(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                (
+                    "users",
+                    "GET".to_string(),
+                    "/users".to_string(),
+                    "actix-web",
+                    "ROLE_ADMIN && OP_READ_USERS".to_string()
+                ),
+                (
+                    "article",
+                    "POST".to_string(),
+                    "/articles/{id}".to_string(),
+                    "actix-web",
+                    "ROLE_ADMIN || ROLE_EDITOR (+ dynamic)".to_string()
+                ),
+                (
+                    "health",
+                    "GET|HEAD".to_string(),
+                    "/health".to_string(),
+                    "actix-web",
+                    "public".to_string()
+                ),
+                (
+                    "forgotten",
+                    "GET".to_string(),
+                    "/forgotten".to_string(),
+                    "actix-web",
+                    "UNPROTECTED".to_string()
+                ),
+                (
+                    "api::Api::admin",
+                    "GET".to_string(),
+                    "/admin".to_string(),
+                    "poem",
+                    "Role::Admin".to_string()
+                ),
+                (
+                    "clear_cache",
+                    "DELETE".to_string(),
+                    "/cache".to_string(),
+                    "rocket",
+                    "ADMIN || during('Mon-Fri 09:00-18:00 UTC')".to_string()
+                ),
+            ],
+            summary
+                .into_iter()
+                .filter(|(handler, ..)| *handler != "invalid")
+                .collect::<Vec<_>>()
+        );
+
+        let invalid = routes
+            .iter()
+            .find(|route| route.handler == "invalid")
+            .unwrap();
+        assert!(
+            matches!(invalid.protection, Protection::Invalid(ref err) if err.contains("invalid rule"))
+        );
+        assert_eq!(6, routes[0].line);
+    }
+
+    #[test]
+    fn test_scan_protect_all() {
+        let source = r#"
+#[protect_axum::protect_all("ROLE_ADMIN")]
+mod admin {
+    pub async fn users() {}
+
+    #[protect("OP_WRITE")]
+    pub async fn update() {}
+
+    #[protect("ROLE_MANAGER", overrides)]
+    pub async fn report() {}
+
+    #[public]
+    pub async fn health() {}
+
+    fn helper() {}
+
+    #[protect_all(skip)]
+    async fn load() {}
+}
+"#;
+        let routes = scan("src/admin.rs", source).unwrap();
+        let summary: Vec<_> = routes
+            .iter()
+            .map(|route| (route.handler.as_str(), route.protection.to_string()))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("admin::users", "ROLE_ADMIN".to_string()),
+                ("admin::update", "ROLE_ADMIN && OP_WRITE".to_string()),
+                ("admin::report", "ROLE_MANAGER".to_string()),
+                ("admin::health", "public".to_string()),
+            ],
+            summary
+        );
+    }
+}
@@ -1,19 +1,41 @@
 use darling::ast::NestedMeta;
-use proc_macro2::{Ident, TokenStream as TokenStream2};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use protect_endpoints_core::condition::Schedule;
+use protect_endpoints_core::network::Networks;
 use quote::{quote, ToTokens};
 use std::ops::Deref;
 use syn::{Block, ItemFn, Meta};
 
 #[cfg(feature = "actix-web")]
 mod actix_web;
+mod anonymous;
 #[cfg(feature = "axum")]
 mod axum;
+mod error;
+mod explain;
+mod network;
+#[cfg(feature = "poem-openapi")]
+mod openapi;
 #[cfg(feature = "poem")]
 mod poem;
+mod principal;
+mod protect_all;
+mod public;
+mod registry;
+mod requirement;
 #[cfg(feature = "rocket")]
 mod rocket;
+mod rule;
 #[cfg(feature = "salvo")]
 mod salvo;
+#[cfg(feature = "salvo-oapi")]
+mod salvo_oapi;
+
+#[cfg(feature = "poem-openapi")]
+pub(crate) use openapi::OpenApiArgs;
+pub(crate) use protect_all::ProtectAll;
+pub(crate) use public::PublicEndpoint;
+pub(crate) use requirement::requirement_fn_ident;
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum Framework {
@@ -32,7 +54,6 @@ pub(crate) enum Framework {
 #[derive(Debug, Clone)]
 pub(crate) enum FnType {
     Fn(ItemFn),
-    #[cfg(feature = "poem")]
     Method(syn::ImplItemFn),
 }
 
@@ -40,7 +61,16 @@ pub(crate) enum FnType {
 enum Condition {
     Any(Conditions),
     All(Conditions),
-    Expr(syn::Expr),
+    Not(Box<Condition>),
+    AtLeast(usize, Conditions),
+    /// Expression with its source for explanations.
+    Expr(syn::Expr, String),
+    /// Handler parameter (or its field) compared with the subject of the principal (`owner = "user_id"`).
+    Owner(syn::Expr),
+    /// Schedule of the current time (`during = "Mon-Fri 09:00-18:00 UTC"`), validated on expansion.
+    During(syn::LitStr),
+    /// Networks of the client address (`from_network = "10.0.0.0/8"`), validated on expansion.
+    Network(syn::LitStr),
     Value(syn::LitStr),
 }
 
@@ -51,7 +81,19 @@ struct Conditions(Vec<Condition>);
 pub(crate) struct ProtectionArgs {
     cond: Condition,
     ty: Option<syn::Expr>,
-    error_fn: Option<Ident>,
+    error_fn: Option<error::ErrorFn>,
+    /// Whether the anonymous principal (or a request without authorities) is checked
+    /// against the condition instead of being rejected as unauthorized.
+    allow_anonymous: bool,
+    /// Type of claims of the principal available to `expr` conditions.
+    principal: Option<syn::Type>,
+    /// Condition forwarded by outer `protect` attributes of the same handler.
+    outer: Option<requirement::Reflection>,
+    /// Security scheme required by the documented endpoint (`salvo-oapi` only).
+    security: Option<syn::LitStr>,
+    /// Whether required authorities are declared as scopes of the `security`.
+    #[cfg_attr(not(feature = "salvo-oapi"), allow(dead_code))]
+    scopes: bool,
 }
 
 pub(crate) struct ProtectEndpoint {
@@ -62,16 +104,102 @@ pub(crate) struct ProtectEndpoint {
 
 impl ProtectEndpoint {
     pub fn new(framework: Framework, args: ProtectionArgs, func: FnType) -> Self {
-        Self {
+        let mut endpoint = Self {
             framework,
             func,
             args,
+        };
+        endpoint.forward_requirement();
+        endpoint
+    }
+
+    /// The handler with the requirement forwarded to the next `protect` attribute (if any).
+    // used by the source scanner only (`cli` feature)
+    #[allow(dead_code)]
+    pub(crate) fn into_func(self) -> FnType {
+        self.func
+    }
+
+    /// `security` is documented for `#[endpoint]` handlers of `salvo-oapi` only.
+    fn validate_security(&self) -> darling::Result<()> {
+        let Some(security) = &self.args.security else {
+            return Ok(());
+        };
+
+        #[cfg(feature = "salvo-oapi")]
+        #[allow(unreachable_patterns)]
+        match self.framework {
+            Framework::Salvo if self.is_endpoint() => return Ok(()),
+            _ => {}
+        }
+
+        Err(darling::Error::custom(
+            "`security` is supported by `#[endpoint]` handlers with the `oapi` feature of `protect-salvo`",
+        )
+        .with_span(security))
+    }
+}
+
+impl Framework {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "actix-web")]
+            Framework::ActixWeb => "actix-web",
+            #[cfg(feature = "poem")]
+            Framework::Poem => "poem",
+            #[cfg(feature = "rocket")]
+            Framework::Rocket => "rocket",
+            #[cfg(feature = "axum")]
+            Framework::Axum => "axum",
+            #[cfg(feature = "salvo")]
+            Framework::Salvo => "salvo",
+        }
+    }
+
+    /// Whether handlers are always marked with a route or handler attribute (`#[handler]`, `#[rocket::get("/")]`),
+    /// `actix-web` and `axum` accept plain `async` functions as well.
+    pub(crate) fn has_handler_attrs(&self) -> bool {
+        match self {
+            #[cfg(feature = "actix-web")]
+            Framework::ActixWeb => false,
+            #[cfg(feature = "poem")]
+            Framework::Poem => true,
+            #[cfg(feature = "rocket")]
+            Framework::Rocket => true,
+            #[cfg(feature = "axum")]
+            Framework::Axum => false,
+            #[cfg(feature = "salvo")]
+            Framework::Salvo => true,
+        }
+    }
+
+    /// Path to the crate of the framework integration.
+    fn krate(&self) -> TokenStream2 {
+        match self {
+            #[cfg(feature = "actix-web")]
+            Framework::ActixWeb => quote!(actix_web_grants),
+            #[cfg(feature = "poem")]
+            Framework::Poem => quote!(poem_grants),
+            #[cfg(feature = "rocket")]
+            Framework::Rocket => quote!(rocket_grants),
+            #[cfg(feature = "axum")]
+            Framework::Axum => quote!(protect_axum),
+            #[cfg(feature = "salvo")]
+            Framework::Salvo => quote!(protect_salvo),
         }
     }
 }
 
 impl ToTokens for ProtectEndpoint {
     fn to_tokens(&self, output: &mut TokenStream2) {
+        if let Err(err) = self.validate_security() {
+            output.extend(err.write_errors());
+            return;
+        }
+
+        self.requirement_fn(output);
+        self.registration_item(output);
+
         match self.framework {
             #[cfg(feature = "actix-web")]
             Framework::ActixWeb => self.to_tokens_actix_web(output),
@@ -88,7 +216,22 @@ impl ToTokens for ProtectEndpoint {
 }
 
 impl Condition {
-    fn to_tokens(&self, auth_details: &Ident, is_typed: bool) -> TokenStream2 {
+    /// Results of expressions are stored into variables (see [`Condition::expr_var`]) on evaluation,
+    /// `exprs` is the number of expressions visited before.
+    fn to_tokens(
+        &self,
+        auth_details: &Ident,
+        is_typed: bool,
+        krate: &TokenStream2,
+        exprs: &mut usize,
+    ) -> TokenStream2 {
+        let mut nested_tokens = |nested: &Conditions| -> Vec<_> {
+            nested
+                .iter()
+                .map(|c| c.to_tokens(auth_details, is_typed, krate, exprs))
+                .collect()
+        };
+
         match self {
             Condition::Any(nested) if nested.iter().all(Condition::is_value) => {
                 let vals = nested.iter().map(|c| match c {
@@ -121,20 +264,24 @@ impl Condition {
                 }
             }
             Condition::Any(nested) => {
-                let exprs: Vec<_> = nested
-                    .iter()
-                    .map(|c| c.to_tokens(auth_details, is_typed))
-                    .collect();
+                let exprs = nested_tokens(nested);
 
-                quote! { #(#exprs)||* }
+                quote! { #((#exprs))||* }
             }
             Condition::All(nested) => {
-                let exprs: Vec<_> = nested
-                    .iter()
-                    .map(|c| c.to_tokens(auth_details, is_typed))
-                    .collect();
+                let exprs = nested_tokens(nested);
 
-                quote! { #(#exprs)&&* }
+                quote! { #((#exprs))&&* }
+            }
+            Condition::Not(nested) => {
+                let expr = nested.to_tokens(auth_details, is_typed, krate, exprs);
+
+                quote! { !(#expr) }
+            }
+            Condition::AtLeast(count, nested) => {
+                let exprs = nested_tokens(nested);
+
+                quote! { [#(#exprs),*].iter().filter(|passed| **passed).count() >= #count }
             }
             Condition::Value(val) => {
                 if is_typed {
@@ -144,15 +291,140 @@ impl Condition {
                     quote! { #auth_details.has_authority(#val) }
                 }
             }
-            Condition::Expr(expr) => {
-                quote! { #expr }
+            Condition::Expr(expr, _) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+
+                quote! {{
+                    #var = #expr;
+                    #var
+                }}
+            }
+            Condition::Owner(param) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+                let subject = ProtectEndpoint::owner_subject_ident();
+                let is_owner = Ident::new("is_owner", Span::mixed_site());
+
+                quote! {{
+                    #[allow(clippy::cmp_owned)]
+                    let #is_owner = |subject: &str| subject == (#param).to_string();
+                    #var = #subject.is_some_and(#is_owner);
+                    #var
+                }}
+            }
+            Condition::During(schedule) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+
+                // parsed once per handler, the source is validated by the macro
+                quote! {{
+                    static SCHEDULE: ::std::sync::OnceLock<
+                        ::std::option::Option<#krate::condition::Schedule>,
+                    > = ::std::sync::OnceLock::new();
+                    #var = SCHEDULE
+                        .get_or_init(|| #krate::condition::Schedule::parse(#schedule).ok())
+                        .as_ref()
+                        .is_some_and(|schedule| schedule.is_active());
+                    #var
+                }}
+            }
+            Condition::Network(networks) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+                let client_ip = ProtectEndpoint::client_ip_ident();
+
+                quote! {{
+                    static NETWORKS: ::std::sync::OnceLock<
+                        ::std::option::Option<#krate::network::Networks>,
+                    > = ::std::sync::OnceLock::new();
+                    #var = NETWORKS
+                        .get_or_init(|| #krate::network::Networks::parse(#networks).ok())
+                        .as_ref()
+                        .is_some_and(|networks| #client_ip.is_from(networks));
+                    #var
+                }}
             }
         }
     }
 
+    fn expr_var(idx: usize) -> Ident {
+        Ident::new(&format!("expr_{idx}"), proc_macro2::Span::mixed_site())
+    }
+
     fn is_value(&self) -> bool {
         matches!(self, Condition::Value(_))
     }
+
+    fn from_expr(value: &syn::Expr) -> darling::Result<Self> {
+        let expr = darling::FromMeta::from_expr(value)?;
+        let source = match value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(source),
+                ..
+            }) => source.value(),
+            other => other.to_token_stream().to_string(),
+        };
+
+        Ok(Condition::Expr(expr, source))
+    }
+
+    fn from_owner(value: &syn::Expr) -> darling::Result<Self> {
+        darling::FromMeta::from_expr(value).map(Condition::Owner)
+    }
+
+    fn from_during(value: &syn::Expr) -> darling::Result<Self> {
+        let schedule: syn::LitStr = darling::FromMeta::from_expr(value)?;
+        Schedule::parse(&schedule.value()).map_err(|err| {
+            darling::Error::custom(format!("invalid schedule: {err}")).with_span(&schedule)
+        })?;
+
+        Ok(Condition::During(schedule))
+    }
+
+    fn from_network(value: &syn::Expr) -> darling::Result<Self> {
+        let networks: syn::LitStr = darling::FromMeta::from_expr(value)?;
+        Networks::parse(&networks.value()).map_err(|err| {
+            darling::Error::custom(format!("invalid network: {err}")).with_span(&networks)
+        })?;
+
+        Ok(Condition::Network(networks))
+    }
+
+    /// Whether the condition compares parameters with the subject of the principal.
+    fn has_owner(&self) -> bool {
+        match self {
+            Condition::Owner(_) => true,
+            Condition::Not(nested) => nested.has_owner(),
+            Condition::Any(nested) | Condition::All(nested) | Condition::AtLeast(_, nested) => {
+                nested.iter().any(Condition::has_owner)
+            }
+            Condition::Expr(..)
+            | Condition::During(_)
+            | Condition::Network(_)
+            | Condition::Value(_) => false,
+        }
+    }
+
+    /// Whether the condition checks the client address.
+    fn has_network(&self) -> bool {
+        match self {
+            Condition::Network(_) => true,
+            Condition::Not(nested) => nested.has_network(),
+            Condition::Any(nested) | Condition::All(nested) | Condition::AtLeast(_, nested) => {
+                nested.iter().any(Condition::has_network)
+            }
+            Condition::Expr(..)
+            | Condition::Owner(_)
+            | Condition::During(_)
+            | Condition::Value(_) => false,
+        }
+    }
+
+    fn from_rule(meta: &Meta) -> darling::Result<Self> {
+        let rule: syn::LitStr = darling::FromMeta::from_meta(meta)?;
+        rule::parse(&rule).map_err(darling::Error::from)
+    }
 }
 
 impl darling::FromMeta for Condition {
@@ -167,12 +439,38 @@ impl darling::FromMeta for Condition {
                     "all" => Ok(Condition::All(
                         darling::FromMeta::from_meta(meta).map_err(|e| e.at("all"))?,
                     )),
-                    "expr" => Ok(Condition::Expr(
-                        darling::FromMeta::from_meta(meta).map_err(|e| e.at("expr"))?,
-                    )),
+                    "expr" => match meta {
+                        Meta::NameValue(meta) => Condition::from_expr(&meta.value),
+                        _ => Err(darling::Error::unsupported_format("list")),
+                    }
+                    .map_err(|e| e.at("expr")),
+                    "owner" => match meta {
+                        Meta::NameValue(meta) => Condition::from_owner(&meta.value),
+                        _ => Err(darling::Error::unsupported_format("list")),
+                    }
+                    .map_err(|e| e.at("owner")),
+                    "during" => match meta {
+                        Meta::NameValue(meta) => Condition::from_during(&meta.value),
+                        _ => Err(darling::Error::unsupported_format("list")),
+                    }
+                    .map_err(|e| e.at("during")),
+                    "from_network" => match meta {
+                        Meta::NameValue(meta) => Condition::from_network(&meta.value),
+                        _ => Err(darling::Error::unsupported_format("list")),
+                    }
+                    .map_err(|e| e.at("from_network")),
+                    "rule" => Condition::from_rule(meta).map_err(|e| e.at("rule")),
                     other => Err(darling::Error::unknown_field_with_alts(
                         other,
-                        &["any", "all", "expr"],
+                        &[
+                            "any",
+                            "all",
+                            "expr",
+                            "owner",
+                            "during",
+                            "from_network",
+                            "rule",
+                        ],
                     )
                     .with_span(meta)),
                 }
@@ -192,7 +490,7 @@ impl darling::FromMeta for Conditions {
         let mut expressions = Vec::new();
         for item in items {
             let expr = match item {
-                nested @ NestedMeta::Meta(_) => Condition::from_list(&[nested.clone()])?,
+                nested @ NestedMeta::Meta(_) => Condition::from_list(std::slice::from_ref(nested))?,
                 NestedMeta::Lit(lit) => Condition::Value(darling::FromMeta::from_value(lit)?),
             };
             expressions.push(expr);
@@ -207,6 +505,11 @@ impl darling::FromMeta for ProtectionArgs {
         let mut conditions = Vec::new();
         let mut ty = None;
         let mut error_fn = None;
+        let mut allow_anonymous = false;
+        let mut principal = None;
+        let mut outer = None;
+        let mut security = None;
+        let mut scopes = false;
 
         let mut errors = ::darling::Error::accumulator();
 
@@ -225,10 +528,49 @@ impl darling::FromMeta for ProtectionArgs {
                         } else {
                             error_fn = errors.handle(darling::FromMeta::from_expr(value));
                         }
+                    } else if path.is_ident("principal") {
+                        if principal.is_some() {
+                            errors.push(darling::Error::duplicate_field("principal"));
+                        } else {
+                            principal = errors.handle(darling::FromMeta::from_expr(value));
+                        }
                     } else if path.is_ident("expr") {
-                        let cond = errors
-                            .handle(darling::FromMeta::from_expr(value))
-                            .map(Condition::Expr);
+                        let cond = errors.handle(Condition::from_expr(value));
+                        if let Some(cond) = cond {
+                            conditions.push(cond);
+                        }
+                    } else if path.is_ident("owner") {
+                        let cond = errors.handle(Condition::from_owner(value));
+                        if let Some(cond) = cond {
+                            conditions.push(cond);
+                        }
+                    } else if path.is_ident("during") {
+                        let cond = errors.handle(Condition::from_during(value));
+                        if let Some(cond) = cond {
+                            conditions.push(cond);
+                        }
+                    } else if path.is_ident("from_network") {
+                        let cond = errors.handle(Condition::from_network(value));
+                        if let Some(cond) = cond {
+                            conditions.push(cond);
+                        }
+                    } else if path.is_ident("security") {
+                        if security.is_some() {
+                            errors.push(darling::Error::duplicate_field("security"));
+                        } else {
+                            security = errors.handle(darling::FromMeta::from_expr(value));
+                        }
+                    } else if path.is_ident(requirement::OUTER_ARG) {
+                        outer = errors.handle(
+                            darling::FromMeta::from_expr(value)
+                                .and_then(|rule| requirement::parse(&rule)),
+                        );
+                    } else if path.is_ident("rule") {
+                        let cond = errors.handle(
+                            darling::FromMeta::from_expr(value).and_then(|rule| {
+                                rule::parse(&rule).map_err(darling::Error::from)
+                            }),
+                        );
                         if let Some(cond) = cond {
                             conditions.push(cond);
                         }
@@ -236,9 +578,27 @@ impl darling::FromMeta for ProtectionArgs {
                         errors.push(darling::Error::unknown_field_path(path));
                     }
                 }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("allow_anonymous") => {
+                    if allow_anonymous {
+                        errors.push(darling::Error::duplicate_field("allow_anonymous"));
+                    }
+                    allow_anonymous = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("scopes") => {
+                    if scopes {
+                        errors.push(darling::Error::duplicate_field("scopes"));
+                    }
+                    scopes = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("principal") => {
+                    if principal.is_some() {
+                        errors.push(darling::Error::duplicate_field("principal"));
+                    }
+                    principal = Some(syn::parse_quote!(()));
+                }
                 // List may mean either `any` or `all` conditions, so we should try to parse it
                 NestedMeta::Meta(Meta::List(_)) => {
-                    let cond = errors.handle(darling::FromMeta::from_list(&[item.clone()]));
+                    let cond = errors.handle(darling::FromMeta::from_list(std::slice::from_ref(item)));
                     if let Some(cond) = cond {
                         conditions.push(cond);
                     }
@@ -252,7 +612,7 @@ impl darling::FromMeta for ProtectionArgs {
                     }
                 }
                 _ => errors.push(darling::Error::custom(
-                    "Unknown attribute, available: 'ty', `all`, `any`, `expr` and string literals",
+                    "Unknown attribute, available: 'ty', `all`, `any`, `expr`, `owner`, `during`, `from_network`, `rule`, `allow_anonymous`, `principal`, `security`, `scopes` and string literals",
                 )),
             }
         }
@@ -271,7 +631,28 @@ impl darling::FromMeta for ProtectionArgs {
             Condition::All(Conditions(conditions))
         };
 
-        Ok(ProtectionArgs { cond, ty, error_fn })
+        if allow_anonymous && (principal.is_some() || cond.has_owner()) {
+            return Err(darling::Error::custom(
+                "`principal` and `owner` can't be used with `allow_anonymous`, anonymous users have no principal",
+            ));
+        }
+
+        if scopes && security.is_none() {
+            return Err(darling::Error::custom(
+                "`scopes` are declared for the `security` scheme, e.g. `security = \"bearer\"`",
+            ));
+        }
+
+        Ok(ProtectionArgs {
+            cond,
+            ty,
+            error_fn,
+            allow_anonymous,
+            principal,
+            outer,
+            security,
+            scopes,
+        })
     }
 }
 
@@ -284,10 +665,9 @@ impl Deref for Conditions {
 }
 
 impl FnType {
-    fn sig(&self) -> &syn::Signature {
+    pub(crate) fn sig(&self) -> &syn::Signature {
         match self {
             FnType::Fn(fun) => &fun.sig,
-            #[cfg(feature = "poem")]
             FnType::Method(fun) => &fun.sig,
         }
     }
@@ -295,7 +675,6 @@ impl FnType {
     fn vis(&self) -> &syn::Visibility {
         match self {
             FnType::Fn(fun) => &fun.vis,
-            #[cfg(feature = "poem")]
             FnType::Method(fun) => &fun.vis,
         }
     }
@@ -303,16 +682,21 @@ impl FnType {
     fn block(&self) -> &Block {
         match self {
             FnType::Fn(fun) => &fun.block,
-            #[cfg(feature = "poem")]
             FnType::Method(fun) => &fun.block,
         }
     }
 
-    fn attrs(&self) -> &Vec<syn::Attribute> {
+    pub(crate) fn attrs(&self) -> &Vec<syn::Attribute> {
         match self {
             FnType::Fn(fun) => &fun.attrs,
-            #[cfg(feature = "poem")]
             FnType::Method(fun) => &fun.attrs,
         }
     }
+
+    pub(crate) fn attrs_mut(&mut self) -> &mut Vec<syn::Attribute> {
+        match self {
+            FnType::Fn(fun) => &mut fun.attrs,
+            FnType::Method(fun) => &mut fun.attrs,
+        }
+    }
 }
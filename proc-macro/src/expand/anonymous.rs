@@ -0,0 +1,58 @@
+//! Anonymous access (`allow_anonymous`).
+//!
+//! `AuthDetails` of the framework crates reject requests of the anonymous principal (and without authorities),
+//! so handlers allowing anonymous access extract optional details and fall back to `AuthDetails::anonymous()`.
+
+use super::ProtectEndpoint;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+
+impl ProtectEndpoint {
+    /// Type of the `AuthDetails<T>` argument added to the handler.
+    pub(super) fn auth_details_ty(&self) -> TokenStream2 {
+        let details = self.details_ty();
+
+        if !self.args.allow_anonymous {
+            return details;
+        }
+
+        match self.framework {
+            #[cfg(feature = "salvo")]
+            super::Framework::Salvo => {
+                let krate = self.framework.krate();
+                let ty = self.authority_ty();
+                quote!(#krate::authorities::OptionalAuthDetails<#ty>)
+            }
+            #[allow(unreachable_patterns)]
+            _ => quote!(::std::option::Option<#details>),
+        }
+    }
+
+    /// Statement shadowing the optional argument by the anonymous details if `allow_anonymous` is set.
+    pub(super) fn anonymous_tokens(&self, auth_details: &Ident) -> TokenStream2 {
+        if !self.args.allow_anonymous {
+            return quote!();
+        }
+
+        let details = self.details_ty();
+        quote! {
+            let #auth_details = ::std::option::Option::<#details>::from(#auth_details)
+                .unwrap_or_else(<#details>::anonymous);
+        }
+    }
+
+    fn details_ty(&self) -> TokenStream2 {
+        let krate = self.framework.krate();
+        let ty = self.authority_ty();
+        quote!(#krate::authorities::AuthDetails<#ty>)
+    }
+
+    /// Type of authorities, `String` by default.
+    pub(super) fn authority_ty(&self) -> TokenStream2 {
+        self.args
+            .ty
+            .as_ref()
+            .map(ToTokens::to_token_stream)
+            .unwrap_or(quote! {String})
+    }
+}
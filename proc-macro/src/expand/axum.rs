@@ -24,37 +24,52 @@ impl ProtectEndpoint {
         let auth_details = format!("_auth_details_{}", fn_args.len());
         let auth_details: Ident = Ident::new(&auth_details, Span::call_site());
 
-        let ty = self
-            .args
-            .ty
-            .as_ref()
-            .map(ToTokens::to_token_stream)
-            .unwrap_or(quote! {String});
+        let auth_details_ty = self.auth_details_ty();
+        let anonymous = self.anonymous_tokens(&auth_details);
+        let check = self.check_tokens(&auth_details);
+        let passed = Self::passed_ident();
+        let denied = Self::denied_ident();
 
-        let condition = self
-            .args
-            .cond
-            .to_tokens(&auth_details, self.args.ty.is_some());
-        let condition = quote!(if #condition);
+        let principal_arg = self
+            .principal_arg(fn_args.len())
+            .map(|principal| quote!(#principal,));
+        let principal = self.principal_tokens(fn_args.len());
 
-        let err_resp = if let Some(expr) = &self.args.error_fn {
-            quote!(#expr())
-        } else {
-            quote!(axum::http::StatusCode::FORBIDDEN)
-        };
+        let client_ip_arg = self
+            .client_ip_arg(fn_args.len())
+            .map(|client_ip| quote!(#client_ip,));
+        let client_ip = self.client_ip_tokens(fn_args.len());
+
+        let request = self.request_arg(fn_args.len());
+        let request_arg = request
+            .as_ref()
+            .map(|request| quote!(#request: axum::http::request::Parts,));
+
+        let err_resp = self.deny_tokens(
+            &auth_details,
+            request.map(|request| quote!(&#request)),
+            quote!(axum::response::IntoResponse::into_response(#denied)),
+        );
 
         let stream = quote! {
             #(#fn_attrs)*
             #func_vis #fn_async fn #fn_name #fn_generics(
-                #auth_details: protect_axum::authorities::AuthDetails<#ty>,
+                #auth_details: #auth_details_ty,
+                #request_arg
+                #principal_arg
+                #client_ip_arg
                 #fn_args
-            ) -> axum::response::Result<#fn_output> {
+            ) -> ::std::result::Result<#fn_output, axum::response::Response> {
                 use protect_axum::authorities::AuthoritiesCheck;
-                #condition {
+                #anonymous
+                #principal
+                #client_ip
+                #check
+                if #passed {
                     let f = || async move #func_block;
                     Ok(f().await)
                 } else {
-                    Err(#err_resp.into())
+                    Err(axum::response::IntoResponse::into_response(#err_resp))
                 }
             }
         };
@@ -0,0 +1,69 @@
+//! Custom error handlers of denied access (`error = ...`).
+//!
+//! A path (`error = "access_denied"`) is called without arguments,
+//! while an expression can use the context of the denial, e.g.
+//! `error = "access_denied(auth_details, denied, request).await"`:
+//! - `auth_details` - `&AuthDetails<T>` of the user;
+//! - `denied` - `&AccessDenied` of the framework crate with the failed condition;
+//! - `request` - reference to the request of the framework (the extractor is added to the handler,
+//!   except `salvo` handlers which already have `&mut Request`).
+
+use super::ProtectEndpoint;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+
+#[derive(Debug)]
+pub(super) enum ErrorFn {
+    Path(syn::Path),
+    Expr(syn::Expr),
+}
+
+impl darling::FromMeta for ErrorFn {
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        let expr: syn::Expr = darling::FromMeta::from_expr(expr)?;
+
+        Ok(match expr {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None, path, ..
+            }) => ErrorFn::Path(path),
+            expr => ErrorFn::Expr(expr),
+        })
+    }
+}
+
+impl ErrorFn {
+    /// `request` is `None` only if the handler doesn't use the context.
+    pub(super) fn to_tokens(
+        &self,
+        auth_details: &Ident,
+        denied: &Ident,
+        request: Option<TokenStream2>,
+    ) -> TokenStream2 {
+        match self {
+            ErrorFn::Path(path) => quote!(#path()),
+            ErrorFn::Expr(expr) => {
+                let [auth_details_var, denied_var, request_var] =
+                    ["auth_details", "denied", "request"]
+                        .map(|name| Ident::new(name, Span::call_site()));
+
+                quote! {{
+                    #[allow(unused_variables)]
+                    let #auth_details_var = &#auth_details;
+                    #[allow(unused_variables)]
+                    let #denied_var = &#denied;
+                    #[allow(unused_variables)]
+                    let #request_var = #request;
+                    #expr
+                }}
+            }
+        }
+    }
+}
+
+impl ProtectEndpoint {
+    /// Argument to extract the request for the custom error handler, if it uses the context.
+    pub(super) fn request_arg(&self, idx: usize) -> Option<Ident> {
+        matches!(self.args.error_fn, Some(ErrorFn::Expr(_)))
+            .then(|| Ident::new(&format!("_request_{idx}"), Span::call_site()))
+    }
+}
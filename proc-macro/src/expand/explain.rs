@@ -0,0 +1,137 @@
+//! Evaluation of the condition and the denial with its explanation.
+//!
+//! Results of expressions are recorded on evaluation, so the explanation is built only
+//! on denial without evaluating expressions twice (they may move the arguments).
+//! Expressions skipped by short-circuiting don't affect the result, so they are explained as failed.
+
+use super::{Condition, ProtectEndpoint};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+
+impl Condition {
+    fn explanation_tokens(
+        &self,
+        auth_details: &Ident,
+        is_typed: bool,
+        krate: &TokenStream2,
+        exprs: &mut usize,
+    ) -> TokenStream2 {
+        let explanation = quote!(#krate::condition::Explanation);
+        let mut nested_tokens = |nested: &[Condition]| {
+            let nested: Vec<_> = nested
+                .iter()
+                .map(|c| c.explanation_tokens(auth_details, is_typed, krate, exprs))
+                .collect();
+            quote!(::std::vec![#(#nested),*])
+        };
+
+        match self {
+            Condition::Value(val) => {
+                let check = self.to_tokens(auth_details, is_typed, krate, &mut 0);
+                quote!(#explanation::authority(::std::string::String::from(#val), #check))
+            }
+            Condition::Expr(_, source) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+                quote!(#explanation::expr(#source, #var))
+            }
+            Condition::Owner(param) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+                let source = format!("owner = {}", param.to_token_stream());
+                quote!(#explanation::expr(#source, #var))
+            }
+            Condition::During(schedule) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+                quote!(#explanation::during(#schedule, #var))
+            }
+            Condition::Network(networks) => {
+                let var = Condition::expr_var(*exprs);
+                *exprs += 1;
+                let source = format!("from_network = {}", networks.value());
+                quote!(#explanation::expr(#source, #var))
+            }
+            Condition::Not(nested) => {
+                let nested = nested.explanation_tokens(auth_details, is_typed, krate, exprs);
+                quote!(#explanation::not(#nested))
+            }
+            Condition::All(nested) => {
+                let nested = nested_tokens(nested);
+                quote!(#explanation::all(#nested))
+            }
+            Condition::Any(nested) => {
+                let nested = nested_tokens(nested);
+                quote!(#explanation::any(#nested))
+            }
+            Condition::AtLeast(count, nested) => {
+                let nested = nested_tokens(nested);
+                quote!(#explanation::at_least(#count, #nested))
+            }
+        }
+    }
+}
+
+impl ProtectEndpoint {
+    /// Statements evaluating the condition into [`ProtectEndpoint::passed_ident`].
+    pub(super) fn check_tokens(&self, auth_details: &Ident) -> TokenStream2 {
+        let mut exprs = 0;
+        let krate = self.framework.krate();
+        let condition =
+            self.args
+                .cond
+                .to_tokens(auth_details, self.args.ty.is_some(), &krate, &mut exprs);
+        let vars = (0..exprs).map(Condition::expr_var);
+        let passed = Self::passed_ident();
+
+        quote! {
+            #(
+                #[allow(unused_assignments)]
+                let mut #vars = false;
+            )*
+            let #passed = #condition;
+        }
+    }
+
+    /// Expression of the denial: the custom error or the default one
+    /// using the framework's `AccessDenied` from [`ProtectEndpoint::denied_ident`].
+    /// `request` is a reference to the request from [`ProtectEndpoint::request_arg`].
+    pub(super) fn deny_tokens(
+        &self,
+        auth_details: &Ident,
+        request: Option<TokenStream2>,
+        default_err: TokenStream2,
+    ) -> TokenStream2 {
+        let krate = self.framework.krate();
+        let explanation =
+            self.args
+                .cond
+                .explanation_tokens(auth_details, self.args.ty.is_some(), &krate, &mut 0);
+        let required = self.required_tokens();
+        let denied = Self::denied_ident();
+
+        let err = match &self.args.error_fn {
+            Some(error_fn) => error_fn.to_tokens(auth_details, &denied, request),
+            None => default_err,
+        };
+
+        quote! {{
+            let #denied = #krate::error::AccessDenied::new(
+                #required.map(::std::string::String::from),
+                #explanation,
+            );
+            #denied.log();
+            #err
+        }}
+    }
+
+    /// Whether the condition is satisfied (`bool`).
+    pub(super) fn passed_ident() -> Ident {
+        Ident::new("passed", Span::mixed_site())
+    }
+
+    /// Error of the denied access (`AccessDenied` of the framework crate).
+    pub(super) fn denied_ident() -> Ident {
+        Ident::new("denied", Span::mixed_site())
+    }
+}
@@ -0,0 +1,41 @@
+//! Client address for `from_network` conditions (`from_network = "10.0.0.0/8"`).
+//!
+//! The handler extracts `ClientIp` of the framework crate, which is bound (reference)
+//! before the condition is evaluated.
+
+use super::ProtectEndpoint;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+
+impl ProtectEndpoint {
+    /// Argument extracting the client address, if it's used.
+    pub(super) fn client_ip_arg(&self, idx: usize) -> Option<TokenStream2> {
+        if !self.args.cond.has_network() {
+            return None;
+        }
+
+        let krate = self.framework.krate();
+        let arg = Self::client_ip_arg_ident(idx);
+        Some(quote!(#arg: #krate::network::ClientIp))
+    }
+
+    /// Statement binding the client address, if it's used.
+    pub(super) fn client_ip_tokens(&self, idx: usize) -> TokenStream2 {
+        if !self.args.cond.has_network() {
+            return quote!();
+        }
+
+        let arg = Self::client_ip_arg_ident(idx);
+        let client_ip = Self::client_ip_ident();
+        quote!(let #client_ip = &#arg;)
+    }
+
+    /// Client address (reference to `ClientIp`) checked by `from_network` conditions.
+    pub(super) fn client_ip_ident() -> Ident {
+        Ident::new("client_ip", Span::mixed_site())
+    }
+
+    fn client_ip_arg_ident(idx: usize) -> Ident {
+        Ident::new(&format!("_client_ip_{idx}"), Span::call_site())
+    }
+}
@@ -0,0 +1,101 @@
+//! Documentation of `poem-openapi` operations protected within `#[poem_grants::open_api]`.
+
+use super::ProtectEndpoint;
+use darling::FromMeta;
+use syn::{parse_quote, GenericArgument, ImplItemFn, PathArguments, ReturnType, Type};
+
+/// Arguments of the `open_api` macro.
+#[derive(Debug, Default, FromMeta)]
+pub(crate) struct OpenApiArgs {
+    /// `SecurityScheme` required by protected operations.
+    #[darling(default)]
+    security: Option<syn::Path>,
+    /// Whether required typed authorities are declared as OAuth scopes of the `security`.
+    #[darling(default)]
+    scopes: bool,
+}
+
+impl OpenApiArgs {
+    pub(crate) fn validate(self) -> darling::Result<Self> {
+        if self.scopes && self.security.is_none() {
+            return Err(darling::Error::custom(
+                "`scopes` are declared for the `security` scheme, e.g. `security = \"OAuth\"`",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+impl ProtectEndpoint {
+    /// Adds the requirement to the description of the generated method, declares `401` and `403` responses
+    /// (via `AccessError`) and requires the security scheme, if any.
+    pub(crate) fn document_operation(
+        &self,
+        method: &mut ImplItemFn,
+        args: &OpenApiArgs,
+    ) -> darling::Result<()> {
+        let description = self.summary().description();
+        // the first paragraph is the summary of the operation
+        method.attrs.push(parse_quote!(#[doc = ""]));
+        method.attrs.push(parse_quote!(#[doc = #description]));
+
+        if let ReturnType::Type(_, ty) = &mut method.sig.output {
+            let output = ok_type(ty).cloned().ok_or_else(|| {
+                darling::Error::custom("expected `poem::Result` of the generated method")
+            })?;
+            *ty = parse_quote!(
+                ::std::result::Result<#output, poem_grants::openapi::AccessError>
+            );
+        }
+        let block = &method.block;
+        method.block = parse_quote!({
+            (#block).map_err(::std::convert::Into::into)
+        });
+
+        if let Some(security) = &args.security {
+            let scopes = if args.scopes {
+                self.scopes()?
+            } else {
+                Vec::new()
+            };
+            let arg: syn::FnArg = if scopes.is_empty() {
+                parse_quote!(_grants_security: #security)
+            } else {
+                parse_quote!(#[oai(#(scope = #scopes),*)] _grants_security: #security)
+            };
+            method.sig.inputs.push(arg);
+        }
+
+        Ok(())
+    }
+
+    /// Typed authorities required by the condition, empty for other conditions (e.g. `any`),
+    /// since the operation can require only all of its scopes.
+    fn scopes(&self) -> darling::Result<Vec<String>> {
+        if self.args.ty.is_none() {
+            return Err(darling::Error::custom(
+                "`scopes` are derived from typed authorities, e.g. `ty = \"Scope\"`",
+            )
+            .with_span(&self.func.sig().ident));
+        }
+
+        Ok(self.required_authorities())
+    }
+}
+
+/// `T` of `Result<T>`.
+fn ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Result" => {
+            match args.args.first()? {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -14,18 +14,27 @@ impl ProtectEndpoint {
         let fn_generics = &fn_sig.generics;
         let fn_async = &fn_sig.asyncness;
 
-        let ty = self
-            .args
-            .ty
-            .as_ref()
-            .map(ToTokens::to_token_stream)
-            .unwrap_or(quote! {String});
-
         let mut fn_args = fn_sig.inputs.clone();
         let auth_details = format!("_auth_details_{}", fn_args.len());
         let auth_details: Ident = Ident::new(&auth_details, Span::call_site());
 
-        fn_args.push(parse_quote!(#auth_details: poem_grants::authorities::AuthDetails<#ty>));
+        let principal = self.principal_tokens(fn_args.len());
+        if let Some(principal) = self.principal_arg(fn_args.len()) {
+            fn_args.push(parse_quote!(#principal));
+        }
+
+        let client_ip = self.client_ip_tokens(fn_args.len());
+        if let Some(client_ip) = self.client_ip_arg(fn_args.len()) {
+            fn_args.push(parse_quote!(#client_ip));
+        }
+
+        let auth_details_ty = self.auth_details_ty();
+        fn_args.push(parse_quote!(#auth_details: #auth_details_ty));
+
+        let request = self.request_arg(fn_args.len());
+        if let Some(request) = &request {
+            fn_args.push(parse_quote!(#request: &poem::Request));
+        }
 
         let (original_out, fn_output) = match &fn_sig.output {
             ReturnType::Type(ref _arrow, ref ty) => {
@@ -60,19 +69,16 @@ impl ProtectEndpoint {
             ReturnType::Default => (quote! {()}, quote! {()}),
         };
 
-        let condition = self
-            .args
-            .cond
-            .to_tokens(&auth_details, self.args.ty.is_some());
-        let condition = quote!(if #condition);
+        let anonymous = self.anonymous_tokens(&auth_details);
+        let check = self.check_tokens(&auth_details);
+        let passed = Self::passed_ident();
+        let denied = Self::denied_ident();
 
-        let err_resp = if let Some(expr) = &self.args.error_fn {
-            quote!(#expr())
-        } else {
-            quote!(poem::Error::from(
-                poem_grants::error::AccessError::ForbiddenRequest
-            ))
-        };
+        let err_resp = self.deny_tokens(
+            &auth_details,
+            request.map(|request| quote!(#request)),
+            quote!(poem::Error::from(#denied)),
+        );
 
         let body = if fn_async.is_some() {
             quote! {
@@ -95,7 +101,11 @@ impl ProtectEndpoint {
             ) -> poem::Result<#fn_output> {
                 use poem::error::IntoResult;
                 use poem_grants::authorities::AuthoritiesCheck;
-                #condition {
+                #anonymous
+                #principal
+                #client_ip
+                #check
+                if #passed {
                     #body
                 } else {
                     Err(#err_resp)
@@ -0,0 +1,85 @@
+//! Principal of the user for `expr` conditions (`principal = "Claims"`) and `owner` checks.
+//!
+//! With `principal`, the handler extracts `Principal<Claims>` of the framework crate, which is bound
+//! as `principal` (reference) before the condition is evaluated.
+//! `owner` alone extracts an optional principal (`Principal<()>` by default), since its absence just fails
+//! the ownership check, e.g. `any("ADMIN", owner = "user_id")` still passes for admins.
+
+use super::ProtectEndpoint;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+
+impl ProtectEndpoint {
+    /// Argument extracting the principal, if it's used.
+    pub(super) fn principal_arg(&self, idx: usize) -> Option<TokenStream2> {
+        let principal = self.principal_ty()?;
+        let arg = Self::principal_ident(idx);
+
+        if self.args.principal.is_some() {
+            return Some(quote!(#arg: #principal));
+        }
+
+        let optional = match self.framework {
+            #[cfg(feature = "salvo")]
+            super::Framework::Salvo => {
+                let krate = self.framework.krate();
+                let claims = self.claims_ty();
+                quote!(#krate::authorities::OptionalPrincipal<#claims>)
+            }
+            #[allow(unreachable_patterns)]
+            _ => quote!(::std::option::Option<#principal>),
+        };
+        Some(quote!(#arg: #optional))
+    }
+
+    /// Statements binding `principal` and the subject for `owner` checks, if they're used.
+    pub(super) fn principal_tokens(&self, idx: usize) -> TokenStream2 {
+        let Some(principal_ty) = self.principal_ty() else {
+            return quote!();
+        };
+
+        let arg = Self::principal_ident(idx);
+        let subject = Self::owner_subject_ident();
+
+        if self.args.principal.is_some() {
+            let principal = Ident::new("principal", Span::call_site());
+            quote! {
+                #[allow(unused_variables)]
+                let #principal = &#arg;
+                #[allow(unused_variables)]
+                let #subject = ::std::option::Option::Some(#principal.subject());
+            }
+        } else {
+            quote! {
+                let #arg = ::std::option::Option::<#principal_ty>::from(#arg);
+                let #subject = #arg.as_ref().map(|principal| principal.subject());
+            }
+        }
+    }
+
+    /// Subject of the principal (`Option<&str>`) compared by `owner` conditions.
+    pub(super) fn owner_subject_ident() -> Ident {
+        Ident::new("owner_subject", Span::mixed_site())
+    }
+
+    fn principal_ty(&self) -> Option<TokenStream2> {
+        if self.args.principal.is_none() && !self.args.cond.has_owner() {
+            return None;
+        }
+
+        let krate = self.framework.krate();
+        let claims = self.claims_ty();
+        Some(quote!(#krate::authorities::Principal<#claims>))
+    }
+
+    fn claims_ty(&self) -> TokenStream2 {
+        match &self.args.principal {
+            Some(claims) => quote!(#claims),
+            None => quote!(()),
+        }
+    }
+
+    fn principal_ident(idx: usize) -> Ident {
+        Ident::new(&format!("_principal_{idx}"), Span::call_site())
+    }
+}
@@ -0,0 +1,276 @@
+//! Default protection of handlers within an `impl` block or an inline `mod` (`#[protect_all(..)]`).
+//!
+//! The default `protect` attribute is added above the attributes of every handler, so it's combined
+//! with `protect` attributes of the handler as stacked ones, unless one of them is marked with `overrides`.
+//! Handlers are functions annotated with a route or handler attribute (`#[get("/")]`, `#[handler]`, `#[oai(..)]`),
+//! `protect` or `public` (the latter are exempted from the default), and `async` functions for frameworks
+//! accepting plain functions (`actix-web` and `axum`). Functions marked with `#[protect_all(skip)]` aren't handlers.
+
+use super::requirement::is_protect_attr;
+use super::{FnType, Framework, ProtectEndpoint, ProtectionArgs, PublicEndpoint};
+use darling::ast::NestedMeta;
+use darling::FromMeta;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{parse_quote, Attribute, ImplItem, Item, Meta};
+
+/// Flag of the handler's `protect` attribute replacing the default requirement.
+const OVERRIDE_ARG: &str = "overrides";
+
+/// Flag of the nested `protect_all` attribute marking a function that isn't a handler (e.g. an `async` helper).
+const SKIP_ARG: &str = "skip";
+
+/// Route and handler attributes of the frameworks, matched by the last segment.
+const HANDLER_ATTRS: &[&str] = &[
+    "get", "post", "put", "delete", "patch", "head", "options", "trace", "connect", "route",
+    "routes", "handler", "endpoint", "oai",
+];
+
+pub(crate) struct ProtectAll {
+    /// Path of the default `protect` attribute, matters for the source scanner only.
+    path: syn::Path,
+    /// Arguments of the default `protect` attribute.
+    args: TokenStream2,
+}
+
+impl ProtectAll {
+    pub(crate) fn new(args: TokenStream2) -> Self {
+        Self {
+            path: parse_quote!(protect),
+            args,
+        }
+    }
+
+    /// `None` for other attributes, `protect` is kept in the same crate as `protect_all` (e.g. `protect_axum::protect`).
+    // used by the source scanner only (`cli` feature)
+    #[allow(dead_code)]
+    pub(crate) fn from_attr(attr: &Attribute) -> Option<Self> {
+        let mut path = attr.path().clone();
+        let last = path.segments.last_mut()?;
+        if last.ident != "protect_all" && !last.ident.to_string().starts_with("protect_all_") {
+            return None;
+        }
+        last.ident = syn::Ident::new("protect", last.ident.span());
+
+        let args = match &attr.meta {
+            Meta::List(list) => list.tokens.clone(),
+            _ => TokenStream2::new(),
+        };
+        Some(Self { path, args })
+    }
+
+    /// Adds the default `protect` attribute to the handler, `false` if the function isn't a handler.
+    pub(crate) fn annotate(
+        &self,
+        framework: Framework,
+        func: &mut FnType,
+    ) -> darling::Result<bool> {
+        let is_async = func.sig().asyncness.is_some();
+        let is_skipped = strip_skip(func)?;
+        let attrs = func.attrs_mut();
+
+        let mut overridden = false;
+        for attr in attrs.iter_mut().filter(|attr| is_protect_attr(attr)) {
+            overridden |= strip_override(attr)?;
+        }
+        let is_protected = attrs.iter().any(is_protect_attr);
+        let is_public = attrs.iter().any(is_public_attr);
+        if is_public && is_protected {
+            return Err(
+                darling::Error::custom("`public` handlers can't be protected")
+                    .with_span(&func.sig().ident),
+            );
+        }
+        if is_skipped {
+            if is_public || is_protected {
+                return Err(darling::Error::custom(
+                    "handlers can't be skipped by `protect_all`, use `public` or `overrides`",
+                )
+                .with_span(&func.sig().ident));
+            }
+            return Ok(false);
+        }
+        let is_handler =
+            attrs.iter().any(is_handler_attr) || (is_async && !framework.has_handler_attrs());
+        if !is_handler && !is_protected && !is_public {
+            return Ok(false);
+        }
+
+        if !is_public && !overridden {
+            let (path, args) = (&self.path, &self.args);
+            attrs.insert(0, parse_quote!(#[#path(#args)]));
+        }
+        Ok(true)
+    }
+
+    /// Expands handlers of the `impl` block or the inline `mod`, other items are kept as is.
+    pub(crate) fn expand(&self, framework: Framework, item: Item) -> darling::Result<TokenStream2> {
+        let mut errors = darling::Error::accumulator();
+
+        match item {
+            Item::Mod(mut module) if module.content.is_some() => {
+                let (_, items) = module.content.as_mut().expect("inline module");
+                for item in items.iter_mut() {
+                    let Item::Fn(func) = item else {
+                        continue;
+                    };
+                    let mut func = FnType::Fn(func.clone());
+                    match errors.handle(self.annotate(framework, &mut func)) {
+                        Some(true) => {
+                            if let Some((tokens, _)) =
+                                errors.handle(expand_handler(framework, func, None))
+                            {
+                                *item = Item::Verbatim(tokens);
+                            }
+                        }
+                        // `protect_all(skip)` is removed from functions which aren't handlers
+                        Some(false) => {
+                            if let FnType::Fn(func) = func {
+                                *item = Item::Fn(func);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+
+                errors.finish_with(module.into_token_stream())
+            }
+            Item::Impl(mut item_impl) => {
+                let mut registrations = Vec::new();
+                for item in item_impl.items.iter_mut() {
+                    let ImplItem::Fn(method) = item else {
+                        continue;
+                    };
+                    let mut func = FnType::Method(method.clone());
+                    match errors.handle(self.annotate(framework, &mut func)) {
+                        Some(true) => {
+                            let expanded =
+                                expand_handler(framework, func, Some(&item_impl.self_ty));
+                            if let Some((tokens, registration)) = errors.handle(expanded) {
+                                *item = ImplItem::Verbatim(tokens);
+                                registrations.extend(registration);
+                            }
+                        }
+                        Some(false) => {
+                            if let FnType::Method(method) = func {
+                                *item = ImplItem::Fn(method);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+
+                errors.finish_with(quote! {
+                    #item_impl
+
+                    const _: () = {
+                        #(#registrations)*
+                    };
+                })
+            }
+            item => Err(darling::Error::custom(
+                "`protect_all` is applicable to an `impl` block or an inline `mod`",
+            )
+            .with_span(&item)),
+        }
+    }
+}
+
+/// Expands `protect` attributes one by one starting from the outermost, as the compiler does for stacked ones,
+/// so the innermost one also returns the registration of a method (methods have no place for sibling items).
+fn expand_handler(
+    framework: Framework,
+    mut func: FnType,
+    owner: Option<&syn::Type>,
+) -> darling::Result<(TokenStream2, Option<TokenStream2>)> {
+    if let Some(idx) = func.attrs().iter().position(is_public_attr) {
+        func.attrs_mut().remove(idx);
+        let endpoint = PublicEndpoint::new(framework, func);
+        let registration = owner.map(|owner| endpoint.registration(Some(owner)));
+        return Ok((endpoint.into_token_stream(), registration));
+    }
+
+    loop {
+        let attrs = func.attrs_mut();
+        let Some(idx) = attrs.iter().position(is_protect_attr) else {
+            unreachable!("the handler is annotated with `protect`");
+        };
+        let attr = attrs.remove(idx);
+
+        let args = ProtectionArgs::from_meta(&attr.meta)?;
+        let endpoint = ProtectEndpoint::new(framework, args, func);
+        let tokens = endpoint.to_token_stream();
+        if !endpoint.func.attrs().iter().any(is_protect_attr) {
+            let registration = match endpoint.func {
+                FnType::Method(_) => Some(endpoint.registration(owner)),
+                FnType::Fn(_) => None,
+            };
+            return Ok((tokens, registration));
+        }
+
+        func = match endpoint.func {
+            FnType::Fn(_) => FnType::Fn(syn::parse2(tokens)?),
+            FnType::Method(_) => FnType::Method(syn::parse2(tokens)?),
+        };
+    }
+}
+
+/// Removes the `overrides` flag from the `protect` attribute, `true` if it's found.
+fn strip_override(attr: &mut Attribute) -> darling::Result<bool> {
+    let Meta::List(list) = &mut attr.meta else {
+        return Ok(false);
+    };
+    let args = NestedMeta::parse_meta_list(list.tokens.clone())?;
+    let is_override = |arg: &NestedMeta| match arg {
+        NestedMeta::Meta(Meta::Path(path)) => path.is_ident(OVERRIDE_ARG),
+        _ => false,
+    };
+    if !args.iter().any(is_override) {
+        return Ok(false);
+    }
+
+    let args = args.into_iter().filter(|arg| !is_override(arg));
+    list.tokens = quote!(#(#args),*);
+    Ok(true)
+}
+
+/// Removes `#[protect_all(skip)]` from the function, `true` if it's found.
+fn strip_skip(func: &mut FnType) -> darling::Result<bool> {
+    let attrs = func.attrs_mut();
+    let Some(idx) = attrs.iter().position(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "protect_all")
+    }) else {
+        return Ok(false);
+    };
+
+    let attr = attrs.remove(idx);
+    let is_skip = match &attr.meta {
+        Meta::List(list) => list
+            .parse_args::<syn::Path>()
+            .is_ok_and(|path| path.is_ident(SKIP_ARG)),
+        _ => false,
+    };
+    if !is_skip {
+        return Err(
+            darling::Error::custom("nested `protect_all` only supports `skip`").with_span(&attr),
+        );
+    }
+    Ok(true)
+}
+
+fn is_handler_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| HANDLER_ATTRS.iter().any(|name| segment.ident == name))
+}
+
+fn is_public_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "public")
+}
@@ -0,0 +1,59 @@
+//! Registration of annotated handlers for the deny-by-default mode:
+//! both `protect` and `public` attributes submit a static record of the handler into the core crate,
+//! which is a no-op unless its `deny-by-default` feature is enabled.
+
+use super::registry::handler_name;
+use super::{FnType, Framework};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+
+pub(crate) struct PublicEndpoint {
+    framework: Framework,
+    func: FnType,
+}
+
+impl PublicEndpoint {
+    pub fn new(framework: Framework, func: FnType) -> Self {
+        Self { framework, func }
+    }
+
+    /// Statement submitting the record of the handler, `owner` is the type of methods.
+    /// Methods are registered by `open_api` and `protect_all` macros, since there is no place for sibling items.
+    pub(crate) fn registration(&self, owner: Option<&syn::Type>) -> TokenStream2 {
+        annotation(self.framework, &self.func.sig().ident, owner)
+    }
+}
+
+impl ToTokens for PublicEndpoint {
+    fn to_tokens(&self, output: &mut TokenStream2) {
+        match &self.func {
+            FnType::Fn(fun) => {
+                fun.to_tokens(output);
+
+                let registration = self.registration(None);
+                output.extend(quote! {
+                    const _: () = {
+                        #registration
+                    };
+                });
+            }
+            FnType::Method(fun) => fun.to_tokens(output),
+        }
+    }
+}
+
+/// Statement submitting the record of the annotated handler, see `deny_by_default` module of the framework crate.
+pub(super) fn annotation(
+    framework: Framework,
+    ident: &Ident,
+    owner: Option<&syn::Type>,
+) -> TokenStream2 {
+    let krate = framework.krate();
+    let name = handler_name(ident, owner);
+
+    quote! {
+        #krate::__annotate_handler!(#krate::deny_by_default::AnnotatedHandler::new(
+            ::std::concat!(::std::module_path!(), "::", #name)
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+//! Registration of protected handlers: a static record is submitted into the registry of the core crate,
+//! which is a no-op unless its `registry` feature is enabled.
+//! The handler is also registered as annotated for the deny-by-default mode.
+
+use super::public::annotation;
+use super::requirement::is_protect_attr;
+use super::{FnType, ProtectEndpoint};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned, ToTokens};
+
+/// Requirement of the handler as it's registered, see [`ProtectEndpoint::summary`].
+pub(crate) struct Summary {
+    /// Type of authorities in the source form, `String` by default.
+    pub(crate) authority_type: String,
+    /// Requirement rule, empty if there are no authorities to check.
+    pub(crate) condition: String,
+    /// Whether a part of the condition depends on the request.
+    pub(crate) dynamic: bool,
+}
+
+impl Summary {
+    /// Description of the requirement for generated API documentation.
+    // used by OpenAPI integrations only (`poem-openapi` and `salvo-oapi` features)
+    #[allow(dead_code)]
+    pub(crate) fn description(&self) -> String {
+        match (self.condition.is_empty(), self.dynamic) {
+            (false, false) => format!("Requires `{}`.", self.condition),
+            (false, true) => format!(
+                "Requires `{}` and a condition on the request.",
+                self.condition
+            ),
+            (true, _) => "Requires a condition on the request.".to_string(),
+        }
+    }
+}
+
+impl ProtectEndpoint {
+    /// Whether the condition of the handler (including outer `protect` attributes) depends on the request.
+    pub(super) fn is_dynamic(&self) -> bool {
+        self.reflection().is_dynamic()
+    }
+
+    /// Registers the handler by the innermost `protect` attribute, which knows the whole requirement.
+    /// Methods are registered by `open_api` macro, since there is no place for sibling items.
+    pub(super) fn registration_item(&self, output: &mut TokenStream2) {
+        let FnType::Fn(func) = &self.func else {
+            return;
+        };
+        if func.attrs.iter().any(is_protect_attr) {
+            return;
+        }
+
+        let registration = self.registration(None);
+        output.extend(quote! {
+            const _: () = {
+                #registration
+            };
+        });
+    }
+
+    /// Static part of the record, shared with the source scanner.
+    pub(crate) fn summary(&self) -> Summary {
+        let authority_type = self
+            .args
+            .ty
+            .as_ref()
+            .map_or_else(
+                || "String".to_string(),
+                |ty| ty.to_token_stream().to_string(),
+            )
+            .replace(' ', "");
+        let condition = self
+            .requirement()
+            .map(|requirement| requirement.to_string())
+            .unwrap_or_default();
+
+        Summary {
+            authority_type,
+            condition,
+            dynamic: self.is_dynamic(),
+        }
+    }
+
+    /// Statement submitting the record of the handler, `owner` is the type of methods.
+    pub(crate) fn registration(&self, owner: Option<&syn::Type>) -> TokenStream2 {
+        let krate = self.framework.krate();
+        let ident = &self.func.sig().ident;
+        let name = handler_name(ident, owner);
+        let location = quote_spanned! {ident.span()=>
+            #krate::registry::Location::new(::std::file!(), ::std::line!(), ::std::column!())
+        };
+        let framework = self.framework.name();
+        let Summary {
+            authority_type,
+            condition,
+            dynamic,
+        } = self.summary();
+        let reflection = self.reflection_tokens();
+        let annotation = annotation(self.framework, ident, owner);
+
+        quote! {
+            #krate::__register_endpoint!(#krate::registry::Endpoint::new(
+                ::std::concat!(::std::module_path!(), "::", #name),
+                #location,
+                #framework,
+                #authority_type,
+                #condition,
+                #dynamic,
+            ).with_reflection(|| #reflection));
+            #annotation
+        }
+    }
+}
+
+/// Name of the handler within its module, `owner` is the type of methods.
+pub(super) fn handler_name(ident: &syn::Ident, owner: Option<&syn::Type>) -> String {
+    match owner {
+        Some(owner) => format!("{}::{ident}", owner.to_token_stream()).replace(' ', ""),
+        None => ident.to_string(),
+    }
+}
@@ -0,0 +1,301 @@
+//! Reflection of handler requirements: a hidden function is emitted next to the handler,
+//! returning its condition as a runtime `Requirement`, where parts depending on the request are opaque.
+
+use super::{Condition, FnType, ProtectEndpoint};
+use proc_macro2::TokenStream as TokenStream2;
+use protect_endpoints_core::condition::{
+    Condition as RuntimeCondition, Part, Requirement as RuntimeRequirement,
+};
+use quote::{format_ident, quote, ToTokens};
+
+/// Authorities are kept in the source form (e.g. `Role::Admin` for typed conditions).
+pub(super) type Requirement = RuntimeCondition<String>;
+
+/// The whole condition, where `expr`, `owner` and `from_network` are dynamic parts in the source form.
+pub(super) type Reflection = RuntimeRequirement<String>;
+
+/// Hidden argument to pass the reflection of outer `protect` attributes to the inner one.
+pub(super) const OUTER_ARG: &str = "__outer_requirement";
+
+/// Prefix of dynamic parts forwarded as authorities of a rule, see [`OUTER_ARG`].
+const DYNAMIC_PREFIX: char = '\0';
+
+pub(super) fn parse(lit: &syn::LitStr) -> darling::Result<Reflection> {
+    let condition = RuntimeCondition::parse(&lit.value())
+        .map_err(|err| darling::Error::custom(err).with_span(lit))?;
+
+    Ok(Reflection::new(condition.map(
+        |authority| match authority.strip_prefix(DYNAMIC_PREFIX) {
+            Some(source) => Part::Dynamic(source.to_string()),
+            None => Part::Authority(authority),
+        },
+    )))
+}
+
+fn forwarded_rule(reflection: &Reflection) -> String {
+    reflection
+        .condition()
+        .clone()
+        .map(|part| match part {
+            Part::Authority(authority) => authority,
+            Part::Dynamic(source) => format!("{DYNAMIC_PREFIX}{source}"),
+        })
+        .to_string()
+}
+
+impl Condition {
+    fn reflection(&self) -> RuntimeCondition<Part<String>> {
+        let nested = |nested: &[Condition]| nested.iter().map(Condition::reflection).collect();
+
+        match self {
+            Condition::Value(val) => RuntimeCondition::Authority(Part::Authority(val.value())),
+            Condition::Expr(_, source) => dynamic(format!("expr({source})")),
+            Condition::Owner(field) => dynamic(format!(
+                "owner({})",
+                field.to_token_stream().to_string().replace(' ', "")
+            )),
+            Condition::Network(networks) => {
+                dynamic(format!("from_network('{}')", networks.value()))
+            }
+            Condition::During(schedule) => match schedule.value().parse() {
+                Ok(schedule) => RuntimeCondition::During(schedule),
+                Err(_) => dynamic(format!("during('{}')", schedule.value())),
+            },
+            Condition::Not(cond) => RuntimeCondition::not(cond.reflection()),
+            Condition::Any(conds) => RuntimeCondition::Any(nested(conds)),
+            Condition::All(conds) => RuntimeCondition::All(nested(conds)),
+            Condition::AtLeast(count, conds) => RuntimeCondition::AtLeast {
+                count: *count,
+                of: nested(conds),
+            },
+        }
+    }
+
+    /// The authorities part of the condition, `None` if it depends on the dynamic parts only.
+    fn requirement(&self) -> Option<Requirement> {
+        authorities_part(&self.reflection())
+    }
+}
+
+fn dynamic(source: String) -> RuntimeCondition<Part<String>> {
+    RuntimeCondition::Authority(Part::Dynamic(source))
+}
+
+/// `None` if the condition is dynamic, which can't be evaluated without a request.
+/// So dynamic parts are dropped from `all` and `at_least` (reducing the count),
+/// while `any` and `not` with them become unknown.
+fn authorities_part(condition: &RuntimeCondition<Part<String>>) -> Option<Requirement> {
+    match condition {
+        RuntimeCondition::Authority(Part::Authority(authority)) => {
+            Some(RuntimeCondition::Authority(authority.clone()))
+        }
+        RuntimeCondition::Authority(Part::Dynamic(_)) => None,
+        RuntimeCondition::During(schedule) => Some(RuntimeCondition::During(schedule.clone())),
+        RuntimeCondition::Not(nested) => authorities_part(nested).map(RuntimeCondition::not),
+        RuntimeCondition::Any(nested) => nested
+            .iter()
+            .map(authorities_part)
+            .collect::<Option<Vec<_>>>()
+            .map(RuntimeCondition::Any),
+        RuntimeCondition::All(nested) => Some(RuntimeCondition::all(
+            nested.iter().filter_map(authorities_part),
+        )),
+        RuntimeCondition::AtLeast { count, of } => {
+            let of: Vec<_> = of.iter().map(authorities_part).collect();
+            let unknown = of.iter().filter(|cond| cond.is_none()).count();
+
+            Some(RuntimeCondition::at_least(
+                count.saturating_sub(unknown),
+                of.into_iter().flatten(),
+            ))
+        }
+    }
+}
+
+impl ProtectEndpoint {
+    /// Stacked `protect` attributes are expanded one by one starting from the outermost,
+    /// so each of them forwards the requirement to the next one and the innermost emits it.
+    pub(super) fn forward_requirement(&mut self) {
+        let rule = forwarded_rule(&self.reflection());
+        let Some(next) = self
+            .func
+            .attrs_mut()
+            .iter_mut()
+            .find(|attr| is_protect_attr(attr))
+        else {
+            return;
+        };
+
+        if let syn::Meta::List(list) = &mut next.meta {
+            let has_trailing_comma = matches!(
+                list.tokens.clone().into_iter().last(),
+                Some(proc_macro2::TokenTree::Punct(punct)) if punct.as_char() == ','
+            );
+            if !has_trailing_comma {
+                list.tokens.extend(quote!(,));
+            }
+
+            let arg = format_ident!("{}", OUTER_ARG);
+            list.tokens.extend(quote!(#arg = #rule));
+        }
+    }
+
+    /// Emits `__protect_requirement_<handler>` function for the innermost `protect` attribute.
+    /// Methods are skipped, since there is no place for sibling items.
+    pub(super) fn requirement_fn(&self, output: &mut TokenStream2) {
+        let FnType::Fn(func) = &self.func else {
+            return;
+        };
+        if func.attrs.iter().any(is_protect_attr) {
+            return;
+        }
+
+        let krate = self.framework.krate();
+        let vis = &func.vis;
+        let name = requirement_fn_ident(&func.sig.ident);
+        let reflection = self.reflection_tokens();
+
+        output.extend(quote! {
+            #[doc(hidden)]
+            #[allow(dead_code, non_snake_case)]
+            #vis fn #name() -> #krate::condition::Requirement<&'static str> {
+                #reflection
+            }
+        });
+    }
+
+    /// `Requirement<&'static str>` with the whole condition of the handler (including outer `protect` attributes).
+    pub(super) fn reflection_tokens(&self) -> TokenStream2 {
+        let krate = self.framework.krate();
+        let cond = quote!(#krate::condition::Condition::<#krate::condition::Part<&'static str>>);
+        let part = quote!(#krate::condition::Part);
+        let condition = to_tokens(
+            self.reflection().condition(),
+            &cond,
+            &|part_of| match part_of {
+                Part::Authority(authority) => quote!(#part::Authority(#authority)),
+                Part::Dynamic(source) => {
+                    quote!(#part::Dynamic(::std::string::String::from(#source)))
+                }
+            },
+        );
+
+        quote!(#krate::condition::Requirement::new(#condition))
+    }
+
+    /// `Condition<&'static str>` with the authorities part of this attribute's condition only.
+    pub(super) fn required_tokens(&self) -> TokenStream2 {
+        let krate = self.framework.krate();
+        to_tokens(
+            &self
+                .args
+                .cond
+                .requirement()
+                .unwrap_or(RuntimeCondition::All(Vec::new())),
+            &quote!(#krate::condition::Condition::<&'static str>),
+            &|authority| quote!(#authority),
+        )
+    }
+
+    /// The whole condition of the handler, conditions of outer `protect` attributes come first.
+    pub(super) fn reflection(&self) -> Reflection {
+        let condition = self.args.cond.reflection();
+        match &self.args.outer {
+            Some(outer) => Reflection::new(RuntimeCondition::all([
+                outer.condition().clone(),
+                condition,
+            ])),
+            None => Reflection::new(condition),
+        }
+    }
+
+    /// The authorities part of the whole condition, see [`Condition::requirement`].
+    pub(super) fn requirement(&self) -> Option<Requirement> {
+        let mut requirements: Vec<_> = self
+            .args
+            .outer
+            .iter()
+            .filter_map(|outer| authorities_part(outer.condition()))
+            .chain(self.args.cond.requirement())
+            .collect();
+
+        match requirements.len() {
+            0 => None,
+            1 => requirements.pop(),
+            _ => Some(RuntimeCondition::All(requirements)),
+        }
+    }
+
+    /// Authorities required all together (e.g. as OAuth scopes of the operation),
+    /// empty if the requirement is another condition (e.g. `any`).
+    // used by OpenAPI integrations only (`poem-openapi` and `salvo-oapi` features)
+    #[allow(dead_code)]
+    pub(super) fn required_authorities(&self) -> Vec<String> {
+        match self.requirement() {
+            Some(RuntimeCondition::Authority(authority)) => vec![authority],
+            Some(RuntimeCondition::All(nested)) => nested
+                .into_iter()
+                .map(|cond| match cond {
+                    RuntimeCondition::Authority(authority) => Some(authority),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Name of the function emitted by [`ProtectEndpoint::requirement_fn`] for the handler.
+pub(crate) fn requirement_fn_ident(handler: &syn::Ident) -> syn::Ident {
+    format_ident!("__protect_requirement_{}", handler)
+}
+
+pub(super) fn is_protect_attr(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "protect")
+}
+
+fn to_tokens<T>(
+    condition: &RuntimeCondition<T>,
+    cond: &TokenStream2,
+    leaf: &impl Fn(&T) -> TokenStream2,
+) -> TokenStream2 {
+    let nested_tokens = |nested: &[RuntimeCondition<T>]| {
+        let nested = nested.iter().map(|nested| to_tokens(nested, cond, leaf));
+        quote!(::std::vec![#(#nested),*])
+    };
+
+    match condition {
+        RuntimeCondition::Authority(authority) => {
+            let authority = leaf(authority);
+            quote!(#cond::Authority(#authority))
+        }
+        RuntimeCondition::Not(nested) => {
+            let nested = to_tokens(nested, cond, leaf);
+            quote!(#cond::Not(::std::boxed::Box::new(#nested)))
+        }
+        RuntimeCondition::All(nested) => {
+            let nested = nested_tokens(nested);
+            quote!(#cond::All(#nested))
+        }
+        RuntimeCondition::Any(nested) => {
+            let nested = nested_tokens(nested);
+            quote!(#cond::Any(#nested))
+        }
+        RuntimeCondition::AtLeast { count, of } => {
+            let of = nested_tokens(of);
+            quote!(#cond::AtLeast { count: #count, of: #of })
+        }
+        RuntimeCondition::During(schedule) => {
+            let schedule = schedule.to_string();
+            quote! {
+                #cond::During(
+                    ::std::str::FromStr::from_str(#schedule).expect("schedule is validated by the macro"),
+                )
+            }
+        }
+    }
+}
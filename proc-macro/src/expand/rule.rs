@@ -0,0 +1,110 @@
+//! Conversion of the boolean rule DSL (`rule = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)"`)
+//! into the condition tree. The syntax itself is defined by the parser of the core crate,
+//! so the same rules can be used at runtime.
+
+use super::{Condition, Conditions};
+use proc_macro2::Span;
+use protect_endpoints_core::condition::{Condition as RuleCondition, Spanned};
+use std::ops::Range;
+
+/// Parses the rule from the string literal, errors point to the relevant part of the literal.
+pub(super) fn parse(lit: &syn::LitStr) -> syn::Result<Condition> {
+    let source = lit.value();
+    let cond = RuleCondition::parse_spanned(&source).map_err(|err| {
+        syn::Error::new(
+            subspan(lit, &source, err.span()),
+            format!("invalid rule: {}", err.message()),
+        )
+    })?;
+
+    Ok(convert(cond, lit, &source))
+}
+
+fn convert(cond: RuleCondition<Spanned<String>>, lit: &syn::LitStr, source: &str) -> Condition {
+    let convert_all = |nested: Vec<RuleCondition<Spanned<String>>>| {
+        Conditions(
+            nested
+                .into_iter()
+                .map(|cond| convert(cond, lit, source))
+                .collect(),
+        )
+    };
+
+    match cond {
+        RuleCondition::Authority(Spanned { value, span }) => {
+            Condition::Value(syn::LitStr::new(&value, subspan(lit, source, span)))
+        }
+        RuleCondition::Not(nested) => Condition::Not(Box::new(convert(*nested, lit, source))),
+        RuleCondition::All(nested) => Condition::All(convert_all(nested)),
+        RuleCondition::Any(nested) => Condition::Any(convert_all(nested)),
+        RuleCondition::AtLeast { count, of } => Condition::AtLeast(count, convert_all(of)),
+        RuleCondition::During(schedule) => {
+            Condition::During(syn::LitStr::new(&schedule.to_string(), lit.span()))
+        }
+    }
+}
+
+/// Points to the part of the string literal, falls back to the whole literal
+/// if the compiler doesn't support sub-spans (or the literal contains escapes).
+fn subspan(lit: &syn::LitStr, source: &str, range: Range<usize>) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    let offset = repr.find('"').map(|idx| idx + 1).unwrap_or_default();
+    let is_verbatim = repr
+        .get(offset..offset + source.len())
+        .is_some_and(|inner| inner == source);
+
+    is_verbatim
+        .then(|| token.subspan(offset + range.start..offset + range.end))
+        .flatten()
+        .unwrap_or_else(|| lit.span())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(cond: &Condition) -> String {
+        let render_all = |nested: &Conditions| nested.iter().map(render).collect::<Vec<_>>();
+
+        match cond {
+            Condition::Value(val) => val.value(),
+            Condition::Not(inner) => format!("!{}", render(inner)),
+            Condition::All(nested) => format!("all({})", render_all(nested).join(", ")),
+            Condition::Any(nested) => format!("any({})", render_all(nested).join(", ")),
+            Condition::AtLeast(count, nested) => {
+                format!("at_least({count}, {})", render_all(nested).join(", "))
+            }
+            Condition::During(schedule) => format!("during({})", schedule.value()),
+            Condition::Expr(..) | Condition::Owner(_) | Condition::Network(_) => unreachable!(),
+        }
+    }
+
+    fn parse_str(rule: &str) -> syn::Result<String> {
+        parse(&syn::LitStr::new(rule, Span::call_site())).map(|cond| render(&cond))
+    }
+
+    #[test]
+    fn test_conversion() {
+        assert_eq!(
+            "any(ROLE_ADMIN, all(OP_WRITE, !SUSPENDED))",
+            parse_str("ROLE_ADMIN || OP_WRITE && !SUSPENDED").unwrap()
+        );
+        assert_eq!(
+            "at_least(2, Role::Admin, any(A, B), read users)",
+            parse_str("at_least(2, Role::Admin, A || B, 'read users')").unwrap()
+        );
+        assert_eq!(
+            "all(DEPLOY, during(Mon-Fri 09:00-18:00 UTC))",
+            parse_str("DEPLOY && during('mon-fri 09:00-18:00')").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            "invalid rule: unclosed `(`",
+            parse_str("(A || B").unwrap_err().to_string()
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use crate::expand::ProtectEndpoint;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens};
-use syn::{parse_quote, ReturnType};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{parse_quote, FnArg, Pat, PatType, ReturnType, Type, TypeReference};
 
 impl ProtectEndpoint {
     pub(super) fn to_tokens_salvo(&self, output: &mut TokenStream2) {
@@ -20,38 +22,71 @@ impl ProtectEndpoint {
             }
         };
 
-        let ty = self
-            .args
-            .ty
-            .as_ref()
-            .map(ToTokens::to_token_stream)
-            .unwrap_or(quote! {String});
-
         let mut fn_args = fn_sig.inputs.clone();
         let auth_details = format!("_auth_details_{}", fn_args.len());
         let auth_details: Ident = Ident::new(&auth_details, Span::call_site());
 
-        fn_args.push(parse_quote!(#auth_details: protect_salvo::authorities::AuthDetails<#ty>));
+        let principal = self.principal_tokens(fn_args.len());
+        if let Some(principal) = self.principal_arg(fn_args.len()) {
+            fn_args.push(parse_quote!(#principal));
+        }
+
+        let client_ip = self.client_ip_tokens(fn_args.len());
+        if let Some(client_ip) = self.client_ip_arg(fn_args.len()) {
+            fn_args.push(parse_quote!(#client_ip));
+        }
+
+        let auth_details_ty = self.auth_details_ty();
+        fn_args.push(parse_quote!(#auth_details: #auth_details_ty));
+
+        let anonymous = self.anonymous_tokens(&auth_details);
+        let check = self.check_tokens(&auth_details);
+        let passed = Self::passed_ident();
+        let denied = Self::denied_ident();
+
+        let request = self.request_arg(fn_args.len()).map(|request| {
+            // only one `&mut Request` is allowed, so the existing one is reused
+            request_param(&fn_args).unwrap_or_else(|| {
+                fn_args.push(parse_quote!(#request: &mut salvo::Request));
+                request
+            })
+        });
 
-        let condition = self
-            .args
-            .cond
-            .to_tokens(&auth_details, self.args.ty.is_some());
-        let condition = quote!(if #condition);
+        let err_resp = self.deny_tokens(
+            &auth_details,
+            request.map(|request| quote!(&*#request)),
+            quote!(#denied),
+        );
+        let err_ty = quote!(impl salvo::Writer + Send + std::fmt::Debug + 'static);
 
-        let err_resp = if let Some(expr) = &self.args.error_fn {
-            quote!(#expr())
+        // `#[endpoint]` documents the output of the handler, so the error type must be nameable
+        #[cfg(feature = "salvo-oapi")]
+        let (fn_attrs, err_ty, err_resp) = if self.is_endpoint() {
+            let mut fn_attrs = fn_attrs.to_vec();
+            if let Err(err) = self.document_endpoint(&mut fn_attrs) {
+                output.extend(err.write_errors());
+                return;
+            }
+            (
+                fn_attrs,
+                quote!(protect_salvo::oapi::AccessError),
+                quote!(protect_salvo::oapi::AccessError::new(#err_resp)),
+            )
         } else {
-            quote!(salvo::prelude::StatusCode::FORBIDDEN)
+            (fn_attrs.to_vec(), err_ty, err_resp)
         };
 
         let stream = quote! {
             #(#fn_attrs)*
             #func_vis #fn_async fn #fn_name #fn_generics(
                 #fn_args
-            ) -> Result<#fn_output, impl salvo::Writer + Send + std::fmt::Debug + 'static> {
+            ) -> Result<#fn_output, #err_ty> {
                 use protect_salvo::authorities::AuthoritiesCheck;
-                #condition {
+                #anonymous
+                #principal
+                #client_ip
+                #check
+                if #passed {
                     let f = || async move #func_block;
                     Ok(f().await)
                 } else {
@@ -63,3 +98,31 @@ impl ProtectEndpoint {
         output.extend(stream);
     }
 }
+
+fn request_param(fn_args: &Punctuated<FnArg, Comma>) -> Option<Ident> {
+    fn_args.iter().find_map(|arg| match arg {
+        FnArg::Typed(PatType { pat, ty, .. }) => match (pat.as_ref(), ty.as_ref()) {
+            (
+                Pat::Ident(pat),
+                Type::Reference(TypeReference {
+                    mutability: Some(_),
+                    elem,
+                    ..
+                }),
+            ) => match elem.as_ref() {
+                Type::Path(path)
+                    if path
+                        .path
+                        .segments
+                        .last()
+                        .is_some_and(|segment| segment.ident == "Request") =>
+                {
+                    Some(pat.ident.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        },
+        FnArg::Receiver(_) => None,
+    })
+}
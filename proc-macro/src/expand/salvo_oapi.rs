@@ -0,0 +1,87 @@
+//! Documentation of `salvo-oapi` endpoints (`#[endpoint]`) protected by `#[protect_salvo::protect]`.
+//!
+//! The `protect` attribute must be above `#[endpoint]`, so the latter sees the generated handler:
+//! its error is `protect_salvo::oapi::AccessError` documenting `401` and `403` responses (`impl Writer` can't be documented),
+//! and the innermost `protect` attribute adds the requirement to the description of the operation.
+
+use super::requirement::is_protect_attr;
+use super::ProtectEndpoint;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::quote;
+use syn::{parse_quote, Attribute, Meta};
+
+impl ProtectEndpoint {
+    /// Whether the handler is documented by `#[endpoint]`.
+    pub(super) fn is_endpoint(&self) -> bool {
+        self.func.attrs().iter().any(is_endpoint_attr)
+    }
+
+    /// Adds the requirement to the description (by the innermost `protect` attribute)
+    /// and the security requirement to the `#[endpoint]` attribute, if `security` is declared.
+    pub(super) fn document_endpoint(&self, attrs: &mut Vec<Attribute>) -> darling::Result<()> {
+        if !attrs.iter().any(is_protect_attr) {
+            let description = self.summary().description();
+            // the first paragraph is the summary of the operation
+            attrs.push(parse_quote!(#[doc = ""]));
+            attrs.push(parse_quote!(#[doc = #description]));
+        }
+
+        let Some(security) = &self.args.security else {
+            return Ok(());
+        };
+        let scopes = if self.args.scopes {
+            self.required_authorities()
+        } else {
+            Vec::new()
+        };
+        let requirement = quote!(security((#security = [#(#scopes),*])));
+
+        let endpoint = attrs
+            .iter_mut()
+            .find(|attr| is_endpoint_attr(attr))
+            .expect("`#[endpoint]` attribute is checked before");
+        match &mut endpoint.meta {
+            Meta::Path(path) => {
+                let path = path.clone();
+                endpoint.meta = parse_quote!(#path(#requirement));
+            }
+            Meta::List(list) => {
+                if has_security(&list.tokens) {
+                    return Err(darling::Error::custom(
+                        "`security` is already declared by `#[endpoint]`, remove it from one of the attributes",
+                    )
+                    .with_span(security));
+                }
+
+                let has_trailing_comma = matches!(
+                    list.tokens.clone().into_iter().last(),
+                    Some(TokenTree::Punct(punct)) if punct.as_char() == ','
+                );
+                if !list.tokens.is_empty() && !has_trailing_comma {
+                    list.tokens.extend(quote!(,));
+                }
+                list.tokens.extend(requirement);
+            }
+            Meta::NameValue(_) => {
+                return Err(darling::Error::unsupported_format("name-value").with_span(endpoint))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_endpoint_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "endpoint")
+}
+
+/// Whether `security(..)` is among the top-level arguments of `#[endpoint]`.
+fn has_security(tokens: &TokenStream2) -> bool {
+    tokens
+        .clone()
+        .into_iter()
+        .any(|token| matches!(token, TokenTree::Ident(ident) if ident == "security"))
+}
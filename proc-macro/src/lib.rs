@@ -5,13 +5,26 @@ use proc_macro::TokenStream;
 use quote::ToTokens;
 use syn::{parse_macro_input, ItemFn};
 
-use crate::expand::{FnType, Framework, ProtectEndpoint, ProtectionArgs};
+use crate::expand::{
+    requirement_fn_ident, FnType, Framework, ProtectAll, ProtectEndpoint, ProtectionArgs,
+    PublicEndpoint,
+};
 
 mod expand;
 
 /// Macro to сheck that the user has all the specified permissions.
 /// Allow to add a conditional restriction based on handlers parameters.
 /// Add the `expr` attribute followed by the the boolean expression to validate based on parameters
+/// Complex conditions over authorities can be written via `rule`, e.g. `rule = \"ADMIN || (WRITE && !SUSPENDED)\"`
+/// Custom denial responses are built via `error`: either a function without arguments or an expression
+/// using `auth_details`, `denied` and `request`, e.g. `error = \"access_denied(auth_details, denied, request).await\"`
+/// The anonymous principal is rejected as unauthorized, unless `allow_anonymous` is specified,
+/// e.g. `#[protect(rule = \"ANONYMOUS || USER\", allow_anonymous)]`
+/// The principal of the user is available to `expr` as `principal` with `principal = \"Claims\"`,
+/// e.g. `#[protect(\"USER\", principal = \"Claims\", expr = \"principal.subject() == owner_id.as_str()\")]`
+/// `owner` compares a parameter with the subject of the principal, e.g. `#[protect(any(\"ADMIN\", owner = \"user_id\"))]`
+/// `during` restricts the access to the schedule, e.g. `#[protect(\"DEPLOY\", during = \"Mon-Fri 09:00-18:00 UTC\")]`
+/// `from_network` restricts the access to the client address, e.g. `#[protect(any(\"ADMIN\", from_network = \"10.0.0.0/8\"))]`
 ///
 /// Also you can use you own types instead of Strings, just add `ty` attribute with path to type
 /// # Examples
@@ -56,6 +69,16 @@ pub fn protect_actix_web(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Macro to сheck that the user has all the specified permissions.
 /// Allow to add a conditional restriction based on handlers parameters.
 /// Add the `expr` attribute followed by the the boolean expression to validate based on parameters
+/// Complex conditions over authorities can be written via `rule`, e.g. `rule = \"ADMIN || (WRITE && !SUSPENDED)\"`
+/// Custom denial responses are built via `error`: either a function without arguments or an expression
+/// using `auth_details`, `denied` and `request`, e.g. `error = \"access_denied(auth_details, denied, request).await\"`
+/// The anonymous principal is rejected as unauthorized, unless `allow_anonymous` is specified,
+/// e.g. `#[protect(rule = \"ANONYMOUS || USER\", allow_anonymous)]`
+/// The principal of the user is available to `expr` as `principal` with `principal = \"Claims\"`,
+/// e.g. `#[protect(\"USER\", principal = \"Claims\", expr = \"principal.subject() == owner_id.as_str()\")]`
+/// `owner` compares a parameter with the subject of the principal, e.g. `#[protect(any(\"ADMIN\", owner = \"user_id\"))]`
+/// `during` restricts the access to the schedule, e.g. `#[protect(\"DEPLOY\", during = \"Mon-Fri 09:00-18:00 UTC\")]`
+/// `from_network` restricts the access to the client address, e.g. `#[protect(any(\"ADMIN\", from_network = \"10.0.0.0/8\"))]`
 ///
 /// Also you can use you own types instead of Strings, just add `ty` attribute with path to type
 #[cfg(feature = "axum")]
@@ -68,6 +91,16 @@ pub fn protect_axum(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Macro to сheck that the user has all the specified permissions.
 /// Allow to add a conditional restriction based on handlers parameters.
 /// Add the `expr` attribute followed by the the boolean expression to validate based on parameters
+/// Complex conditions over authorities can be written via `rule`, e.g. `rule = \"ADMIN || (WRITE && !SUSPENDED)\"`
+/// Custom denial responses are built via `error`: either a function without arguments or an expression
+/// using `auth_details`, `denied` and `request`, e.g. `error = \"access_denied(auth_details, denied, request).await\"`
+/// The anonymous principal is rejected as unauthorized, unless `allow_anonymous` is specified,
+/// e.g. `#[protect(rule = \"ANONYMOUS || USER\", allow_anonymous)]`
+/// The principal of the user is available to `expr` as `principal` with `principal = \"Claims\"`,
+/// e.g. `#[protect(\"USER\", principal = \"Claims\", expr = \"principal.subject() == owner_id.as_str()\")]`
+/// `owner` compares a parameter with the subject of the principal, e.g. `#[protect(any(\"ADMIN\", owner = \"user_id\"))]`
+/// `during` restricts the access to the schedule, e.g. `#[protect(\"DEPLOY\", during = \"Mon-Fri 09:00-18:00 UTC\")]`
+/// `from_network` restricts the access to the client address, e.g. `#[protect(any(\"ADMIN\", from_network = \"10.0.0.0/8\"))]`
 ///
 /// Also you can use you own types instead of Strings, just add `ty` attribute with path to type
 /// # Examples
@@ -112,6 +145,16 @@ pub fn protect_rocket(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Macro to check that the user has all the specified permissions.
 /// Allow to add a conditional restriction based on handlers parameters.
 /// Add the `expr` attribute followed by the boolean expression to validate based on parameters
+/// Complex conditions over authorities can be written via `rule`, e.g. `rule = \"ADMIN || (WRITE && !SUSPENDED)\"`
+/// Custom denial responses are built via `error`: either a function without arguments or an expression
+/// using `auth_details`, `denied` and `request`, e.g. `error = \"access_denied(auth_details, denied, request).await\"`
+/// The anonymous principal is rejected as unauthorized, unless `allow_anonymous` is specified,
+/// e.g. `#[protect(rule = \"ANONYMOUS || USER\", allow_anonymous)]`
+/// The principal of the user is available to `expr` as `principal` with `principal = \"Claims\"`,
+/// e.g. `#[protect(\"USER\", principal = \"Claims\", expr = \"principal.subject() == owner_id.as_str()\")]`
+/// `owner` compares a parameter with the subject of the principal, e.g. `#[protect(any(\"ADMIN\", owner = \"user_id\"))]`
+/// `during` restricts the access to the schedule, e.g. `#[protect(\"DEPLOY\", during = \"Mon-Fri 09:00-18:00 UTC\")]`
+/// `from_network` restricts the access to the client address, e.g. `#[protect(any(\"ADMIN\", from_network = \"10.0.0.0/8\"))]`
 ///
 /// Also, you can use you own types instead of Strings, just add `ty` attribute with path to type
 /// # Examples
@@ -156,6 +199,7 @@ pub fn protect_poem(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Macro for `poem-openapi` support
 /// Add macro `#[poem_grants::open_api]` above of `#[poem_openapi::OpenApi]` and mark all needed methods with necessary security-methods:
 /// One of [`has_permissions`, `has_any_permission`, `has_roles`, `has_any_role`]
+/// Methods intended to be public are marked with `#[public]` for the deny-by-default mode.
 ///
 /// # Examples
 /// ```rust,no_run
@@ -175,12 +219,38 @@ pub fn protect_poem(args: TokenStream, input: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
+///
+/// With the `poem-openapi` feature, the requirement of a protected method is added to the
+/// description of its operation and `401` / `403` responses are declared.
+/// `security = "Scheme"` additionally requires the `SecurityScheme` for protected operations,
+/// and `scopes` declares typed authorities of the requirement as OAuth scopes of the scheme,
+/// e.g. `#[poem_grants::open_api(security = "OAuth", scopes)]`.
 #[cfg(feature = "poem")]
 #[cfg_attr(docsrs, doc(cfg(feature = "poem")))]
 #[proc_macro_attribute]
-pub fn open_api(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn open_api(args: TokenStream, input: TokenStream) -> TokenStream {
+    #[cfg(feature = "poem-openapi")]
+    let openapi_args = {
+        let args = match NestedMeta::parse_meta_list(args.into()) {
+            Ok(v) => v,
+            Err(e) => {
+                return TokenStream::from(darling::Error::from(e).write_errors());
+            }
+        };
+        match expand::OpenApiArgs::from_list(&args).and_then(expand::OpenApiArgs::validate) {
+            Ok(v) => v,
+            Err(e) => {
+                return TokenStream::from(e.write_errors());
+            }
+        }
+    };
+    #[cfg(not(feature = "poem-openapi"))]
+    let _ = args;
+
     let mut item_impl = parse_macro_input!(input as syn::ItemImpl);
     let mut methods = Vec::new();
+    // the last `protect` attribute of a method wins, see the replacement below
+    let mut registrations = std::collections::BTreeMap::new();
     for (idx, item) in item_impl.items.iter().enumerate() {
         if let syn::ImplItem::Fn(method) = item {
             for grants_attr in method
@@ -195,17 +265,38 @@ pub fn open_api(_args: TokenStream, input: TokenStream) -> TokenStream {
                     }
                 };
 
-                let generated =
-                    ProtectEndpoint::new(Framework::Poem, args, FnType::Method(method.clone()))
-                        .into_token_stream()
-                        .into();
+                let mut method = method.clone();
+                method.attrs.retain(|attr| attr != grants_attr);
 
-                let mut gen_method = parse_macro_input!(generated as syn::ImplItemFn);
+                let endpoint = ProtectEndpoint::new(Framework::Poem, args, FnType::Method(method));
+                registrations.insert(idx, endpoint.registration(Some(&item_impl.self_ty)));
+                let generated = endpoint.to_token_stream().into();
 
-                gen_method.attrs.retain(|attr| attr != grants_attr);
+                let gen_method = parse_macro_input!(generated as syn::ImplItemFn);
+                #[cfg(feature = "poem-openapi")]
+                let mut gen_method = gen_method;
+                #[cfg(feature = "poem-openapi")]
+                if let Err(e) = endpoint.document_operation(&mut gen_method, &openapi_args) {
+                    return TokenStream::from(e.write_errors());
+                }
 
                 methods.push((idx, gen_method));
             }
+
+            if let Some(public_attr) = method
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("public"))
+            {
+                let mut method = method.clone();
+                method.attrs.retain(|attr| attr != public_attr);
+
+                let endpoint = PublicEndpoint::new(Framework::Poem, FnType::Method(method));
+                registrations.insert(idx, endpoint.registration(Some(&item_impl.self_ty)));
+                let generated = endpoint.into_token_stream().into();
+
+                methods.push((idx, parse_macro_input!(generated as syn::ImplItemFn)));
+            }
         }
     }
 
@@ -213,8 +304,13 @@ pub fn open_api(_args: TokenStream, input: TokenStream) -> TokenStream {
         let _ = std::mem::replace(&mut item_impl.items[idx], syn::ImplItem::Fn(method));
     }
 
+    let registrations = registrations.values();
     let res = quote::quote! {
         #item_impl
+
+        const _: () = {
+            #(#registrations)*
+        };
     };
 
     res.into()
@@ -223,6 +319,20 @@ pub fn open_api(_args: TokenStream, input: TokenStream) -> TokenStream {
 /// Macro to check that the user has all the specified permissions.
 /// Allow to add a conditional restriction based on handlers parameters.
 /// Add the `expr` attribute followed by the boolean expression to validate based on parameters
+/// Complex conditions over authorities can be written via `rule`, e.g. `rule = \"ADMIN || (WRITE && !SUSPENDED)\"`
+/// Custom denial responses are built via `error`: either a function without arguments or an expression
+/// using `auth_details`, `denied` and `request`, e.g. `error = \"access_denied(auth_details, denied, request).await\"`
+/// The anonymous principal is rejected as unauthorized, unless `allow_anonymous` is specified,
+/// e.g. `#[protect(rule = \"ANONYMOUS || USER\", allow_anonymous)]`
+/// The principal of the user is available to `expr` as `principal` with `principal = \"Claims\"`,
+/// e.g. `#[protect(\"USER\", principal = \"Claims\", expr = \"principal.subject() == owner_id.as_str()\")]`
+/// `owner` compares a parameter with the subject of the principal, e.g. `#[protect(any(\"ADMIN\", owner = \"user_id\"))]`
+/// `during` restricts the access to the schedule, e.g. `#[protect(\"DEPLOY\", during = \"Mon-Fri 09:00-18:00 UTC\")]`
+/// `from_network` restricts the access to the client address, e.g. `#[protect(any(\"ADMIN\", from_network = \"10.0.0.0/8\"))]`
+///
+/// With the `salvo-oapi` feature, the macro placed above `#[endpoint]` adds the requirement to the description
+/// of the operation and declares `401` / `403` responses. `security = \"scheme\"` additionally requires the security scheme,
+/// and `scopes` declares the required authorities as its scopes, e.g. `#[protect(\"read\", security = \"oauth2\", scopes)]`
 ///
 /// Also, you can use you own types instead of Strings, just add `ty` attribute with path to type
 /// # Examples
@@ -268,6 +378,160 @@ pub fn protect_salvo(args: TokenStream, input: TokenStream) -> TokenStream {
     protect_endpoint(Framework::Salvo, args, input)
 }
 
+/// Applies the default requirement (arguments of `protect`) to every handler of an `impl` block or an inline `mod`.
+///
+/// Handlers are functions annotated with a route or handler attribute (e.g. `#[get("/")]`, `#[handler]`, `#[oai(..)]`),
+/// `protect` or `public`, as well as `async` functions for `actix-web` and `axum`, other items are kept as is.
+/// `async` functions which aren't handlers are marked with `#[protect_all(skip)]`.
+/// `protect` attributes of a handler are combined with the default one (as stacked attributes),
+/// unless one of them is marked with `overrides`, e.g. `#[protect("ROLE_SUPPORT", overrides)]`.
+/// Handlers marked with `public` are exempted.
+/// Requirements of methods are available to `registry::requirement_of` only, not to `requirement_of`.
+#[cfg(feature = "actix-web")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actix-web")))]
+#[proc_macro_attribute]
+pub fn protect_all_actix_web(args: TokenStream, input: TokenStream) -> TokenStream {
+    protect_all(Framework::ActixWeb, args, input)
+}
+
+/// Applies the default requirement (arguments of `protect`) to every handler of an `impl` block or an inline `mod`.
+///
+/// Handlers are functions annotated with a route or handler attribute (e.g. `#[get("/")]`, `#[handler]`, `#[oai(..)]`),
+/// `protect` or `public`, as well as `async` functions for `actix-web` and `axum`, other items are kept as is.
+/// `async` functions which aren't handlers are marked with `#[protect_all(skip)]`.
+/// `protect` attributes of a handler are combined with the default one (as stacked attributes),
+/// unless one of them is marked with `overrides`, e.g. `#[protect("ROLE_SUPPORT", overrides)]`.
+/// Handlers marked with `public` are exempted.
+/// Requirements of methods are available to `registry::requirement_of` only, not to `requirement_of`.
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+#[proc_macro_attribute]
+pub fn protect_all_axum(args: TokenStream, input: TokenStream) -> TokenStream {
+    protect_all(Framework::Axum, args, input)
+}
+
+/// Applies the default requirement (arguments of `protect`) to every handler of an `impl` block or an inline `mod`.
+///
+/// Handlers are functions annotated with a route or handler attribute (e.g. `#[get("/")]`, `#[handler]`, `#[oai(..)]`),
+/// `protect` or `public`, as well as `async` functions for `actix-web` and `axum`, other items are kept as is.
+/// `async` functions which aren't handlers are marked with `#[protect_all(skip)]`.
+/// `protect` attributes of a handler are combined with the default one (as stacked attributes),
+/// unless one of them is marked with `overrides`, e.g. `#[protect("ROLE_SUPPORT", overrides)]`.
+/// Handlers marked with `public` are exempted.
+/// Requirements of methods are available to `registry::requirement_of` only, not to `requirement_of`.
+#[cfg(feature = "rocket")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rocket")))]
+#[proc_macro_attribute]
+pub fn protect_all_rocket(args: TokenStream, input: TokenStream) -> TokenStream {
+    protect_all(Framework::Rocket, args, input)
+}
+
+/// Applies the default requirement (arguments of `protect`) to every handler of an `impl` block or an inline `mod`.
+///
+/// Handlers are functions annotated with a route or handler attribute (e.g. `#[get("/")]`, `#[handler]`, `#[oai(..)]`),
+/// `protect` or `public`, as well as `async` functions for `actix-web` and `axum`, other items are kept as is.
+/// `async` functions which aren't handlers are marked with `#[protect_all(skip)]`.
+/// `protect` attributes of a handler are combined with the default one (as stacked attributes),
+/// unless one of them is marked with `overrides`, e.g. `#[protect("ROLE_SUPPORT", overrides)]`.
+/// Handlers marked with `public` are exempted.
+/// Requirements of methods are available to `registry::requirement_of` only, not to `requirement_of`.
+#[cfg(feature = "poem")]
+#[cfg_attr(docsrs, doc(cfg(feature = "poem")))]
+#[proc_macro_attribute]
+pub fn protect_all_poem(args: TokenStream, input: TokenStream) -> TokenStream {
+    protect_all(Framework::Poem, args, input)
+}
+
+/// Applies the default requirement (arguments of `protect`) to every handler of an `impl` block or an inline `mod`.
+///
+/// Handlers are functions annotated with a route or handler attribute (e.g. `#[get("/")]`, `#[handler]`, `#[oai(..)]`),
+/// `protect` or `public`, as well as `async` functions for `actix-web` and `axum`, other items are kept as is.
+/// `async` functions which aren't handlers are marked with `#[protect_all(skip)]`.
+/// `protect` attributes of a handler are combined with the default one (as stacked attributes),
+/// unless one of them is marked with `overrides`, e.g. `#[protect("ROLE_SUPPORT", overrides)]`.
+/// Handlers marked with `public` are exempted.
+/// Requirements of methods are available to `registry::requirement_of` only, not to `requirement_of`.
+#[cfg(feature = "salvo")]
+#[cfg_attr(docsrs, doc(cfg(feature = "salvo")))]
+#[proc_macro_attribute]
+pub fn protect_all_salvo(args: TokenStream, input: TokenStream) -> TokenStream {
+    protect_all(Framework::Salvo, args, input)
+}
+
+/// Marks the handler as intentionally public for the deny-by-default mode,
+/// see `deny_by_default` module of the framework crate.
+///
+/// The handler isn't checked in any way, the attribute only acknowledges that it was not forgotten.
+#[cfg(feature = "actix-web")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actix-web")))]
+#[proc_macro_attribute]
+pub fn public_actix_web(_args: TokenStream, input: TokenStream) -> TokenStream {
+    public_endpoint(Framework::ActixWeb, input)
+}
+
+/// Marks the handler as intentionally public for the deny-by-default mode,
+/// see `deny_by_default` module of the framework crate.
+///
+/// The handler isn't checked in any way, the attribute only acknowledges that it was not forgotten.
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+#[proc_macro_attribute]
+pub fn public_axum(_args: TokenStream, input: TokenStream) -> TokenStream {
+    public_endpoint(Framework::Axum, input)
+}
+
+/// Marks the handler as intentionally public for the deny-by-default mode,
+/// see `deny_by_default` module of the framework crate.
+///
+/// The handler isn't checked in any way, the attribute only acknowledges that it was not forgotten.
+#[cfg(feature = "rocket")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rocket")))]
+#[proc_macro_attribute]
+pub fn public_rocket(_args: TokenStream, input: TokenStream) -> TokenStream {
+    public_endpoint(Framework::Rocket, input)
+}
+
+/// Marks the handler as intentionally public for the deny-by-default mode,
+/// see `deny_by_default` module of the framework crate.
+///
+/// The handler isn't checked in any way, the attribute only acknowledges that it was not forgotten.
+#[cfg(feature = "poem")]
+#[cfg_attr(docsrs, doc(cfg(feature = "poem")))]
+#[proc_macro_attribute]
+pub fn public_poem(_args: TokenStream, input: TokenStream) -> TokenStream {
+    public_endpoint(Framework::Poem, input)
+}
+
+/// Marks the handler as intentionally public for the deny-by-default mode,
+/// see `deny_by_default` module of the framework crate.
+///
+/// The handler isn't checked in any way, the attribute only acknowledges that it was not forgotten.
+#[cfg(feature = "salvo")]
+#[cfg_attr(docsrs, doc(cfg(feature = "salvo")))]
+#[proc_macro_attribute]
+pub fn public_salvo(_args: TokenStream, input: TokenStream) -> TokenStream {
+    public_endpoint(Framework::Salvo, input)
+}
+
+/// Requirement of the handler annotated with `protect` as `Requirement<&'static str>`, e.g. for unit tests
+/// without requests: `assert!(requirement_of!(api::users).is_satisfied_by(&["ROLE_ADMIN"]))`.
+///
+/// It contains the whole condition (including stacked attributes), where `expr`, `owner` and `from_network`
+/// are opaque dynamic parts, since they can't be evaluated without a request: such a requirement is satisfied
+/// by authorities only if it doesn't depend on them (see `may_be_satisfied_by` otherwise).
+/// Typed authorities are kept in the source form, e.g. `Role::ADMIN`.
+/// Methods and `actix-web` handlers with route macros (e.g. `#[get("/")]`, which expand the handler within
+/// the generated service) aren't supported, use `registry::requirement_of` of the `registry` feature for them.
+#[proc_macro]
+pub fn requirement_of(input: TokenStream) -> TokenStream {
+    let mut path = parse_macro_input!(input as syn::Path);
+    if let Some(handler) = path.segments.last_mut() {
+        handler.ident = requirement_fn_ident(&handler.ident);
+    }
+
+    quote::quote!(#path()).into()
+}
+
 fn protect_endpoint(framework: Framework, args: TokenStream, input: TokenStream) -> TokenStream {
     let args = match NestedMeta::parse_meta_list(args.into()) {
         Ok(v) => v,
@@ -288,3 +552,20 @@ fn protect_endpoint(framework: Framework, args: TokenStream, input: TokenStream)
         .into_token_stream()
         .into()
 }
+
+fn protect_all(framework: Framework, args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as syn::Item);
+
+    ProtectAll::new(args.into())
+        .expand(framework, item)
+        .unwrap_or_else(|e| e.write_errors())
+        .into()
+}
+
+fn public_endpoint(framework: Framework, input: TokenStream) -> TokenStream {
+    let func = parse_macro_input!(input as ItemFn);
+
+    PublicEndpoint::new(framework, FnType::Fn(func))
+        .into_token_stream()
+        .into()
+}
@@ -0,0 +1,212 @@
+//! `protect-endpoints` scans sources of a crate for handlers and reports their routes,
+//! methods and conditions, flagging handlers annotated neither with `protect` nor with `public`.
+//!
+//! Arguments of `protect` attributes are parsed by the same code as the macro expands them
+//! (so the report matches the registry of the `registry` feature).
+//! Handlers of routers (e.g. `axum` or `salvo`) are found by `protect`, `public` or `handler`
+//! attributes only, so unannotated `axum` handlers can't be flagged.
+//!
+//! ```text
+//! protect-endpoints [--format text|markdown|json] [--deny-unprotected] [PATH]...
+//! ```
+
+mod cli {
+    pub(crate) mod report;
+    pub(crate) mod scan;
+}
+// the expansion of the macro is reused for requirements only
+#[allow(dead_code, unused_imports)]
+mod expand;
+
+use cli::report::{self, Format};
+use cli::scan;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Reports routes of the handlers with their conditions
+
+Usage: protect-endpoints [OPTIONS] [PATH]...
+
+Arguments:
+  [PATH]...  Source files or directories to scan [default: src]
+
+Options:
+      --format <FORMAT>   Format of the report: text, markdown or json [default: text]
+      --deny-unprotected  Exit with an error if any handler is unprotected (or invalid)
+  -h, --help              Print help
+";
+
+struct Args {
+    paths: Vec<PathBuf>,
+    format: Format,
+    deny_unprotected: bool,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut files = Vec::new();
+    for path in &args.paths {
+        if let Err(err) = collect_files(path, &mut files) {
+            eprintln!("error: can't read `{}`: {err}", path.display());
+            return ExitCode::from(2);
+        }
+    }
+
+    let mut routes = Vec::new();
+    for file in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("error: can't read `{}`: {err}", file.display());
+                return ExitCode::from(2);
+            }
+        };
+        let name = file.display().to_string();
+        match scan::scan(&name, &source) {
+            Ok(found) => routes.extend(found),
+            Err(err) => {
+                let start = err.span().start();
+                eprintln!(
+                    "warning: skipped `{name}:{}:{}`: {err}",
+                    start.line,
+                    start.column + 1
+                );
+            }
+        }
+    }
+
+    print!("{}", report::render(&routes, args.format));
+
+    if args.deny_unprotected && routes.iter().any(report::Route::is_unprotected) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// `None` if the help is requested.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args {
+        paths: Vec::new(),
+        format: Format::Text,
+        deny_unprotected: false,
+    };
+
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--deny-unprotected" => parsed.deny_unprotected = true,
+            "--format" => {
+                let format = match value {
+                    Some(value) => value.to_string(),
+                    None => args.next().ok_or("a value is required for `--format`")?,
+                };
+                parsed.format = match format.as_str() {
+                    "text" => Format::Text,
+                    "markdown" | "md" => Format::Markdown,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format `{other}`")),
+                };
+            }
+            other if other.starts_with('-') => return Err(format!("unknown option `{other}`")),
+            _ => parsed.paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if parsed.paths.is_empty() {
+        parsed.paths.push(PathBuf::from("src"));
+    }
+
+    Ok(Some(parsed))
+}
+
+/// Collects `.rs` files recursively in the order of paths, skipping hidden directories and `target`.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !path.is_dir() {
+        // missing files are reported on reading
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for entry in entries {
+        let name = entry
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if entry.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect_files(&entry, files)?;
+            }
+        } else if name.ends_with(".rs") {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&[]).unwrap().unwrap();
+        assert_eq!(vec![PathBuf::from("src")], args.paths);
+        assert_eq!(Format::Text, args.format);
+        assert!(!args.deny_unprotected);
+
+        let args = parse(&[
+            "--format",
+            "json",
+            "--deny-unprotected",
+            "api/src",
+            "lib.rs",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            vec![PathBuf::from("api/src"), PathBuf::from("lib.rs")],
+            args.paths
+        );
+        assert_eq!(Format::Json, args.format);
+        assert!(args.deny_unprotected);
+
+        assert_eq!(
+            Format::Markdown,
+            parse(&["--format=markdown"]).unwrap().unwrap().format
+        );
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert_eq!(
+            "unknown format `xml`",
+            parse(&["--format", "xml"]).err().unwrap()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Documentation of handlers protected by [`protect`](crate::protect) in [`aide`] specs.
+//!
+//! With the `aide` feature, protected handlers can be routed by `ApiRouter` of `aide`:
+//! extractors of the crate (e.g. [`AuthDetails`]) implement [`OperationInput`] without documenting anything,
+//! since they aren't parameters of the request. Requirements of the handlers are added to the finished spec
+//! by [`ProtectedOperations`], which matches operations by their ids.
+//!
+//! # Example
+//! ```
+//! use aide::axum::routing::get_with;
+//! use aide::axum::ApiRouter;
+//! use aide::openapi::OpenApi;
+//! use protect_axum::aide::ProtectedOperations;
+//!
+//! const PROTECTED: ProtectedOperations = ProtectedOperations::new().security("bearer");
+//!
+//! #[protect_axum::protect("ROLE_ADMIN")]
+//! async fn users() -> &'static str {
+//!     "Users"
+//! }
+//!
+//! let mut api = OpenApi::default();
+//! let app: axum::Router = ApiRouter::new()
+//!     .api_route("/users", get_with(users, |op| op.id("users")))
+//!     .finish_api_with(&mut api, |api| PROTECTED.transform_aide(api));
+//! ```
+//!
+//! [`aide`]: https://github.com/tamasfe/aide
+
+use crate::authorities::{AuthDetails, Principal};
+use crate::network::ClientIp;
+use ::aide::OperationInput;
+use std::hash::Hash;
+
+pub use protect_endpoints_core::registry::openapi::{ProtectedOperations, EXTENSION};
+
+impl<T: Eq + Hash> OperationInput for AuthDetails<T> {}
+
+impl<C> OperationInput for Principal<C> {}
+
+impl OperationInput for ClientIp {}
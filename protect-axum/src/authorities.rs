@@ -1,10 +1,19 @@
-use axum::extract::FromRequestParts;
+use crate::error::Unauthorized;
+use axum::extract::{FromRequestParts, OptionalFromRequestParts};
 use axum::http::request::Parts;
-use protect_endpoints_core::authorities::AuthDetails as AuthDetailsCore;
+use protect_endpoints_core::authorities::{
+    AuthDetails as AuthDetailsCore, Principal as PrincipalCore,
+};
+use std::convert::Infallible;
 use std::hash::Hash;
 use std::ops::Deref;
 
-pub use protect_endpoints_core::authorities::{AttachAuthorities, AuthoritiesCheck};
+pub use protect_endpoints_core::authorities::extractor::{active_authorities, AuthoritiesStore};
+pub use protect_endpoints_core::authorities::{
+    anonymous_authorities, is_anonymous, now, reset_anonymous_authorities, reset_clock,
+    set_anonymous_authorities, set_clock, AttachAuthorities, AttachPrincipal, AuthoritiesCheck,
+    Clock, SystemClock, TimedAuthority, Validity,
+};
 
 pub struct AuthDetails<T = String>(AuthDetailsCore<T>)
 where
@@ -15,15 +24,41 @@ where
     T: Eq + Hash + Send + Sync + 'static,
     S: Send + Sync,
 {
-    type Rejection = axum::http::StatusCode;
+    type Rejection = Unauthorized;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        parts
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .unwrap_or_else(|never| match never {})
+            .ok_or(Unauthorized)
+    }
+}
+
+/// `None` for requests without authorities and for the anonymous principal.
+impl<S, T> OptionalFromRequestParts<S> for AuthDetails<T>
+where
+    T: Eq + Hash + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts
             .extensions
             .get::<AuthDetailsCore<T>>()
+            .filter(|details| !details.is_anonymous())
             .cloned()
-            .map(AuthDetails)
-            .ok_or(axum::http::StatusCode::UNAUTHORIZED)
+            .map(AuthDetails))
+    }
+}
+
+impl<T: Eq + Hash + 'static> AuthDetails<T> {
+    /// Details of the anonymous principal, empty if anonymous authorities aren't configured.
+    pub fn anonymous() -> AuthDetails<T> {
+        AuthDetails(AuthDetailsCore::anonymous())
     }
 }
 
@@ -34,3 +69,50 @@ impl<T: Eq + Hash> Deref for AuthDetails<T> {
         &self.0
     }
 }
+
+/// Subject identifier and typed claims of the user, see [`AttachPrincipal`].
+///
+/// Requests without the attached principal are rejected as unauthorized.
+pub struct Principal<C = ()>(PrincipalCore<C>);
+
+impl<S, C> FromRequestParts<S> for Principal<C>
+where
+    C: Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Unauthorized;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .unwrap_or_else(|never| match never {})
+            .ok_or(Unauthorized)
+    }
+}
+
+impl<S, C> OptionalFromRequestParts<S> for Principal<C>
+where
+    C: Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<PrincipalCore<C>>()
+            .cloned()
+            .map(Principal))
+    }
+}
+
+impl<C> Deref for Principal<C> {
+    type Target = PrincipalCore<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,36 @@
+//! Deny-by-default mode: requests which aren't routed to handlers annotated with [`protect`](crate::protect)
+//! or [`public`](crate::public) are rejected with `403`, see [`protect_endpoints_core::deny_by_default`].
+//!
+//! The mode is enabled by [`DenyByDefaultLayer`] configured with [`Routes`] of the app.
+//! Requests are checked before they're routed, so unannotated handlers never run.
+//!
+//! # Example
+//! ```
+//! use axum::routing::get;
+//! use axum::Router;
+//! use protect_axum::deny_by_default::{DenyByDefaultLayer, Routes};
+//!
+//! #[protect_axum::public]
+//! async fn index() -> &'static str {
+//!     "Hi!"
+//! }
+//!
+//! #[protect_axum::protect("ROLE_ADMIN")]
+//! async fn admin() -> &'static str {
+//!     "Hello, admin!"
+//! }
+//!
+//! let routes = Routes::new()
+//!     .route("GET", "/", index)
+//!     .route("GET", "/admin", admin);
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(index))
+//!     .route("/admin", get(admin))
+//!     .layer(DenyByDefaultLayer::new(routes));
+//! ```
+
+pub use protect_endpoints_core::deny_by_default::{is_annotated, AnnotatedHandler, Route, Routes};
+pub use protect_endpoints_core::tower::deny_by_default::{
+    DenyByDefaultLayer, DenyByDefaultService,
+};
@@ -0,0 +1,68 @@
+use axum::body::Body;
+use axum::http::header::WWW_AUTHENTICATE;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use protect_endpoints_core::condition::{Condition, Explanation};
+use protect_endpoints_core::error::{unauthorized_challenge, AccessDenied as AccessDeniedCore};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+pub use protect_endpoints_core::error::{
+    set_challenge, set_denial_renderer, Challenge, PROBLEM_JSON,
+};
+
+/// Default error of the [`protect`](crate::protect) macro.
+///
+/// Rendered as `application/problem+json`, see [`protect_endpoints_core::error`]
+/// for details and customization.
+#[derive(Debug, Clone)]
+pub struct AccessDenied(AccessDeniedCore);
+
+impl AccessDenied {
+    pub fn new(required: Condition<String>, explanation: Explanation<String>) -> Self {
+        Self(AccessDeniedCore::new(required, explanation))
+    }
+}
+
+impl IntoResponse for AccessDenied {
+    fn into_response(self) -> Response {
+        self.0.to_response().map(Body::from)
+    }
+}
+
+impl From<AccessDeniedCore> for AccessDenied {
+    fn from(value: AccessDeniedCore) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for AccessDenied {
+    type Target = AccessDeniedCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for AccessDenied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for AccessDenied {}
+
+/// [`AuthDetails`](crate::authorities::AuthDetails) are missing in the request,
+/// rendered with `WWW-Authenticate` challenge.
+#[derive(Debug, Clone, Copy)]
+pub struct Unauthorized;
+
+impl IntoResponse for Unauthorized {
+    fn into_response(self) -> Response {
+        let mut response = StatusCode::UNAUTHORIZED.into_response();
+        if let Some(challenge) = unauthorized_challenge().and_then(|c| c.try_into().ok()) {
+            response.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+        }
+        response
+    }
+}
@@ -0,0 +1,30 @@
+//! Admin endpoint serving the registered endpoints and simulating the access of authorities,
+//! see [`protect_endpoints_core::registry::introspection`].
+//!
+//! # Example
+//! ```
+//! use axum::routing::get;
+//! use axum::Router;
+//!
+//! #[protect_axum::protect("ROLE_SUPPORT")]
+//! async fn introspection(uri: axum::http::Uri) -> axum::response::Response {
+//!     protect_axum::introspection::handler(uri).await
+//! }
+//!
+//! // GET /admin/access?authorities=ROLE_USER,OP_READ
+//! let app: Router = Router::new().route("/admin/access", get(introspection));
+//! ```
+
+use axum::http::header::CONTENT_TYPE;
+use axum::http::Uri;
+use axum::response::{IntoResponse, Response};
+use protect_endpoints_core::registry::introspection;
+
+/// Responds with the registered endpoints, and their access if `authorities` are given in the query.
+pub async fn handler(uri: Uri) -> Response {
+    (
+        [(CONTENT_TYPE, introspection::CONTENT_TYPE)],
+        introspection::respond(uri.query()),
+    )
+        .into_response()
+}
@@ -14,7 +14,31 @@
 
 use protect_endpoints_core::tower::middleware::GrantsLayer as CoreGrantsLayer;
 
+#[cfg(feature = "aide")]
+pub mod aide;
 pub mod authorities;
+#[cfg(feature = "deny-by-default")]
+pub mod deny_by_default;
+pub mod error;
+#[cfg(feature = "registry")]
+pub mod introspection;
+pub mod network;
+pub mod table;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[doc(hidden)]
+pub use protect_endpoints_core::__annotate_handler;
+#[doc(hidden)]
+pub use protect_endpoints_core::__register_endpoint;
+pub use protect_endpoints_core::condition;
+#[cfg(any(
+    feature = "policy-toml",
+    feature = "policy-yaml",
+    feature = "policy-json"
+))]
+pub use protect_endpoints_core::policy;
+#[cfg(feature = "registry")]
+pub use protect_endpoints_core::registry;
 
 pub type GrantsLayer<Extractor, Type, Err> =
     CoreGrantsLayer<Extractor, axum::extract::Request, Type, Err>;
@@ -38,6 +62,12 @@ pub type GrantsLayer<Extractor, Type, Err> =
 ///     (StatusCode::OK, "some secured info")
 /// }
 ///
+/// // User should be ADMIN, or have OP_WRITE permission without being SUSPENDED
+/// #[protect_axum::protect(rule = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)")]
+/// async fn rule_macro_secured() -> (StatusCode, &'static str) {
+///     (StatusCode::OK, "some secured info")
+/// }
+///
 /// // Additional security condition to ensure the protection of the endpoint
 /// #[protect_axum::protect("USER", expr = "*user_id == user.id")]
 /// async fn role_macro_secured_with_params(user_id: Path<i32>, user: Extension<&User>) -> (StatusCode, &'static str) {
@@ -56,7 +86,10 @@ pub type GrantsLayer<Extractor, Type, Err> =
 /// ```
 #[cfg(feature = "macro-check")]
 pub mod proc_macro {
-    pub use protect_endpoints_proc_macro::protect_axum as protect;
+    pub use protect_endpoints_proc_macro::{
+        protect_all_axum as protect_all, protect_axum as protect, public_axum as public,
+        requirement_of,
+    };
 }
 
 /// Just a shortcut for proc-macros
@@ -0,0 +1,126 @@
+//! Network conditions: the client IP address within ranges, see [`protect_endpoints_core::network`].
+//!
+//! Handlers are restricted via `from_network` argument of the [`protect`](crate::protect) macro,
+//! whole routers or routes via [`NetworkLayer`].
+//!
+//! The peer address is taken from [`ConnectInfo<SocketAddr>`](axum::extract::ConnectInfo),
+//! so the app should be served with `into_make_service_with_connect_info::<SocketAddr>()`.
+
+use crate::error::AccessDenied;
+use axum::extract::{ConnectInfo, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use protect_endpoints_core::network::ClientIp as ClientIpCore;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+pub use protect_endpoints_core::network::{
+    client_ip, register_network, reset_trusted_proxies, set_trusted_proxies, ForwardedHeader,
+    IpNet, NetworkError, Networks,
+};
+
+/// Address of the client: the peer address or the forwarded one, if the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(ClientIpCore);
+
+impl ClientIp {
+    fn resolve(extensions: &Extensions, headers: &HeaderMap) -> Self {
+        let peer = extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| peer.ip());
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        ClientIp(ClientIpCore::new(peer, headers))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ClientIp::resolve(&parts.extensions, &parts.headers))
+    }
+}
+
+impl Deref for ClientIp {
+    type Target = ClientIpCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Tower compatible middleware denying requests from clients outside of the networks.
+///
+/// # Example
+/// ```
+/// use axum::routing::get;
+/// use axum::Router;
+/// use protect_axum::network::NetworkLayer;
+///
+/// let app: Router = Router::new()
+///     .route("/internal/metrics", get(|| async { "up 1" }))
+///     .layer(NetworkLayer::new("10.0.0.0/8".parse().unwrap()));
+/// ```
+#[derive(Clone)]
+pub struct NetworkLayer {
+    networks: Arc<Networks>,
+}
+
+impl NetworkLayer {
+    pub fn new(networks: Networks) -> Self {
+        Self {
+            networks: Arc::new(networks),
+        }
+    }
+}
+
+impl<S> Layer<S> for NetworkLayer {
+    type Service = NetworkService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NetworkService {
+            inner,
+            networks: self.networks.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NetworkService<S> {
+    inner: S,
+    networks: Arc<Networks>,
+}
+
+impl<S> Service<Request> for NetworkService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let client = ClientIp::resolve(req.extensions(), req.headers());
+        match client.check(&self.networks) {
+            Ok(()) => Box::pin(self.inner.call(req)),
+            Err(denied) => {
+                let response = AccessDenied::from(denied).into_response();
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
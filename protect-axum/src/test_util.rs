@@ -0,0 +1,57 @@
+//! Injection of authorities into test requests, see [`protect_endpoints_core::test_util`].
+//!
+//! # Example
+//! ```
+//! use axum::body::Body;
+//! use axum::http::{Request, StatusCode};
+//! use axum::routing::get;
+//! use axum::Router;
+//! use protect_axum::test_util::{MockExtractor, WithAuthorities};
+//! use protect_axum::GrantsLayer;
+//! use tower::ServiceExt;
+//!
+//! #[protect_axum::protect("ROLE_ADMIN")]
+//! async fn secured() -> &'static str {
+//!     "some secured info"
+//! }
+//!
+//! # tokio_test();
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn tokio_test() {
+//! let extractor = MockExtractor::new();
+//! let app = Router::new()
+//!     .route("/", get(secured))
+//!     .layer(GrantsLayer::with_extractor(extractor.clone()));
+//!
+//! let req = Request::get("/").with_authorities(["ROLE_ADMIN"]);
+//! let resp = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+//!
+//! assert_eq!(StatusCode::OK, resp.status());
+//! assert_eq!(1, extractor.calls().len());
+//! # }
+//! ```
+
+use axum::http::request::Builder;
+use axum::response::Response;
+pub use protect_endpoints_core::test_util::{header_value, Call, AUTHORITIES_HEADER};
+
+/// Extractor of authorities given by [`WithAuthorities`], scripted results and errors take precedence.
+pub type MockExtractor = protect_endpoints_core::test_util::MockExtractor<Response>;
+
+/// Passes authorities of the test request to the [`MockExtractor`].
+pub trait WithAuthorities {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>;
+}
+
+impl WithAuthorities for Builder {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.header(AUTHORITIES_HEADER, header_value(authorities))
+    }
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "aide")]
+
+use aide::axum::routing::get_with;
+use aide::axum::ApiRouter;
+use aide::openapi::OpenApi;
+use axum::Router;
+use protect_axum::aide::{ProtectedOperations, EXTENSION};
+use serde_json::{json, Value};
+
+#[protect_axum::protect("OP_READ_USERS")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect(any("ROLE_ADMIN", "ROLE_EDITOR"))]
+async fn articles() -> &'static str {
+    "Hi!"
+}
+
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+const PROTECTED: ProtectedOperations = ProtectedOperations::new().security("oauth2").scopes();
+
+fn app(api: &mut OpenApi) -> Router {
+    ApiRouter::new()
+        .api_route(
+            "/users",
+            get_with(users, |op| op.id("users").description("Lists users.")),
+        )
+        .api_route("/articles", get_with(articles, |op| op.id("articles")))
+        .api_route("/", get_with(index, |op| op.id("index")))
+        .finish_api_with(api, |api| PROTECTED.transform_aide(api))
+}
+
+fn operation(spec: &Value, path: &str) -> Value {
+    spec["paths"][path]["get"].clone()
+}
+
+#[test]
+fn test_protected_operations() {
+    let mut api = OpenApi::default();
+    let _app = app(&mut api);
+    let spec = serde_json::to_value(&api).unwrap();
+
+    let users = operation(&spec, "/users");
+    assert_eq!(
+        "Lists users.\n\nRequires `OP_READ_USERS`.",
+        users["description"]
+    );
+    assert_eq!("OP_READ_USERS", users[EXTENSION]);
+    assert!(users["responses"]["401"].is_object());
+    assert!(users["responses"]["403"].is_object());
+    assert_eq!(json!([{ "oauth2": ["OP_READ_USERS"] }]), users["security"]);
+
+    let articles = operation(&spec, "/articles");
+    assert_eq!("ROLE_ADMIN || ROLE_EDITOR", articles[EXTENSION]);
+    assert_eq!(json!([{ "oauth2": [] }]), articles["security"]);
+
+    let index = operation(&spec, "/");
+    assert!(index.get(EXTENSION).is_none());
+    assert!(index.get("security").is_none());
+}
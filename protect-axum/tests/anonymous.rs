@@ -0,0 +1,94 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use http_body_util::BodyExt;
+use protect_axum::authorities::{set_anonymous_authorities, AuthDetails, AuthoritiesCheck};
+use protect_axum::{protect, GrantsLayer};
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+#[protect(rule = "ANONYMOUS || ROLE_USER", allow_anonymous)]
+async fn allow_anonymous() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_USER")]
+async fn user_only() -> &'static str {
+    "Hi!"
+}
+
+async fn optional(details: Option<AuthDetails>) -> &'static str {
+    match details {
+        Some(details) if details.has_authority("ROLE_USER") => "user",
+        Some(_) => "authenticated",
+        None => "anonymous",
+    }
+}
+
+#[tokio::test]
+async fn test_anonymous() {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    assert_eq!(
+        (StatusCode::OK, "Hi!".into()),
+        get_response("/allow_anonymous", None).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "Hi!".into()),
+        get_response("/allow_anonymous", Some("ROLE_USER")).await
+    );
+    let (status, _) = get_response("/allow_anonymous", Some("ROLE_OTHER")).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let (status, _) = get_response("/user_only", None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+
+    assert_eq!(
+        (StatusCode::OK, "anonymous".into()),
+        get_response("/optional", None).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "user".into()),
+        get_response("/optional", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "authenticated".into()),
+        get_response("/optional", Some("ROLE_OTHER")).await
+    );
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_response(uri: &str, role: Option<&str>) -> (StatusCode, String) {
+    let app = Router::new()
+        .route("/allow_anonymous", get(allow_anonymous))
+        .route("/user_only", get(user_only))
+        .route("/optional", get(optional))
+        .layer(GrantsLayer::with_extractor(extract));
+
+    let mut request = Request::builder().uri(uri);
+    if let Some(role) = role {
+        request = request.header(AUTHORIZATION, role);
+    }
+    let resp = app
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
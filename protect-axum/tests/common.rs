@@ -2,12 +2,19 @@ use axum::extract::Request;
 use axum::http::header::{HeaderValue, AUTHORIZATION};
 use axum::response::Response;
 use http_body_util::BodyExt;
+use protect_axum::authorities::AttachPrincipal;
 use serde::Deserialize;
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 
 pub const ROLE_ADMIN: &str = "ROLE_ADMIN";
 pub const ROLE_MANAGER: &str = "ROLE_MANAGER";
+pub const TENANT_HEADER: &str = "x-tenant";
+pub const SUBJECT: &str = "user-1";
+
+pub struct Claims {
+    pub tenant: String,
+}
 
 #[derive(Eq, PartialEq, Hash)]
 #[allow(clippy::upper_case_acronyms)]
@@ -17,6 +24,15 @@ pub enum Role {
 }
 
 pub async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    let tenant = req
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .map(str::to_string);
+    if let Some(tenant) = tenant {
+        req.attach_principal(SUBJECT, Claims { tenant });
+    }
+
     let auth_header: Option<&str> = req
         .headers()
         .get(AUTHORIZATION)
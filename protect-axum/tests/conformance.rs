@@ -0,0 +1,105 @@
+use axum::body::Body;
+use axum::extract::{Query, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use protect_axum::GrantsLayer;
+use protect_endpoints_testkit::{
+    header_value, parse_header_value, Adapter, Credentials, Role, AUTHORITIES_HEADER, OWNER,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+struct NamePayload {
+    name: Option<String>,
+}
+
+#[protect_axum::protect("ROLE_ADMIN")]
+async fn single() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect("ROLE_ADMIN", "ROLE_MANAGER")]
+async fn all() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]
+async fn any() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect("ROLE_ADMIN", expr = "payload.name.as_deref() == Some(OWNER)")]
+async fn expr(payload: Query<NamePayload>) -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect("Role::Admin", ty = "Role")]
+async fn typed() -> &'static str {
+    "Hi!"
+}
+
+fn authorities(req: &Request) -> HashSet<String> {
+    req.headers()
+        .get(AUTHORITIES_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .map(parse_header_value)
+        .unwrap_or_default()
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(authorities(req))
+}
+
+async fn extract_roles(req: &mut Request) -> Result<HashSet<Role>, Response> {
+    Ok(authorities(req)
+        .iter()
+        .filter_map(|authority| authority.parse().ok())
+        .collect())
+}
+
+async fn fail<T>(_req: &mut Request) -> Result<HashSet<T>, Response> {
+    Err(StatusCode::UNAUTHORIZED.into_response())
+}
+
+struct Axum;
+
+impl Adapter for Axum {
+    async fn status(&self, path: &str, credentials: &Credentials) -> u16 {
+        let routes = Router::new()
+            .route("/single", get(single))
+            .route("/all", get(all))
+            .route("/any", get(any))
+            .route("/expr", get(expr));
+        let typed = Router::new().route("/typed", get(typed));
+
+        let app = match credentials {
+            Credentials::Authorities(_) => routes
+                .layer(GrantsLayer::with_extractor(extract))
+                .merge(typed.layer(GrantsLayer::with_extractor(extract_roles))),
+            Credentials::Missing => routes.merge(typed),
+            Credentials::ExtractorError => routes
+                .layer(GrantsLayer::with_extractor(fail::<String>))
+                .merge(typed.layer(GrantsLayer::with_extractor(fail::<Role>))),
+        };
+
+        let mut request = Request::get(path);
+        if let Credentials::Authorities(authorities) = credentials {
+            request = request.header(AUTHORITIES_HEADER, header_value(*authorities));
+        }
+
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+}
+
+#[tokio::test]
+async fn test_conformance() {
+    protect_endpoints_testkit::run(&Axum).await;
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "deny-by-default")]
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use protect_axum::deny_by_default::{DenyByDefaultLayer, Routes};
+use protect_axum::GrantsLayer;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tower::ServiceExt;
+
+#[protect_axum::public]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect("ROLE_ADMIN")]
+async fn admin() -> &'static str {
+    "Hello, admin!"
+}
+
+static FORGOTTEN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+async fn forgotten() -> &'static str {
+    FORGOTTEN_CALLS.fetch_add(1, Ordering::SeqCst);
+    "Oops!"
+}
+
+#[protect_axum::public]
+async fn unlisted() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_deny_by_default() {
+    assert_eq!(StatusCode::OK, get_status("/", None).await);
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/unlisted", None).await);
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/missing", None).await);
+}
+
+#[tokio::test]
+async fn test_forgotten_handler_not_called() {
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(0, FORGOTTEN_CALLS.load(Ordering::SeqCst));
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+fn routes() -> Routes {
+    Routes::new()
+        .route("GET", "/", index)
+        .route("GET", "/admin", admin)
+        .route("GET", "/forgotten", forgotten)
+}
+
+async fn get_status(uri: &str, role: Option<&str>) -> StatusCode {
+    let app = Router::new()
+        .route("/", get(index))
+        .route("/admin", get(admin))
+        .route("/forgotten", get(forgotten))
+        .route("/unlisted", get(unlisted))
+        .layer(DenyByDefaultLayer::new(routes()))
+        .layer(GrantsLayer::with_extractor(extract));
+
+    let mut request = Request::builder().uri(uri);
+    if let Some(role) = role {
+        request = request.header(AUTHORIZATION, role);
+    }
+
+    app.oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
@@ -0,0 +1,89 @@
+//! Explanations are enabled globally, so they are tested in a separate binary.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use http_body_util::BodyExt;
+use protect_axum::condition::{set_explain_mode, ExplainMode};
+use protect_axum::{protect, GrantsLayer};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)")]
+async fn rule_response() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_explain_modes() {
+    set_explain_mode(ExplainMode::Full);
+    let (status, body) = get_user_response("ROLE_MANAGER,ROLE_BLOCKED").await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        "[failed] any\n  [failed] ROLE_ADMIN\n  [failed] all\n    [passed] ROLE_MANAGER\n    [failed] not\n      [passed] ROLE_BLOCKED",
+        problem["detail"]
+    );
+    assert_eq!(
+        "ROLE_ADMIN || ROLE_MANAGER && !ROLE_BLOCKED",
+        problem["required"]
+    );
+    assert_eq!(json!(["ROLE_ADMIN"]), problem["missing_authorities"]);
+
+    let (status, body) = get_user_response("ROLE_MANAGER").await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!("Hi!", body);
+
+    set_explain_mode(ExplainMode::Redacted);
+    let (status, body) = get_user_response("ROLE_USER").await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        "[failed] any\n  [failed] ***\n  [failed] all\n    [failed] ***\n    [passed] not\n      [failed] ***",
+        problem["detail"]
+    );
+    assert_eq!(Value::Null, problem["required"]);
+
+    set_explain_mode(ExplainMode::Disabled);
+    let (status, body) = get_user_response("ROLE_USER").await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(403, problem["status"]);
+    assert!(problem["correlation_id"].is_string());
+    assert_eq!(Value::Null, problem["detail"]);
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_user_response(role: &str) -> (StatusCode, String) {
+    let app = Router::new()
+        .route("/rule", get(rule_response))
+        .layer(GrantsLayer::with_extractor(extract));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .header(AUTHORIZATION, role)
+                .uri("/rule")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "registry")]
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::CONTENT_TYPE;
+use axum::routing::get;
+use axum::Router;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+#[protect_axum::protect("ROLE_ADMIN")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect(any("ROLE_ADMIN", "ROLE_EDITOR"), expr = "true")]
+async fn article() -> &'static str {
+    "Hi!"
+}
+
+async fn get_body(uri: &str) -> String {
+    let app = Router::new()
+        .route("/users", get(users))
+        .route("/article", get(article))
+        .route("/admin/access", get(protect_axum::introspection::handler));
+
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!("application/json", response.headers()[CONTENT_TYPE]);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_introspection() {
+    let body = get_body("/admin/access").await;
+    assert!(body.starts_with(r#"{"endpoints":[{"handler":"introspection::users","#));
+    assert!(body.contains(r#""condition":"ROLE_ADMIN || ROLE_EDITOR","dynamic":true}"#));
+    assert!(!body.contains("access"));
+
+    let body = get_body("/admin/access?authorities=ROLE_EDITOR").await;
+    assert!(body.starts_with(r#"{"authorities":["ROLE_EDITOR"],"endpoints":["#));
+    assert!(body.contains(r#""condition":"ROLE_ADMIN","dynamic":false,"access":"denied"}"#));
+    assert!(body.contains(r#""dynamic":true,"access":"depends"}"#));
+}
@@ -0,0 +1,120 @@
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Request};
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use protect_axum::network::NetworkLayer;
+use protect_axum::{protect, GrantsLayer};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use tower::ServiceExt;
+
+const INTERNAL: &str = "10.1.2.3:4000";
+const EXTERNAL: &str = "203.0.113.1:4000";
+
+#[protect(any("ROLE_ADMIN", from_network = "10.0.0.0/8, ::1"))]
+async fn from_network() -> &'static str {
+    "Hi!"
+}
+
+// every condition keeps its own parsed networks
+#[protect(any(
+    from_network = "192.168.0.0/16",
+    all("ROLE_USER", from_network = "10.0.0.0/8")
+))]
+async fn from_networks() -> &'static str {
+    "Hi!"
+}
+
+async fn metrics() -> &'static str {
+    "up 1"
+}
+
+#[tokio::test]
+async fn test_from_network() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", Some(INTERNAL)).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", Some("[::1]:4000")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_ADMIN", Some(EXTERNAL)).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/from_network", "ROLE_USER", Some(EXTERNAL)).await
+    );
+    // without `ConnectInfo` the client is unknown
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/from_network", "ROLE_USER", None).await
+    );
+}
+
+#[tokio::test]
+async fn test_from_networks() {
+    for _ in 0..2 {
+        assert_eq!(
+            StatusCode::OK,
+            get_status("/from_networks", "ROLE_OTHER", Some("192.168.1.1:4000")).await
+        );
+        assert_eq!(
+            StatusCode::OK,
+            get_status("/from_networks", "ROLE_USER", Some(INTERNAL)).await
+        );
+        assert_eq!(
+            StatusCode::FORBIDDEN,
+            get_status("/from_networks", "ROLE_OTHER", Some(INTERNAL)).await
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_network_layer() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/internal/metrics", "ROLE_USER", Some(INTERNAL)).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/internal/metrics", "ROLE_ADMIN", Some(EXTERNAL)).await
+    );
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str, peer: Option<&str>) -> StatusCode {
+    let internal = Router::new()
+        .route("/metrics", get(metrics))
+        .layer(NetworkLayer::new("10.0.0.0/8".parse().unwrap()));
+    let app = Router::new()
+        .route("/from_network", get(from_network))
+        .route("/from_networks", get(from_networks))
+        .nest("/internal", internal)
+        .layer(GrantsLayer::with_extractor(extract));
+
+    let mut req = Request::builder()
+        .uri(uri)
+        .header(AUTHORIZATION, role)
+        .body(Body::empty())
+        .unwrap();
+    if let Some(peer) = peer {
+        let peer: SocketAddr = peer.parse().unwrap();
+        req.extensions_mut().insert(ConnectInfo(peer));
+    }
+
+    app.oneshot(req).await.unwrap().status()
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "utoipa")]
+
+use axum::routing::get;
+use axum::Router;
+use protect_axum::registry::openapi::{ProtectedOperations, EXTENSION};
+use serde_json::{json, Value};
+use utoipa::openapi::security::{Flow, Implicit, OAuth2, Scopes, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+/// Lists users.
+#[utoipa::path(get, path = "/users", responses((status = 200, description = "Users")))]
+#[protect_axum::protect("OP_READ_USERS")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[utoipa::path(get, path = "/articles", responses((status = 200, description = "Articles")))]
+#[protect_axum::protect(any("ROLE_ADMIN", "ROLE_EDITOR"))]
+async fn articles() -> &'static str {
+    "Hi!"
+}
+
+#[utoipa::path(get, path = "/", responses((status = 200, description = "Index")))]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+struct OAuthScheme;
+
+impl Modify for OAuthScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let flow = Implicit::new("https://example.com/authorize", Scopes::new());
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                "oauth2",
+                SecurityScheme::OAuth2(OAuth2::new([Flow::Implicit(flow)])),
+            );
+    }
+}
+
+const PROTECTED: ProtectedOperations = ProtectedOperations::new().security("oauth2").scopes();
+
+#[derive(OpenApi)]
+#[openapi(paths(users, articles, index), modifiers(&OAuthScheme, &PROTECTED))]
+struct ApiDoc;
+
+fn app() -> Router {
+    Router::new()
+        .route("/users", get(users))
+        .route("/articles", get(articles))
+        .route("/", get(index))
+}
+
+fn operation(spec: &Value, path: &str) -> Value {
+    spec["paths"][path]["get"].clone()
+}
+
+#[test]
+fn test_protected_operations() {
+    let _app = app();
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+    let users = operation(&spec, "/users");
+    assert_eq!("Lists users.", users["summary"]);
+    assert_eq!("Requires `OP_READ_USERS`.", users["description"]);
+    assert_eq!("OP_READ_USERS", users[EXTENSION]);
+    assert!(users["responses"]["200"].is_object());
+    assert!(users["responses"]["401"].is_object());
+    assert!(users["responses"]["403"].is_object());
+    assert_eq!(json!([{ "oauth2": ["OP_READ_USERS"] }]), users["security"]);
+
+    let articles = operation(&spec, "/articles");
+    assert_eq!("ROLE_ADMIN || ROLE_EDITOR", articles[EXTENSION]);
+    assert_eq!(json!([{ "oauth2": [] }]), articles["security"]);
+
+    let index = operation(&spec, "/");
+    assert!(index.get(EXTENSION).is_none());
+    assert!(index["responses"].get("403").is_none());
+    assert!(index.get("security").is_none());
+}
@@ -2,12 +2,16 @@ use crate::common::{self, ROLE_ADMIN, ROLE_MANAGER};
 use axum::body::Body;
 use axum::extract::{Path, Query};
 use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::request::Parts;
 use axum::http::{Request, StatusCode};
 use axum::response::Response;
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use protect_axum::{protect, GrantsLayer};
+use protect_axum::authorities::{reset_clock, set_clock, AuthDetails, AuthoritiesCheck, Principal};
+use protect_axum::error::AccessDenied;
+use protect_axum::{protect, requirement_of, GrantsLayer};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 use tower::ServiceExt;
 
 #[protect("ROLE_ADMIN")]
@@ -50,6 +54,38 @@ async fn result_response(
     Ok(format!("Welcome {}!", name))
 }
 
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)")]
+async fn rule_response() -> &'static str {
+    "Hi!"
+}
+
+async fn access_denied_with_context(
+    details: &AuthDetails,
+    denied: &AccessDenied,
+    parts: &Parts,
+) -> (StatusCode, String) {
+    let user = if details.has_authority(ROLE_MANAGER) {
+        "Manager"
+    } else {
+        "User"
+    };
+
+    let body = format!(
+        "{user} can't access {}, missing: {}",
+        parts.uri.path(),
+        denied.missing_authorities().join(", ")
+    );
+    (StatusCode::FORBIDDEN, body)
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    error = "access_denied_with_context(auth_details, denied, request).await"
+)]
+async fn context_response() -> &'static str {
+    "Hi!"
+}
+
 #[tokio::test]
 async fn test_http_response() {
     let test_admin = get_user_response("/http_response", ROLE_ADMIN).await;
@@ -104,23 +140,144 @@ async fn test_result() {
     common::test_body(test_err, "bad request").await;
 }
 
+#[tokio::test]
+async fn test_rule() {
+    let test_admin = get_user_response("/rule", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/rule", ROLE_MANAGER).await;
+    let test_blocked = get_user_response("/rule", "ROLE_MANAGER,ROLE_BLOCKED").await;
+
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::OK, test_manager.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_blocked.status());
+}
+
+#[tokio::test]
+async fn test_access_denied_context() {
+    let test_admin = get_user_response("/context", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/context", ROLE_MANAGER).await;
+
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_manager.status());
+
+    common::test_body(test_admin, "Hi!").await;
+    common::test_body(
+        test_manager,
+        "Manager can't access /context, missing: ROLE_ADMIN",
+    )
+    .await;
+}
+
+#[test]
+fn test_requirement() {
+    let requirement = requirement_of!(rule_response);
+    assert_eq!(
+        "ROLE_ADMIN || ROLE_MANAGER && !ROLE_BLOCKED",
+        requirement.to_string()
+    );
+    assert!(requirement.is_satisfied_by(&[ROLE_MANAGER]));
+    assert!(!requirement.is_satisfied_by(&[ROLE_MANAGER, "ROLE_BLOCKED"]));
+
+    // `expr` can't be checked without a request, so authorities only are never sufficient
+    let requirement = requirement_of!(secure_user_id);
+    assert_eq!(
+        "ROLE_ADMIN && expr(user_id == user.id)",
+        requirement.to_string()
+    );
+    assert!(requirement.is_dynamic());
+    assert!(!requirement.is_satisfied_by(&[ROLE_ADMIN]));
+    assert!(requirement.may_be_satisfied_by(&[ROLE_ADMIN]));
+    assert!(!requirement.may_be_satisfied_by(&[ROLE_MANAGER]));
+
+    let requirement = requirement_of!(owner_response);
+    assert_eq!("ROLE_ADMIN || owner(user_id)", requirement.to_string());
+    assert!(requirement.is_satisfied_by(&[ROLE_ADMIN]));
+    assert!(!requirement.is_satisfied_by(&[ROLE_MANAGER]));
+    assert!(requirement.may_be_satisfied_by(&[ROLE_MANAGER]));
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    principal = "common::Claims",
+    expr = "principal.claims().tenant == \"acme\""
+)]
+async fn principal_response(user: Principal<common::Claims>) -> String {
+    user.subject().to_string()
+}
+
+#[protect("ROLE_ADMIN", during = "Mon-Fri 09:00-18:00 UTC")]
+async fn during_response() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_during() {
+    // Monday, 2024-01-01 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_103_200));
+    let test_working_hours = get_user_response("/during", ROLE_ADMIN).await;
+    // Saturday, 2024-01-06 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_535_200));
+    let test_weekend = get_user_response("/during", ROLE_ADMIN).await;
+    reset_clock();
+
+    assert_eq!(StatusCode::OK, test_working_hours.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_weekend.status());
+}
+
+#[tokio::test]
+async fn test_principal() {
+    let test_acme = get_tenant_response("/principal", ROLE_ADMIN, Some("acme")).await;
+    let test_other = get_tenant_response("/principal", ROLE_ADMIN, Some("other")).await;
+    let test_anonymous = get_tenant_response("/principal", ROLE_ADMIN, None).await;
+
+    assert_eq!(StatusCode::OK, test_acme.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_other.status());
+    assert_eq!(StatusCode::UNAUTHORIZED, test_anonymous.status());
+
+    common::test_body(test_acme, common::SUBJECT).await;
+}
+
+#[protect(any("ROLE_ADMIN", owner = "user_id"), principal = "common::Claims")]
+async fn owner_response(Path(user_id): Path<String>) -> String {
+    format!("Hi, {user_id}!")
+}
+
+#[tokio::test]
+async fn test_owner() {
+    let test_owner = get_tenant_response("/owner/user-1", ROLE_MANAGER, Some("acme")).await;
+    let test_other = get_tenant_response("/owner/user-2", ROLE_MANAGER, Some("acme")).await;
+    let test_admin = get_tenant_response("/owner/user-2", ROLE_ADMIN, Some("acme")).await;
+    let test_without_principal = get_tenant_response("/owner/user-1", ROLE_MANAGER, None).await;
+
+    assert_eq!(StatusCode::OK, test_owner.status());
+    assert_eq!(StatusCode::FORBIDDEN, test_other.status());
+    assert_eq!(StatusCode::OK, test_admin.status());
+    assert_eq!(StatusCode::UNAUTHORIZED, test_without_principal.status());
+}
+
 async fn get_user_response(uri: &str, role: &str) -> Response {
+    get_tenant_response(uri, role, None).await
+}
+
+async fn get_tenant_response(uri: &str, role: &str, tenant: Option<&str>) -> Response {
     let app = Router::new()
         .route("/http_response", get(http_response))
         .route("/str", get(str_response))
         .route("/return", get(return_response))
         .route("/result", get(result_response))
+        .route("/context", get(context_response))
+        .route("/rule", get(rule_response))
+        .route("/principal", get(principal_response))
+        .route("/owner/{user_id}", get(owner_response))
+        .route("/during", get(during_response))
         .layer(GrantsLayer::with_extractor(common::extract));
 
-    app.oneshot(
-        Request::builder()
-            .header(AUTHORIZATION, role)
-            .uri(uri)
-            .body(Body::empty())
-            .unwrap(),
-    )
-    .await
-    .unwrap()
+    let mut request = Request::builder().header(AUTHORIZATION, role).uri(uri);
+    if let Some(tenant) = tenant {
+        request = request.header(common::TENANT_HEADER, tenant);
+    }
+    app.oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
 }
 
 async fn post_user_response<T: Serialize>(uri: &str, role: &str, data: &T) -> Response {
@@ -1,7 +1,7 @@
 use crate::common;
 use crate::common::Role::{self, ADMIN, MANAGER};
 use axum::body::Body;
-use axum::http::header::AUTHORIZATION;
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
 use axum::http::{Request, StatusCode};
 use axum::response::Response;
 use axum::routing::get;
@@ -36,6 +36,16 @@ async fn incorrect_enum_secure() -> Response {
         .unwrap()
 }
 
+// Stacked conditions must be satisfied both
+#[protect("ADMIN", ty = "Role")]
+#[protect("MANAGER", ty = "Role")]
+async fn stacked_enum_secure() -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .body(().into())
+        .unwrap()
+}
+
 #[tokio::test]
 async fn test_http_response_for_imported_enum() {
     let test_admin = get_user_response("/imported_enum_secure", &ADMIN.to_string()).await;
@@ -58,6 +68,27 @@ async fn test_http_response_for_full_path_enum() {
 async fn test_incorrect_http_response() {
     let test = get_user_response("/incorrect_enum_secure", &ADMIN.to_string()).await;
     assert_eq!(StatusCode::UNAUTHORIZED, test.status());
+    assert_eq!("Bearer", test.headers()[WWW_AUTHENTICATE]);
+}
+
+#[tokio::test]
+async fn test_http_response_for_stacked_enum() {
+    let test_admin = get_user_response("/stacked_enum_secure", &ADMIN.to_string()).await;
+    let test_both = get_user_response("/stacked_enum_secure", "ADMIN,MANAGER").await;
+
+    assert_eq!(StatusCode::FORBIDDEN, test_admin.status());
+    assert_eq!(StatusCode::OK, test_both.status());
+}
+
+#[test]
+fn test_stacked_requirement() {
+    assert_eq!(
+        "ADMIN && MANAGER",
+        protect_axum::requirement_of!(stacked_enum_secure).to_string()
+    );
+    // typed authorities are kept in the source form
+    assert!(protect_axum::requirement_of!(self::full_path_enum_secure)
+        .is_satisfied_by(&["crate::common::Role::ADMIN"]));
 }
 
 async fn get_user_response(uri: &str, role: &str) -> Response {
@@ -65,6 +96,7 @@ async fn get_user_response(uri: &str, role: &str) -> Response {
         .route("/imported_enum_secure", get(imported_path_enum_secure))
         .route("/full_path_enum_secure", get(full_path_enum_secure))
         .route("/incorrect_enum_secure", get(incorrect_enum_secure))
+        .route("/stacked_enum_secure", get(stacked_enum_secure))
         .layer(GrantsLayer::with_extractor(common::enum_extract));
 
     app.oneshot(
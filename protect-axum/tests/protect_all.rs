@@ -0,0 +1,161 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+#[cfg(feature = "deny-by-default")]
+use protect_axum::deny_by_default::{DenyByDefaultLayer, Routes};
+use protect_axum::GrantsLayer;
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+#[protect_axum::protect_all("ROLE_ADMIN")]
+mod admin {
+    use axum::extract::Path;
+
+    pub async fn index() -> &'static str {
+        "Hello, admin!"
+    }
+
+    #[protect(expr = "id > 0")]
+    pub async fn user(Path(id): Path<u32>) -> String {
+        format!("User {id}")
+    }
+
+    #[protect(any("ROLE_ADMIN", "ROLE_SUPPORT"), overrides)]
+    pub async fn tickets() -> &'static str {
+        "Tickets"
+    }
+
+    #[public]
+    pub async fn health() -> &'static str {
+        "Ok"
+    }
+
+    pub fn title() -> &'static str {
+        "Admin"
+    }
+
+    #[protect_all(skip)]
+    pub async fn load_title() -> &'static str {
+        title()
+    }
+}
+
+struct Reports;
+
+#[protect_axum::protect_all("ROLE_MANAGER")]
+impl Reports {
+    async fn daily() -> &'static str {
+        "Daily"
+    }
+
+    #[protect("ROLE_ADMIN")]
+    async fn yearly() -> &'static str {
+        "Yearly"
+    }
+
+    #[protect_all(skip)]
+    async fn load(name: &'static str) -> &'static str {
+        name
+    }
+}
+
+#[tokio::test]
+async fn test_module() {
+    assert_eq!("Admin", admin::title());
+    assert_eq!("Admin", admin::load_title().await);
+    assert_eq!(StatusCode::OK, get_status("/admin", "ROLE_ADMIN").await);
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", "ROLE_SUPPORT").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/users/1", "ROLE_ADMIN").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin/users/0", "ROLE_ADMIN").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin/users/1", "ROLE_USER").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/tickets", "ROLE_SUPPORT").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin/tickets", "ROLE_USER").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/health", "ROLE_USER").await
+    );
+}
+
+#[tokio::test]
+async fn test_impl() {
+    assert_eq!("Daily", Reports::load("Daily").await);
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/reports/daily", "ROLE_MANAGER").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/reports/daily", "ROLE_ADMIN").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/reports/yearly", "ROLE_MANAGER,ROLE_ADMIN").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/reports/yearly", "ROLE_MANAGER").await
+    );
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str) -> StatusCode {
+    let app = Router::new()
+        .route("/admin", get(admin::index))
+        .route("/admin/users/{id}", get(admin::user))
+        .route("/admin/tickets", get(admin::tickets))
+        .route("/admin/health", get(admin::health))
+        .route("/reports/daily", get(Reports::daily))
+        .route("/reports/yearly", get(Reports::yearly));
+    #[cfg(feature = "deny-by-default")]
+    let app = app.layer(DenyByDefaultLayer::new(
+        Routes::new()
+            .route("GET", "/admin", admin::index)
+            .route("GET", "/admin/users/{id}", admin::user)
+            .route("GET", "/admin/tickets", admin::tickets)
+            .route("GET", "/admin/health", admin::health)
+            .route("GET", "/reports/daily", Reports::daily)
+            .route("GET", "/reports/yearly", Reports::yearly),
+    ));
+    let app = app.layer(GrantsLayer::with_extractor(extract));
+
+    let request = Request::builder()
+        .uri(uri)
+        .header(AUTHORIZATION, role)
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap().status()
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "registry")]
+
+use axum::extract::Path;
+use axum::routing::get;
+use axum::Router;
+use protect_axum::registry::{self, Endpoint, Permission, PermissionMatrix};
+
+#[derive(Eq, PartialEq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+enum Role {
+    ADMIN,
+}
+
+#[protect_axum::protect("ROLE_ADMIN", "OP_READ_USERS")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect(any("ROLE_ADMIN", "ROLE_EDITOR"), expr = "id > 0")]
+async fn article(Path(id): Path<u32>) -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect(expr = "id > 0")]
+#[protect_axum::protect("OP_DELETE")]
+async fn remove(Path(id): Path<u32>) -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect("Role::ADMIN", ty = "Role")]
+async fn typed() -> &'static str {
+    "Hi!"
+}
+
+#[protect_axum::protect_all("AUDIT")]
+mod audit {
+    #[protect("AUDIT_EXPORT")]
+    pub async fn export() -> &'static str {
+        "Hi!"
+    }
+}
+
+struct Reports;
+
+#[protect_axum::protect_all("REPORTS")]
+impl Reports {
+    async fn daily() -> &'static str {
+        "Hi!"
+    }
+}
+
+fn endpoint(name: &str) -> &'static Endpoint {
+    let handler = format!("{}::{name}", module_path!());
+    registry::endpoints()
+        .into_iter()
+        .find(|endpoint| endpoint.handler() == handler)
+        .unwrap_or_else(|| panic!("`{handler}` is not registered"))
+}
+
+#[test]
+fn test_registry() {
+    // records don't depend on the routes
+    let _: Router = Router::new()
+        .route("/users", get(users))
+        .route("/articles/{id}", get(article).delete(remove))
+        .route("/typed", get(typed));
+
+    let users = endpoint("users");
+    assert_eq!("ROLE_ADMIN && OP_READ_USERS", users.condition());
+    assert_eq!("axum", users.framework());
+    assert_eq!("String", users.authority_type());
+    assert!(!users.is_dynamic());
+    assert!(users.location().file().ends_with("registry.rs"));
+    assert_eq!(15, users.location().line());
+
+    let article = endpoint("article");
+    assert_eq!("ROLE_ADMIN || ROLE_EDITOR", article.condition());
+    assert!(article.is_dynamic());
+
+    // stacked attributes are registered once with the whole condition
+    let remove = endpoint("remove");
+    assert_eq!("OP_DELETE", remove.condition());
+    assert!(remove.is_dynamic());
+    assert_eq!(
+        1,
+        registry::endpoints()
+            .iter()
+            .filter(|endpoint| endpoint.handler().ends_with("::remove"))
+            .count()
+    );
+
+    let typed = endpoint("typed");
+    assert_eq!("Role::ADMIN", typed.condition());
+    assert_eq!("Role", typed.authority_type());
+}
+
+#[test]
+fn test_protect_all() {
+    let _: Router = Router::new()
+        .route("/audit", get(audit::export))
+        .route("/reports", get(Reports::daily));
+
+    assert_eq!(
+        "AUDIT && AUDIT_EXPORT",
+        endpoint("audit::export").condition()
+    );
+    assert_eq!("REPORTS", endpoint("Reports::daily").condition());
+}
+
+#[test]
+fn test_permission_matrix() {
+    let matrix = PermissionMatrix::collect();
+
+    assert_eq!(
+        vec![(endpoint("remove"), Permission::Required)],
+        matrix.endpoints_with("OP_DELETE")
+    );
+    assert_eq!(
+        vec![
+            (endpoint("users"), Permission::Required),
+            (endpoint("article"), Permission::Alternative),
+        ],
+        matrix.endpoints_with("ROLE_ADMIN")
+    );
+    assert!(matrix.to_csv().contains(",OP_DELETE,OP_READ_USERS,"));
+}
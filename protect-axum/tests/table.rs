@@ -0,0 +1,132 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::AUTHORIZATION;
+use axum::http::{Method, StatusCode};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::Router;
+use protect_axum::authorities::set_anonymous_authorities;
+use protect_axum::table::{Access, AuthorizationTable, SharedTable, TableLayer};
+use protect_axum::GrantsLayer;
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+async fn hi() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_table() {
+    assert_eq!(StatusCode::OK, get_status(Method::GET, "/", None).await);
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        get_status(Method::GET, "/profile", None).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/profile", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/admin/users", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/admin/users", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::POST, "/articles/1", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::POST, "/articles/1", Some("ROLE_EDITOR")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/internal/metrics", Some("ROLE_ADMIN")).await
+    );
+}
+
+#[tokio::test]
+async fn test_shared_table() {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+    let shared = SharedTable::new(table());
+    let app = Router::new()
+        .route("/profile", get(hi))
+        .layer(TableLayer::new(shared.clone()))
+        .layer(GrantsLayer::with_extractor(extract));
+    let request = || {
+        Request::builder()
+            .uri("/profile")
+            .header(AUTHORIZATION, "ROLE_USER")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let resp = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(StatusCode::OK, resp.status());
+
+    // e.g. a reloaded policy
+    shared.replace(table().otherwise(Access::DenyAll));
+    let resp = app.oneshot(request()).await.unwrap();
+    assert_eq!(StatusCode::FORBIDDEN, resp.status());
+}
+
+#[tokio::test]
+async fn test_normalized_paths() {
+    for uri in ["/%61dmin/users", "//admin/users", "/./admin/users"] {
+        assert_eq!(
+            StatusCode::FORBIDDEN,
+            get_status(Method::GET, uri, Some("ROLE_USER")).await,
+            "{uri}"
+        );
+    }
+}
+
+fn table() -> AuthorizationTable {
+    AuthorizationTable::new()
+        .route("GET", "/", Access::PermitAll)
+        .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+        .route(
+            "POST",
+            "/articles/{id}",
+            "ROLE_EDITOR || ROLE_ADMIN".parse::<Access>().unwrap(),
+        )
+        .route("*", "/internal/**", Access::DenyAll)
+        .otherwise(Access::Authenticated)
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(method: Method, uri: &str, role: Option<&str>) -> StatusCode {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    let app = Router::new()
+        .route("/", get(hi))
+        .route("/profile", get(hi))
+        .route("/admin/users", get(hi))
+        .route("/articles/1", post(hi))
+        .route("/internal/metrics", get(hi))
+        .layer(TableLayer::new(table()))
+        .layer(GrantsLayer::with_extractor(extract));
+
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(role) = role {
+        request = request.header(AUTHORIZATION, role);
+    }
+
+    app.oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
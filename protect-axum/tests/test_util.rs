@@ -0,0 +1,82 @@
+#![cfg(feature = "test-util")]
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use protect_axum::test_util::{MockExtractor, WithAuthorities};
+use protect_axum::GrantsLayer;
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+const ROLE_ADMIN: &str = "ROLE_ADMIN";
+
+#[protect_axum::protect("ROLE_ADMIN")]
+async fn admin() -> &'static str {
+    "Hi!"
+}
+
+async fn get_status(extractor: &MockExtractor, request: Request) -> StatusCode {
+    let app = Router::new()
+        .route("/admin", get(admin))
+        .layer(GrantsLayer::with_extractor(extractor.clone()));
+
+    app.oneshot(request).await.unwrap().status()
+}
+
+fn admin_request(authorities: &[&str]) -> Request {
+    Request::get("/admin")
+        .with_authorities(authorities)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_with_authorities() {
+    let extractor = MockExtractor::new();
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(&extractor, admin_request(&[])).await
+    );
+    let request = Request::get("/admin").body(Body::empty()).unwrap();
+    assert_eq!(StatusCode::FORBIDDEN, get_status(&extractor, request).await);
+
+    let calls = extractor.calls();
+    assert_eq!(3, calls.len());
+    assert_eq!("GET", calls[0].method());
+    assert_eq!("/admin", calls[0].path());
+    assert_eq!(
+        Some(&HashSet::from([ROLE_ADMIN.to_string()])),
+        calls[0].authorities()
+    );
+    assert_eq!(None, calls[2].authorities());
+}
+
+#[tokio::test]
+async fn test_scripted_results() {
+    let extractor = MockExtractor::new();
+    extractor
+        .then_return([ROLE_ADMIN])
+        .then_fail(StatusCode::UNAUTHORIZED.into_response());
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status(&extractor, admin_request(&[])).await
+    );
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(3, extractor.calls().len());
+}
@@ -0,0 +1,61 @@
+//! The clock is configured globally, so time-bounded grants are tested in a separate binary.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use protect_axum::authorities::{active_authorities, set_clock, TimedAuthority};
+use protect_axum::{protect, GrantsLayer};
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+use tower::ServiceExt;
+
+const SHIFT_START: u64 = 1_700_000_000;
+const SHIFT_END: u64 = SHIFT_START + 8 * 3600;
+
+#[protect("ROLE_ON_CALL")]
+async fn on_call() -> &'static str {
+    "Hi!"
+}
+
+async fn store(_subject: &str) -> Result<Vec<TimedAuthority<String>>, Response> {
+    Ok(vec![
+        TimedAuthority::new("ROLE_USER".to_string()),
+        TimedAuthority::new("ROLE_ON_CALL".to_string())
+            .not_before(at(SHIFT_START))
+            .expires_at(at(SHIFT_END)),
+    ])
+}
+
+async fn extract(_req: &mut Request) -> Result<HashSet<String>, Response> {
+    active_authorities(&store, "user-1").await
+}
+
+#[tokio::test]
+async fn test_time_bounded_grants() {
+    set_clock(|| at(SHIFT_START - 1));
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/on_call").await);
+
+    set_clock(|| at(SHIFT_START));
+    assert_eq!(StatusCode::OK, get_status("/on_call").await);
+
+    set_clock(|| at(SHIFT_END));
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/on_call").await);
+}
+
+fn at(secs: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+async fn get_status(uri: &str) -> StatusCode {
+    let app = Router::new()
+        .route("/on_call", get(on_call))
+        .layer(GrantsLayer::with_extractor(extract));
+
+    app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
@@ -1,21 +1,40 @@
-use protect_endpoints_core::authorities::AuthDetails as AuthDetailsCore;
+use protect_endpoints_core::authorities::{
+    AuthDetails as AuthDetailsCore, Principal as PrincipalCore,
+};
+use protect_endpoints_core::error::unauthorized_challenge;
 use salvo::extract::{Extractible, Metadata};
+use salvo::http::header::WWW_AUTHENTICATE;
 use salvo::http::StatusCode;
 use salvo::{Request, Response, Writer};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::ops::Deref;
 
-pub use protect_endpoints_core::authorities::AuthoritiesCheck;
+pub use protect_endpoints_core::authorities::extractor::{active_authorities, AuthoritiesStore};
+pub use protect_endpoints_core::authorities::{
+    anonymous_authorities, is_anonymous, now, reset_anonymous_authorities, reset_clock,
+    set_anonymous_authorities, set_clock, AttachPrincipal, AuthoritiesCheck, Clock, SystemClock,
+    TimedAuthority, Validity,
+};
 
 pub struct AuthDetails<T = String>(AuthDetailsCore<T>)
 where
     T: Eq + Hash;
 
+/// Optional [`AuthDetails`]: `None` for requests without authorities and for the anonymous principal.
+///
+/// `salvo` doesn't extract `Option<T>`, so this extractor is its counterpart.
+pub struct OptionalAuthDetails<T = String>(Option<AuthDetails<T>>)
+where
+    T: Eq + Hash;
+
 #[derive(Debug)]
 pub struct AuthDetailsNotFound;
 
 static METADATA: Metadata = Metadata::new("AuthDetails");
+static OPTIONAL_METADATA: Metadata = Metadata::new("OptionalAuthDetails");
+static PRINCIPAL_METADATA: Metadata = Metadata::new("Principal");
+static OPTIONAL_PRINCIPAL_METADATA: Metadata = Metadata::new("OptionalPrincipal");
 
 impl<'ex, T> Extractible<'ex> for AuthDetails<T>
 where
@@ -29,17 +48,73 @@ where
     where
         Self: Sized,
     {
-        req.extensions()
-            .get::<AuthDetailsCore<T>>()
-            .cloned()
-            .map(AuthDetails)
+        OptionalAuthDetails::<T>::from_request(req)
+            .0
             .ok_or(AuthDetailsNotFound)
     }
 }
 
+impl<'ex, T> Extractible<'ex> for OptionalAuthDetails<T>
+where
+    T: Eq + Hash + Send + Sync + 'static,
+{
+    fn metadata() -> &'ex Metadata {
+        &OPTIONAL_METADATA
+    }
+
+    async fn extract(req: &'ex mut Request) -> Result<Self, impl Writer + Send + Debug + 'static>
+    where
+        Self: Sized,
+    {
+        Ok::<_, AuthDetailsNotFound>(OptionalAuthDetails::from_request(req))
+    }
+}
+
+impl<T: Eq + Hash + Send + Sync + 'static> OptionalAuthDetails<T> {
+    fn from_request(req: &Request) -> Self {
+        OptionalAuthDetails(
+            req.extensions()
+                .get::<AuthDetailsCore<T>>()
+                .filter(|details| !details.is_anonymous())
+                .cloned()
+                .map(AuthDetails),
+        )
+    }
+}
+
+impl<T: Eq + Hash> OptionalAuthDetails<T> {
+    pub fn into_inner(self) -> Option<AuthDetails<T>> {
+        self.0
+    }
+}
+
+impl<T: Eq + Hash> From<OptionalAuthDetails<T>> for Option<AuthDetails<T>> {
+    fn from(details: OptionalAuthDetails<T>) -> Self {
+        details.0
+    }
+}
+
+impl<T: Eq + Hash> Deref for OptionalAuthDetails<T> {
+    type Target = Option<AuthDetails<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Eq + Hash + 'static> AuthDetails<T> {
+    /// Details of the anonymous principal, empty if anonymous authorities aren't configured.
+    pub fn anonymous() -> AuthDetails<T> {
+        AuthDetails(AuthDetailsCore::anonymous())
+    }
+}
+
 impl salvo::Scribe for AuthDetailsNotFound {
     fn render(self, res: &mut Response) {
         res.status_code(StatusCode::UNAUTHORIZED);
+        if let Some(challenge) = unauthorized_challenge().and_then(|c| c.try_into().ok()) {
+            res.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+        }
     }
 }
 
@@ -50,3 +125,84 @@ impl<T: Eq + Hash> Deref for AuthDetails<T> {
         &self.0
     }
 }
+
+/// Subject identifier and typed claims of the user, see [`AttachPrincipal`].
+///
+/// Requests without the attached principal are rejected as unauthorized.
+pub struct Principal<C = ()>(PrincipalCore<C>);
+
+impl<'ex, C> Extractible<'ex> for Principal<C>
+where
+    C: Send + Sync + 'static,
+{
+    fn metadata() -> &'ex Metadata {
+        &PRINCIPAL_METADATA
+    }
+
+    async fn extract(req: &'ex mut Request) -> Result<Self, impl Writer + Send + Debug + 'static>
+    where
+        Self: Sized,
+    {
+        OptionalPrincipal::<C>::from_request(req)
+            .0
+            .ok_or(AuthDetailsNotFound)
+    }
+}
+
+/// Optional [`Principal`], the counterpart of `Option<Principal>` (see [`OptionalAuthDetails`]).
+pub struct OptionalPrincipal<C = ()>(Option<Principal<C>>);
+
+impl<'ex, C> Extractible<'ex> for OptionalPrincipal<C>
+where
+    C: Send + Sync + 'static,
+{
+    fn metadata() -> &'ex Metadata {
+        &OPTIONAL_PRINCIPAL_METADATA
+    }
+
+    async fn extract(req: &'ex mut Request) -> Result<Self, impl Writer + Send + Debug + 'static>
+    where
+        Self: Sized,
+    {
+        Ok::<_, AuthDetailsNotFound>(OptionalPrincipal::from_request(req))
+    }
+}
+
+impl<C: Send + Sync + 'static> OptionalPrincipal<C> {
+    fn from_request(req: &Request) -> Self {
+        OptionalPrincipal(
+            req.extensions()
+                .get::<PrincipalCore<C>>()
+                .cloned()
+                .map(Principal),
+        )
+    }
+}
+
+impl<C> OptionalPrincipal<C> {
+    pub fn into_inner(self) -> Option<Principal<C>> {
+        self.0
+    }
+}
+
+impl<C> From<OptionalPrincipal<C>> for Option<Principal<C>> {
+    fn from(principal: OptionalPrincipal<C>) -> Self {
+        principal.0
+    }
+}
+
+impl<C> Deref for OptionalPrincipal<C> {
+    type Target = Option<Principal<C>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> Deref for Principal<C> {
+    type Target = PrincipalCore<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
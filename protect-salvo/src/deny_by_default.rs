@@ -0,0 +1,29 @@
+//! Deny-by-default mode: requests which aren't routed to handlers annotated with [`protect`](crate::protect)
+//! or [`public`](crate::public) are rejected with `403`, see [`protect_endpoints_core::deny_by_default`].
+//!
+//! The mode is enabled by [`DenyByDefaultLayer`] (via `.compat()`) configured with [`Routes`] of the app.
+//! Requests are checked before they reach handlers, so unannotated handlers never run.
+//!
+//! # Example
+//! ```no_run
+//! use protect_salvo::deny_by_default::{DenyByDefaultLayer, Routes};
+//! use protect_salvo::salvo_extra::TowerLayerCompat;
+//! use salvo::prelude::*;
+//!
+//! #[protect_salvo::public]
+//! #[handler]
+//! async fn index() -> &'static str {
+//!     "Hi!"
+//! }
+//!
+//! let routes = Routes::new().route("GET", "/", index);
+//!
+//! let router = Router::new()
+//!     .hoop(DenyByDefaultLayer::new(routes).compat())
+//!     .get(index);
+//! ```
+
+pub use protect_endpoints_core::deny_by_default::{is_annotated, AnnotatedHandler, Route, Routes};
+pub use protect_endpoints_core::tower::deny_by_default::{
+    DenyByDefaultLayer, DenyByDefaultService,
+};
@@ -0,0 +1,53 @@
+use protect_endpoints_core::condition::{Condition, Explanation};
+use protect_endpoints_core::error::AccessDenied as AccessDeniedCore;
+use salvo::{Response, Scribe};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+pub use protect_endpoints_core::error::{
+    set_challenge, set_denial_renderer, Challenge, PROBLEM_JSON,
+};
+
+/// Default error of the [`protect`](crate::protect) macro.
+///
+/// Rendered as `application/problem+json`, see [`protect_endpoints_core::error`]
+/// for details and customization.
+#[derive(Debug, Clone)]
+pub struct AccessDenied(AccessDeniedCore);
+
+impl AccessDenied {
+    pub fn new(required: Condition<String>, explanation: Explanation<String>) -> Self {
+        Self(AccessDeniedCore::new(required, explanation))
+    }
+}
+
+impl Scribe for AccessDenied {
+    fn render(self, res: &mut Response) {
+        let (parts, body) = self.0.to_response().into_parts();
+        res.status_code(parts.status);
+        res.headers_mut().extend(parts.headers);
+        res.body(body);
+    }
+}
+
+impl From<AccessDeniedCore> for AccessDenied {
+    fn from(value: AccessDeniedCore) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for AccessDenied {
+    type Target = AccessDeniedCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for AccessDenied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for AccessDenied {}
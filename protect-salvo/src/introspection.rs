@@ -0,0 +1,22 @@
+//! Admin endpoint serving the registered endpoints and simulating the access of authorities,
+//! see [`protect_endpoints_core::registry::introspection`].
+//!
+//! The endpoint isn't protected by itself, e.g. use the authorization [`table`](crate::table) for it.
+//!
+//! # Example
+//! ```
+//! use salvo::Router;
+//!
+//! // GET /admin/access?authorities=ROLE_USER,OP_READ
+//! let router = Router::with_path("admin/access").get(protect_salvo::introspection::handler);
+//! ```
+
+use protect_endpoints_core::registry::introspection;
+use salvo::prelude::Text;
+use salvo::{Request, Response};
+
+/// Responds with the registered endpoints, and their access if `authorities` are given in the query.
+#[salvo::handler]
+pub async fn handler(req: &mut Request, res: &mut Response) {
+    res.render(Text::Json(introspection::respond(req.uri().query())));
+}
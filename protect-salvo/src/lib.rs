@@ -20,6 +20,30 @@ use salvo::http::ReqBody;
 pub use salvo_extra;
 
 pub mod authorities;
+#[cfg(feature = "deny-by-default")]
+pub mod deny_by_default;
+pub mod error;
+#[cfg(feature = "registry")]
+pub mod introspection;
+pub mod network;
+#[cfg(feature = "oapi")]
+pub mod oapi;
+pub mod table;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[doc(hidden)]
+pub use protect_endpoints_core::__annotate_handler;
+#[doc(hidden)]
+pub use protect_endpoints_core::__register_endpoint;
+pub use protect_endpoints_core::condition;
+#[cfg(any(
+    feature = "policy-toml",
+    feature = "policy-yaml",
+    feature = "policy-json"
+))]
+pub use protect_endpoints_core::policy;
+#[cfg(feature = "registry")]
+pub use protect_endpoints_core::registry;
 
 pub type GrantsLayer<Extractor, Type, Err> =
     CoreGrantsLayer<Extractor, salvo::hyper::Request<ReqBody>, Type, Err>;
@@ -45,6 +69,13 @@ pub type GrantsLayer<Extractor, Type, Err> =
 ///     "some secured info"
 /// }
 ///
+/// // User should be ADMIN, or have OP_WRITE permission without being SUSPENDED
+/// #[protect_salvo::protect(rule = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)")]
+/// #[handler]
+/// async fn rule_macro_secured() -> &'static str {
+///     "some secured info"
+/// }
+///
 /// #[derive(serde::Deserialize, Extractible)]
 /// struct UserParams { user_id: i32 }
 /// struct User { id: i32 }
@@ -69,7 +100,10 @@ pub type GrantsLayer<Extractor, Type, Err> =
 /// ```
 #[cfg(feature = "macro-check")]
 pub mod proc_macro {
-    pub use protect_endpoints_proc_macro::protect_salvo as protect;
+    pub use protect_endpoints_proc_macro::{
+        protect_all_salvo as protect_all, protect_salvo as protect, public_salvo as public,
+        requirement_of,
+    };
 }
 
 /// Just a shortcut for proc-macros
@@ -0,0 +1,97 @@
+//! Network conditions: the client IP address within ranges, see [`protect_endpoints_core::network`].
+//!
+//! Handlers are restricted via `from_network` argument of the [`protect`](crate::protect) macro,
+//! whole routers via [`NetworkMiddleware`] hoop.
+
+use crate::error::AccessDenied;
+use protect_endpoints_core::network::ClientIp as ClientIpCore;
+use salvo::extract::{Extractible, Metadata};
+use salvo::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Writer};
+use std::convert::Infallible;
+use std::fmt::Debug;
+use std::ops::Deref;
+
+pub use protect_endpoints_core::network::{
+    client_ip, register_network, reset_trusted_proxies, set_trusted_proxies, ForwardedHeader,
+    IpNet, NetworkError, Networks,
+};
+
+static METADATA: Metadata = Metadata::new("ClientIp");
+
+/// Address of the client: the peer address or the forwarded one, if the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(ClientIpCore);
+
+impl ClientIp {
+    fn resolve(req: &Request) -> Self {
+        let peer = req.remote_addr().clone().into_std().map(|peer| peer.ip());
+        let headers = req
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        ClientIp(ClientIpCore::new(peer, headers))
+    }
+}
+
+impl<'ex> Extractible<'ex> for ClientIp {
+    fn metadata() -> &'ex Metadata {
+        &METADATA
+    }
+
+    async fn extract(req: &'ex mut Request) -> Result<Self, impl Writer + Send + Debug + 'static>
+    where
+        Self: Sized,
+    {
+        Ok::<_, Infallible>(ClientIp::resolve(req))
+    }
+}
+
+impl Deref for ClientIp {
+    type Target = ClientIpCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Hoop denying requests from clients outside of the networks.
+///
+/// # Example
+/// ```
+/// use protect_salvo::network::NetworkMiddleware;
+/// use salvo::prelude::*;
+///
+/// #[handler]
+/// async fn metrics() -> &'static str {
+///     "up 1"
+/// }
+///
+/// let router = Router::with_path("internal/metrics")
+///     .hoop(NetworkMiddleware::new("10.0.0.0/8".parse().unwrap()))
+///     .get(metrics);
+/// ```
+pub struct NetworkMiddleware {
+    networks: Networks,
+}
+
+impl NetworkMiddleware {
+    pub fn new(networks: Networks) -> Self {
+        Self { networks }
+    }
+}
+
+#[async_trait]
+impl Handler for NetworkMiddleware {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        if let Err(denied) = ClientIp::resolve(req).check(&self.networks) {
+            res.render(AccessDenied::from(denied));
+            ctrl.skip_rest();
+        }
+    }
+}
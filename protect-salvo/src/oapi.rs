@@ -0,0 +1,116 @@
+//! Documentation of handlers protected by [`protect`](crate::protect) for the built-in OpenAPI of `salvo`.
+//!
+//! With the `oapi` feature, the macro placed above `#[endpoint]` adds the requirement of the handler
+//! to the description of its operation and declares `401` and `403` responses via [`AccessError`].
+//! `security = "scheme"` requires the security scheme for the operation, and `scopes` declares
+//! the required authorities as its scopes.
+//!
+//! Extractors of the crate (e.g. [`AuthDetails`]) implement [`EndpointArgRegister`] without documenting anything,
+//! since they aren't parameters of the request.
+//!
+//! # Example
+//! ```no_run
+//! use salvo::oapi::endpoint;
+//! use salvo::prelude::*;
+//!
+//! /// Reports of the tenant
+//! #[protect_salvo::protect("ROLE_ADMIN", security = "bearer")]
+//! #[endpoint]
+//! async fn reports() -> &'static str {
+//!     "Reports"
+//! }
+//! ```
+
+use crate::authorities::{AuthDetails, OptionalAuthDetails, OptionalPrincipal, Principal};
+use crate::network::ClientIp;
+use salvo::oapi::{self, Components, EndpointArgRegister, EndpointOutRegister, Operation};
+use salvo::{async_trait, Depot, Request, Response, Writer};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::Pin;
+
+/// An error of a protected `#[endpoint]`, documented as `401 Unauthorized` and `403 Forbidden` responses.
+///
+/// It's written as the wrapped error of the handler (e.g. [`AccessDenied`](crate::error::AccessDenied)
+/// or the result of the custom `error` function).
+pub struct AccessError(Box<dyn BoxedWriter>);
+
+impl AccessError {
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Writer + Send + 'static,
+    {
+        Self(Box::new(writer))
+    }
+}
+
+#[async_trait]
+impl Writer for AccessError {
+    async fn write(self, req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        self.0.write_boxed(req, depot, res).await
+    }
+}
+
+impl EndpointOutRegister for AccessError {
+    fn register(_components: &mut Components, operation: &mut Operation) {
+        operation.responses.insert(
+            "401",
+            oapi::Response::new("Unauthorized: the authorities of the request can't be extracted"),
+        );
+        operation.responses.insert(
+            "403",
+            oapi::Response::new("Forbidden: the request doesn't satisfy the required authorities"),
+        );
+    }
+}
+
+impl Debug for AccessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessError").finish_non_exhaustive()
+    }
+}
+
+/// Object-safe counterpart of [`Writer`].
+trait BoxedWriter: Send {
+    fn write_boxed<'a>(
+        self: Box<Self>,
+        req: &'a mut Request,
+        depot: &'a mut Depot,
+        res: &'a mut Response,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+}
+
+impl<W> BoxedWriter for W
+where
+    W: Writer + Send + 'static,
+{
+    fn write_boxed<'a>(
+        self: Box<Self>,
+        req: &'a mut Request,
+        depot: &'a mut Depot,
+        res: &'a mut Response,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        (*self).write(req, depot, res)
+    }
+}
+
+impl<T: Eq + Hash> EndpointArgRegister for AuthDetails<T> {
+    fn register(_components: &mut Components, _operation: &mut Operation, _arg: &str) {}
+}
+
+impl<T: Eq + Hash> EndpointArgRegister for OptionalAuthDetails<T> {
+    fn register(_components: &mut Components, _operation: &mut Operation, _arg: &str) {}
+}
+
+impl<C> EndpointArgRegister for Principal<C> {
+    fn register(_components: &mut Components, _operation: &mut Operation, _arg: &str) {}
+}
+
+impl<C> EndpointArgRegister for OptionalPrincipal<C> {
+    fn register(_components: &mut Components, _operation: &mut Operation, _arg: &str) {}
+}
+
+impl EndpointArgRegister for ClientIp {
+    fn register(_components: &mut Components, _operation: &mut Operation, _arg: &str) {}
+}
@@ -0,0 +1,9 @@
+//! Central authorization table: `(method, path pattern) -> access`, see [`protect_endpoints_core::table`].
+//!
+//! The table is applied via [`TableLayer`], which must be added before the [`GrantsLayer`](crate::GrantsLayer)
+//! to see the attached authorities.
+
+pub use protect_endpoints_core::table::{
+    Access, AuthorizationTable, PathPattern, Rejection, Rule, SharedTable, TableError,
+};
+pub use protect_endpoints_core::tower::table::{TableLayer, TableService};
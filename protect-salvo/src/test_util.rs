@@ -0,0 +1,60 @@
+//! Injection of authorities into test requests, see [`protect_endpoints_core::test_util`].
+//!
+//! # Example
+//! ```no_run
+//! use protect_salvo::test_util::{MockExtractor, WithAuthorities};
+//! use protect_salvo::GrantsLayer;
+//! use salvo::prelude::*;
+//! use salvo::test::TestClient;
+//! use salvo_extra::TowerLayerCompat;
+//!
+//! #[protect_salvo::protect("ROLE_ADMIN")]
+//! #[handler]
+//! async fn secured() -> &'static str {
+//!     "some secured info"
+//! }
+//!
+//! # tokio_test();
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn tokio_test() {
+//! let extractor = MockExtractor::new();
+//! let app = Service::new(
+//!     Router::with_hoop(GrantsLayer::with_extractor(extractor.clone()).compat()).get(secured),
+//! );
+//!
+//! let resp = TestClient::get("http://localhost/")
+//!     .with_authorities(["ROLE_ADMIN"])
+//!     .send(&app)
+//!     .await;
+//!
+//! assert_eq!(Some(StatusCode::OK), resp.status_code);
+//! assert_eq!(1, extractor.calls().len());
+//! # }
+//! ```
+
+use protect_endpoints_core::test_util::header_value;
+pub use protect_endpoints_core::test_util::{Call, AUTHORITIES_HEADER};
+use salvo::http::ResBody;
+use salvo::test::RequestBuilder;
+
+/// Extractor of authorities given by [`WithAuthorities`], scripted results and errors take precedence.
+pub type MockExtractor =
+    protect_endpoints_core::test_util::MockExtractor<salvo::hyper::Response<ResBody>>;
+
+/// Passes authorities of the test request to the [`MockExtractor`].
+pub trait WithAuthorities {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>;
+}
+
+impl WithAuthorities for RequestBuilder {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.add_header(AUTHORITIES_HEADER, header_value(authorities), true)
+    }
+}
@@ -0,0 +1,98 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use http_body_util::BodyExt;
+use protect_salvo::authorities::{
+    set_anonymous_authorities, AuthoritiesCheck, OptionalAuthDetails,
+};
+use protect_salvo::{protect, GrantsLayer};
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::{ReqBody, ResBody};
+use salvo::prelude::*;
+use salvo::test::TestClient;
+use salvo_extra::TowerLayerCompat;
+use std::collections::HashSet;
+
+#[protect(rule = "ANONYMOUS || ROLE_USER", allow_anonymous)]
+#[handler]
+async fn allow_anonymous() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_USER")]
+#[handler]
+async fn user_only() -> &'static str {
+    "Hi!"
+}
+
+#[handler]
+async fn optional(details: OptionalAuthDetails) -> &'static str {
+    match details.into_inner() {
+        Some(details) if details.has_authority("ROLE_USER") => "user",
+        Some(_) => "authenticated",
+        None => "anonymous",
+    }
+}
+
+#[tokio::test]
+async fn test_anonymous() {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    assert_eq!(
+        (StatusCode::OK, "Hi!".into()),
+        get_response("/allow_anonymous", None).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "Hi!".into()),
+        get_response("/allow_anonymous", Some("ROLE_USER")).await
+    );
+    let (status, _) = get_response("/allow_anonymous", Some("ROLE_OTHER")).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let (status, _) = get_response("/user_only", None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+
+    assert_eq!(
+        (StatusCode::OK, "anonymous".into()),
+        get_response("/optional", None).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "user".into()),
+        get_response("/optional", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        (StatusCode::OK, "authenticated".into()),
+        get_response("/optional", Some("ROLE_OTHER")).await
+    );
+}
+
+async fn extract(
+    req: &mut salvo::hyper::Request<ReqBody>,
+) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_response(uri: &str, role: Option<&str>) -> (StatusCode, String) {
+    let app = Service::new(
+        Router::with_path("/")
+            .hoop(GrantsLayer::with_extractor(extract).compat())
+            .push(Router::with_path("/allow_anonymous").get(allow_anonymous))
+            .push(Router::with_path("/user_only").get(user_only))
+            .push(Router::with_path("/optional").get(optional)),
+    );
+
+    let mut request = TestClient::get(format!("http://localhost{uri}"));
+    if let Some(role) = role {
+        request = request.add_header(AUTHORIZATION, role, true);
+    }
+    let mut resp = request.send(&app).await;
+    let body = resp.take_body().collect().await.unwrap().to_bytes();
+    (
+        resp.status_code.unwrap_or(StatusCode::OK),
+        String::from_utf8(body.to_vec()).unwrap(),
+    )
+}
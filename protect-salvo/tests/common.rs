@@ -1,5 +1,6 @@
 use http_body_util::BodyExt;
-use salvo::http::header::{HeaderValue, AUTHORIZATION};
+use protect_salvo::authorities::AttachPrincipal;
+use salvo::http::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use salvo::http::{ReqBody, ResBody};
 use salvo::macros::Extractible;
 use serde::Deserialize;
@@ -8,6 +9,12 @@ use std::fmt::{Display, Formatter};
 
 pub const ROLE_ADMIN: &str = "ROLE_ADMIN";
 pub const ROLE_MANAGER: &str = "ROLE_MANAGER";
+pub const TENANT_HEADER: &str = "x-tenant";
+pub const SUBJECT: &str = "user-1";
+
+pub struct Claims {
+    pub tenant: String,
+}
 
 #[derive(Eq, PartialEq, Hash)]
 #[allow(clippy::upper_case_acronyms)]
@@ -19,6 +26,15 @@ pub enum Role {
 pub async fn extract(
     req: &mut salvo::hyper::Request<ReqBody>,
 ) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    let tenant = req
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .map(str::to_string);
+    if let Some(tenant) = tenant {
+        req.attach_principal(SUBJECT, Claims { tenant });
+    }
+
     let auth_header: Option<&str> = req
         .headers()
         .get(AUTHORIZATION)
@@ -56,6 +72,26 @@ pub async fn enum_extract(
         })
 }
 
+/// Default denial without explanation, the correlation id is generated.
+pub async fn test_problem(mut resp: salvo::Response) {
+    assert_eq!(
+        Some("application/problem+json"),
+        resp.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    );
+    let body = resp
+        .take_body()
+        .collect()
+        .await
+        .expect("Failed to collect body")
+        .to_bytes();
+
+    assert!(body.starts_with(
+        br#"{"type":"about:blank","title":"Forbidden","status":403,"correlation_id":""#
+    ));
+}
+
 pub async fn test_body(mut resp: salvo::Response, expected_body: &str) {
     let body = resp
         .take_body()
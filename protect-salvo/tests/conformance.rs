@@ -0,0 +1,117 @@
+use protect_endpoints_testkit::{
+    header_value, parse_header_value, Adapter, Credentials, Role, AUTHORITIES_HEADER, OWNER,
+};
+use protect_salvo::GrantsLayer;
+use salvo::http::{ReqBody, ResBody};
+use salvo::macros::Extractible;
+use salvo::prelude::*;
+use salvo::test::TestClient;
+use salvo_extra::TowerLayerCompat;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+#[derive(Deserialize, Extractible)]
+#[salvo(extract(default_source(from = "query")))]
+struct NamePayload {
+    name: Option<String>,
+}
+
+#[protect_salvo::protect("ROLE_ADMIN")]
+#[handler]
+async fn single() -> &'static str {
+    "Hi!"
+}
+
+#[protect_salvo::protect("ROLE_ADMIN", "ROLE_MANAGER")]
+#[handler]
+async fn all() -> &'static str {
+    "Hi!"
+}
+
+#[protect_salvo::protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]
+#[handler]
+async fn any() -> &'static str {
+    "Hi!"
+}
+
+#[protect_salvo::protect("ROLE_ADMIN", expr = "payload.name.as_deref() == Some(OWNER)")]
+#[handler]
+async fn expr(payload: NamePayload) -> &'static str {
+    "Hi!"
+}
+
+#[protect_salvo::protect("Role::Admin", ty = "Role")]
+#[handler]
+async fn typed() -> &'static str {
+    "Hi!"
+}
+
+type Request = salvo::hyper::Request<ReqBody>;
+type Response = salvo::hyper::Response<ResBody>;
+
+fn authorities(req: &Request) -> HashSet<String> {
+    req.headers()
+        .get(AUTHORITIES_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .map(parse_header_value)
+        .unwrap_or_default()
+}
+
+async fn extract(req: &mut Request) -> Result<HashSet<String>, Response> {
+    Ok(authorities(req))
+}
+
+async fn extract_roles(req: &mut Request) -> Result<HashSet<Role>, Response> {
+    Ok(authorities(req)
+        .iter()
+        .filter_map(|authority| authority.parse().ok())
+        .collect())
+}
+
+async fn fail<T>(_req: &mut Request) -> Result<HashSet<T>, Response> {
+    Err(salvo::hyper::Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .body(ResBody::None)
+        .unwrap())
+}
+
+struct Salvo;
+
+impl Adapter for Salvo {
+    async fn status(&self, path: &str, credentials: &Credentials) -> u16 {
+        let (routes, typed_routes) = match credentials {
+            Credentials::Authorities(_) => (
+                Router::with_hoop(GrantsLayer::with_extractor(extract).compat()),
+                Router::with_hoop(GrantsLayer::with_extractor(extract_roles).compat()),
+            ),
+            Credentials::Missing => (Router::new(), Router::new()),
+            Credentials::ExtractorError => (
+                Router::with_hoop(GrantsLayer::with_extractor(fail::<String>).compat()),
+                Router::with_hoop(GrantsLayer::with_extractor(fail::<Role>).compat()),
+            ),
+        };
+        let app = Service::new(
+            Router::new()
+                .push(
+                    routes
+                        .push(Router::with_path("single").get(single))
+                        .push(Router::with_path("all").get(all))
+                        .push(Router::with_path("any").get(any))
+                        .push(Router::with_path("expr").get(expr)),
+                )
+                .push(typed_routes.push(Router::with_path("typed").get(typed))),
+        );
+
+        let mut request = TestClient::get(format!("http://localhost{path}"));
+        if let Credentials::Authorities(authorities) = credentials {
+            request = request.add_header(AUTHORITIES_HEADER, header_value(*authorities), true);
+        }
+
+        request.send(&app).await.status_code.unwrap().as_u16()
+    }
+}
+
+#[tokio::test]
+async fn test_conformance() {
+    protect_endpoints_testkit::run(&Salvo).await;
+}
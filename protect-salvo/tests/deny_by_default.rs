@@ -0,0 +1,106 @@
+#![cfg(feature = "deny-by-default")]
+
+use protect_salvo::deny_by_default::{DenyByDefaultLayer, Routes};
+use protect_salvo::GrantsLayer;
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::{Method, ReqBody, ResBody};
+use salvo::prelude::*;
+use salvo::test::RequestBuilder;
+use salvo_extra::TowerLayerCompat;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[protect_salvo::public]
+#[handler]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+#[protect_salvo::protect("ROLE_ADMIN")]
+#[handler]
+async fn admin() -> &'static str {
+    "Hello, admin!"
+}
+
+static FORGOTTEN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[handler]
+async fn forgotten() -> &'static str {
+    FORGOTTEN_CALLS.fetch_add(1, Ordering::SeqCst);
+    "Oops!"
+}
+
+#[protect_salvo::public]
+#[handler]
+async fn unlisted() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_deny_by_default() {
+    assert_eq!(StatusCode::OK, get_status("/", None).await);
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(StatusCode::FORBIDDEN, get_status("/unlisted", None).await);
+    assert_eq!(StatusCode::NOT_FOUND, get_status("/missing", None).await);
+}
+
+#[tokio::test]
+async fn test_forgotten_handler_not_called() {
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(0, FORGOTTEN_CALLS.load(Ordering::SeqCst));
+}
+
+async fn extract(
+    req: &mut salvo::hyper::Request<ReqBody>,
+) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+fn routes() -> Routes {
+    Routes::new()
+        .route("GET", "/", index)
+        .route("GET", "/admin", admin)
+        .route("GET", "/forgotten", forgotten)
+}
+
+async fn get_status(uri: &str, role: Option<&str>) -> StatusCode {
+    let app = Service::new(
+        Router::new()
+            .hoop(GrantsLayer::with_extractor(extract).compat())
+            .hoop(DenyByDefaultLayer::new(routes()).compat())
+            .get(index)
+            .push(Router::with_path("admin").get(admin))
+            .push(Router::with_path("forgotten").get(forgotten))
+            .push(Router::with_path("unlisted").get(unlisted)),
+    );
+
+    let mut request = RequestBuilder::new(format!("http://localhost{uri}"), Method::GET);
+    if let Some(role) = role {
+        request = request.add_header(AUTHORIZATION, role, true);
+    }
+
+    request
+        .send(&app)
+        .await
+        .status_code
+        .unwrap_or(StatusCode::OK)
+}
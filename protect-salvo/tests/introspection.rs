@@ -0,0 +1,35 @@
+#![cfg(feature = "registry")]
+
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+
+#[protect_salvo::protect("ROLE_ADMIN")]
+#[handler]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_introspection() {
+    let app = Service::new(
+        Router::new()
+            .push(Router::with_path("users").get(users))
+            .push(Router::with_path("admin/access").get(protect_salvo::introspection::handler)),
+    );
+
+    let mut resp = TestClient::get("http://localhost/admin/access?authorities=ROLE_ADMIN")
+        .send(&app)
+        .await;
+    assert_eq!(
+        Some("application/json; charset=utf-8"),
+        resp.headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+    );
+
+    let body = resp.take_string().await.unwrap();
+    assert!(body.starts_with(
+        r#"{"authorities":["ROLE_ADMIN"],"endpoints":[{"handler":"introspection::users","#
+    ));
+    assert!(body.ends_with(r#""condition":"ROLE_ADMIN","dynamic":false,"access":"allowed"}]}"#));
+}
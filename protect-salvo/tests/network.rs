@@ -0,0 +1,87 @@
+use protect_salvo::network::NetworkMiddleware;
+use protect_salvo::{protect, GrantsLayer};
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::{ReqBody, ResBody};
+use salvo::prelude::*;
+use salvo::test::TestClient;
+use salvo_extra::TowerLayerCompat;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+const INTERNAL: &str = "10.1.2.3:4000";
+const EXTERNAL: &str = "203.0.113.1:4000";
+
+#[protect(any("ROLE_ADMIN", from_network = "10.0.0.0/8, ::1"))]
+#[handler]
+async fn from_network() -> &'static str {
+    "Hi!"
+}
+
+#[handler]
+async fn metrics() -> &'static str {
+    "up 1"
+}
+
+#[tokio::test]
+async fn test_from_network() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_USER", "[::1]:4000").await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/from_network", "ROLE_ADMIN", EXTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/from_network", "ROLE_USER", EXTERNAL).await
+    );
+}
+
+#[tokio::test]
+async fn test_network_middleware() {
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/internal/metrics", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/internal/metrics", "ROLE_ADMIN", EXTERNAL).await
+    );
+}
+
+async fn extract(
+    req: &mut salvo::hyper::Request<ReqBody>,
+) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str, peer: &str) -> StatusCode {
+    let app = Service::new(
+        Router::with_path("/")
+            .hoop(GrantsLayer::with_extractor(extract).compat())
+            .push(Router::with_path("/from_network").get(from_network))
+            .push(
+                Router::with_path("/internal/metrics")
+                    .hoop(NetworkMiddleware::new("10.0.0.0/8".parse().unwrap()))
+                    .get(metrics),
+            ),
+    );
+
+    let mut req = TestClient::get(format!("http://localhost{uri}"))
+        .add_header(AUTHORIZATION, role, true)
+        .build();
+    let peer: SocketAddr = peer.parse().unwrap();
+    *req.remote_addr_mut() = peer.into();
+
+    app.handle(req).await.status_code.unwrap_or(StatusCode::OK)
+}
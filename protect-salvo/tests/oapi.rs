@@ -0,0 +1,154 @@
+#![cfg(feature = "oapi")]
+
+use protect_salvo::authorities::AuthDetails;
+use protect_salvo::GrantsLayer;
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::{ReqBody, ResBody};
+use salvo::oapi::extract::QueryParam;
+use salvo::oapi::{endpoint, OpenApi};
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+use salvo_extra::TowerLayerCompat;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Admin info
+#[protect_salvo::protect("ROLE_ADMIN")]
+#[endpoint]
+async fn admin() -> &'static str {
+    "Hello, admin!"
+}
+
+#[protect_salvo::protect(any("ROLE_ADMIN", "ROLE_MANAGER"), expr = "*id > 0")]
+#[endpoint(tags("staff"))]
+async fn staff(id: QueryParam<i32, true>) -> &'static str {
+    "Hello, staff!"
+}
+
+#[protect_salvo::protect("ROLE_ADMIN", security = "bearer")]
+#[endpoint]
+async fn secured() -> &'static str {
+    "Hello, admin!"
+}
+
+#[protect_salvo::protect("read", "write", security = "oauth2", scopes)]
+#[endpoint]
+async fn edit() -> &'static str {
+    "Edited!"
+}
+
+#[endpoint]
+async fn manual(details: AuthDetails) -> String {
+    details
+        .authorities
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[endpoint]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+fn routes() -> Router {
+    Router::new()
+        .get(index)
+        .push(Router::with_path("admin").get(admin))
+        .push(Router::with_path("staff").get(staff))
+        .push(Router::with_path("secured").get(secured))
+        .push(Router::with_path("edit").post(edit))
+        .push(Router::with_path("manual").get(manual))
+}
+
+fn router() -> Router {
+    Router::new()
+        .hoop(GrantsLayer::with_extractor(extract).compat())
+        .push(routes())
+}
+
+fn spec() -> Value {
+    let spec = OpenApi::new("Api", "1.0").merge_router(&routes());
+    serde_json::to_value(&spec).unwrap()
+}
+
+#[test]
+fn test_description_and_responses() {
+    let spec = spec();
+
+    let admin_op = &spec["paths"]["/admin"]["get"];
+    assert_eq!("Admin info", admin_op["summary"]);
+    assert_eq!("Requires `ROLE_ADMIN`.", admin_op["description"]);
+    let responses = admin_op["responses"].as_object().unwrap();
+    assert!(responses.contains_key("200"));
+    assert!(responses.contains_key("401"));
+    assert!(responses.contains_key("403"));
+
+    let staff_op = &spec["paths"]["/staff"]["get"];
+    assert_eq!(
+        "Requires `ROLE_ADMIN || ROLE_MANAGER` and a condition on the request.",
+        staff_op["description"]
+    );
+    assert_eq!("id", staff_op["parameters"][0]["name"]);
+    assert_eq!(1, staff_op["parameters"].as_array().unwrap().len());
+
+    let index_op = &spec["paths"]["/"]["get"];
+    assert!(!index_op["responses"]
+        .as_object()
+        .unwrap()
+        .contains_key("403"));
+
+    let manual_op = &spec["paths"]["/manual"]["get"];
+    assert!(manual_op.get("parameters").is_none());
+}
+
+#[test]
+fn test_security() {
+    let spec = spec();
+
+    assert_eq!(
+        serde_json::json!([{ "bearer": [] }]),
+        spec["paths"]["/secured"]["get"]["security"]
+    );
+    assert_eq!(
+        serde_json::json!([{ "oauth2": ["read", "write"] }]),
+        spec["paths"]["/edit"]["post"]["security"]
+    );
+    assert!(spec["paths"]["/admin"]["get"].get("security").is_none());
+}
+
+#[tokio::test]
+async fn test_access() {
+    let service = Service::new(router());
+
+    let resp = TestClient::get("http://localhost/admin")
+        .add_header(AUTHORIZATION, "ROLE_ADMIN", true)
+        .send(&service)
+        .await;
+    assert_eq!(Some(StatusCode::OK), resp.status_code);
+
+    let resp = TestClient::get("http://localhost/admin")
+        .add_header(AUTHORIZATION, "ROLE_MANAGER", true)
+        .send(&service)
+        .await;
+    assert_eq!(Some(StatusCode::FORBIDDEN), resp.status_code);
+
+    let mut resp = TestClient::get("http://localhost/manual")
+        .add_header(AUTHORIZATION, "ROLE_MANAGER", true)
+        .send(&service)
+        .await;
+    assert_eq!(Some(StatusCode::OK), resp.status_code);
+    assert_eq!("ROLE_MANAGER", resp.take_string().await.unwrap());
+}
+
+async fn extract(
+    req: &mut salvo::hyper::Request<ReqBody>,
+) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
@@ -1,10 +1,15 @@
 use crate::common::{self, ROLE_ADMIN, ROLE_MANAGER};
+use protect_salvo::authorities::{
+    reset_clock, set_clock, AuthDetails, AuthoritiesCheck, Principal,
+};
+use protect_salvo::error::AccessDenied;
 use protect_salvo::{protect, GrantsLayer};
 use salvo::http::header::{AUTHORIZATION, CONTENT_TYPE};
 use salvo::prelude::*;
 use salvo::test::TestClient;
 use salvo_extra::TowerLayerCompat;
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
 #[protect("ROLE_ADMIN")]
 #[handler]
@@ -49,6 +54,59 @@ async fn result_response(payload: common::NamePayload) -> Result<String, StatusC
     Ok(format!("Welcome {}!", name))
 }
 
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)")]
+#[handler]
+async fn rule_response() -> &'static str {
+    "Hi!"
+}
+
+#[derive(Debug)]
+struct ContextError(String);
+
+impl Scribe for ContextError {
+    fn render(self, res: &mut Response) {
+        res.status_code(StatusCode::FORBIDDEN);
+        res.render(self.0);
+    }
+}
+
+async fn access_denied_with_context(
+    details: &AuthDetails,
+    denied: &AccessDenied,
+    req: &Request,
+) -> ContextError {
+    let user = if details.has_authority(ROLE_MANAGER) {
+        "Manager"
+    } else {
+        "User"
+    };
+
+    ContextError(format!(
+        "{user} can't access {}, missing: {}",
+        req.uri().path(),
+        denied.missing_authorities().join(", ")
+    ))
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    error = "access_denied_with_context(auth_details, denied, request).await"
+)]
+#[handler]
+async fn context_response() -> &'static str {
+    "Hi!"
+}
+
+// The existing `&mut Request` argument is passed to the error handler
+#[protect(
+    "ROLE_ADMIN",
+    error = "access_denied_with_context(auth_details, denied, request).await"
+)]
+#[handler]
+async fn context_with_request(req: &mut Request) -> String {
+    format!("Hi from {}!", req.uri().path())
+}
+
 #[tokio::test]
 async fn test_http_response() {
     let test_admin = get_user_response("/http_response", ROLE_ADMIN).await;
@@ -67,6 +125,7 @@ async fn test_str() {
     assert_eq!(Some(StatusCode::FORBIDDEN), test_manager.status_code);
 
     common::test_body(test_admin, "Hi!").await;
+    common::test_problem(test_manager).await;
 }
 
 #[tokio::test]
@@ -102,20 +161,140 @@ async fn test_result() {
     common::test_body(test_ok, "Welcome Test!").await;
 }
 
+#[tokio::test]
+async fn test_rule() {
+    let test_admin = get_user_response("/rule", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/rule", ROLE_MANAGER).await;
+    let test_blocked = get_user_response("/rule", "ROLE_MANAGER,ROLE_BLOCKED").await;
+
+    assert_eq!(Some(StatusCode::OK), test_admin.status_code);
+    assert_eq!(Some(StatusCode::OK), test_manager.status_code);
+    assert_eq!(Some(StatusCode::FORBIDDEN), test_blocked.status_code);
+}
+
+#[tokio::test]
+async fn test_access_denied_context() {
+    let test_admin = get_user_response("/context", ROLE_ADMIN).await;
+    let test_manager = get_user_response("/context", ROLE_MANAGER).await;
+    let test_with_request = get_user_response("/context_with_request", ROLE_MANAGER).await;
+
+    assert_eq!(Some(StatusCode::OK), test_admin.status_code);
+    assert_eq!(Some(StatusCode::FORBIDDEN), test_manager.status_code);
+    assert_eq!(Some(StatusCode::FORBIDDEN), test_with_request.status_code);
+
+    common::test_body(test_admin, "Hi!").await;
+    common::test_body(
+        test_manager,
+        "Manager can't access /context, missing: ROLE_ADMIN",
+    )
+    .await;
+    common::test_body(
+        test_with_request,
+        "Manager can't access /context_with_request, missing: ROLE_ADMIN",
+    )
+    .await;
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    principal = "common::Claims",
+    expr = "principal.claims().tenant == \"acme\""
+)]
+#[handler]
+async fn principal_response(user: Principal<common::Claims>) -> String {
+    user.subject().to_string()
+}
+
+#[protect("ROLE_ADMIN", during = "Mon-Fri 09:00-18:00 UTC")]
+#[handler]
+async fn during_response() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_during() {
+    // Monday, 2024-01-01 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_103_200));
+    let test_working_hours = get_user_response("/during", ROLE_ADMIN).await;
+    // Saturday, 2024-01-06 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_535_200));
+    let test_weekend = get_user_response("/during", ROLE_ADMIN).await;
+    reset_clock();
+
+    assert_eq!(Some(StatusCode::OK), test_working_hours.status_code);
+    assert_eq!(Some(StatusCode::FORBIDDEN), test_weekend.status_code);
+}
+
+#[tokio::test]
+async fn test_principal() {
+    let test_acme = get_tenant_response("/principal", ROLE_ADMIN, Some("acme")).await;
+    let test_other = get_tenant_response("/principal", ROLE_ADMIN, Some("other")).await;
+    let test_anonymous = get_tenant_response("/principal", ROLE_ADMIN, None).await;
+
+    assert_eq!(Some(StatusCode::OK), test_acme.status_code);
+    assert_eq!(Some(StatusCode::FORBIDDEN), test_other.status_code);
+    assert_eq!(Some(StatusCode::UNAUTHORIZED), test_anonymous.status_code);
+
+    common::test_body(test_acme, common::SUBJECT).await;
+}
+
+#[derive(Deserialize, Extractible)]
+#[salvo(extract(default_source(from = "param")))]
+struct OwnerParam {
+    user_id: String,
+}
+
+#[protect(
+    any("ROLE_ADMIN", owner = "param.user_id"),
+    principal = "common::Claims"
+)]
+#[handler]
+async fn owner_response(param: OwnerParam) -> String {
+    format!("Hi, {}!", param.user_id)
+}
+
+#[tokio::test]
+async fn test_owner() {
+    let test_owner = get_tenant_response("/owner/user-1", ROLE_MANAGER, Some("acme")).await;
+    let test_other = get_tenant_response("/owner/user-2", ROLE_MANAGER, Some("acme")).await;
+    let test_admin = get_tenant_response("/owner/user-2", ROLE_ADMIN, Some("acme")).await;
+    let test_without_principal = get_tenant_response("/owner/user-1", ROLE_MANAGER, None).await;
+
+    assert_eq!(Some(StatusCode::OK), test_owner.status_code);
+    assert_eq!(Some(StatusCode::FORBIDDEN), test_other.status_code);
+    assert_eq!(Some(StatusCode::OK), test_admin.status_code);
+    assert_eq!(
+        Some(StatusCode::UNAUTHORIZED),
+        test_without_principal.status_code
+    );
+}
+
 async fn get_user_response(uri: &str, role: &str) -> Response {
+    get_tenant_response(uri, role, None).await
+}
+
+async fn get_tenant_response(uri: &str, role: &str, tenant: Option<&str>) -> Response {
     let app = Service::new(
         Router::with_path("/")
             .hoop(GrantsLayer::with_extractor(common::extract).compat())
             .push(Router::with_path("/http_response").get(http_response))
             .push(Router::with_path("/str").get(str_response))
             .push(Router::with_path("/return").get(return_response))
-            .push(Router::with_path("/result").get(result_response)),
+            .push(Router::with_path("/result").get(result_response))
+            .push(Router::with_path("/context").get(context_response))
+            .push(Router::with_path("/context_with_request").get(context_with_request))
+            .push(Router::with_path("/rule").get(rule_response))
+            .push(Router::with_path("/principal").get(principal_response))
+            .push(Router::with_path("/owner/<user_id>").get(owner_response))
+            .push(Router::with_path("/during").get(during_response)),
     );
 
-    TestClient::get(format!("http://localhost{uri}"))
-        .add_header(AUTHORIZATION, role, true)
-        .send(&app)
-        .await
+    let mut request =
+        TestClient::get(format!("http://localhost{uri}")).add_header(AUTHORIZATION, role, true);
+    if let Some(tenant) = tenant {
+        request = request.add_header(common::TENANT_HEADER, tenant, true);
+    }
+    request.send(&app).await
 }
 
 async fn post_user_response<T: Serialize>(uri: &str, role: &str, data: &T) -> Response {
@@ -2,7 +2,7 @@ use crate::common;
 use crate::common::Role::{self, ADMIN, MANAGER};
 use protect_salvo::protect;
 use protect_salvo::GrantsLayer;
-use salvo::http::header::AUTHORIZATION;
+use salvo::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
 use salvo::prelude::*;
 use salvo::test::TestClient;
 use salvo_extra::TowerLayerCompat;
@@ -50,6 +50,7 @@ async fn test_http_response_for_full_path_enum() {
 async fn test_incorrect_http_response() {
     let test = get_user_response("/incorrect_enum_secure", &ADMIN.to_string()).await;
     assert_eq!(Some(StatusCode::UNAUTHORIZED), test.status_code);
+    assert_eq!("Bearer", test.headers()[WWW_AUTHENTICATE]);
 }
 
 async fn get_user_response(uri: &str, role: &str) -> Response {
@@ -0,0 +1,109 @@
+#[cfg(feature = "deny-by-default")]
+use protect_salvo::deny_by_default::{DenyByDefaultLayer, Routes};
+use protect_salvo::GrantsLayer;
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::{Method, ReqBody, ResBody};
+use salvo::prelude::*;
+use salvo::test::RequestBuilder;
+use salvo_extra::TowerLayerCompat;
+use std::collections::HashSet;
+
+#[protect_salvo::protect_all("ROLE_ADMIN")]
+mod admin {
+    use salvo::prelude::*;
+
+    #[handler]
+    pub async fn index() -> &'static str {
+        "Hello, admin!"
+    }
+
+    #[protect("OP_WRITE")]
+    #[handler]
+    pub async fn users() -> &'static str {
+        "Users"
+    }
+
+    #[protect(any("ROLE_ADMIN", "ROLE_SUPPORT"), overrides)]
+    #[handler]
+    pub async fn tickets() -> &'static str {
+        "Tickets"
+    }
+
+    #[public]
+    #[handler]
+    pub async fn health() -> &'static str {
+        "Ok"
+    }
+
+    pub async fn load_title() -> &'static str {
+        "Admin"
+    }
+}
+
+#[tokio::test]
+async fn test_module() {
+    assert_eq!("Admin", admin::load_title().await);
+    assert_eq!(StatusCode::OK, get_status("/admin", "ROLE_ADMIN").await);
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin", "ROLE_SUPPORT").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/users", "ROLE_ADMIN,OP_WRITE").await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status("/admin/users", "ROLE_ADMIN").await
+    );
+
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/tickets", "ROLE_SUPPORT").await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status("/admin/health", "ROLE_USER").await
+    );
+}
+
+async fn extract(
+    req: &mut salvo::hyper::Request<ReqBody>,
+) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(uri: &str, role: &str) -> StatusCode {
+    let router = Router::with_path("admin").hoop(GrantsLayer::with_extractor(extract).compat());
+    #[cfg(feature = "deny-by-default")]
+    let router = router.hoop(
+        DenyByDefaultLayer::new(
+            Routes::new()
+                .route("GET", "/admin", admin::index)
+                .route("GET", "/admin/users", admin::users)
+                .route("GET", "/admin/tickets", admin::tickets)
+                .route("GET", "/admin/health", admin::health),
+        )
+        .compat(),
+    );
+    let app = Service::new(
+        router
+            .get(admin::index)
+            .push(Router::with_path("users").get(admin::users))
+            .push(Router::with_path("tickets").get(admin::tickets))
+            .push(Router::with_path("health").get(admin::health)),
+    );
+
+    RequestBuilder::new(format!("http://localhost{uri}"), Method::GET)
+        .add_header(AUTHORIZATION, role, true)
+        .send(&app)
+        .await
+        .status_code
+        .unwrap_or(StatusCode::OK)
+}
@@ -0,0 +1,110 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use protect_salvo::authorities::set_anonymous_authorities;
+use protect_salvo::table::{Access, AuthorizationTable, TableLayer};
+use protect_salvo::GrantsLayer;
+use salvo::http::header::AUTHORIZATION;
+use salvo::http::{Method, ReqBody, ResBody};
+use salvo::prelude::*;
+use salvo::test::RequestBuilder;
+use salvo_extra::TowerLayerCompat;
+use std::collections::HashSet;
+
+#[handler]
+async fn hi() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_table() {
+    assert_eq!(StatusCode::OK, get_status(Method::GET, "/", None).await);
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        get_status(Method::GET, "/profile", None).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/profile", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/admin/users", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::GET, "/admin/users", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::POST, "/articles/1", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        StatusCode::OK,
+        get_status(Method::POST, "/articles/1", Some("ROLE_EDITOR")).await
+    );
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        get_status(Method::GET, "/internal/metrics", Some("ROLE_ADMIN")).await
+    );
+}
+
+#[tokio::test]
+async fn test_normalized_paths() {
+    for uri in ["/%61dmin/users", "//admin/users", "/./admin/users"] {
+        assert_eq!(
+            StatusCode::FORBIDDEN,
+            get_status(Method::GET, uri, Some("ROLE_USER")).await,
+            "{uri}"
+        );
+    }
+}
+
+fn table() -> AuthorizationTable {
+    AuthorizationTable::new()
+        .route("GET", "/", Access::PermitAll)
+        .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+        .route(
+            "POST",
+            "/articles/{id}",
+            "ROLE_EDITOR || ROLE_ADMIN".parse::<Access>().unwrap(),
+        )
+        .route("*", "/internal/**", Access::DenyAll)
+        .otherwise(Access::Authenticated)
+}
+
+async fn extract(
+    req: &mut salvo::hyper::Request<ReqBody>,
+) -> Result<HashSet<String>, salvo::hyper::Response<ResBody>> {
+    Ok(req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .map(|header| header.split(',').map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+async fn get_status(method: Method, uri: &str, role: Option<&str>) -> StatusCode {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    let app = Service::new(
+        Router::new()
+            .hoop(GrantsLayer::with_extractor(extract).compat())
+            .hoop(TableLayer::new(table()).compat())
+            .get(hi)
+            .push(Router::with_path("profile").get(hi))
+            .push(Router::with_path("admin/users").get(hi))
+            .push(Router::with_path("articles/1").post(hi))
+            .push(Router::with_path("internal/metrics").get(hi)),
+    );
+
+    let mut request = RequestBuilder::new(format!("http://localhost{uri}"), method);
+    if let Some(role) = role {
+        request = request.add_header(AUTHORIZATION, role, true);
+    }
+
+    request
+        .send(&app)
+        .await
+        .status_code
+        .unwrap_or(StatusCode::OK)
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "test-util")]
+
+use protect_salvo::test_util::{MockExtractor, WithAuthorities};
+use protect_salvo::GrantsLayer;
+use salvo::http::ResBody;
+use salvo::prelude::*;
+use salvo::test::{RequestBuilder, TestClient};
+use salvo_extra::TowerLayerCompat;
+use std::collections::HashSet;
+
+const ROLE_ADMIN: &str = "ROLE_ADMIN";
+
+#[protect_salvo::protect("ROLE_ADMIN")]
+#[handler]
+async fn admin() -> &'static str {
+    "Hi!"
+}
+
+async fn get_status(extractor: &MockExtractor, request: RequestBuilder) -> Option<StatusCode> {
+    let app = Service::new(
+        Router::with_hoop(GrantsLayer::with_extractor(extractor.clone()).compat())
+            .push(Router::with_path("admin").get(admin)),
+    );
+
+    request.send(&app).await.status_code
+}
+
+fn admin_request(authorities: &[&str]) -> RequestBuilder {
+    TestClient::get("http://localhost/admin").with_authorities(authorities)
+}
+
+#[tokio::test]
+async fn test_with_authorities() {
+    let extractor = MockExtractor::new();
+
+    assert_eq!(
+        Some(StatusCode::OK),
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(
+        Some(StatusCode::FORBIDDEN),
+        get_status(&extractor, admin_request(&[])).await
+    );
+    let request = TestClient::get("http://localhost/admin");
+    assert_eq!(
+        Some(StatusCode::FORBIDDEN),
+        get_status(&extractor, request).await
+    );
+
+    let calls = extractor.calls();
+    assert_eq!(3, calls.len());
+    assert_eq!("GET", calls[0].method());
+    assert_eq!("/admin", calls[0].path());
+    assert_eq!(
+        Some(&HashSet::from([ROLE_ADMIN.to_string()])),
+        calls[0].authorities()
+    );
+    assert_eq!(None, calls[2].authorities());
+}
+
+#[tokio::test]
+async fn test_scripted_results() {
+    let extractor = MockExtractor::new();
+    let unauthorized = salvo::hyper::Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .body(ResBody::None)
+        .unwrap();
+    extractor.then_return([ROLE_ADMIN]).then_fail(unauthorized);
+
+    assert_eq!(
+        Some(StatusCode::OK),
+        get_status(&extractor, admin_request(&[])).await
+    );
+    assert_eq!(
+        Some(StatusCode::UNAUTHORIZED),
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(
+        Some(StatusCode::OK),
+        get_status(&extractor, admin_request(&[ROLE_ADMIN])).await
+    );
+    assert_eq!(3, extractor.calls().len());
+}
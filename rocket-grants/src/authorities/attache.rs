@@ -1,5 +1,9 @@
-use crate::authorities::{AuthDetails, AuthDetailsWrapper};
+use crate::authorities::{AuthDetails, AuthDetailsWrapper, TimedAuthority};
+use protect_endpoints_core::authorities::{
+    anonymous_authorities, principal_authorities, validity_windows,
+};
 use rocket::Request;
+use std::collections::HashMap;
 use std::hash::Hash;
 
 /// Allows you to transfer permissions to [`rocket-grants`] from your custom fairing.
@@ -23,14 +27,50 @@ use std::hash::Hash;
 /// [`&mut Request`]: rocket::Request
 pub trait AttachAuthorities<Type> {
     fn attach(&mut self, authorities: Option<impl IntoIterator<Item = Type>>);
+
+    /// Attaches time-bounded authorities, expired ones are ignored by checks.
+    ///
+    /// By default, only authorities active at the moment are attached (without their windows).
+    fn attach_timed(&mut self, authorities: Option<impl IntoIterator<Item = TimedAuthority<Type>>>)
+    where
+        Type: Clone,
+    {
+        self.attach(authorities.map(|authorities| {
+            authorities
+                .into_iter()
+                .filter(TimedAuthority::is_active)
+                .map(|timed| timed.authority)
+        }));
+    }
 }
 
 impl<Type: Eq + Hash + Send + Sync + 'static> AttachAuthorities<Type> for &mut Request<'_> {
     fn attach(&mut self, authorities: Option<impl IntoIterator<Item = Type>>) {
-        let auth_details = authorities
-            .map(AuthDetails::new)
-            .map(|details| AuthDetailsWrapper(Some(details)))
-            .unwrap_or(AuthDetailsWrapper(None));
+        let authorities = match authorities {
+            Some(authorities) => Some(principal_authorities(authorities)),
+            None => anonymous_authorities(),
+        };
+        let auth_details = AuthDetailsWrapper(
+            authorities.map(|authorities| AuthDetails::with_validity(authorities, HashMap::new())),
+        );
         self.local_cache(|| auth_details);
     }
+
+    fn attach_timed(&mut self, authorities: Option<impl IntoIterator<Item = TimedAuthority<Type>>>)
+    where
+        Type: Clone,
+    {
+        let auth_details = match authorities {
+            Some(authorities) => {
+                let validity = validity_windows(authorities);
+                Some(AuthDetails::with_validity(
+                    principal_authorities(validity.keys().cloned()),
+                    validity,
+                ))
+            }
+            None => anonymous_authorities()
+                .map(|authorities| AuthDetails::with_validity(authorities, HashMap::new())),
+        };
+        self.local_cache(|| AuthDetailsWrapper(auth_details));
+    }
 }
@@ -0,0 +1,69 @@
+use crate::error::PendingChallenge;
+use protect_endpoints_core::authorities::Principal as PrincipalCore;
+use protect_endpoints_core::error::unauthorized_challenge;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use std::ops::Deref;
+
+/// Subject identifier and typed claims of the user, see [`AttachPrincipal`].
+///
+/// Requests without the attached principal are rejected as unauthorized.
+pub struct Principal<C = ()>(PrincipalCore<C>);
+
+struct PrincipalWrapper<C>(Option<PrincipalCore<C>>);
+
+#[rocket::async_trait]
+impl<'r, C: Send + Sync + 'static> FromRequest<'r> for Principal<C> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.local_cache(|| PrincipalWrapper::<C>(None)) {
+            PrincipalWrapper(Some(principal)) => Outcome::Success(Principal(principal.clone())),
+            PrincipalWrapper(None) => {
+                request.local_cache(|| PendingChallenge(unauthorized_challenge()));
+                Outcome::Error((Status::Unauthorized, ()))
+            }
+        }
+    }
+}
+
+impl<C> Deref for Principal<C> {
+    type Target = PrincipalCore<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> Clone for Principal<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Allows you to transfer [`Principal`] to [`rocket-grants`], e.g. from the authorities extractor.
+///
+/// # Example
+///
+/// ```
+/// use rocket_grants::authorities::AttachPrincipal;
+///
+/// struct Claims { tenant: String }
+///
+/// fn attach(req: &rocket::Request, subject: String, tenant: String) {
+///     req.attach_principal(subject, Claims { tenant });
+/// }
+/// ```
+///
+/// [`rocket-grants`]: crate
+pub trait AttachPrincipal<C> {
+    fn attach_principal(&self, subject: impl Into<String>, claims: C);
+}
+
+impl<C: Send + Sync + 'static> AttachPrincipal<C> for Request<'_> {
+    fn attach_principal(&self, subject: impl Into<String>, claims: C) {
+        let principal = PrincipalCore::new(subject, claims);
+        self.local_cache(|| PrincipalWrapper(Some(principal)));
+    }
+}
@@ -0,0 +1,68 @@
+//! Deny-by-default mode: requests which aren't routed to handlers annotated with [`protect`](crate::protect)
+//! or [`public`](crate::public) are rejected with `403`, see [`protect_endpoints_core::deny_by_default`].
+//!
+//! The mode is enabled by [`DenyByDefaultFairing`] configured with [`Routes`] of the app
+//! (with mount points of the routes in path patterns). Requests are checked before they're routed,
+//! so unannotated handlers never run.
+//!
+//! # Example
+//! ```
+//! use rocket_grants::deny_by_default::{DenyByDefaultFairing, Routes};
+//!
+//! #[rocket_grants::public]
+//! #[rocket::get("/")]
+//! async fn index() -> &'static str {
+//!     "Hi!"
+//! }
+//!
+//! let routes = Routes::new().route("GET", "/", index);
+//!
+//! let rocket = rocket::build()
+//!     .mount("/", rocket::routes![index])
+//!     .attach(DenyByDefaultFairing::new(routes));
+//! ```
+
+use crate::guard::{self, PendingRejection};
+use protect_endpoints_core::table::Rejection;
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::{Build, Data, Request, Rocket};
+
+pub use protect_endpoints_core::deny_by_default::{is_annotated, AnnotatedHandler, Route, Routes};
+
+/// Fairing rejecting requests which aren't routed to annotated handlers.
+///
+/// Fairings can't respond on their own, so the fairing mounts a guard route per method matching any path
+/// before other routes (shared with [`TableFairing`](crate::table::TableFairing)), which renders the rejection.
+#[derive(Debug, Clone)]
+pub struct DenyByDefaultFairing {
+    routes: Routes,
+}
+
+impl DenyByDefaultFairing {
+    pub fn new(routes: Routes) -> Self {
+        Self { routes }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for DenyByDefaultFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rocket-Grants Deny-By-Default",
+            kind: Kind::Ignite | Kind::Request,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        Ok(guard::mount(rocket))
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        if let Err(denied) = self
+            .routes
+            .check(req.method().as_str(), req.uri().path().as_str())
+        {
+            PendingRejection::reject(req, Rejection::Forbidden(denied));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use protect_endpoints_core::condition::{Condition, Explanation};
+use protect_endpoints_core::error::AccessDenied as AccessDeniedCore;
+use rocket::http::uri::Origin;
+use rocket::http::{HeaderMap, Method, Status};
+use rocket::request::{self, FromRequest};
+use rocket::response::{self, Responder, Response};
+use rocket::Request;
+use std::convert::Infallible;
+use std::fmt::{self, Display, Formatter};
+use std::io::Cursor;
+use std::ops::Deref;
+
+pub use protect_endpoints_core::error::{
+    set_challenge, set_denial_renderer, Challenge, PROBLEM_JSON,
+};
+
+/// Default error of the [`protect`](crate::protect) macro.
+///
+/// Rendered as `application/problem+json`, see [`protect_endpoints_core::error`]
+/// for details and customization. If a `403` catcher is registered, it's used instead.
+#[derive(Debug, Clone)]
+pub struct AccessDenied(AccessDeniedCore);
+
+impl AccessDenied {
+    pub fn new(required: Condition<String>, explanation: Explanation<String>) -> Self {
+        Self(AccessDeniedCore::new(required, explanation))
+    }
+}
+
+impl<'r> Responder<'r, 'static> for AccessDenied {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = Status::Forbidden;
+        if req
+            .rocket()
+            .catchers()
+            .any(|catcher| catcher.code == Some(status.code))
+        {
+            req.local_cache(|| PendingChallenge(self.www_authenticate()));
+            return Err(status);
+        }
+
+        let (parts, body) = self.0.to_response().into_parts();
+        let mut builder = Response::build();
+        builder.status(Status::new(parts.status.as_u16()));
+        for (name, value) in &parts.headers {
+            if let Ok(value) = value.to_str() {
+                builder.raw_header(name.to_string(), value.to_string());
+            }
+        }
+
+        builder.sized_body(body.len(), Cursor::new(body)).ok()
+    }
+}
+
+impl From<AccessDeniedCore> for AccessDenied {
+    fn from(value: AccessDeniedCore) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for AccessDenied {
+    type Target = AccessDeniedCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for AccessDenied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for AccessDenied {}
+
+/// `WWW-Authenticate` challenge for responses rendered by catchers,
+/// added by [`GrantsFairing`](crate::GrantsFairing) if the response doesn't have one.
+pub(crate) struct PendingChallenge(pub(crate) Option<String>);
+
+/// Parts of the request for custom error handlers of the [`protect`](crate::protect) macro,
+/// since `&Request` isn't a request guard.
+#[derive(Debug)]
+pub struct RequestHead<'r> {
+    pub method: Method,
+    pub uri: &'r Origin<'r>,
+    pub headers: &'r HeaderMap<'r>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestHead<'r> {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(RequestHead {
+            method: req.method(),
+            uri: req.uri(),
+            headers: req.headers(),
+        })
+    }
+}
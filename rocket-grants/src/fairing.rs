@@ -1,10 +1,14 @@
 use crate::authorities::AttachAuthorities;
+use crate::error::PendingChallenge;
 use futures_core::future::BoxFuture;
 use rocket::fairing::{Fairing, Info, Kind};
-use rocket::{Data, Request};
+use rocket::http::Status;
+use rocket::{Data, Request, Response};
 use std::collections::HashSet;
 use std::hash::Hash;
 
+const WWW_AUTHENTICATE: &str = "WWW-Authenticate";
+
 type Extractor<Type> = Box<
     dyn for<'a> Fn(&'a mut Request<'_>) -> BoxFuture<'a, Option<HashSet<Type>>>
         + Send
@@ -102,7 +106,7 @@ impl<Type: Eq + Hash + Send + Sync + 'static> Fairing for GrantsFairing<Type> {
     fn info(&self) -> Info {
         Info {
             name: "Rocket-Grants Extractor",
-            kind: Kind::Request,
+            kind: Kind::Request | Kind::Response,
         }
     }
 
@@ -110,4 +114,18 @@ impl<Type: Eq + Hash + Send + Sync + 'static> Fairing for GrantsFairing<Type> {
         let authorities: Option<HashSet<Type>> = (self.extractor)(req).await;
         req.attach(authorities);
     }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        // The challenge is pending even if the error was swallowed by `Option<AuthDetails>`
+        if ![Status::Unauthorized, Status::Forbidden].contains(&res.status()) {
+            return;
+        }
+
+        let PendingChallenge(challenge) = req.local_cache(|| PendingChallenge(None));
+        if let Some(challenge) = challenge {
+            if !res.headers().contains(WWW_AUTHENTICATE) {
+                res.set_raw_header(WWW_AUTHENTICATE, challenge.clone());
+            }
+        }
+    }
 }
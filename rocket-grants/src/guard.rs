@@ -0,0 +1,75 @@
+//! Guard routes rejecting requests before other routes, shared by fairings which can't respond on their own.
+
+use crate::error::{AccessDenied, PendingChallenge};
+use protect_endpoints_core::error::unauthorized_challenge;
+use protect_endpoints_core::table::Rejection;
+use rocket::http::{Method, Status};
+use rocket::route::{self, Handler, Route};
+use rocket::{Build, Data, Request, Rocket};
+
+/// Rank of the guard routes, so they are tried before any other route.
+const GUARD_RANK: isize = isize::MIN;
+
+const GUARD_NAME: &str = "rocket_grants::guard";
+
+const METHODS: [Method; 9] = [
+    Method::Get,
+    Method::Put,
+    Method::Post,
+    Method::Delete,
+    Method::Options,
+    Method::Head,
+    Method::Trace,
+    Method::Connect,
+    Method::Patch,
+];
+
+/// Rejection of the current request stored by a fairing, the first one wins.
+pub(crate) struct PendingRejection(pub(crate) Option<Rejection>);
+
+impl PendingRejection {
+    pub(crate) fn reject(req: &Request<'_>, rejection: Rejection) {
+        req.local_cache(|| PendingRejection(Some(rejection)));
+    }
+}
+
+#[derive(Clone)]
+struct Guard;
+
+#[rocket::async_trait]
+impl Handler for Guard {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        match request.local_cache(|| PendingRejection(None)) {
+            PendingRejection(Some(Rejection::Forbidden(denied))) => {
+                route::Outcome::from(request, AccessDenied::from(denied.clone()))
+            }
+            PendingRejection(Some(Rejection::Unauthorized)) => {
+                request.local_cache(|| PendingChallenge(unauthorized_challenge()));
+                route::Outcome::Error(Status::Unauthorized)
+            }
+            PendingRejection(None) => route::Outcome::forward(data, Status::NotFound),
+        }
+    }
+}
+
+/// Mounts a guard route per method matching any path (once), it renders the [`PendingRejection`]
+/// of the request or forwards it to the next route.
+pub(crate) fn mount(rocket: Rocket<Build>) -> Rocket<Build> {
+    if rocket
+        .routes()
+        .any(|route| route.name.as_deref() == Some(GUARD_NAME))
+    {
+        return rocket;
+    }
+
+    let routes = METHODS
+        .into_iter()
+        .map(|method| {
+            let mut route = Route::ranked(GUARD_RANK, method, "/<_..>", Guard);
+            route.name = Some(GUARD_NAME.into());
+            route
+        })
+        .collect::<Vec<_>>();
+
+    rocket.mount("/", routes)
+}
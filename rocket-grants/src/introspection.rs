@@ -0,0 +1,32 @@
+//! Admin endpoint serving the registered endpoints and simulating the access of authorities,
+//! see [`protect_endpoints_core::registry::introspection`].
+//!
+//! The endpoint isn't protected by itself, e.g. use the authorization [`table`](crate::table) for it.
+//!
+//! # Example
+//! ```
+//! // GET /admin/access?authorities=ROLE_USER,OP_READ
+//! let rocket = rocket::build().mount("/admin/access", rocket_grants::introspection::routes());
+//! ```
+
+use protect_endpoints_core::registry::introspection;
+use rocket::http::{ContentType, Method};
+use rocket::route::{Handler, Outcome, Route};
+use rocket::{Data, Request};
+
+/// Handler of the endpoint, see [`routes`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Introspection;
+
+/// Route of the endpoint (`GET /`) to mount.
+pub fn routes() -> Vec<Route> {
+    vec![Route::new(Method::Get, "/", Introspection)]
+}
+
+#[rocket::async_trait]
+impl Handler for Introspection {
+    async fn handle<'r>(&self, req: &'r Request<'_>, _data: Data<'r>) -> Outcome<'r> {
+        let query = req.uri().query().map(|query| query.as_str());
+        Outcome::from(req, (ContentType::JSON, introspection::respond(query)))
+    }
+}
@@ -18,7 +18,30 @@
 #![doc = include_str!("../README.md")]
 
 pub mod authorities;
+#[cfg(feature = "deny-by-default")]
+pub mod deny_by_default;
+pub mod error;
+#[cfg(feature = "registry")]
+pub mod introspection;
+pub mod network;
+pub mod table;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[doc(hidden)]
+pub use protect_endpoints_core::__annotate_handler;
+#[doc(hidden)]
+pub use protect_endpoints_core::__register_endpoint;
+pub use protect_endpoints_core::condition;
+#[cfg(any(
+    feature = "policy-toml",
+    feature = "policy-yaml",
+    feature = "policy-json"
+))]
+pub use protect_endpoints_core::policy;
+#[cfg(feature = "registry")]
+pub use protect_endpoints_core::registry;
 mod fairing;
+mod guard;
 
 pub use fairing::GrantsFairing;
 
@@ -43,6 +66,13 @@ pub use fairing::GrantsFairing;
 ///    "some secured info"
 /// }
 ///
+/// // User should be ADMIN, or have OP_WRITE permission without being SUSPENDED
+/// #[rocket_grants::protect(rule = "ROLE_ADMIN || (OP_WRITE && !SUSPENDED)")]
+/// #[rocket::get("/rule")]
+/// async fn rule_macro_secured() -> &'static str {
+///    "some secured info"
+/// }
+///
 /// // Additional security condition to ensure the protection of the endpoint
 /// #[rocket_grants::protect("USER", expr = "user_id == user.id")]
 /// #[rocket::post("/secure/<user_id>", data = "<user>")]
@@ -65,7 +95,10 @@ pub use fairing::GrantsFairing;
 /// ```
 #[cfg(feature = "macro-check")]
 pub mod proc_macro {
-    pub use protect_endpoints_proc_macro::protect_rocket as protect;
+    pub use protect_endpoints_proc_macro::{
+        protect_all_rocket as protect_all, protect_rocket as protect, public_rocket as public,
+        requirement_of,
+    };
 }
 
 /// Just a shortcut for proc-macros
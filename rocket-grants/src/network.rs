@@ -0,0 +1,105 @@
+//! Network conditions: the client IP address within ranges, see [`protect_endpoints_core::network`].
+//!
+//! Handlers are restricted via `from_network` argument of the [`protect`](crate::protect) macro,
+//! groups of routes via [`NetworkHandler::wrap`].
+
+use crate::error::AccessDenied;
+use protect_endpoints_core::network::ClientIp as ClientIpCore;
+use rocket::request::{FromRequest, Outcome};
+use rocket::route::{self, Handler, Route};
+use rocket::{Data, Request};
+use std::convert::Infallible;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub use protect_endpoints_core::network::{
+    client_ip, register_network, reset_trusted_proxies, set_trusted_proxies, ForwardedHeader,
+    IpNet, NetworkError, Networks,
+};
+
+const FORWARDED: &str = "Forwarded";
+const X_FORWARDED_FOR: &str = "X-Forwarded-For";
+
+/// Address of the client: the peer address or the forwarded one, if the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(ClientIpCore);
+
+impl ClientIp {
+    fn resolve(req: &Request<'_>) -> Self {
+        let headers = req.headers();
+        let forwarded = [FORWARDED, X_FORWARDED_FOR]
+            .into_iter()
+            .flat_map(|name| headers.get(name).map(move |value| (name, value)));
+        ClientIp(ClientIpCore::new(
+            req.remote().map(|peer| peer.ip()),
+            forwarded,
+        ))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientIp {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(ClientIp::resolve(request))
+    }
+}
+
+impl Deref for ClientIp {
+    type Target = ClientIpCore;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Handler of a route denying requests from clients outside of the networks.
+#[derive(Clone)]
+pub struct NetworkHandler {
+    networks: Arc<Networks>,
+    inner: Box<dyn Handler>,
+}
+
+impl NetworkHandler {
+    /// Restricts the routes to the networks.
+    ///
+    /// # Example
+    /// ```
+    /// use rocket_grants::network::NetworkHandler;
+    ///
+    /// #[rocket::get("/metrics")]
+    /// async fn metrics() -> &'static str {
+    ///     "up 1"
+    /// }
+    ///
+    /// let rocket = rocket::build().mount(
+    ///     "/internal",
+    ///     NetworkHandler::wrap("10.0.0.0/8".parse().unwrap(), rocket::routes![metrics]),
+    /// );
+    /// ```
+    pub fn wrap(networks: Networks, routes: Vec<Route>) -> Vec<Route> {
+        let networks = Arc::new(networks);
+
+        routes
+            .into_iter()
+            .map(|mut route| {
+                route.handler = Box::new(NetworkHandler {
+                    networks: networks.clone(),
+                    inner: route.handler,
+                });
+                route
+            })
+            .collect()
+    }
+}
+
+#[rocket::async_trait]
+impl Handler for NetworkHandler {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        match ClientIp::resolve(request).check(&self.networks) {
+            Ok(()) => self.inner.handle(request, data).await,
+            Err(denied) => route::Outcome::from(request, AccessDenied::from(denied)),
+        }
+    }
+}
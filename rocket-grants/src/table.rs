@@ -0,0 +1,82 @@
+//! Central authorization table: `(method, path pattern) -> access`, see [`protect_endpoints_core::table`].
+//!
+//! The table is applied via [`TableFairing`], which must be attached after the
+//! [`GrantsFairing`](crate::GrantsFairing) to see the attached authorities.
+
+use crate::authorities::AuthDetailsWrapper;
+use crate::guard::{self, PendingRejection};
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::{Build, Data, Request, Rocket};
+use std::fmt::Display;
+use std::hash::Hash;
+
+pub use protect_endpoints_core::table::{
+    Access, AuthorizationTable, PathPattern, Rejection, Rule, SharedTable, TableError,
+};
+
+/// Fairing checking requests against the [`AuthorizationTable`].
+///
+/// Fairings can't respond on their own, so the fairing mounts a guard route per method matching any path
+/// before other routes: it renders the rejection of the request or forwards it to the next route.
+/// So rejected requests never reach the handlers and the URI of the request is kept as is.
+///
+/// # Example
+/// ```
+/// use rocket_grants::table::{Access, AuthorizationTable, TableFairing};
+/// use rocket_grants::GrantsFairing;
+/// use std::collections::HashSet;
+///
+/// async fn extract(_req: &rocket::Request<'_>) -> Option<HashSet<String>> {
+///     Some(HashSet::from(["ROLE_ADMIN".to_string()]))
+/// }
+///
+/// let table = AuthorizationTable::new()
+///     .route("GET", "/", Access::PermitAll)
+///     .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+///     .otherwise(Access::Authenticated);
+///
+/// let rocket = rocket::build()
+///     .attach(GrantsFairing::with_extractor_fn(|req| Box::pin(extract(req))))
+///     .attach(TableFairing::new(table));
+/// ```
+pub struct TableFairing<T = String> {
+    table: SharedTable<T>,
+}
+
+impl<T> TableFairing<T> {
+    pub fn new(table: impl Into<SharedTable<T>>) -> Self {
+        Self {
+            table: table.into(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<T> Fairing for TableFairing<T>
+where
+    T: Eq + Hash + Clone + Display + Send + Sync + 'static,
+{
+    fn info(&self) -> Info {
+        Info {
+            name: "Rocket-Grants Authorization Table",
+            kind: Kind::Ignite | Kind::Request,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        Ok(guard::mount(rocket))
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let AuthDetailsWrapper(details) = req.local_cache(|| AuthDetailsWrapper::<T>(None));
+        let details = details.as_ref().filter(|details| !details.is_anonymous());
+
+        if let Err(rejection) =
+            self.table
+                .load()
+                .authorize(req.method().as_str(), req.uri().path().as_str(), details)
+        {
+            PendingRejection::reject(req, rejection);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Injection of authorities into test requests, see [`protect_endpoints_core::test_util`].
+//!
+//! Extractors of `rocket` can't fail, so scripted errors of the [`MockExtractor`] are just missing authorities.
+//!
+//! # Example
+//! ```
+//! use rocket::http::Status;
+//! use rocket::local::blocking::Client;
+//! use rocket_grants::test_util::{MockExtractor, WithAuthorities};
+//! use rocket_grants::GrantsFairing;
+//!
+//! #[rocket_grants::protect("ROLE_ADMIN")]
+//! #[rocket::get("/")]
+//! async fn secured() -> &'static str {
+//!     "some secured info"
+//! }
+//!
+//! let extractor = MockExtractor::new();
+//! let rocket = rocket::build()
+//!     .mount("/", rocket::routes![secured])
+//!     .attach(GrantsFairing::with_mock_extractor(extractor.clone()));
+//! let client = Client::untracked(rocket).unwrap();
+//!
+//! let resp = client.get("/").with_authorities(["ROLE_ADMIN"]).dispatch();
+//!
+//! assert_eq!(Status::Ok, resp.status());
+//! assert_eq!(1, extractor.calls().len());
+//! ```
+
+use crate::GrantsFairing;
+use protect_endpoints_core::test_util::header_value;
+pub use protect_endpoints_core::test_util::{Call, AUTHORITIES_HEADER};
+use rocket::http::Header;
+use rocket::local::{asynchronous, blocking};
+
+/// Extractor of authorities given by [`WithAuthorities`], scripted results and errors take precedence.
+pub type MockExtractor = protect_endpoints_core::test_util::MockExtractor<()>;
+
+impl GrantsFairing<String> {
+    /// Creating fairing with the [`MockExtractor`], a failed extraction leaves the request without authorities.
+    pub fn with_mock_extractor(extractor: MockExtractor) -> Self {
+        Self::with_extractor_fn(move |req| {
+            let call = Call::new(
+                req.method().as_str(),
+                req.uri().path().as_str(),
+                req.headers().get_one(AUTHORITIES_HEADER),
+            );
+            let authorities = extractor.respond(call).ok();
+            Box::pin(async move { authorities })
+        })
+    }
+}
+
+/// Passes authorities of the test request to the [`MockExtractor`].
+pub trait WithAuthorities {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>;
+}
+
+impl WithAuthorities for asynchronous::LocalRequest<'_> {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.header(Header::new(AUTHORITIES_HEADER, header_value(authorities)))
+    }
+}
+
+impl WithAuthorities for blocking::LocalRequest<'_> {
+    fn with_authorities<I, S>(self, authorities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.header(Header::new(AUTHORITIES_HEADER, header_value(authorities)))
+    }
+}
@@ -0,0 +1,95 @@
+//! The anonymous principal is configured globally, so it's tested in a separate binary.
+
+use rocket::http::hyper::header::AUTHORIZATION;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::Request;
+use rocket_grants::authorities::{set_anonymous_authorities, AuthDetails, AuthoritiesCheck};
+use rocket_grants::{protect, GrantsFairing};
+use std::collections::HashSet;
+
+#[protect(rule = "ANONYMOUS || ROLE_USER", allow_anonymous)]
+#[rocket::get("/allow_anonymous")]
+async fn allow_anonymous() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_USER")]
+#[rocket::get("/user_only")]
+async fn user_only() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::get("/optional")]
+async fn optional(details: Option<AuthDetails>) -> &'static str {
+    match details {
+        Some(details) if details.has_authority("ROLE_USER") => "user",
+        Some(_) => "authenticated",
+        None => "anonymous",
+    }
+}
+
+#[tokio::test]
+async fn test_anonymous() {
+    set_anonymous_authorities(["ANONYMOUS".to_string()]);
+
+    let app = rocket::build()
+        .mount("/", rocket::routes![allow_anonymous, user_only, optional])
+        .attach(GrantsFairing::with_extractor_fn(|req| {
+            Box::pin(extract(req))
+        }));
+    let client = Client::untracked(app).await.unwrap();
+
+    let get_response = |uri: &'static str, role: Option<&'static str>| {
+        let mut request = client.get(uri);
+        if let Some(role) = role {
+            request = request.header(Header::new(AUTHORIZATION.as_str(), role));
+        }
+        async move {
+            let resp = request.dispatch().await;
+            let challenge = resp
+                .headers()
+                .get_one("WWW-Authenticate")
+                .map(str::to_string);
+            (
+                resp.status(),
+                resp.into_string().await.unwrap_or_default(),
+                challenge,
+            )
+        }
+    };
+
+    assert_eq!(
+        (Status::Ok, "Hi!".into(), None),
+        get_response("/allow_anonymous", None).await
+    );
+    assert_eq!(
+        (Status::Ok, "Hi!".into(), None),
+        get_response("/allow_anonymous", Some("ROLE_USER")).await
+    );
+    let (status, _, _) = get_response("/allow_anonymous", Some("ROLE_OTHER")).await;
+    assert_eq!(Status::Forbidden, status);
+
+    let (status, _, challenge) = get_response("/user_only", None).await;
+    assert_eq!(Status::Unauthorized, status);
+    assert_eq!(Some("Bearer".to_string()), challenge);
+
+    assert_eq!(
+        (Status::Ok, "anonymous".into(), None),
+        get_response("/optional", None).await
+    );
+    assert_eq!(
+        (Status::Ok, "user".into(), None),
+        get_response("/optional", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        (Status::Ok, "authenticated".into(), None),
+        get_response("/optional", Some("ROLE_OTHER")).await
+    );
+}
+
+async fn extract(req: &mut Request<'_>) -> Option<HashSet<String>> {
+    req.headers()
+        .get_one(AUTHORIZATION.as_str())
+        .map(|header| header.split(',').map(str::to_string).collect())
+}
@@ -1,11 +1,18 @@
 use rocket::http::hyper::header::AUTHORIZATION;
 use rocket::local::asynchronous::LocalResponse;
 use rocket::Request;
+use rocket_grants::authorities::AttachPrincipal;
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 
 pub const ROLE_ADMIN: &str = "ROLE_ADMIN";
 pub const ROLE_MANAGER: &str = "ROLE_MANAGER";
+pub const TENANT_HEADER: &str = "x-tenant";
+pub const SUBJECT: &str = "user-1";
+
+pub struct Claims {
+    pub tenant: String,
+}
 
 #[derive(PartialEq, Eq, Hash)]
 pub enum Role {
@@ -14,6 +21,11 @@ pub enum Role {
 }
 
 pub async fn extract(req: &mut Request<'_>) -> Option<HashSet<String>> {
+    if let Some(tenant) = req.headers().get_one(TENANT_HEADER) {
+        let tenant = tenant.to_string();
+        req.attach_principal(SUBJECT, Claims { tenant });
+    }
+
     let auth_headers: Vec<&str> = req.headers().get(AUTHORIZATION.as_str()).collect();
 
     auth_headers
@@ -29,6 +41,18 @@ pub async fn enum_extract(req: &mut Request<'_>) -> Option<HashSet<Role>> {
         .map(|h| h.split(',').map(|name| name.into()).collect())
 }
 
+/// Default denial without explanation, the correlation id is generated.
+pub async fn test_problem(resp: LocalResponse<'_>) {
+    assert_eq!(
+        Some("application/problem+json"),
+        resp.headers().get_one("Content-Type")
+    );
+    let body = resp.into_string().await.unwrap();
+    assert!(body.starts_with(
+        r#"{"type":"about:blank","title":"Forbidden","status":403,"correlation_id":""#
+    ));
+}
+
 pub async fn test_body(resp: LocalResponse<'_>, expected_body: &str) {
     let body = resp.into_string().await.unwrap();
     assert_eq!(expected_body, &body);
@@ -0,0 +1,85 @@
+use protect_endpoints_testkit::{
+    header_value, parse_header_value, Adapter, Credentials, Role, AUTHORITIES_HEADER, OWNER,
+};
+use rocket::futures::future::BoxFuture;
+use rocket::http::Header;
+use rocket::local::asynchronous::Client;
+use rocket::Request;
+use rocket_grants::{protect, GrantsFairing};
+use std::hash::Hash;
+use std::str::FromStr;
+
+#[protect("ROLE_ADMIN")]
+#[rocket::get("/single")]
+async fn single() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_ADMIN", "ROLE_MANAGER")]
+#[rocket::get("/all")]
+async fn all() -> &'static str {
+    "Hi!"
+}
+
+#[protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]
+#[rocket::get("/any")]
+async fn any() -> &'static str {
+    "Hi!"
+}
+
+#[protect("ROLE_ADMIN", expr = "name.as_deref() == Some(OWNER)")]
+#[rocket::get("/expr?<name>")]
+async fn expr(name: Option<String>) -> &'static str {
+    "Hi!"
+}
+
+#[protect("Role::Admin", ty = "Role")]
+#[rocket::get("/typed")]
+async fn typed() -> &'static str {
+    "Hi!"
+}
+
+fn fairing<T>(credentials: Credentials) -> GrantsFairing<T>
+where
+    T: FromStr + Eq + Hash + Send + Sync + 'static,
+{
+    GrantsFairing::with_extractor_fn(move |req: &mut Request<'_>| -> BoxFuture<'_, _> {
+        let authorities = (credentials != Credentials::ExtractorError).then(|| {
+            req.headers()
+                .get_one(AUTHORITIES_HEADER)
+                .map(parse_header_value)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|authority| authority.parse().ok())
+                .collect()
+        });
+        Box::pin(async move { authorities })
+    })
+}
+
+struct Rocket;
+
+impl Adapter for Rocket {
+    async fn status(&self, path: &str, credentials: &Credentials) -> u16 {
+        let mut rocket = rocket::build().mount("/", rocket::routes![single, all, any, expr, typed]);
+        if *credentials != Credentials::Missing {
+            rocket = rocket
+                .attach(fairing::<String>(*credentials))
+                .attach(fairing::<Role>(*credentials));
+        }
+        let client = Client::untracked(rocket).await.unwrap();
+
+        let mut request = client.get(path);
+        if let Credentials::Authorities(authorities) = credentials {
+            request = request.header(Header::new(AUTHORITIES_HEADER, header_value(*authorities)));
+        }
+
+        let status = request.dispatch().await.status();
+        status.code
+    }
+}
+
+#[rocket::async_test]
+async fn test_conformance() {
+    protect_endpoints_testkit::run(&Rocket).await;
+}
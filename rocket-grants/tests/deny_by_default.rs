@@ -0,0 +1,124 @@
+#![cfg(feature = "deny-by-default")]
+
+use rocket::http::hyper::header::AUTHORIZATION;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::Request;
+use rocket_grants::deny_by_default::{DenyByDefaultFairing, Routes};
+use rocket_grants::table::{Access, AuthorizationTable, TableFairing};
+use rocket_grants::GrantsFairing;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[rocket_grants::public]
+#[rocket::get("/")]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+#[rocket_grants::protect("ROLE_ADMIN")]
+#[rocket::get("/admin")]
+async fn admin() -> &'static str {
+    "Hello, admin!"
+}
+
+static FORGOTTEN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[rocket::get("/forgotten")]
+async fn forgotten() -> &'static str {
+    FORGOTTEN_CALLS.fetch_add(1, Ordering::SeqCst);
+    "Oops!"
+}
+
+#[rocket_grants::public]
+#[rocket::get("/unlisted")]
+async fn unlisted() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::async_test]
+async fn test_deny_by_default() {
+    let client = client().await;
+
+    assert_eq!(Status::Ok, get_status(&client, "/", None).await);
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/admin", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/admin", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/unlisted", None).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/missing", None).await
+    );
+}
+
+#[rocket::async_test]
+async fn test_forgotten_handler_not_called() {
+    let client = client().await;
+
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/forgotten", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(0, FORGOTTEN_CALLS.load(Ordering::SeqCst));
+}
+
+#[rocket::async_test]
+async fn test_with_table() {
+    let app = rocket::build()
+        .mount("/", rocket::routes![index, admin, forgotten])
+        .attach(DenyByDefaultFairing::new(routes()))
+        .attach(TableFairing::new(
+            AuthorizationTable::<String>::new().otherwise(Access::PermitAll),
+        ));
+    let client = Client::untracked(app).await.unwrap();
+
+    assert_eq!(Status::Ok, get_status(&client, "/", None).await);
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/forgotten", None).await
+    );
+}
+
+async fn extract(req: &mut Request<'_>) -> Option<HashSet<String>> {
+    req.headers()
+        .get_one(AUTHORIZATION.as_str())
+        .map(|header| header.split(',').map(str::to_string).collect())
+}
+
+fn routes() -> Routes {
+    Routes::new()
+        .route("GET", "/", index)
+        .route("GET", "/admin", admin)
+        .route("GET", "/forgotten", forgotten)
+}
+
+async fn client() -> Client {
+    let app = rocket::build()
+        .mount("/", rocket::routes![index, admin, forgotten, unlisted])
+        .attach(GrantsFairing::with_extractor_fn(|req| {
+            Box::pin(extract(req))
+        }))
+        .attach(DenyByDefaultFairing::new(routes()));
+
+    Client::untracked(app).await.unwrap()
+}
+
+async fn get_status(client: &Client, uri: &'static str, role: Option<&'static str>) -> Status {
+    let mut request = client.get(uri);
+    if let Some(role) = role {
+        request = request.header(Header::new(AUTHORIZATION.as_str(), role));
+    }
+    request.dispatch().await.status()
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "registry")]
+
+use rocket::http::ContentType;
+use rocket::local::asynchronous::Client;
+
+#[rocket_grants::protect("ROLE_ADMIN")]
+#[rocket::get("/users")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::async_test]
+async fn test_introspection() {
+    let rocket = rocket::build()
+        .mount("/", rocket::routes![users])
+        .mount("/admin/access", rocket_grants::introspection::routes());
+    let client = Client::untracked(rocket).await.unwrap();
+
+    let resp = client
+        .get("/admin/access?authorities=ROLE_USER")
+        .dispatch()
+        .await;
+    assert_eq!(Some(ContentType::JSON), resp.content_type());
+
+    let body = resp.into_string().await.unwrap();
+    assert!(body.starts_with(
+        r#"{"authorities":["ROLE_USER"],"endpoints":[{"handler":"introspection::users","#
+    ));
+    assert!(body.ends_with(r#""condition":"ROLE_ADMIN","dynamic":false,"access":"denied"}]}"#));
+
+    let body = client
+        .get("/admin/access")
+        .dispatch()
+        .await
+        .into_string()
+        .await;
+    assert!(body.unwrap().starts_with(r#"{"endpoints":["#));
+}
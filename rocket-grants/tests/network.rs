@@ -0,0 +1,87 @@
+use rocket::http::hyper::header::AUTHORIZATION;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::Request;
+use rocket_grants::network::NetworkHandler;
+use rocket_grants::{protect, GrantsFairing};
+use std::collections::HashSet;
+
+const INTERNAL: &str = "10.1.2.3:4000";
+const EXTERNAL: &str = "203.0.113.1:4000";
+
+#[protect(any("ROLE_ADMIN", from_network = "10.0.0.0/8, ::1"))]
+#[rocket::get("/from_network")]
+async fn from_network() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::get("/metrics")]
+async fn metrics() -> &'static str {
+    "up 1"
+}
+
+#[tokio::test]
+async fn test_from_network() {
+    let client = client().await;
+
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/from_network", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/from_network", "ROLE_USER", "[::1]:4000").await
+    );
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/from_network", "ROLE_ADMIN", EXTERNAL).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/from_network", "ROLE_USER", EXTERNAL).await
+    );
+}
+
+#[tokio::test]
+async fn test_network_handler() {
+    let client = client().await;
+
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/internal/metrics", "ROLE_USER", INTERNAL).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/internal/metrics", "ROLE_ADMIN", EXTERNAL).await
+    );
+}
+
+async fn extract(req: &mut Request<'_>) -> Option<HashSet<String>> {
+    req.headers()
+        .get_one(AUTHORIZATION.as_str())
+        .map(|header| header.split(',').map(str::to_string).collect())
+}
+
+async fn client() -> Client {
+    let app = rocket::build()
+        .mount("/", rocket::routes![from_network])
+        .mount(
+            "/internal",
+            NetworkHandler::wrap("10.0.0.0/8".parse().unwrap(), rocket::routes![metrics]),
+        )
+        .attach(GrantsFairing::with_extractor_fn(|req| {
+            Box::pin(extract(req))
+        }));
+
+    Client::untracked(app).await.unwrap()
+}
+
+async fn get_status(client: &Client, uri: &'static str, role: &'static str, peer: &str) -> Status {
+    client
+        .get(uri)
+        .header(Header::new(AUTHORIZATION.as_str(), role))
+        .remote(peer.parse().unwrap())
+        .dispatch()
+        .await
+        .status()
+}
@@ -3,8 +3,13 @@ use rocket::http::hyper::header::AUTHORIZATION;
 use rocket::http::{Header, Status};
 use rocket::local::asynchronous::{Client, LocalResponse};
 use rocket::serde::json::Json;
+use rocket_grants::authorities::{
+    reset_clock, set_clock, AuthDetails, AuthoritiesCheck, Principal,
+};
+use rocket_grants::error::{AccessDenied, RequestHead};
 use rocket_grants::{protect, GrantsFairing};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
 #[protect("ROLE_ADMIN")]
 #[rocket::get("/http_response")]
@@ -42,6 +47,40 @@ async fn result_response(name: Option<String>) -> Result<String, Status> {
     Ok(format!("Welcome {}!", name))
 }
 
+#[protect(rule = "ROLE_ADMIN || (ROLE_MANAGER && !ROLE_BLOCKED)")]
+#[rocket::get("/rule")]
+async fn rule_response() -> &'static str {
+    "Hi!"
+}
+
+async fn access_denied_with_context(
+    details: &AuthDetails,
+    denied: &AccessDenied,
+    req: &RequestHead<'_>,
+) -> (Status, String) {
+    let user = if details.has_authority(ROLE_MANAGER) {
+        "Manager"
+    } else {
+        "User"
+    };
+
+    let body = format!(
+        "{user} can't access {}, missing: {}",
+        req.uri.path(),
+        denied.missing_authorities().join(", ")
+    );
+    (Status::Forbidden, body)
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    error = "access_denied_with_context(auth_details, denied, request).await"
+)]
+#[rocket::get("/context")]
+async fn context_response() -> &'static str {
+    "Hi!"
+}
+
 #[tokio::test]
 async fn test_http_response() {
     let client = get_client().await;
@@ -62,6 +101,7 @@ async fn test_str() {
     assert_eq!(Status::Forbidden, test_manager.status());
 
     common::test_body(test_admin, "Hi!").await;
+    common::test_problem(test_manager).await;
 }
 
 #[tokio::test]
@@ -100,6 +140,18 @@ async fn test_result() {
     common::test_body(test_ok, "Welcome Test!").await;
 }
 
+#[tokio::test]
+async fn test_rule() {
+    let client = get_client().await;
+    let test_admin = get_user_response(&client, "/rule", ROLE_ADMIN).await;
+    let test_manager = get_user_response(&client, "/rule", ROLE_MANAGER).await;
+    let test_blocked = get_user_response(&client, "/rule", "ROLE_MANAGER,ROLE_BLOCKED").await;
+
+    assert_eq!(Status::Ok, test_admin.status());
+    assert_eq!(Status::Ok, test_manager.status());
+    assert_eq!(Status::Forbidden, test_blocked.status());
+}
+
 #[rocket::catch(403)]
 fn forbidden_catcher(_req: &rocket::Request) -> String {
     "Custom Forbidden error message".to_string()
@@ -130,11 +182,122 @@ async fn test_custom_error() {
     assert_eq!(Status::Ok, test_ok.status());
     assert_eq!(Status::Forbidden, test_forbidden.status());
     assert_eq!(Status::Unauthorized, test_unauthorized.status());
+    // challenges are added to responses of catchers
+    assert_eq!(
+        Some(r#"Bearer error="insufficient_scope", scope="ROLE_ADMIN""#),
+        test_forbidden.headers().get_one("WWW-Authenticate")
+    );
+    assert_eq!(
+        Some("Bearer"),
+        test_unauthorized.headers().get_one("WWW-Authenticate")
+    );
 
     common::test_body(test_forbidden, "Custom Forbidden error message").await;
     common::test_body(test_unauthorized, "Custom Unauthorized error message").await;
 }
 
+#[tokio::test]
+async fn test_access_denied_context() {
+    let client = get_client().await;
+    let test_admin = get_user_response(&client, "/context", ROLE_ADMIN).await;
+    let test_manager = get_user_response(&client, "/context", ROLE_MANAGER).await;
+
+    assert_eq!(Status::Ok, test_admin.status());
+    assert_eq!(Status::Forbidden, test_manager.status());
+
+    common::test_body(test_admin, "Hi!").await;
+    common::test_body(
+        test_manager,
+        "Manager can't access /context, missing: ROLE_ADMIN",
+    )
+    .await;
+}
+
+#[protect(
+    "ROLE_ADMIN",
+    principal = "common::Claims",
+    expr = "principal.claims().tenant == \"acme\""
+)]
+#[rocket::get("/principal")]
+async fn principal_response(user: Principal<common::Claims>) -> String {
+    user.subject().to_string()
+}
+
+#[protect(any("ROLE_ADMIN", owner = "user_id"), principal = "common::Claims")]
+#[rocket::get("/owner/<user_id>")]
+async fn owner_response(user_id: &str) -> String {
+    format!("Hi, {user_id}!")
+}
+
+#[tokio::test]
+async fn test_owner() {
+    let client = get_client().await;
+    let owner_response = |uri: &'static str, role: &'static str, tenant: Option<&'static str>| {
+        let mut request = client
+            .get(uri)
+            .header(Header::new(AUTHORIZATION.as_str(), role));
+        if let Some(tenant) = tenant {
+            request = request.header(Header::new(common::TENANT_HEADER, tenant));
+        }
+        request.dispatch()
+    };
+
+    let test_owner = owner_response("/owner/user-1", ROLE_MANAGER, Some("acme")).await;
+    let test_other = owner_response("/owner/user-2", ROLE_MANAGER, Some("acme")).await;
+    let test_admin = owner_response("/owner/user-2", ROLE_ADMIN, Some("acme")).await;
+    let test_without_principal = owner_response("/owner/user-1", ROLE_MANAGER, None).await;
+
+    assert_eq!(Status::Ok, test_owner.status());
+    assert_eq!(Status::Forbidden, test_other.status());
+    assert_eq!(Status::Ok, test_admin.status());
+    assert_eq!(Status::Unauthorized, test_without_principal.status());
+}
+
+#[protect("ROLE_ADMIN", during = "Mon-Fri 09:00-18:00 UTC")]
+#[rocket::get("/during")]
+async fn during_response() -> &'static str {
+    "Hi!"
+}
+
+#[tokio::test]
+async fn test_during() {
+    let client = get_client().await;
+    // Monday, 2024-01-01 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_103_200));
+    let test_working_hours = get_user_response(&client, "/during", ROLE_ADMIN).await;
+    // Saturday, 2024-01-06 10:00 UTC
+    set_clock(|| SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_535_200));
+    let test_weekend = get_user_response(&client, "/during", ROLE_ADMIN).await;
+    reset_clock();
+
+    assert_eq!(Status::Ok, test_working_hours.status());
+    assert_eq!(Status::Forbidden, test_weekend.status());
+}
+
+#[tokio::test]
+async fn test_principal() {
+    let client = get_client().await;
+    let principal_response = |tenant: Option<&'static str>| {
+        let mut request = client
+            .get("/principal")
+            .header(Header::new(AUTHORIZATION.as_str(), ROLE_ADMIN));
+        if let Some(tenant) = tenant {
+            request = request.header(Header::new(common::TENANT_HEADER, tenant));
+        }
+        request.dispatch()
+    };
+
+    let test_acme = principal_response(Some("acme")).await;
+    let test_other = principal_response(Some("other")).await;
+    let test_anonymous = principal_response(None).await;
+
+    assert_eq!(Status::Ok, test_acme.status());
+    assert_eq!(Status::Forbidden, test_other.status());
+    assert_eq!(Status::Unauthorized, test_anonymous.status());
+
+    common::test_body(test_acme, common::SUBJECT).await;
+}
+
 async fn get_client() -> Client {
     let app = rocket::build()
         .mount(
@@ -145,6 +308,11 @@ async fn get_client() -> Client {
                 return_response,
                 result_response,
                 secure_user_id,
+                context_response,
+                rule_response,
+                principal_response,
+                owner_response,
+                during_response,
             ],
         )
         .attach(GrantsFairing::with_extractor_fn(|req| {
@@ -55,6 +55,7 @@ async fn test_incorrect_http_response() {
     let client = get_client().await;
     let test = get_user_response(&client, "/incorrect_enum_secure", Admin.to_string()).await;
     assert_eq!(Status::Unauthorized, test.status());
+    assert_eq!(Some("Bearer"), test.headers().get_one("WWW-Authenticate"));
 }
 
 async fn get_client() -> Client {
@@ -0,0 +1,108 @@
+use rocket::http::hyper::header::AUTHORIZATION;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::Request;
+#[cfg(feature = "deny-by-default")]
+use rocket_grants::deny_by_default::{DenyByDefaultFairing, Routes};
+use rocket_grants::GrantsFairing;
+use std::collections::HashSet;
+
+#[rocket_grants::protect_all("ROLE_ADMIN")]
+mod admin {
+    #[rocket::get("/admin")]
+    pub async fn index() -> &'static str {
+        "Hello, admin!"
+    }
+
+    #[protect("OP_WRITE")]
+    #[rocket::get("/admin/users")]
+    pub async fn users() -> &'static str {
+        "Users"
+    }
+
+    #[protect(any("ROLE_ADMIN", "ROLE_SUPPORT"), overrides)]
+    #[rocket::get("/admin/tickets")]
+    pub async fn tickets() -> &'static str {
+        "Tickets"
+    }
+
+    #[public]
+    #[rocket::get("/admin/health")]
+    pub async fn health() -> &'static str {
+        "Ok"
+    }
+
+    pub async fn load_title() -> &'static str {
+        "Admin"
+    }
+}
+
+#[rocket::async_test]
+async fn test_module() {
+    assert_eq!("Admin", admin::load_title().await);
+
+    let client = client().await;
+
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/admin", "ROLE_ADMIN").await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/admin", "ROLE_SUPPORT").await
+    );
+
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/admin/users", "ROLE_ADMIN,OP_WRITE").await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, "/admin/users", "ROLE_ADMIN").await
+    );
+
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/admin/tickets", "ROLE_SUPPORT").await
+    );
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, "/admin/health", "ROLE_USER").await
+    );
+}
+
+async fn extract(req: &mut Request<'_>) -> Option<HashSet<String>> {
+    req.headers()
+        .get_one(AUTHORIZATION.as_str())
+        .map(|header| header.split(',').map(str::to_string).collect())
+}
+
+async fn client() -> Client {
+    let app = rocket::build()
+        .mount(
+            "/",
+            rocket::routes![admin::index, admin::users, admin::tickets, admin::health],
+        )
+        .attach(GrantsFairing::with_extractor_fn(|req| {
+            Box::pin(extract(req))
+        }));
+    #[cfg(feature = "deny-by-default")]
+    let app = app.attach(DenyByDefaultFairing::new(
+        Routes::new()
+            .route("GET", "/admin", admin::index)
+            .route("GET", "/admin/users", admin::users)
+            .route("GET", "/admin/tickets", admin::tickets)
+            .route("GET", "/admin/health", admin::health),
+    ));
+
+    Client::untracked(app).await.unwrap()
+}
+
+async fn get_status(client: &Client, uri: &'static str, role: &'static str) -> Status {
+    client
+        .get(uri)
+        .header(Header::new(AUTHORIZATION.as_str(), role))
+        .dispatch()
+        .await
+        .status()
+}
@@ -0,0 +1,154 @@
+use rocket::fairing::AdHoc;
+use rocket::http::hyper::header::AUTHORIZATION;
+use rocket::http::{Header, Method, Status};
+use rocket::local::asynchronous::Client;
+use rocket::Request;
+use rocket_grants::table::{Access, AuthorizationTable, TableFairing};
+use rocket_grants::GrantsFairing;
+use std::collections::HashSet;
+
+#[rocket::get("/")]
+async fn index() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::get("/profile")]
+async fn profile() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::get("/admin/users")]
+async fn users() -> &'static str {
+    "Hi!"
+}
+
+#[rocket::post("/articles/<id>")]
+async fn article(id: u32) -> String {
+    format!("Article {id}")
+}
+
+#[rocket::get("/internal/metrics")]
+async fn metrics() -> &'static str {
+    "up 1"
+}
+
+#[tokio::test]
+async fn test_table() {
+    let client = client().await;
+
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, Method::Get, "/", None).await
+    );
+    assert_eq!(
+        Status::Unauthorized,
+        get_status(&client, Method::Get, "/profile", None).await
+    );
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, Method::Get, "/profile", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, Method::Get, "/admin/users", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, Method::Get, "/admin/users", Some("ROLE_ADMIN")).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(&client, Method::Post, "/articles/1", Some("ROLE_USER")).await
+    );
+    assert_eq!(
+        Status::Ok,
+        get_status(&client, Method::Post, "/articles/1", Some("ROLE_EDITOR")).await
+    );
+    assert_eq!(
+        Status::Forbidden,
+        get_status(
+            &client,
+            Method::Get,
+            "/internal/metrics",
+            Some("ROLE_ADMIN")
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn test_normalized_paths() {
+    let client = client().await;
+
+    for uri in ["/%61dmin/users", "//admin/users", "/./admin/users"] {
+        assert_eq!(
+            Status::Forbidden,
+            get_status(&client, Method::Get, uri, Some("ROLE_USER")).await,
+            "{uri}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_rejection_keeps_uri() {
+    let client = client().await;
+
+    let response = client
+        .get("/admin/users")
+        .header(Header::new(AUTHORIZATION.as_str(), "ROLE_USER"))
+        .dispatch()
+        .await;
+    assert_eq!(Status::Forbidden, response.status());
+    assert_eq!(Some("/admin/users"), response.headers().get_one("X-Path"));
+}
+
+fn table() -> AuthorizationTable {
+    AuthorizationTable::new()
+        .route("GET", "/", Access::PermitAll)
+        .route("*", "/admin/**", "ROLE_ADMIN".parse::<Access>().unwrap())
+        .route(
+            "POST",
+            "/articles/{id}",
+            "ROLE_EDITOR || ROLE_ADMIN".parse::<Access>().unwrap(),
+        )
+        .route("*", "/internal/**", Access::DenyAll)
+        .otherwise(Access::Authenticated)
+}
+
+async fn extract(req: &mut Request<'_>) -> Option<HashSet<String>> {
+    req.headers()
+        .get_one(AUTHORIZATION.as_str())
+        .map(|header| header.split(',').map(str::to_string).collect())
+}
+
+async fn client() -> Client {
+    let app = rocket::build()
+        .mount(
+            "/",
+            rocket::routes![index, profile, users, article, metrics],
+        )
+        .attach(GrantsFairing::with_extractor_fn(|req| {
+            Box::pin(extract(req))
+        }))
+        .attach(TableFairing::new(table()))
+        .attach(AdHoc::on_response("Path", |req, res| {
+            Box::pin(async move {
+                res.set_raw_header("X-Path", req.uri().path().to_string());
+            })
+        }));
+
+    Client::untracked(app).await.unwrap()
+}
+
+async fn get_status(
+    client: &Client,
+    method: Method,
+    uri: &'static str,
+    role: Option<&'static str>,
+) -> Status {
+    let mut request = client.req(method, uri);
+    if let Some(role) = role {
+        request = request.header(Header::new(AUTHORIZATION.as_str(), role));
+    }
+    request.dispatch().await.status()
+}
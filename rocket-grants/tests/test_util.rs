@@ -0,0 +1,72 @@
+#![cfg(feature = "test-util")]
+
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+use rocket_grants::test_util::{MockExtractor, WithAuthorities};
+use rocket_grants::GrantsFairing;
+use std::collections::HashSet;
+
+const ROLE_ADMIN: &str = "ROLE_ADMIN";
+
+#[rocket_grants::protect("ROLE_ADMIN")]
+#[rocket::get("/admin")]
+async fn admin() -> &'static str {
+    "Hi!"
+}
+
+async fn client(extractor: &MockExtractor) -> Client {
+    let rocket = rocket::build()
+        .mount("/", rocket::routes![admin])
+        .attach(GrantsFairing::with_mock_extractor(extractor.clone()));
+    Client::untracked(rocket).await.unwrap()
+}
+
+#[rocket::async_test]
+async fn test_with_authorities() {
+    let extractor = MockExtractor::new();
+    let client = client(&extractor).await;
+
+    let resp = client
+        .get("/admin")
+        .with_authorities([ROLE_ADMIN])
+        .dispatch();
+    assert_eq!(Status::Ok, resp.await.status());
+    let resp = client
+        .get("/admin")
+        .with_authorities(["ROLE_USER"])
+        .dispatch();
+    assert_eq!(Status::Forbidden, resp.await.status());
+    let resp = client.get("/admin").dispatch();
+    assert_eq!(Status::Forbidden, resp.await.status());
+
+    let calls = extractor.calls();
+    assert_eq!(3, calls.len());
+    assert_eq!("GET", calls[0].method());
+    assert_eq!("/admin", calls[0].path());
+    assert_eq!(
+        Some(&HashSet::from([ROLE_ADMIN.to_string()])),
+        calls[0].authorities()
+    );
+    assert_eq!(None, calls[2].authorities());
+}
+
+#[rocket::async_test]
+async fn test_scripted_results() {
+    let extractor = MockExtractor::new();
+    extractor.then_return([ROLE_ADMIN]).then_fail(());
+    let client = client(&extractor).await;
+
+    let resp = client.get("/admin").dispatch();
+    assert_eq!(Status::Ok, resp.await.status());
+    let resp = client
+        .get("/admin")
+        .with_authorities([ROLE_ADMIN])
+        .dispatch();
+    assert_eq!(Status::Unauthorized, resp.await.status());
+    let resp = client
+        .get("/admin")
+        .with_authorities([ROLE_ADMIN])
+        .dispatch();
+    assert_eq!(Status::Ok, resp.await.status());
+    assert_eq!(3, extractor.calls().len());
+}
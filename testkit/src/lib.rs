@@ -0,0 +1,238 @@
+//! Conformance scenarios for integrations of `protect-endpoints`.
+//!
+//! Integrations differ in extractors, request types and errors, but the same `#[protect]` condition
+//! must give the same outcome in all of them. Each integration (including third-party ones) implements
+//! an [`Adapter`] serving the [routes](#routes) and runs the suite with [`run`] in its tests.
+//!
+//! # Routes
+//! All routes respond to `GET` with `200 OK`:
+//!
+//! | Path             | Condition                                                          |
+//! |------------------|--------------------------------------------------------------------|
+//! | `/single`        | `#[protect("ROLE_ADMIN")]`                                         |
+//! | `/all`           | `#[protect("ROLE_ADMIN", "ROLE_MANAGER")]`                         |
+//! | `/any`           | `#[protect(any("ROLE_ADMIN", "ROLE_MANAGER"))]`                    |
+//! | `/expr?name=..`  | `#[protect("ROLE_ADMIN", expr = "..")]`, the `name` equals [`OWNER`] |
+//! | `/typed`         | `#[protect("Role::Admin", ty = "Role")]` with this [`Role`]        |
+//!
+//! # Credentials
+//! - [`Credentials::Authorities`] are returned by the extractor, e.g. passed via the [`AUTHORITIES_HEADER`].
+//!   For `/typed`, they're parsed into [`Role`], unknown ones are skipped;
+//! - [`Credentials::Missing`]: the routes are served without the extractor, so the request has no authorities;
+//! - [`Credentials::ExtractorError`]: the extractor fails with `401 Unauthorized`.
+//!
+//! # Example
+//! ```
+//! use protect_endpoints_testkit::{Adapter, Credentials};
+//!
+//! struct Axum;
+//!
+//! impl Adapter for Axum {
+//!     async fn status(&self, path: &str, credentials: &Credentials) -> u16 {
+//!         // builds the app with routes for the credentials and sends `GET` request of the path
+//! #       let _ = (path, credentials);
+//!         200
+//!     }
+//! }
+//!
+//! // e.g. with `#[tokio::test]`
+//! async fn test_conformance() {
+//!     protect_endpoints_testkit::run(&Axum).await;
+//! }
+//! ```
+
+use std::fmt::{self, Display, Formatter, Write};
+use std::future::Future;
+use std::str::FromStr;
+
+pub use protect_endpoints_core::test_util::{header_value, parse_header_value, AUTHORITIES_HEADER};
+
+pub const ROLE_ADMIN: &str = "ROLE_ADMIN";
+pub const ROLE_MANAGER: &str = "ROLE_MANAGER";
+/// Value of the `name` query parameter allowed by `expr` of the `/expr` route.
+pub const OWNER: &str = "owner";
+
+/// Typed authority of the `/typed` route, parsed from `ADMIN` and `MANAGER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Admin,
+    Manager,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Admin => f.write_str("ADMIN"),
+            Role::Manager => f.write_str("MANAGER"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ADMIN" => Ok(Role::Admin),
+            "MANAGER" => Ok(Role::Manager),
+            other => Err(format!("unknown role `{other}`")),
+        }
+    }
+}
+
+/// Authentication state of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Credentials {
+    Authorities(&'static [&'static str]),
+    Missing,
+    ExtractorError,
+}
+
+/// Expected response of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Allowed,
+    Forbidden,
+    Unauthorized,
+}
+
+impl Outcome {
+    pub fn status(&self) -> u16 {
+        match self {
+            Outcome::Allowed => 200,
+            Outcome::Forbidden => 403,
+            Outcome::Unauthorized => 401,
+        }
+    }
+}
+
+/// Request of a route with the credentials and the expected outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scenario {
+    pub name: &'static str,
+    pub path: &'static str,
+    pub credentials: Credentials,
+    pub expected: Outcome,
+}
+
+const fn scenario(
+    name: &'static str,
+    path: &'static str,
+    credentials: Credentials,
+    expected: Outcome,
+) -> Scenario {
+    Scenario {
+        name,
+        path,
+        credentials,
+        expected,
+    }
+}
+
+use Credentials::{Authorities, ExtractorError, Missing};
+use Outcome::{Allowed, Forbidden, Unauthorized};
+
+/// Scenarios of the suite, see [`run`].
+pub const SCENARIOS: &[Scenario] = &[
+    scenario("allowed", "/single", Authorities(&[ROLE_ADMIN]), Allowed),
+    scenario(
+        "allowed with extra authorities",
+        "/single",
+        Authorities(&[ROLE_MANAGER, ROLE_ADMIN]),
+        Allowed,
+    ),
+    scenario("denied", "/single", Authorities(&[ROLE_MANAGER]), Forbidden),
+    scenario(
+        "denied without authorities",
+        "/single",
+        Authorities(&[]),
+        Forbidden,
+    ),
+    scenario(
+        "denied by case",
+        "/single",
+        Authorities(&["role_admin"]),
+        Forbidden,
+    ),
+    scenario("missing details", "/single", Missing, Unauthorized),
+    scenario("extractor error", "/single", ExtractorError, Unauthorized),
+    scenario(
+        "all",
+        "/all",
+        Authorities(&[ROLE_ADMIN, ROLE_MANAGER]),
+        Allowed,
+    ),
+    scenario(
+        "all, partially",
+        "/all",
+        Authorities(&[ROLE_MANAGER]),
+        Forbidden,
+    ),
+    scenario("any, first", "/any", Authorities(&[ROLE_ADMIN]), Allowed),
+    scenario("any, second", "/any", Authorities(&[ROLE_MANAGER]), Allowed),
+    scenario("any, none", "/any", Authorities(&["ROLE_USER"]), Forbidden),
+    scenario(
+        "expr",
+        "/expr?name=owner",
+        Authorities(&[ROLE_ADMIN]),
+        Allowed,
+    ),
+    scenario(
+        "expr, other value",
+        "/expr?name=other",
+        Authorities(&[ROLE_ADMIN]),
+        Forbidden,
+    ),
+    scenario(
+        "expr without authority",
+        "/expr?name=owner",
+        Authorities(&[ROLE_MANAGER]),
+        Forbidden,
+    ),
+    scenario("typed enum", "/typed", Authorities(&["ADMIN"]), Allowed),
+    scenario(
+        "typed enum, denied",
+        "/typed",
+        Authorities(&["MANAGER"]),
+        Forbidden,
+    ),
+    scenario(
+        "typed enum, raw string",
+        "/typed",
+        Authorities(&[ROLE_ADMIN]),
+        Forbidden,
+    ),
+];
+
+/// Integration under test, serving the routes described in the [crate docs](crate).
+pub trait Adapter {
+    /// Status of the `GET` request of the path (with a query) sent with the credentials.
+    fn status(&self, path: &str, credentials: &Credentials) -> impl Future<Output = u16>;
+}
+
+/// Runs all [`SCENARIOS`] against the adapter.
+///
+/// # Panics
+/// If any scenario has an unexpected status, listing all of them.
+pub async fn run<A: Adapter>(adapter: &A) {
+    let mut failures = String::new();
+    for scenario in SCENARIOS {
+        let status = adapter.status(scenario.path, &scenario.credentials).await;
+        if status != scenario.expected.status() {
+            writeln!(
+                failures,
+                "- {} (`{}` with {:?}): expected {}, got {status}",
+                scenario.name,
+                scenario.path,
+                scenario.credentials,
+                scenario.expected.status()
+            )
+            .unwrap();
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "scenarios with unexpected status:\n{failures}"
+    );
+}